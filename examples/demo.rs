@@ -5,6 +5,7 @@
 //! - `{FONT_DIR}/{name}-Bold.ttf`
 //! - `{FONT_DIR}/{name}-Italic.ttf`
 //! - `{FONT_DIR}/{name}-BoldItalic.ttf`
+//!
 //! for `name` in {`DEFAULT_FONT_NAME`, `MONO_FONT_NAME`}.
 
 use std::env;
@@ -264,25 +265,18 @@ fn main() {
 
     let mut table = elements::TableLayout::new(vec![1, 5]);
     table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+    table.set_cell_padding(1);
     table
         .row()
-        .element(
-            elements::Paragraph::new("Index")
-                .styled(style::Effect::Bold)
-                .padded(1),
-        )
-        .element(
-            elements::Paragraph::new("Text")
-                .styled(style::Effect::Bold)
-                .padded(1),
-        )
+        .element(elements::Paragraph::new("Index").styled(style::Effect::Bold))
+        .element(elements::Paragraph::new("Text").styled(style::Effect::Bold))
         .push()
         .expect("Invalid table row");
     for i in 0..10 {
         table
             .row()
-            .element(elements::Paragraph::new(format!("#{}", i)).padded(1))
-            .element(elements::Paragraph::new(LOREM_IPSUM).padded(1))
+            .element(elements::Paragraph::new(format!("#{}", i)))
+            .element(elements::Paragraph::new(LOREM_IPSUM))
             .push()
             .expect("Invalid table row");
     }
@@ -298,7 +292,7 @@ fn main() {
 mod images {
     use super::*;
 
-    const IMAGE_PATH_JPG: &'static str = "examples/images/test_image.jpg";
+    const IMAGE_PATH_JPG: &str = "examples/images/test_image.jpg";
 
     pub fn do_image_test(doc: &mut rckive_genpdf::Document) {
         doc.push(elements::Paragraph::new(
@@ -6,53 +6,90 @@
 //! It includes the following elements:
 //! - Containers:
 //!   - [`LinearLayout`][]: arranges its elements sequentially
+//!   - [`ColumnLayout`][]: arranges its element in multiple columns
+//!   - [`Overlay`][]: renders its elements on top of each other
 //!   - [`TableLayout`][]: arranges its elements in columns and rows
 //!   - [`OrderedList`][] and [`UnorderedList`][]: arrange their elements sequentially with bullet
 //!     points
 //! - Text:
 //!   - [`Text`][]: a single line of text
 //!   - [`Paragraph`][]: a wrapped and aligned paragraph of text
+//!   - [`ListOfFigures`][]: a list of the captions of figures and tables with their page numbers
 //! - Wrappers:
 //!   - [`FramedElement`][]: draws a frame around the wrapped element
 //!   - [`PaddedElement`][]: adds a padding to the wrapped element
+//!   - [`DecoratedElement`][]: adds a margin, a border, a padding and a background color to the
+//!     wrapped element
 //!   - [`StyledElement`][]: sets a default style for the wrapped element and its children
+//!   - [`PageBreakBefore`][]: inserts a page break before the wrapped element
+//!   - [`KeepWithNext`][]: keeps space for the following content below the wrapped element
+//!   - [`TrackedElement`][]: records the page and position of the wrapped element
+//!   - [`MarginNote`][]: adds a note in the page margin next to the wrapped element
+//!   - [`Positioned`][]: renders the wrapped element at a fixed position on the page
+//!   - [`OnPageParity`][]: only renders the wrapped element on odd or on even pages
+//!   - [`Figure`][] and [`Table`][]: add a numbered caption to the wrapped element
 //! - Other:
 //!   - [`Image`][]: an image (requires the `images` feature)
+//!   - [`PdfPage`][]: a page of another PDF document
 //!   - [`Break`][]: adds forced line breaks as a spacer
+//!   - [`HorizontalRule`][]: draws a horizontal line across the area
 //!   - [`PageBreak`][]: adds a forced page break
+//!   - [`FromFn`][]: renders a closure
 //!
 //! You can create custom elements by implementing the [`Element`][] trait.
 //!
 //! [`Element`]: ../trait.Element.html
 //! [`LinearLayout`]: struct.LinearLayout.html
+//! [`ColumnLayout`]: struct.ColumnLayout.html
+//! [`Overlay`]: struct.Overlay.html
 //! [`TableLayout`]: struct.TableLayout.html
 //! [`OrderedList`]: struct.OrderedList.html
 //! [`UnorderedList`]: struct.UnorderedList.html
 //! [`Text`]: struct.Text.html
 //! [`Image`]: struct.Image.html
+//! [`PdfPage`]: struct.PdfPage.html
 //! [`Break`]: struct.Break.html
+//! [`HorizontalRule`]: struct.HorizontalRule.html
 //! [`PageBreak`]: struct.PageBreak.html
+//! [`FromFn`]: struct.FromFn.html
 //! [`Paragraph`]: struct.Paragraph.html
 //! [`FramedElement`]: struct.FramedElement.html
 //! [`PaddedElement`]: struct.PaddedElement.html
+//! [`DecoratedElement`]: struct.DecoratedElement.html
 //! [`StyledElement`]: struct.StyledElement.html
+//! [`PageBreakBefore`]: struct.PageBreakBefore.html
+//! [`KeepWithNext`]: struct.KeepWithNext.html
+//! [`TrackedElement`]: struct.TrackedElement.html
+//! [`MarginNote`]: struct.MarginNote.html
+//! [`Positioned`]: struct.Positioned.html
+//! [`OnPageParity`]: struct.OnPageParity.html
+//! [`Figure`]: struct.Figure.html
+//! [`Table`]: struct.Table.html
+//! [`ListOfFigures`]: struct.ListOfFigures.html
 
 #[cfg(feature = "images")]
 mod images;
 
+use std::borrow;
+use std::cell;
 use std::collections;
 use std::iter;
 use std::mem;
+use std::ops;
+use std::rc;
 
 use crate::error::{Error, ErrorKind};
 use crate::fonts;
 use crate::render;
-use crate::style::{LineStyle, Style, StyledString};
+use crate::style::{self, Color, LineStyle, Style, StyledString};
 use crate::wrap;
-use crate::{Alignment, Context, Element, Margins, Mm, Position, RenderResult, Size};
+use crate::{
+    Alignment, CellMetrics, Context, Destination, Element, ElementHandle, LineOverflow, Margins,
+    Mm, Position, RenderResult, Size, Truncation, Whitespace, WordBreak,
+};
 
 #[cfg(feature = "images")]
-pub use images::Image;
+pub use images::{Image, ImageFit};
 
 /// Helper trait for creating boxed elements.
 pub trait IntoBoxedElement {
@@ -131,8 +168,11 @@ impl LinearLayout {
     ) -> Result<RenderResult, Error> {
         let mut result = RenderResult::default();
         while area.size().height > Mm(0.0) && self.render_idx < self.elements.len() {
-            let element_result =
-                self.elements[self.render_idx].render(context, area.clone(), style)?;
+            let element_result = context.render_element(
+                self.elements[self.render_idx].as_mut(),
+                area.clone(),
+                style,
+            )?;
             area.add_offset(Position::new(0, element_result.size.height));
             result.size = result.size.stack_vertical(element_result.size);
             if element_result.has_more {
@@ -141,6 +181,15 @@ impl LinearLayout {
             }
             self.render_idx += 1;
         }
+        // Spacing at the end of a page should not be moved to the next page.
+        while self
+            .elements
+            .get(self.render_idx)
+            .map(|element| element.is_spacing())
+            .unwrap_or_default()
+        {
+            self.render_idx += 1;
+        }
         result.has_more = self.render_idx < self.elements.len();
         Ok(result)
     }
@@ -165,1347 +214,6361 @@ impl<E: IntoBoxedElement> iter::Extend<E> for LinearLayout {
     }
 }
 
-/// A single line of formatted text.
+/// Renders a list of elements into the same area on top of each other.
 ///
-/// This element renders a single styled string on a single line.  It does not wrap it if the
-/// string is longer than the line.  Therefore you should prefer [`Paragraph`][] over `Text` for
-/// most use cases.
+/// The elements are rendered in the order they have been added, so later elements are drawn on
+/// top of earlier elements, for example a text caption on top of an image.  The size of the
+/// overlay is the maximum width and the maximum height of the rendered elements.  If some
+/// elements do not fit into the area, they are continued in the area on the next page, while the
+/// elements that have been rendered completely are skipped.
 ///
-/// [`Paragraph`]: struct.Paragraph.html
-#[derive(Clone, Debug, Default)]
-pub struct Text {
-    text: StyledString,
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::{elements, style, Element as _};
+/// let overlay = elements::Overlay::new()
+///     .element(elements::Paragraph::new("Background"))
+///     .element(elements::Paragraph::new("DRAFT").styled(style::Color::Rgb(255, 0, 0)));
+/// ```
+#[derive(Default)]
+pub struct Overlay {
+    elements: Vec<Box<dyn Element>>,
+    finished: Vec<bool>,
 }
 
-impl Text {
-    /// Creates a new instance with the given styled string.
-    pub fn new(text: impl Into<StyledString>) -> Text {
-        Text { text: text.into() }
+impl Overlay {
+    /// Creates a new overlay without elements.
+    pub fn new() -> Overlay {
+        Overlay::default()
+    }
+
+    /// Adds the given element on top of the elements of this overlay.
+    pub fn push<E: IntoBoxedElement>(&mut self, element: E) {
+        self.elements.push(element.into_boxed_element());
+    }
+
+    /// Adds the given element on top of the elements of this overlay and returns the overlay.
+    pub fn element<E: IntoBoxedElement>(mut self, element: E) -> Self {
+        self.push(element);
+        self
     }
 }
 
-impl Element for Text {
+impl Element for Overlay {
     fn render(
         &mut self,
         context: &Context,
         area: render::Area<'_>,
-        mut style: Style,
+        style: Style,
     ) -> Result<RenderResult, Error> {
         let mut result = RenderResult::default();
-        style.merge(self.text.style);
-        if area.print_str(
-            &context.font_cache,
-            Position::default(),
-            style,
-            &self.text.s,
-        )? {
-            result.size = Size::new(
-                style.str_width(&context.font_cache, &self.text.s),
-                style.line_height(&context.font_cache),
-            );
-        } else {
-            result.has_more = true;
+        self.finished.resize(self.elements.len(), false);
+        for (element, is_finished) in self.elements.iter_mut().zip(self.finished.iter_mut()) {
+            if *is_finished {
+                continue;
+            }
+            let element_result = context.render_element(element.as_mut(), area.clone(), style)?;
+            *is_finished = !element_result.has_more;
+            result.has_more |= element_result.has_more;
+            result.size.width = result.size.width.max(element_result.size.width);
+            result.size.height = result.size.height.max(element_result.size.height);
         }
         Ok(result)
     }
 }
 
-/// A multi-line wrapped paragraph of formatted text.
-///
-/// If the text of this paragraph is longer than the page width, the paragraph is wrapped at word
-/// borders (and additionally at string borders if it contains multiple strings).  If a word in the
-/// paragraph is longer than the page width, the text is truncated.
-///
-/// Use the [`push`][], [`string`][], [`push_styled`][] and [`string_styled`][] methods to add
-/// strings to this paragraph.  Besides the styling of the text (see [`Style`][]), you can also set
-/// an [`Alignment`][] for the paragraph.
-///
-/// The line height and spacing are calculated based on the style of each string.
+impl<E: IntoBoxedElement> iter::Extend<E> for Overlay {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        self.elements
+            .extend(iter.into_iter().map(|e| e.into_boxed_element()))
+    }
+}
+
+/// Arranges an element in multiple columns.
 ///
-/// # Examples
+/// The wrapped element is rendered into the first column.  If it does not fit, the remaining
+/// content is rendered into the next column, and so on.  Per default, each column is filled
+/// completely before the next column is started.  In balanced mode (see [`set_balanced`][]), the
+/// height of the columns is reduced so that all columns end at roughly the same height.  This
+/// requires rendering copies of the element to measure its content, so it is only available for
+/// elements that implement [`Clone`][].
 ///
-/// With setters:
-/// ```
-/// use rckive_genpdf::{elements, style};
-/// let mut p = elements::Paragraph::default();
-/// p.push("This is an ");
-/// p.push_styled("important", style::Color::Rgb(255, 0, 0));
-/// p.push(" message!");
-/// p.set_alignment(rckive_genpdf::Alignment::Center);
-/// ```
+/// # Example
 ///
-/// Chained:
 /// ```
-/// use rckive_genpdf::{elements, style};
-/// let p = elements::Paragraph::default()
-///     .string("This is an ")
-///     .styled_string("important", style::Color::Rgb(255, 0, 0))
-///     .string(" message!")
-///     .aligned(rckive_genpdf::Alignment::Center);
+/// use rckive_genpdf::elements;
+/// let columns = elements::ColumnLayout::new(elements::Paragraph::new("Lorem ipsum"), 2)
+///     .with_gap(5)
+///     .with_balanced(true);
 /// ```
 ///
-/// [`Style`]: ../style/struct.Style.html
-/// [`Alignment`]: ../enum.Alignment.html
-/// [`Element::styled`]: ../trait.Element.html#method.styled
-/// [`push`]: #method.push
-/// [`push_styled`]: #method.push_styled
-/// [`string`]: #method.string
-/// [`string_styled`]: #method.string_styled
-#[derive(Clone, Debug, Default)]
-pub struct Paragraph {
-    text: Vec<StyledString>,
-    words: collections::VecDeque<StyledString>,
-    style_applied: bool,
-    alignment: Alignment,
+/// [`set_balanced`]: #method.set_balanced
+/// [`Clone`]: https://doc.rust-lang.org/std/clone/trait.Clone.html
+pub struct ColumnLayout<E: Element> {
+    element: E,
+    columns: usize,
+    gap: Mm,
+    balance: Option<fn(&E) -> E>,
 }
 
-impl Paragraph {
-    /// Creates a new paragraph with the given content.
-    pub fn new(text: impl Into<StyledString>) -> Paragraph {
-        Paragraph {
-            text: vec![text.into()],
-            ..Default::default()
+impl<E: Element> ColumnLayout<E> {
+    /// Creates a new layout that arranges the given element in the given number of columns.
+    pub fn new(element: E, columns: usize) -> ColumnLayout<E> {
+        ColumnLayout {
+            element,
+            columns: columns.max(1),
+            gap: Mm(0.0),
+            balance: None,
         }
     }
 
-    /// Sets the alignment of this paragraph.
-    pub fn set_alignment(&mut self, alignment: Alignment) {
-        self.alignment = alignment;
-    }
-
-    /// Sets the alignment of this paragraph and returns the paragraph.
-    pub fn aligned(mut self, alignment: Alignment) -> Self {
-        self.set_alignment(alignment);
-        self
-    }
-
-    /// Adds a string to the end of this paragraph.
-    pub fn push(&mut self, s: impl Into<StyledString>) {
-        self.text.push(s.into());
-    }
-
-    /// Adds a string to the end of this paragraph and returns the paragraph.
-    pub fn string(mut self, s: impl Into<StyledString>) -> Self {
-        self.push(s);
-        self
-    }
-
-    /// Adds a string with the given style to the end of this paragraph.
-    pub fn push_styled(&mut self, s: impl Into<String>, style: impl Into<Style>) {
-        self.text.push(StyledString::new(s, style))
+    /// Sets the horizontal gap between the columns.
+    pub fn set_gap(&mut self, gap: impl Into<Mm>) {
+        self.gap = gap.into();
     }
 
-    /// Adds a string with the given style to the end of this paragraph and returns the paragraph.
-    pub fn styled_string(mut self, s: impl Into<String>, style: impl Into<Style>) -> Self {
-        self.push_styled(s, style);
+    /// Sets the horizontal gap between the columns and returns the layout.
+    pub fn with_gap(mut self, gap: impl Into<Mm>) -> Self {
+        self.set_gap(gap);
         self
     }
 
-    fn get_offset(&self, width: Mm, max_width: Mm) -> Mm {
-        match self.alignment {
-            Alignment::Left => Mm::default(),
-            Alignment::Center => (max_width - width) / 2.0,
-            Alignment::Right => max_width - width,
-        }
-    }
-
-    fn apply_style(&mut self, style: Style) {
-        if !self.style_applied {
-            for s in &mut self.text {
-                s.style = style.and(s.style);
-            }
-            self.style_applied = true;
-        }
+    fn column_width(&self, area: &render::Area<'_>) -> Mm {
+        let gaps = self.gap * (self.columns - 1) as f32;
+        ((area.size().width - gaps) / self.columns as f32).max(Mm(0.0))
     }
-}
 
-impl Element for Paragraph {
-    fn render(
-        &mut self,
+    /// Returns the smallest column height that is sufficient to render the remaining content in
+    /// the columns, or the given maximum height if the content does not fit.
+    ///
+    /// The content is measured with a dry run in a column of the given area, see
+    /// [`Context::measure`][].
+    ///
+    /// [`Context::measure`]: ../struct.Context.html#method.measure
+    fn balanced_height(
+        &self,
+        clone: fn(&E) -> E,
         context: &Context,
-        mut area: render::Area<'_>,
+        area: &render::Area<'_>,
+        width: Mm,
         style: Style,
-    ) -> Result<RenderResult, Error> {
-        let mut result = RenderResult::default();
-
-        self.apply_style(style);
-
-        if self.words.is_empty() {
-            if self.text.is_empty() {
-                return Ok(result);
-            }
-            self.words = wrap::Words::new(mem::take(&mut self.text)).collect();
-        }
-
-        let words = self.words.iter().map(Into::into);
-        let mut rendered_len = 0;
-        let mut wrapper = wrap::Wrapper::new(words, context, area.size().width);
-        for (line, delta) in &mut wrapper {
-            let width = line.iter().map(|s| s.width(&context.font_cache)).sum();
-            // Calculate the maximum line height
-            let metrics = line
-                .iter()
-                .map(|s| s.style.metrics(&context.font_cache))
-                .fold(fonts::Metrics::default(), |max, m| max.max(&m));
-            let position = Position::new(self.get_offset(width, area.size().width), 0);
-
-            if let Some(mut section) = area.text_section(&context.font_cache, position, metrics) {
-                for s in line {
-                    section.print_str(&s.s, s.style)?;
-                    rendered_len += s.s.len();
+    ) -> Result<Mm, Error> {
+        let max_height = area.size().height;
+        let fits = |height: Mm| -> Result<bool, Error> {
+            let mut element = clone(&self.element);
+            let mut area = area.clone();
+            area.set_width(width);
+            area.set_height(height);
+            for _ in 0..self.columns {
+                if !context.measure(&mut element, area.clone(), style)?.has_more {
+                    return Ok(true);
                 }
-                rendered_len -= delta;
-            } else {
-                result.has_more = true;
-                break;
             }
-            result.size = result
-                .size
-                .stack_vertical(Size::new(width, metrics.line_height));
-            area.add_offset(Position::new(0, metrics.line_height));
-        }
+            Ok(false)
+        };
 
-        if wrapper.has_overflowed() {
-            return Err(Error::new(
-                "Page overflowed while trying to wrap a string",
-                ErrorKind::PageSizeExceeded,
-            ));
+        if !fits(max_height)? {
+            return Ok(max_height);
         }
-
-        // Remove the rendered data from self.words so that we don’t render it again on the next
-        // call to render.
-        while rendered_len > 0 && !self.words.is_empty() {
-            if self.words[0].s.len() <= rendered_len {
-                rendered_len -= self.words[0].s.len();
-                self.words.pop_front();
+        let mut min = Mm(0.0);
+        let mut max = max_height;
+        while max - min > Mm(0.1) {
+            let height = (min + max) / 2.0;
+            if fits(height)? {
+                max = height;
             } else {
-                self.words[0].s.replace_range(..rendered_len, "");
-                rendered_len = 0;
+                min = height;
             }
         }
-
-        Ok(result)
-    }
-}
-
-impl From<Vec<StyledString>> for Paragraph {
-    fn from(text: Vec<StyledString>) -> Paragraph {
-        Paragraph {
-            text,
-            ..Default::default()
-        }
-    }
-}
-
-impl<T: Into<StyledString>> iter::Extend<T> for Paragraph {
-    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-        for s in iter {
-            self.push(s);
-        }
+        Ok(max)
     }
 }
 
-impl<T: Into<StyledString>> iter::FromIterator<T> for Paragraph {
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let mut paragraph = Paragraph::default();
-        paragraph.extend(iter);
-        paragraph
+impl<E: Element + Clone> ColumnLayout<E> {
+    /// Sets whether the content should be balanced so that all columns end at roughly the same
+    /// height.
+    pub fn set_balanced(&mut self, balanced: bool) {
+        self.balance = if balanced { Some(E::clone) } else { None };
     }
-}
-
-/// A line break.
-///
-/// This element inserts a given number of empty lines.
-///
-/// # Example
-///
-/// ```
-/// // Draws 5 empty lines (calculating the line height using the current style)
-/// let b = rckive_genpdf::elements::Break::new(5.);
-/// ```
-#[derive(Clone, Copy, Debug, Default)]
-pub struct Break {
-    lines: f32,
-}
 
-impl Break {
-    /// Creates a new break with the given number of lines.
-    pub fn new(lines: impl Into<f32>) -> Break {
-        Break {
-            lines: lines.into(),
-        }
+    /// Sets whether the content should be balanced so that all columns end at roughly the same
+    /// height and returns the layout.
+    pub fn with_balanced(mut self, balanced: bool) -> Self {
+        self.set_balanced(balanced);
+        self
     }
 }
 
-impl Element for Break {
+impl<E: Element> Element for ColumnLayout<E> {
     fn render(
         &mut self,
         context: &Context,
         area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        let mut result = RenderResult::default();
-        if self.lines <= 0.0 {
-            return Ok(result);
-        }
-        let line_height = style.line_height(&context.font_cache);
-        let break_height = line_height * self.lines;
-        if break_height < area.size().height {
-            result.size.height = break_height;
-            self.lines = 0.0;
+        let width = self.column_width(&area);
+        let height = if let Some(clone) = self.balance {
+            self.balanced_height(clone, context, &area, width, style)?
         } else {
-            result.size.height = area.size().height;
-            self.lines -= result.size.height.0 / line_height.0;
+            area.size().height
+        };
+
+        let mut result = RenderResult::default();
+        for i in 0..self.columns {
+            let mut column_area = area.clone();
+            column_area.add_offset(Position::new((width + self.gap) * i as f32, 0));
+            column_area.set_size(Size::new(width, height));
+            let column_result = context.render_element(&mut self.element, column_area, style)?;
+            result.size.height = result.size.height.max(column_result.size.height);
+            result.has_more = column_result.has_more;
+            if !result.has_more {
+                break;
+            }
         }
+        result.size.width = area.size().width;
         Ok(result)
     }
 }
 
-/// A page break.
+/// A single line of formatted text.
 ///
-/// This element inserts a page break.
+/// This element renders a single styled string on a single line.  It does not wrap it if the
+/// string is longer than the line.  Therefore you should prefer [`Paragraph`][] over `Text` for
+/// most use cases.
 ///
-/// # Example
+/// Tab characters are replaced with spaces up to the next tab stop.  Per default, there is a tab
+/// stop every eight characters, see [`set_tab_width`][].  With a monospaced font, this aligns the
+/// text in columns.
 ///
-/// ```
-/// let pb = rckive_genpdf::elements::PageBreak::new();
-/// ```
-#[derive(Clone, Copy, Debug, Default)]
-pub struct PageBreak {
-    cont: bool,
-}
-
-impl PageBreak {
-    /// Creates a new page break.
-    pub fn new() -> PageBreak {
-        PageBreak::default()
+/// If a maximum width is set with [`set_ellipsis`][], text that is wider than the maximum width is
+/// truncated and an ellipsis (`…`) is inserted so that it fits into the maximum width.  Per
+/// default, the end of the text is removed.  Use [`set_truncation`][] to remove the start or the
+/// middle instead, and [`set_ellipsis_string`][] to use a different ellipsis.
+///
+/// Leading and trailing whitespace is rendered per default.  Use [`set_whitespace`][] to remove
+/// it instead.
+///
+/// [`Paragraph`]: struct.Paragraph.html
+/// [`set_tab_width`]: #method.set_tab_width
+/// [`set_ellipsis`]: #method.set_ellipsis
+/// [`set_truncation`]: #method.set_truncation
+/// [`set_ellipsis_string`]: #method.set_ellipsis_string
+/// [`set_whitespace`]: #method.set_whitespace
+#[derive(Clone, Debug)]
+pub struct Text {
+    text: StyledString,
+    tab_width: usize,
+    max_width: Option<Mm>,
+    truncation: Truncation,
+    ellipsis: String,
+    whitespace: Whitespace,
+    structure: Option<render::StructureElement>,
+}
+
+impl Text {
+    /// Creates a new instance with the given styled string.
+    pub fn new(text: impl Into<StyledString>) -> Text {
+        Text {
+            text: text.into(),
+            tab_width: 8,
+            max_width: None,
+            truncation: Truncation::End,
+            ellipsis: "…".to_owned(),
+            whitespace: Whitespace::Preserve,
+            structure: None,
+        }
+    }
+
+    /// Sets the distance between two tab stops in characters.
+    pub fn set_tab_width(&mut self, tab_width: usize) {
+        self.tab_width = tab_width;
+    }
+
+    /// Sets the distance between two tab stops in characters and returns the text.
+    pub fn with_tab_width(mut self, tab_width: usize) -> Self {
+        self.set_tab_width(tab_width);
+        self
+    }
+
+    /// Sets the maximum width of this text.
+    ///
+    /// If the text is wider than the maximum width, it is truncated and an ellipsis (`…`) is
+    /// inserted, see [`set_truncation`][].  If not even the ellipsis fits into the maximum width,
+    /// nothing is printed.
+    ///
+    /// [`set_truncation`]: #method.set_truncation
+    pub fn set_ellipsis(&mut self, max_width: impl Into<Mm>) {
+        self.max_width = Some(max_width.into());
+    }
+
+    /// Sets the maximum width of this text and returns the text.
+    ///
+    /// See [`set_ellipsis`][] for more information.
+    ///
+    /// [`set_ellipsis`]: #method.set_ellipsis
+    pub fn with_ellipsis(mut self, max_width: impl Into<Mm>) -> Self {
+        self.set_ellipsis(max_width);
+        self
+    }
+
+    /// Sets the part of this text that is removed if it is wider than the maximum width.
+    ///
+    /// The default is [`Truncation::End`][].  The setting is only used if a maximum width is set
+    /// with [`set_ellipsis`][].
+    ///
+    /// [`Truncation::End`]: ../enum.Truncation.html#variant.End
+    /// [`set_ellipsis`]: #method.set_ellipsis
+    pub fn set_truncation(&mut self, truncation: Truncation) {
+        self.truncation = truncation;
+    }
+
+    /// Sets the part of this text that is removed if it is wider than the maximum width and
+    /// returns the text.
+    pub fn with_truncation(mut self, truncation: Truncation) -> Self {
+        self.set_truncation(truncation);
+        self
+    }
+
+    /// Sets the string that is inserted if this text is truncated.
+    ///
+    /// The default is the horizontal ellipsis character (`…`).
+    pub fn set_ellipsis_string(&mut self, ellipsis: impl Into<String>) {
+        self.ellipsis = ellipsis.into();
+    }
+
+    /// Sets the string that is inserted if this text is truncated and returns the text.
+    pub fn with_ellipsis_string(mut self, ellipsis: impl Into<String>) -> Self {
+        self.set_ellipsis_string(ellipsis);
+        self
+    }
+
+    /// Sets the handling of leading and trailing whitespace for this text.
+    pub fn set_whitespace(&mut self, whitespace: Whitespace) {
+        self.whitespace = whitespace;
+    }
+
+    /// Sets the handling of leading and trailing whitespace for this text and returns the text.
+    pub fn with_whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.set_whitespace(whitespace);
+        self
     }
 }
 
-impl Element for PageBreak {
+impl Default for Text {
+    fn default() -> Text {
+        Text::new(StyledString::default())
+    }
+}
+
+impl Element for Text {
     fn render(
         &mut self,
-        _context: &Context,
-        _area: render::Area<'_>,
-        _style: Style,
+        context: &Context,
+        area: render::Area<'_>,
+        mut style: Style,
     ) -> Result<RenderResult, Error> {
-        if self.cont {
-            Ok(RenderResult::default())
+        let mut result = RenderResult::default();
+        render::check_text_width(&area)?;
+        style.merge(self.text.style);
+        if self.structure.is_none() {
+            self.structure = area.add_structure_element(render::Tag::Paragraph);
+        }
+        let _marked_content = self
+            .structure
+            .map(|element| area.begin_marked_content(element));
+        let mut metrics = style.metrics(&context.font_cache);
+        if context.line_overflow() == LineOverflow::Shrink
+            && metrics.glyph_height > area.size().height
+        {
+            shrink_font_size(&mut style, area.size().height.0 / metrics.glyph_height.0);
+            metrics = style.metrics(&context.font_cache);
+        }
+        let section = if context.line_overflow() == LineOverflow::Clip
+            || (context.line_overflow() == LineOverflow::Shrink
+                && metrics.glyph_height > area.size().height)
+        {
+            Some(area.clipped_text_section(&context.font_cache, Position::default(), metrics))
         } else {
-            // We don’t use (0,0) as the size as this might abort the render process if this is the
-            // first element on a new page, see the Rendering Process section of the crate
-            // documentation.
-            self.cont = true;
-            Ok(RenderResult {
-                size: Size::new(1, 0),
-                has_more: true,
-            })
+            area.text_section(&context.font_cache, Position::default(), metrics)
+        };
+        if let Some(mut section) = section {
+            let mut s = expand_tabs(&self.text.s, self.tab_width);
+            if self.whitespace == Whitespace::Trim {
+                s = s.trim().to_owned().into();
+            }
+            if let Some(max_width) = self.max_width {
+                s = ellipsize(
+                    &context.font_cache,
+                    s,
+                    style,
+                    max_width,
+                    &self.ellipsis,
+                    self.truncation,
+                );
+            }
+            section.print_str(&s, style)?;
+            result.size = Size::new(style.str_width(&context.font_cache, &s), section.height());
+        } else {
+            result.has_more = true;
+        }
+        Ok(result)
+    }
+
+    fn cell_metrics(&self, context: &Context, mut style: Style) -> CellMetrics {
+        style.merge(self.text.style);
+        let mut s = expand_tabs(&self.text.s, self.tab_width);
+        if self.whitespace == Whitespace::Trim {
+            s = s.trim().to_owned().into();
+        }
+        CellMetrics {
+            text_widths: Some(decimal_widths(
+                &context.font_cache,
+                iter::once((&*s, style)),
+            )),
+            padding: None,
         }
     }
 }
 
-/// Adds a padding to the wrapped element.
-///
-/// # Examples
-///
-/// Direct usage:
-/// ```
-/// use rckive_genpdf::elements;
-/// let p = elements::PaddedElement::new(
-///     elements::Paragraph::new("text"),
-///     rckive_genpdf::Margins::trbl(5, 2, 5, 10),
-/// );
-/// ```
-///
-/// Using [`Element::padded`][]:
-/// ```
-/// use rckive_genpdf::{elements, Element as _};
-/// let p = elements::Paragraph::new("text")
-///     .padded(rckive_genpdf::Margins::trbl(5, 2, 5, 10));
-/// ```
-///
-/// [`Element::padded`]: ../trait.Element.html#method.padded
-#[derive(Clone, Debug, Default)]
-pub struct PaddedElement<E: Element> {
-    element: E,
-    padding: Margins,
+/// Returns the width of the given runs before the decimal separator and the width of the
+/// separator and the following runs.
+fn decimal_widths<'a>(
+    font_cache: &fonts::FontCache,
+    runs: impl IntoIterator<Item = (&'a str, Style)>,
+) -> (Mm, Mm) {
+    let mut integer = Mm(0.0);
+    let mut fraction: Option<Mm> = None;
+    for (s, style) in runs {
+        match (&mut fraction, s.find('.')) {
+            (Some(fraction), _) => *fraction += style.str_width(font_cache, s),
+            (None, Some(idx)) => {
+                integer += style.str_width(font_cache, &s[..idx]);
+                fraction = Some(style.str_width(font_cache, &s[idx..]));
+            }
+            (None, None) => integer += style.str_width(font_cache, s),
+        }
+    }
+    (integer, fraction.unwrap_or_default())
 }
 
-impl<E: Element> PaddedElement<E> {
-    /// Creates a new padded element that wraps the given element with the given padding.
-    pub fn new(element: E, padding: impl Into<Margins>) -> PaddedElement<E> {
-        PaddedElement {
-            element,
-            padding: padding.into(),
+/// Replaces the tab characters in the given string with spaces up to the next tab stop.
+fn expand_tabs(s: &str, tab_width: usize) -> borrow::Cow<'_, str> {
+    if !s.contains('\t') || tab_width == 0 {
+        return s.into();
+    }
+    let mut expanded = String::with_capacity(s.len());
+    let mut column = 0;
+    for c in s.chars() {
+        if c == '\t' {
+            let spaces = tab_width - column % tab_width;
+            expanded.push_str(&" ".repeat(spaces));
+            column += spaces;
+        } else {
+            expanded.push(c);
+            column += 1;
         }
     }
+    expanded.into()
 }
 
-impl<E: Element> Element for PaddedElement<E> {
-    fn render(
-        &mut self,
-        context: &Context,
-        mut area: render::Area<'_>,
-        style: Style,
-    ) -> Result<RenderResult, Error> {
-        area.add_margins(Margins {
-            bottom: Mm(0.0),
-            ..self.padding
-        });
-        let mut result = self.element.render(context, area, style)?;
-        result.size.width += self.padding.left + self.padding.right;
-        result.size.height += self.padding.top + self.padding.bottom;
-        Ok(result)
+/// Truncates the given string and inserts the ellipsis so that it is not wider than the given
+/// width.
+///
+/// If not even the ellipsis fits into the given width, an empty string is returned.
+fn ellipsize<'s>(
+    font_cache: &fonts::FontCache,
+    s: borrow::Cow<'s, str>,
+    style: Style,
+    max_width: Mm,
+    ellipsis: &str,
+    truncation: Truncation,
+) -> borrow::Cow<'s, str> {
+    if style.str_width(font_cache, &s) <= max_width {
+        return s;
+    }
+    if style.str_width(font_cache, ellipsis) > max_width {
+        return borrow::Cow::Borrowed("");
+    }
+    let chars: Vec<char> = s.chars().collect();
+    let join = |head: usize, tail: usize| {
+        let mut truncated = String::with_capacity(s.len());
+        truncated.extend(&chars[..head]);
+        truncated.push_str(ellipsis);
+        truncated.extend(&chars[chars.len() - tail..]);
+        truncated
+    };
+    // The number of characters to keep from the start and from the end of the string
+    let (mut head, mut tail) = (0, 0);
+    while head + tail < chars.len() {
+        let (next_head, next_tail) = match truncation {
+            Truncation::Start => (head, tail + 1),
+            Truncation::Middle if tail < head => (head, tail + 1),
+            Truncation::Middle | Truncation::End => (head + 1, tail),
+        };
+        if style.str_width(font_cache, &join(next_head, next_tail)) > max_width {
+            break;
+        }
+        head = next_head;
+        tail = next_tail;
+    }
+    // Whitespace next to the ellipsis would make the truncation less obvious.
+    while head > 0 && chars[head - 1].is_whitespace() {
+        head -= 1;
+    }
+    while tail > 0 && chars[chars.len() - tail].is_whitespace() {
+        tail -= 1;
     }
+    join(head, tail).into()
 }
 
-/// Adds a default style to the wrapped element and its children.
+/// A format for numbers with a fixed number of decimals and grouped thousands, see
+/// [`NumberCell`][].
 ///
-/// # Examples
+/// Per default, numbers are formatted with two decimals, a comma as the thousands separator and a
+/// period as the decimal separator, for example `1,234,567.50`.
 ///
-/// Direct usage:
-/// ```
-/// use rckive_genpdf::{elements, style};
-/// let p = elements::StyledElement::new(
-///     elements::Paragraph::new("text"),
-///     style::Effect::Bold,
-/// );
-/// ```
+/// # Example
 ///
-/// Using [`Element::styled`][]:
 /// ```
-/// use rckive_genpdf::{elements, style, Element as _};
-/// let p = elements::Paragraph::new("text")
-///     .styled(style::Effect::Bold);
+/// use rckive_genpdf::elements;
+/// let format = elements::NumberFormat::new()
+///     .with_thousands_separator(Some('.'))
+///     .with_decimal_separator(',');
+/// assert_eq!("1.234.567,50", format.format(1234567.5));
 /// ```
 ///
-/// [`Element::styled`]: ../trait.Element.html#method.styled
-#[derive(Clone, Debug, Default)]
-pub struct StyledElement<E: Element> {
-    element: E,
-    style: Style,
+/// [`NumberCell`]: struct.NumberCell.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumberFormat {
+    decimals: usize,
+    thousands_separator: Option<char>,
+    decimal_separator: char,
 }
 
-impl<E: Element> StyledElement<E> {
-    /// Creates a new styled element that wraps the given element with the given style.
-    pub fn new(element: E, style: impl Into<Style>) -> StyledElement<E> {
-        StyledElement {
-            element,
-            style: style.into(),
+impl NumberFormat {
+    /// Creates a new number format with the default settings.
+    pub fn new() -> NumberFormat {
+        NumberFormat::default()
+    }
+
+    /// Sets the number of decimals.  The numbers are rounded to this number of decimals.
+    pub fn set_decimals(&mut self, decimals: usize) {
+        self.decimals = decimals;
+    }
+
+    /// Sets the number of decimals and returns the format.
+    pub fn with_decimals(mut self, decimals: usize) -> Self {
+        self.set_decimals(decimals);
+        self
+    }
+
+    /// Sets the separator that is inserted between groups of three digits, or `None` if the
+    /// digits should not be grouped.
+    pub fn set_thousands_separator(&mut self, separator: Option<char>) {
+        self.thousands_separator = separator;
+    }
+
+    /// Sets the separator that is inserted between groups of three digits and returns the format.
+    pub fn with_thousands_separator(mut self, separator: Option<char>) -> Self {
+        self.set_thousands_separator(separator);
+        self
+    }
+
+    /// Sets the separator between the integer part and the decimals.
+    pub fn set_decimal_separator(&mut self, separator: char) {
+        self.decimal_separator = separator;
+    }
+
+    /// Sets the separator between the integer part and the decimals and returns the format.
+    pub fn with_decimal_separator(mut self, separator: char) -> Self {
+        self.set_decimal_separator(separator);
+        self
+    }
+
+    /// Formats the given number.
+    ///
+    /// Numbers that are not finite are formatted with their default representation.
+    pub fn format(&self, value: f64) -> String {
+        let (integer, fraction) = self.format_parts(value);
+        integer + &fraction
+    }
+
+    /// Formats the given number and returns the integer part and the separator with the decimals.
+    fn format_parts(&self, value: f64) -> (String, String) {
+        if !value.is_finite() {
+            return (value.to_string(), String::new());
+        }
+        let formatted = format!("{:.*}", self.decimals, value.abs());
+        let (digits, decimals) = formatted.split_once('.').unwrap_or((&formatted, ""));
+        let mut integer = String::with_capacity(formatted.len() * 2);
+        if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            integer.push('-');
         }
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                integer.extend(self.thousands_separator);
+            }
+            integer.push(digit);
+        }
+        let fraction = if decimals.is_empty() {
+            String::new()
+        } else {
+            format!("{}{}", self.decimal_separator, decimals)
+        };
+        (integer, fraction)
     }
 }
 
-impl<E: Element> Element for StyledElement<E> {
-    fn render(
-        &mut self,
-        context: &Context,
-        area: render::Area<'_>,
-        mut style: Style,
-    ) -> Result<RenderResult, Error> {
-        style.merge(self.style);
-        self.element.render(context, area, style)
+impl Default for NumberFormat {
+    fn default() -> NumberFormat {
+        NumberFormat {
+            decimals: 2,
+            thousands_separator: Some(','),
+            decimal_separator: '.',
+        }
     }
 }
 
-/// Adds a frame around the wrapped element.
+/// A number that is formatted with a [`NumberFormat`][], typically used in a table.
 ///
-/// # Examples
+/// The number is rendered like a [`Text`][] element.  In a [`TableLayout`][] column with the
+/// [`ColumnAlignment::Decimal`][] alignment, the number is aligned on the decimal separator of
+/// the format, even if it is not a period.
 ///
-/// Direct usage:
-/// ```
-/// use rckive_genpdf::elements;
-/// let p = elements::FramedElement::new(
-///     elements::Paragraph::new("text"),
-/// );
-/// ```
+/// # Example
 ///
-/// Using [`Element::framed`][]:
 /// ```
-/// use rckive_genpdf::{elements, style, Element as _};
-/// let p = elements::Paragraph::new("text").framed(style::LineStyle::new());
+/// use rckive_genpdf::elements;
+/// let table = elements::TableLayout::new(vec![3, 1])
+///     .with_column_alignment(1, elements::ColumnAlignment::Decimal)
+///     .row()
+///     .element(elements::Text::new("Total"))
+///     .element(elements::NumberCell::new(1234567.5, elements::NumberFormat::new()))
+///     .push()
+///     .expect("Invalid table row");
 /// ```
 ///
-/// [`Element::framed`]: ../trait.Element.html#method.framed
-#[derive(Clone, Debug, Default)]
-pub struct FramedElement<E: Element> {
-    element: E,
-    is_first: bool,
-    line_style: LineStyle,
+/// [`NumberFormat`]: struct.NumberFormat.html
+/// [`Text`]: struct.Text.html
+/// [`TableLayout`]: struct.TableLayout.html
+/// [`ColumnAlignment::Decimal`]: enum.ColumnAlignment.html#variant.Decimal
+#[derive(Clone, Debug)]
+pub struct NumberCell {
+    text: Text,
+    integer: String,
+    fraction: String,
 }
 
-impl<E: Element> FramedElement<E> {
-    /// Creates a new framed element that wraps the given element.
-    pub fn new(element: E) -> FramedElement<E> {
-        FramedElement::with_line_style(element, LineStyle::new())
-    }
-
-    /// Creates a new framed element that wraps the given element,
-    /// and with the given line style.
-    pub fn with_line_style(element: E, line_style: impl Into<LineStyle>) -> FramedElement<E> {
-        Self {
-            is_first: true,
-            element,
-            line_style: line_style.into(),
+impl NumberCell {
+    /// Creates a new number cell with the given value and format.
+    pub fn new(value: f64, format: NumberFormat) -> NumberCell {
+        let (integer, fraction) = format.format_parts(value);
+        NumberCell {
+            text: Text::new(integer.clone() + &fraction),
+            integer,
+            fraction,
         }
     }
 }
 
-impl<E: Element> Element for FramedElement<E> {
+impl Element for NumberCell {
     fn render(
         &mut self,
         context: &Context,
         area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        // For the element area calculations, we have to take into account the full line thickness.
-        // For the frame area, we only need half because we specify the center of the line.
-        let line_thickness = self.line_style.thickness();
-        let line_offset = line_thickness / 2.0;
+        self.text.render(context, area, style)
+    }
 
-        // Calculate the areas in which to draw the element and the frame.
-        let mut element_area = area.clone();
-        let mut frame_area = area.clone();
-        element_area.add_margins(Margins::trbl(
-            0,
-            line_thickness,
-            line_thickness,
-            line_thickness,
-        ));
-        frame_area.add_margins(Margins::trbl(0, line_offset, 0, line_offset));
-        if self.is_first {
-            element_area.add_margins(Margins::trbl(line_thickness, 0, 0, 0));
-            frame_area.add_margins(Margins::trbl(line_offset, 0, 0, 0));
+    fn cell_metrics(&self, context: &Context, style: Style) -> CellMetrics {
+        CellMetrics {
+            text_widths: Some((
+                style.str_width(&context.font_cache, &self.integer),
+                style.str_width(&context.font_cache, &self.fraction),
+            )),
+            padding: None,
+        }
+    }
+}
+
+/// A multi-line wrapped paragraph of formatted text.
+///
+/// If the text of this paragraph is longer than the page width, the paragraph is wrapped at word
+/// borders (and additionally at string borders if it contains multiple strings).  If a word in the
+/// paragraph is longer than the page width, the text is truncated.
+///
+/// Use the [`push`][], [`string`][], [`push_styled`][] and [`string_styled`][] methods to add
+/// strings to this paragraph, and the [`push_link`][] and [`link`][] methods to add strings that
+/// link to a URI.  Besides the styling of the text (see [`Style`][]), you can also set an
+/// [`Alignment`][] for the paragraph.
+///
+/// The line height and spacing are calculated based on the style of each string.
+///
+/// Leading and trailing whitespace of the lines is rendered per default.  Use
+/// [`set_whitespace`][] to remove it instead.
+///
+/// # Examples
+///
+/// With setters:
+/// ```
+/// use rckive_genpdf::{elements, style};
+/// let mut p = elements::Paragraph::default();
+/// p.push("This is an ");
+/// p.push_styled("important", style::Color::Rgb(255, 0, 0));
+/// p.push(" message!");
+/// p.set_alignment(rckive_genpdf::Alignment::Center);
+/// ```
+///
+/// Chained:
+/// ```
+/// use rckive_genpdf::{elements, style};
+/// let p = elements::Paragraph::default()
+///     .string("This is an ")
+///     .styled_string("important", style::Color::Rgb(255, 0, 0))
+///     .string(" message, see ")
+///     .link("the website", "https://example.org")
+///     .string(" for details!")
+///     .aligned(rckive_genpdf::Alignment::Center);
+/// ```
+///
+/// [`Style`]: ../style/struct.Style.html
+/// [`Alignment`]: ../enum.Alignment.html
+/// [`Element::styled`]: ../trait.Element.html#method.styled
+/// [`push`]: #method.push
+/// [`push_styled`]: #method.push_styled
+/// [`string`]: #method.string
+/// [`string_styled`]: #method.string_styled
+/// [`push_link`]: #method.push_link
+/// [`link`]: #method.link
+/// [`set_whitespace`]: #method.set_whitespace
+#[derive(Clone, Debug)]
+pub struct Paragraph {
+    text: Vec<StyledString>,
+    words: collections::VecDeque<StyledString>,
+    // The byte ranges of the links in the words, followed by the text that has not been split
+    // into words yet
+    links: Vec<(ops::Range<usize>, String)>,
+    style_applied: bool,
+    alignment: Alignment,
+    last_line_alignment: Option<Alignment>,
+    justify_last_line: Option<f32>,
+    max_word_stretch: Option<f32>,
+    hanging_indent: Mm,
+    line_count: usize,
+    word_break: WordBreak,
+    whitespace: Whitespace,
+    preserve_spaces: bool,
+    tag: render::Tag,
+    structure: Option<render::StructureElement>,
+}
+
+impl Default for Paragraph {
+    fn default() -> Paragraph {
+        Paragraph {
+            text: Vec::new(),
+            words: collections::VecDeque::new(),
+            links: Vec::new(),
+            style_applied: false,
+            alignment: Alignment::default(),
+            last_line_alignment: None,
+            justify_last_line: None,
+            max_word_stretch: None,
+            hanging_indent: Mm(0.0),
+            line_count: 0,
+            word_break: WordBreak::default(),
+            whitespace: Whitespace::default(),
+            preserve_spaces: true,
+            tag: render::Tag::default(),
+            structure: None,
+        }
+    }
+}
+
+impl Paragraph {
+    /// Creates a new paragraph with the given content.
+    pub fn new(text: impl Into<StyledString>) -> Paragraph {
+        Paragraph {
+            text: vec![text.into()],
+            ..Default::default()
+        }
+    }
+
+    /// Sets the alignment of this paragraph.
+    ///
+    /// The alignment applies to all lines of the paragraph, including lines that have already
+    /// been split up for rendering, unless the last line has its own alignment (see
+    /// [`set_last_line_alignment`][]).
+    ///
+    /// [`set_last_line_alignment`]: #method.set_last_line_alignment
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        self.alignment = alignment;
+    }
+
+    /// Sets the alignment of this paragraph and returns the paragraph.
+    pub fn aligned(mut self, alignment: Alignment) -> Self {
+        self.set_alignment(alignment);
+        self
+    }
+
+    /// Sets the alignment of the last line of this paragraph, overriding the paragraph alignment.
+    ///
+    /// This can be used to center the last line of a justified paragraph, for example for
+    /// headings.  If the last line is set to [`Alignment::Justify`][], it is always justified,
+    /// regardless of the [`set_justify_last_line`][] setting.
+    ///
+    /// [`Alignment::Justify`]: ../enum.Alignment.html#variant.Justify
+    /// [`set_justify_last_line`]: #method.set_justify_last_line
+    pub fn set_last_line_alignment(&mut self, alignment: Alignment) {
+        self.last_line_alignment = Some(alignment);
+    }
+
+    /// Sets the alignment of the last line of this paragraph, overriding the paragraph alignment,
+    /// and returns the paragraph.
+    pub fn with_last_line_alignment(mut self, alignment: Alignment) -> Self {
+        self.set_last_line_alignment(alignment);
+        self
+    }
+
+    /// Justifies the last line of this paragraph if it fills at least the given ratio of the
+    /// available width.
+    ///
+    /// This setting is only used if the paragraph has the [`Alignment::Justify`][] alignment.
+    /// Per default, the last line of a justified paragraph is left-flushed.  The ratio is measured
+    /// without the additional word spacing, so `0.8` means that the last line is justified if the
+    /// words fill at least 80 percent of the line.  Use `0.0` to always justify the last line.
+    ///
+    /// [`Alignment::Justify`]: ../enum.Alignment.html#variant.Justify
+    pub fn set_justify_last_line(&mut self, min_fill_ratio: f32) {
+        self.justify_last_line = Some(min_fill_ratio);
+    }
+
+    /// Justifies the last line of this paragraph if it fills at least the given ratio of the
+    /// available width and returns the paragraph.
+    ///
+    /// See [`set_justify_last_line`][] for more information.
+    ///
+    /// [`set_justify_last_line`]: #method.set_justify_last_line
+    pub fn with_justify_last_line(mut self, min_fill_ratio: f32) -> Self {
+        self.set_justify_last_line(min_fill_ratio);
+        self
+    }
+
+    /// Limits how much the word spaces of a justified line may be stretched.
+    ///
+    /// This setting is only used if the paragraph has the [`Alignment::Justify`][] alignment.  The
+    /// ratio is the additional space relative to the natural width of the word spaces, so `1.0`
+    /// means that the spaces may be at most twice as wide as normal.  Lines that would have to be
+    /// stretched more, for example because the next word is very long, are left-flushed instead
+    /// to avoid large gaps.  Per default, the word spaces are stretched without limit.
+    ///
+    /// [`Alignment::Justify`]: ../enum.Alignment.html#variant.Justify
+    pub fn set_max_word_stretch(&mut self, max_ratio: f32) {
+        self.max_word_stretch = Some(max_ratio);
+    }
+
+    /// Limits how much the word spaces of a justified line may be stretched and returns the
+    /// paragraph.
+    ///
+    /// See [`set_max_word_stretch`][] for more information.
+    ///
+    /// [`set_max_word_stretch`]: #method.set_max_word_stretch
+    pub fn with_max_word_stretch(mut self, max_ratio: f32) -> Self {
+        self.set_max_word_stretch(max_ratio);
+        self
+    }
+
+    /// Sets the hanging indent of this paragraph.
+    ///
+    /// The first line of the paragraph starts at the left edge of its area, and all following
+    /// lines are indented by the given width, for example for bibliographies or definition
+    /// lists.  Per default, the lines are not indented.
+    pub fn set_hanging_indent(&mut self, indent: impl Into<Mm>) {
+        self.hanging_indent = indent.into();
+    }
+
+    /// Sets the hanging indent of this paragraph and returns the paragraph.
+    ///
+    /// See [`set_hanging_indent`][] for more information.
+    ///
+    /// [`set_hanging_indent`]: #method.set_hanging_indent
+    pub fn with_hanging_indent(mut self, indent: impl Into<Mm>) -> Self {
+        self.set_hanging_indent(indent);
+        self
+    }
+
+    /// Returns the number of lines of this paragraph that have been rendered so far.
+    ///
+    /// The lines of all calls to [`Element::render`][] are counted, so after the paragraph has
+    /// been rendered completely, this is the total number of lines it occupies, even if it has
+    /// been split across several pages.
+    ///
+    /// [`Element::render`]: ../trait.Element.html#tymethod.render
+    pub fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    /// Sets the fallback for words that are longer than a line.
+    ///
+    /// Per default, such words cause an error unless the paragraph is rendered in a table column
+    /// with the [`CellOverflow::Wrap`][] policy.  See [`WordBreak`][] for more information.
+    ///
+    /// [`CellOverflow::Wrap`]: enum.CellOverflow.html#variant.Wrap
+    /// [`WordBreak`]: ../enum.WordBreak.html
+    pub fn set_word_break(&mut self, word_break: WordBreak) {
+        self.word_break = word_break;
+    }
+
+    /// Sets the fallback for words that are longer than a line and returns the paragraph.
+    pub fn with_word_break(mut self, word_break: WordBreak) -> Self {
+        self.set_word_break(word_break);
+        self
+    }
+
+    /// Sets the handling of leading and trailing whitespace of the lines of this paragraph.
+    pub fn set_whitespace(&mut self, whitespace: Whitespace) {
+        self.whitespace = whitespace;
+    }
+
+    /// Sets the handling of leading and trailing whitespace of the lines of this paragraph and
+    /// returns the paragraph.
+    pub fn with_whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.set_whitespace(whitespace);
+        self
+    }
+
+    /// Sets whether consecutive spaces of this paragraph are preserved.
+    ///
+    /// Per default, every space is rendered with its advance width, so multiple spaces can be used
+    /// to align text.  If this is set to false, runs of consecutive spaces are collapsed into a
+    /// single space.  This only has an effect before the paragraph is rendered for the first time.
+    pub fn set_preserve_spaces(&mut self, preserve_spaces: bool) {
+        self.preserve_spaces = preserve_spaces;
+    }
+
+    /// Sets whether consecutive spaces of this paragraph are preserved and returns the paragraph.
+    pub fn with_preserve_spaces(mut self, preserve_spaces: bool) -> Self {
+        self.set_preserve_spaces(preserve_spaces);
+        self
+    }
+
+    /// Sets the structure tag of this paragraph, for example to mark it as a heading.
+    ///
+    /// The tag is only used if tagging is enabled for the document (see
+    /// [`Document::enable_tagging`][]).  The default tag is [`Tag::Paragraph`][].
+    ///
+    /// [`Document::enable_tagging`]: ../struct.Document.html#method.enable_tagging
+    /// [`Tag::Paragraph`]: ../render/enum.Tag.html#variant.Paragraph
+    pub fn set_tag(&mut self, tag: render::Tag) {
+        self.tag = tag;
+    }
+
+    /// Sets the structure tag of this paragraph and returns the paragraph.
+    pub fn with_tag(mut self, tag: render::Tag) -> Self {
+        self.set_tag(tag);
+        self
+    }
+
+    /// Adds a string to the end of this paragraph.
+    pub fn push(&mut self, s: impl Into<StyledString>) {
+        self.text.push(s.into());
+    }
+
+    /// Adds a string to the end of this paragraph and returns the paragraph.
+    pub fn string(mut self, s: impl Into<StyledString>) -> Self {
+        self.push(s);
+        self
+    }
+
+    /// Adds a string with the given style to the end of this paragraph.
+    pub fn push_styled(&mut self, s: impl Into<String>, style: impl Into<Style>) {
+        self.text.push(StyledString::new(s, style))
+    }
+
+    /// Adds a string with the given style to the end of this paragraph and returns the paragraph.
+    pub fn styled_string(mut self, s: impl Into<String>, style: impl Into<Style>) -> Self {
+        self.push_styled(s, style);
+        self
+    }
+
+    /// Adds a string that links to the given URI to the end of this paragraph.
+    ///
+    /// The link is added as a link annotation that covers the rendered string.  It does not
+    /// change the style of the string, so use for example [`Style::with_color`][] to make the link
+    /// visible.
+    ///
+    /// [`Style::with_color`]: ../style/struct.Style.html#method.with_color
+    pub fn push_link(&mut self, s: impl Into<StyledString>, uri: impl Into<String>) {
+        let s = s.into();
+        let start: usize = self.words.iter().chain(&self.text).map(|s| s.s.len()).sum();
+        self.links.push((start..start + s.s.len(), uri.into()));
+        self.text.push(s);
+    }
+
+    /// Adds a string that links to the given URI to the end of this paragraph and returns the
+    /// paragraph.
+    pub fn link(mut self, s: impl Into<StyledString>, uri: impl Into<String>) -> Self {
+        self.push_link(s, uri);
+        self
+    }
+
+    fn line_alignment(&self, is_last_line: bool) -> Alignment {
+        if is_last_line {
+            self.last_line_alignment.unwrap_or(self.alignment)
+        } else {
+            self.alignment
+        }
+    }
+
+    fn get_offset(&self, width: Mm, max_width: Mm, is_last_line: bool) -> Mm {
+        match self.line_alignment(is_last_line) {
+            Alignment::Left | Alignment::Justify => Mm::default(),
+            Alignment::Center => (max_width - width) / 2.0,
+            Alignment::Right => max_width - width,
+        }
+    }
+
+    /// Returns the additional space to insert after every word of the given line (except for the
+    /// last word) if this paragraph is justified.
+    fn get_word_spacing(
+        &self,
+        font_cache: &fonts::FontCache,
+        line: &[style::StyledCow<'_>],
+        max_width: Mm,
+        is_last_line: bool,
+    ) -> Mm {
+        if self.line_alignment(is_last_line) != Alignment::Justify {
+            return Mm(0.0);
+        }
+        let gaps = line[..line.len().saturating_sub(1)]
+            .iter()
+            .filter(|s| s.s.ends_with(' '));
+        let space_width: Mm = gaps
+            .clone()
+            .map(|s| s.style.char_width(font_cache, ' '))
+            .sum();
+        let gaps = gaps.count();
+        let last = if let Some(last) = line.last() {
+            last
+        } else {
+            return Mm(0.0);
+        };
+        if gaps == 0 {
+            return Mm(0.0);
+        }
+        // The trailing space of the last word is not visible, so it is not stretched.
+        let width = wrap::line_width(
+            font_cache,
+            line[..line.len() - 1]
+                .iter()
+                .map(|s| (s.s.as_ref(), s.style))
+                .chain(iter::once((last.s.trim_end(), last.style))),
+        );
+        if is_last_line && self.last_line_alignment.is_none() {
+            match self.justify_last_line {
+                Some(min_fill_ratio) if width.0 >= max_width.0 * min_fill_ratio => {}
+                _ => return Mm(0.0),
+            }
+        }
+        let stretch = (max_width - width).max(Mm(0.0));
+        if let Some(max_ratio) = self.max_word_stretch {
+            if stretch > space_width * max_ratio {
+                return Mm(0.0);
+            }
+        }
+        stretch / gaps as f32
+    }
+
+    fn apply_style(&mut self, style: Style) {
+        if !self.style_applied {
+            for s in &mut self.text {
+                s.style = style.and(s.style);
+            }
+            self.style_applied = true;
+        }
+    }
+}
+
+impl Element for Paragraph {
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+
+        self.apply_style(style);
+
+        if self.words.is_empty() {
+            if self.text.is_empty() {
+                return Ok(result);
+            }
+            let words = wrap::Words::new(mem::take(&mut self.text));
+            self.words = if self.preserve_spaces {
+                words.collect()
+            } else {
+                collapse_spaces(words, &mut self.links)
+            };
+        }
+
+        render::check_text_width(&area)?;
+        // The width of the indented lines may be negative if the hanging indent is wider than the
+        // area.  This is only an error if the paragraph does not fit on its first line.
+        let indent = self.hanging_indent.max(Mm(0.0));
+        let line_width = area.size().width - indent;
+
+        if self.structure.is_none() {
+            self.structure = area.add_structure_element(self.tag);
+        }
+        let _marked_content = self
+            .structure
+            .map(|element| area.begin_marked_content(element));
+
+        let total_len: usize = self.words.iter().map(|s| s.s.len()).sum();
+        let words = self.words.iter().map(Into::into);
+        let mut rendered_len = 0;
+        let word_break = match self.word_break {
+            WordBreak::Never => context.word_break().unwrap_or(self.word_break),
+            word_break => word_break,
+        };
+        let mut wrapper =
+            wrap::Wrapper::new(words, context, line_width).with_word_break(word_break);
+        if self.line_count == 0 {
+            wrapper = wrapper.with_first_line_width(area.size().width);
+        }
+        for (mut line, delta) in &mut wrapper {
+            // Only the first line of the paragraph is not indented
+            let (indent, max_width) = if self.line_count > 0 {
+                (indent, line_width)
+            } else {
+                (Mm(0.0), area.size().width)
+            };
+            let line_len = line.iter().map(|s| s.s.len()).sum::<usize>() - delta;
+            let is_last_line = rendered_len + line_len == total_len;
+            // The strings of a line are consecutive parts of the words, so we can look up the
+            // links by their byte offsets.
+            let mut links: Vec<_> = line
+                .iter()
+                .scan(rendered_len, |offset, s| {
+                    let start = *offset;
+                    *offset += s.s.len();
+                    Some(
+                        self.links
+                            .iter()
+                            .position(|(range, _)| range.contains(&start)),
+                    )
+                })
+                .collect();
+            if self.whitespace == Whitespace::Trim {
+                let kept = trim_line(&mut line);
+                links.truncate(kept.end);
+                links.drain(..kept.start);
+                if line.is_empty() {
+                    rendered_len += line_len;
+                    continue;
+                }
+            }
+
+            // The line overflow fallback only applies to the first line
+            let line_overflow = if result.size == Size::default() {
+                context.line_overflow()
+            } else {
+                LineOverflow::Error
+            };
+            // Calculate the maximum line height
+            let mut metrics = line_metrics(context, &line);
+            if line_overflow == LineOverflow::Shrink && metrics.glyph_height > area.size().height {
+                let factor = area.size().height.0 / metrics.glyph_height.0;
+                for s in &mut line {
+                    shrink_font_size(&mut s.style, factor);
+                }
+                metrics = line_metrics(context, &line);
+            }
+            // If the line does not fit with the minimum font size, it is clipped instead
+            let clip = line_overflow == LineOverflow::Clip
+                || (line_overflow == LineOverflow::Shrink
+                    && metrics.glyph_height > area.size().height);
+            let mut width = wrap::line_width(
+                &context.font_cache,
+                line.iter().map(|s| (s.s.as_ref(), s.style)),
+            );
+            let position =
+                Position::new(indent + self.get_offset(width, max_width, is_last_line), 0);
+            let word_spacing =
+                self.get_word_spacing(&context.font_cache, &line, max_width, is_last_line);
+            if word_spacing > Mm(0.0) {
+                width = max_width;
+            }
+            width += indent;
+
+            let section = if clip {
+                Some(area.clipped_text_section(&context.font_cache, position, metrics))
+            } else {
+                area.text_section(&context.font_cache, position, metrics)
+            };
+            let height = if let Some(mut section) = section {
+                let words = line.len();
+                let mut link_rects = Vec::new();
+                for (i, (s, link)) in line.into_iter().zip(links).enumerate() {
+                    section.print_str(&s.s, s.style)?;
+                    if let Some(link) = link {
+                        // The cursor position before printing the string does not include the
+                        // left side bearing shift at the start of the line, so we measure back from
+                        // the end of the string.  The link does not cover the trailing spaces.
+                        let end = section.cursor_position().x;
+                        let start = end - s.width(&context.font_cache);
+                        let width = s.style.str_width(&context.font_cache, s.s.trim_end());
+                        link_rects.push((link, start, width));
+                    }
+                    if word_spacing > Mm(0.0) && i + 1 < words && s.s.ends_with(' ') {
+                        section.add_space(word_spacing);
+                    }
+                }
+                rendered_len += line_len;
+                self.line_count += 1;
+                let height = section.height();
+                drop(section);
+                for (link, x, width) in link_rects {
+                    let size = Size::new(width, height);
+                    area.add_link(Position::new(x, 0), size, &self.links[link].1);
+                }
+                height
+            } else {
+                result.has_more = true;
+                break;
+            };
+            result.size = result.size.stack_vertical(Size::new(width, height));
+            area.add_offset(Position::new(0, height));
+        }
+
+        if wrapper.has_overflowed() {
+            if line_width <= Mm(0.0) {
+                return Err(Error::new(
+                    format!(
+                        "Cannot render a paragraph with a hanging indent of {} mm in an area with \
+                         a width of {} mm",
+                        indent.0,
+                        area.size().width.0
+                    ),
+                    ErrorKind::AreaTooSmall,
+                ));
+            }
+            return Err(Error::new(
+                "Page overflowed while trying to wrap a string",
+                ErrorKind::PageSizeExceeded,
+            ));
+        }
+
+        // Remove the rendered data from self.words so that we don’t render it again on the next
+        // call to render.
+        self.links.retain_mut(|(range, _)| {
+            range.start = range.start.saturating_sub(rendered_len);
+            range.end = range.end.saturating_sub(rendered_len);
+            range.start < range.end
+        });
+        while rendered_len > 0 && !self.words.is_empty() {
+            if self.words[0].s.len() <= rendered_len {
+                rendered_len -= self.words[0].s.len();
+                self.words.pop_front();
+            } else {
+                self.words[0].s.replace_range(..rendered_len, "");
+                rendered_len = 0;
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn cell_metrics(&self, context: &Context, style: Style) -> CellMetrics {
+        // Before the first call to render, the text has not been split into words yet.
+        let runs = self.text.iter().chain(&self.words).map(|s| {
+            let run_style = if self.style_applied {
+                s.style
+            } else {
+                style.and(s.style)
+            };
+            (s.s.as_str(), run_style)
+        });
+        CellMetrics {
+            text_widths: Some(decimal_widths(&context.font_cache, runs)),
+            padding: None,
+        }
+    }
+}
+
+/// Drops the words that only consist of a space and follow a word that ends with a space, and
+/// moves the given byte ranges of links accordingly.
+///
+/// The words are split after every space, so a run of consecutive spaces is split into one word
+/// per space.
+fn collapse_spaces(
+    words: impl IntoIterator<Item = StyledString>,
+    links: &mut [(ops::Range<usize>, String)],
+) -> collections::VecDeque<StyledString> {
+    let mut collapsed = collections::VecDeque::new();
+    let mut ends_with_space = false;
+    let mut offset = 0;
+    for word in words {
+        if word.s == " " && ends_with_space {
+            for (range, _) in links.iter_mut() {
+                if range.start > offset {
+                    range.start -= 1;
+                }
+                if range.end > offset {
+                    range.end -= 1;
+                }
+            }
+            continue;
+        }
+        ends_with_space = word.s.ends_with(' ');
+        offset += word.s.len();
+        collapsed.push_back(word);
+    }
+    collapsed
+}
+
+/// Removes the leading and trailing whitespace of the given line, dropping strings that only
+/// contain whitespace, and returns the range of the strings that were kept.
+fn trim_line(line: &mut Vec<style::StyledCow<'_>>) -> ops::Range<usize> {
+    let start = line
+        .iter()
+        .position(|s| !s.s.trim_start().is_empty())
+        .unwrap_or(line.len());
+    line.drain(..start);
+    while line.last().map(|s| s.s.trim_end().is_empty()) == Some(true) {
+        line.pop();
+    }
+    if let Some(first) = line.first_mut() {
+        first.s = first.s.trim_start().to_owned().into();
+    }
+    if let Some(last) = line.last_mut() {
+        last.s = last.s.trim_end().to_owned().into();
+    }
+    start..start + line.len()
+}
+
+/// Returns the maximum metrics of the strings in the given line.
+fn line_metrics(context: &Context, line: &[style::StyledCow<'_>]) -> fonts::Metrics {
+    line.iter()
+        .map(|s| s.style.metrics(&context.font_cache))
+        .fold(fonts::Metrics::default(), |max, m| max.max(&m))
+}
+
+/// Scales the font size of the given style with the given factor, rounding down so that the
+/// scaled text is not higher than expected, but not below the minimum font size of the style.
+fn shrink_font_size(style: &mut Style, factor: f32) {
+    let font_size = (f32::from(style.font_size()) * factor).floor();
+    let min_font_size = style.min_font_size().min(style.font_size());
+    style.set_font_size(font_size.max(f32::from(min_font_size)) as u8);
+}
+
+impl From<Vec<StyledString>> for Paragraph {
+    fn from(text: Vec<StyledString>) -> Paragraph {
+        Paragraph {
+            text,
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Into<StyledString>> iter::Extend<T> for Paragraph {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for s in iter {
+            self.push(s);
+        }
+    }
+}
+
+impl<T: Into<StyledString>> iter::FromIterator<T> for Paragraph {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut paragraph = Paragraph::default();
+        paragraph.extend(iter);
+        paragraph
+    }
+}
+
+/// A line break.
+///
+/// This element inserts a given number of empty lines or a vertical space with a fixed height.
+/// If the break does not fit on the current page, it ends at the bottom of the page.
+///
+/// # Example
+///
+/// ```
+/// // Draws 5 empty lines (calculating the line height using the current style)
+/// let b = rckive_genpdf::elements::Break::new(5.);
+/// // Inserts a vertical space of 12 mm
+/// let b = rckive_genpdf::elements::Break::with_height(12);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Break {
+    lines: f32,
+    height: Option<Mm>,
+}
+
+impl Break {
+    /// Creates a new break with the given number of lines.
+    pub fn new(lines: impl Into<f32>) -> Break {
+        Break {
+            lines: lines.into(),
+            height: None,
+        }
+    }
+
+    /// Creates a new break with the given height that does not depend on the current style.
+    pub fn with_height(height: impl Into<Mm>) -> Break {
+        Break {
+            lines: 0.0,
+            height: Some(height.into()),
+        }
+    }
+}
+
+impl Element for Break {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if let Some(height) = self.height {
+            if height < area.size().height {
+                result.size.height = height.max(Mm(0.0));
+                self.height = Some(Mm(0.0));
+            } else {
+                result.size.height = area.size().height;
+                self.height = Some(height - result.size.height);
+            }
+            return Ok(result);
+        }
+        if self.lines <= 0.0 {
+            return Ok(result);
+        }
+        let line_height = style.line_height(&context.font_cache);
+        let break_height = line_height * self.lines;
+        if break_height < area.size().height {
+            result.size.height = break_height;
+            self.lines = 0.0;
+        } else {
+            result.size.height = area.size().height;
+            self.lines -= result.size.height.0 / line_height.0;
+        }
+        Ok(result)
+    }
+
+    fn is_spacing(&self) -> bool {
+        true
+    }
+}
+
+/// A horizontal line across the width of the area.
+///
+/// The height of the rule is the thickness of its line.  If the rule does not fit on the current
+/// page, it is moved to the next page.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::{elements, style};
+/// // Uses the default line style of the document
+/// let rule = elements::HorizontalRule::new();
+/// let rule = elements::HorizontalRule::with_line_style(style::Color::Greyscale(128));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HorizontalRule {
+    line_style: Option<LineStyle>,
+}
+
+impl HorizontalRule {
+    /// Creates a new horizontal rule with the default line style of the document, see
+    /// [`Document::set_default_line_style`][].
+    ///
+    /// [`Document::set_default_line_style`]: ../struct.Document.html#method.set_default_line_style
+    pub fn new() -> HorizontalRule {
+        HorizontalRule::default()
+    }
+
+    /// Creates a new horizontal rule with the given line style.
+    pub fn with_line_style(line_style: impl Into<LineStyle>) -> HorizontalRule {
+        HorizontalRule {
+            line_style: Some(line_style.into()),
+        }
+    }
+}
+
+impl Element for HorizontalRule {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        let line_style = self
+            .line_style
+            .unwrap_or_else(|| context.default_line_style());
+        let thickness = line_style.thickness();
+        if thickness > area.size().height {
+            result.has_more = true;
+            return Ok(result);
+        }
+        // The line position is the center of the line.
+        let y = thickness / 2.0;
+        area.draw_line(
+            vec![Position::new(0, y), Position::new(area.size().width, y)],
+            line_style,
+        );
+        result.size = Size::new(area.size().width, thickness);
+        Ok(result)
+    }
+}
+
+/// A page break.
+///
+/// This element inserts a page break so that the next element starts on a new page.  If the page
+/// break is rendered at the start of a page (see [`Area::is_page_start`][]), it is ignored.
+///
+/// The page break can also change the paper size of the following pages, see
+/// [`with_paper_size`][].
+///
+/// # Example
+///
+/// ```
+/// let pb = rckive_genpdf::elements::PageBreak::new();
+/// ```
+///
+/// [`Area::is_page_start`]: ../render/struct.Area.html#method.is_page_start
+/// [`with_paper_size`]: #method.with_paper_size
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PageBreak {
+    cont: bool,
+    paper_size: Option<Size>,
+}
+
+impl PageBreak {
+    /// Creates a new page break.
+    pub fn new() -> PageBreak {
+        PageBreak::default()
+    }
+
+    /// Creates a new page break that changes the paper size of all following pages.
+    ///
+    /// If the page break is ignored because it is rendered at the start of a page, the current
+    /// page keeps its paper size and the new paper size is used from the next page on.
+    pub fn with_paper_size(paper_size: impl Into<Size>) -> PageBreak {
+        PageBreak {
+            cont: false,
+            paper_size: Some(paper_size.into()),
+        }
+    }
+}
+
+impl Element for PageBreak {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        if !self.cont {
+            if let Some(paper_size) = self.paper_size {
+                context.set_next_paper_size(paper_size);
+            }
+        }
+        if self.cont || area.is_page_start() {
+            // There is nothing on the current page, so we don’t need a new page.
+            self.cont = true;
+            Ok(RenderResult::default())
+        } else {
+            // We don’t use (0,0) as the size as this might abort the render process if this is the
+            // first element on a new page, see the Rendering Process section of the crate
+            // documentation.
+            self.cont = true;
+            Ok(RenderResult {
+                size: Size::new(1, 0),
+                has_more: true,
+            })
+        }
+    }
+}
+
+/// A page of another PDF document.
+///
+/// The page is drawn at the upper left corner of the area with its original size.  If it is
+/// wider than the area, it is scaled down, preserving its aspect ratio, so that it fits into the
+/// area.  If it is higher than the remaining space on the page, it is moved to the next page, and
+/// only scaled down if it is higher than the area there.  See [`render::ImportedPage`][] for more information on how the page is imported.
+///
+/// To use the page as a template, for example a form that is filled out, draw it in a
+/// [`PageDecorator`][] and return the unchanged area so that the document content is rendered on
+/// top of it.
+///
+/// # Example
+///
+/// ```no_run
+/// use rckive_genpdf::elements;
+/// let data = std::fs::read("template.pdf").expect("Failed to read template");
+/// let page = elements::PdfPage::from_bytes(&data, 0).expect("Failed to import page");
+/// ```
+///
+/// [`render::ImportedPage`]: ../render/struct.ImportedPage.html
+/// [`PageDecorator`]: ../trait.PageDecorator.html
+#[derive(Clone, Debug)]
+pub struct PdfPage {
+    page: render::ImportedPage,
+    is_deferred: bool,
+}
+
+impl PdfPage {
+    /// Imports the page with the given index (starting at zero) from the given PDF document.
+    ///
+    /// See [`render::ImportedPage::from_bytes`][] for more information.
+    ///
+    /// [`render::ImportedPage::from_bytes`]: ../render/struct.ImportedPage.html#method.from_bytes
+    pub fn from_bytes(data: &[u8], page_index: usize) -> Result<PdfPage, Error> {
+        render::ImportedPage::from_bytes(data, page_index).map(PdfPage::from)
+    }
+}
+
+impl From<render::ImportedPage> for PdfPage {
+    fn from(page: render::ImportedPage) -> PdfPage {
+        PdfPage {
+            page,
+            is_deferred: false,
+        }
+    }
+}
+
+impl Element for PdfPage {
+    fn render(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        let size = self.page.size();
+        let mut scale = (area.size().width.0 / size.width.0).min(1.0);
+        if size.height * scale > area.size().height {
+            // The page might fit into the area on the next page, so we only shrink it to the
+            // remaining height if it is already at the start of a page or has been moved.
+            if !area.is_page_start() && !self.is_deferred {
+                self.is_deferred = true;
+                return Ok(RenderResult {
+                    size: Size::new(0, 0),
+                    has_more: true,
+                });
+            }
+            scale = scale.min(area.size().height.0 / size.height.0);
+        }
+        if scale <= 0.0 {
+            return Ok(RenderResult {
+                size: Size::new(0, 0),
+                has_more: true,
+            });
+        }
+        self.is_deferred = false;
+        area.draw_imported_page(&self.page, Position::default(), scale);
+        Ok(RenderResult {
+            size: Size::new(size.width * scale, size.height * scale),
+            has_more: false,
+        })
+    }
+}
+
+/// An element that renders a closure.
+///
+/// This element can be used for custom content without implementing the [`Element`][] trait.  The
+/// closure is called with the same arguments as [`Element::render`][] and is called again with the
+/// area on the next page if it returns a [`RenderResult`][] with `has_more` set to true.  It may
+/// capture and modify state, for example to keep track of the content that is still to be
+/// rendered.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::{elements, style, Position, RenderResult, Size};
+/// let line = elements::FromFn(|_context, area, _style| {
+///     area.draw_line(
+///         vec![Position::new(0, 0), Position::new(area.size().width, 0)],
+///         style::LineStyle::new(),
+///     );
+///     Ok(RenderResult {
+///         size: Size::new(area.size().width, 1),
+///         has_more: false,
+///     })
+/// });
+/// ```
+///
+/// [`Element`]: ../trait.Element.html
+/// [`Element::render`]: ../trait.Element.html#tymethod.render
+/// [`RenderResult`]: ../struct.RenderResult.html
+pub struct FromFn<F>(pub F)
+where
+    F: FnMut(&Context, render::Area<'_>, Style) -> Result<RenderResult, Error>;
+
+impl<F> FromFn<F>
+where
+    F: FnMut(&Context, render::Area<'_>, Style) -> Result<RenderResult, Error>,
+{
+    /// Creates a new element that renders the given closure.
+    pub fn new(f: F) -> FromFn<F> {
+        FromFn(f)
+    }
+}
+
+impl<F> Element for FromFn<F>
+where
+    F: FnMut(&Context, render::Area<'_>, Style) -> Result<RenderResult, Error>,
+{
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        (self.0)(context, area, style)
+    }
+}
+
+/// Inserts a page break before the wrapped element.
+///
+/// See [`PageBreak`][] for more information.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::{elements, Element as _};
+/// let p = elements::Paragraph::new("Chapter 2").with_page_break_before();
+/// ```
+///
+/// [`PageBreak`]: struct.PageBreak.html
+#[derive(Clone, Debug, Default)]
+pub struct PageBreakBefore<E: Element> {
+    page_break: PageBreak,
+    element: E,
+}
+
+impl<E: Element> PageBreakBefore<E> {
+    /// Creates a new wrapper that inserts a page break before the given element.
+    pub fn new(element: E) -> PageBreakBefore<E> {
+        PageBreakBefore {
+            page_break: PageBreak::new(),
+            element,
+        }
+    }
+}
+
+impl<E: Element> Element for PageBreakBefore<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let result = context.render_element(&mut self.page_break, area.clone(), style)?;
+        if result.has_more {
+            Ok(result)
+        } else {
+            context.render_element(&mut self.element, area, style)
+        }
+    }
+
+    fn cell_metrics(&self, context: &Context, style: Style) -> CellMetrics {
+        self.element.cell_metrics(context, style)
+    }
+}
+
+/// Keeps the wrapped element together with the following content.
+///
+/// The wrapped element is only rendered on the current page if it fits completely and there is at
+/// least the given amount of space left below it.  Otherwise, the complete element is moved to the
+/// next page.  This can be used to make sure that a heading is not the last element on a page.  At
+/// the start of a page (see [`Area::is_page_start`][]) or if the element has already been moved
+/// once, the full area is used, so the element is never moved more than once, even if it is
+/// rendered in a padded area or if it is higher than a page together with the reserved space.
+///
+/// The wrapped element is measured with a clone before it is rendered (see
+/// [`Context::measure`][]), so it has to implement [`Clone`][].  This excludes elements that
+/// cannot be cloned, like [`LinearLayout`][] and [`TableLayout`][].  To keep such a layout with
+/// the following content, wrap its first element instead.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::{elements, Element as _};
+/// let heading = elements::Paragraph::new("Chapter 2").keep_with_next(20);
+/// ```
+///
+/// [`Area::is_page_start`]: ../render/struct.Area.html#method.is_page_start
+/// [`Context::measure`]: ../struct.Context.html#method.measure
+/// [`Clone`]: https://doc.rust-lang.org/std/clone/trait.Clone.html
+/// [`LinearLayout`]: struct.LinearLayout.html
+/// [`TableLayout`]: struct.TableLayout.html
+#[derive(Clone, Debug, Default)]
+pub struct KeepWithNext<E: Element> {
+    element: E,
+    space: Mm,
+    is_placed: bool,
+}
+
+impl<E: Element> KeepWithNext<E> {
+    /// Creates a new wrapper that keeps the given space below the given element.
+    pub fn new(element: E, space: impl Into<Mm>) -> KeepWithNext<E> {
+        KeepWithNext {
+            element,
+            space: space.into(),
+            is_placed: false,
+        }
+    }
+}
+
+impl<E: Element + Clone> Element for KeepWithNext<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        if !area.is_page_start() && !self.is_placed {
+            let mut measure_area = area.clone();
+            measure_area.set_height((area.size().height - self.space).max(Mm(0.0)));
+            let result = context.measure(&mut self.element.clone(), measure_area, style)?;
+            if result.has_more {
+                // Moving the element again would not give it more space, so we render it in the
+                // next area in any case.
+                self.is_placed = true;
+                return Ok(RenderResult {
+                    has_more: true,
+                    ..Default::default()
+                });
+            }
+        }
+        self.is_placed = true;
+        context.render_element(&mut self.element, area, style)
+    }
+
+    fn cell_metrics(&self, context: &Context, style: Style) -> CellMetrics {
+        self.element.cell_metrics(context, style)
+    }
+}
+
+/// Records the page and the position of the wrapped element during the rendering process.
+///
+/// The destination of the element can be retrieved with the [`ElementHandle`][] returned by
+/// [`handle`][] after the document has been rendered, for example to build a table of contents or
+/// cross-references.  The destination is the upper left corner of the area that the element was
+/// first rendered in.  If the element is moved to the next page because it did not fit on the
+/// current page, the destination is on the next page.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::elements;
+/// let heading = elements::TrackedElement::new(elements::Paragraph::new("Introduction"));
+/// let handle = heading.handle();
+/// // push the heading to the document and render it, then:
+/// if let Some(destination) = handle.destination() {
+///     println!("Introduction: page {}", destination.page);
+/// }
+/// ```
+///
+/// [`ElementHandle`]: ../struct.ElementHandle.html
+/// [`handle`]: #method.handle
+#[derive(Clone, Debug, Default)]
+pub struct TrackedElement<E: Element> {
+    element: E,
+    handle: ElementHandle,
+}
+
+impl<E: Element> TrackedElement<E> {
+    /// Creates a new wrapper that records the destination of the given element.
+    pub fn new(element: E) -> TrackedElement<E> {
+        TrackedElement {
+            element,
+            handle: ElementHandle::default(),
+        }
+    }
+
+    /// Returns a handle that can be used to retrieve the destination of the wrapped element after
+    /// it has been rendered.
+    pub fn handle(&self) -> ElementHandle {
+        self.handle.clone()
+    }
+}
+
+impl<E: Element> Element for TrackedElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let destination = Destination::new(area.page_number(), area.origin());
+        let result = context.render_element(&mut self.element, area, style)?;
+        // If nothing has been rendered, the element is moved to the next page.
+        if (result.size != Size::default() || !result.has_more) && !context.is_dry_run() {
+            self.handle.set_destination(destination);
+        }
+        Ok(result)
+    }
+
+    fn is_spacing(&self) -> bool {
+        self.element.is_spacing()
+    }
+
+    fn cell_metrics(&self, context: &Context, style: Style) -> CellMetrics {
+        self.element.cell_metrics(context, style)
+    }
+}
+
+/// The page margin that a [`MarginNote`][] is placed in.
+///
+/// [`MarginNote`]: struct.MarginNote.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum MarginSide {
+    /// The left margin.
+    Left,
+    /// The right margin.
+    Right,
+    /// The outer margin, i. e. the right margin on odd pages and the left margin on even pages.
+    #[default]
+    Outer,
+    /// The inner margin, i. e. the left margin on odd pages and the right margin on even pages.
+    Inner,
+}
+
+impl MarginSide {
+    fn is_left(&self, page_number: usize) -> bool {
+        let is_odd = page_number % 2 == 1;
+        match self {
+            MarginSide::Left => true,
+            MarginSide::Right => false,
+            MarginSide::Outer => !is_odd,
+            MarginSide::Inner => is_odd,
+        }
+    }
+}
+
+/// Adds a note in the page margin next to the wrapped element.
+///
+/// The note is rendered in the margin between the edge of the page and the content area of the
+/// page (see [`Context::content_area`][]), keeping the gap set with [`set_gap`][] to both of them,
+/// even if the wrapped element is nested in a table or in a column.  Outside of a document, the
+/// area of the wrapped element is used instead of the content area.  Its upper edge is aligned
+/// with the upper edge of the first part of the wrapped element that is rendered.  Per default,
+/// the note is placed in the outer margin, see [`MarginSide`][].
+///
+/// The note is rendered once and does not affect the layout of the document.  If it does not fit
+/// into the margin, it is truncated.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::{elements, Element as _};
+/// let p = elements::Paragraph::new("Newton’s first law states that …")
+///     .with_margin_note(elements::Paragraph::new("see also p. 12"));
+/// ```
+///
+/// [`Context::content_area`]: ../struct.Context.html#method.content_area
+/// [`set_gap`]: #method.set_gap
+/// [`MarginSide`]: enum.MarginSide.html
+pub struct MarginNote<E: Element> {
+    element: E,
+    note: Box<dyn Element>,
+    side: MarginSide,
+    gap: Mm,
+    is_rendered: bool,
+}
+
+impl<E: Element> MarginNote<E> {
+    /// Creates a new wrapper that adds the given note in the outer margin next to the given
+    /// element.
+    pub fn new(element: E, note: impl IntoBoxedElement) -> MarginNote<E> {
+        MarginNote {
+            element,
+            note: note.into_boxed_element(),
+            side: MarginSide::default(),
+            gap: Mm::from(3),
+            is_rendered: false,
+        }
+    }
+
+    /// Sets the margin that the note is placed in.
+    pub fn set_side(&mut self, side: MarginSide) {
+        self.side = side;
+    }
+
+    /// Sets the margin that the note is placed in and returns the wrapper.
+    pub fn with_side(mut self, side: MarginSide) -> Self {
+        self.set_side(side);
+        self
+    }
+
+    /// Sets the gap between the note and the page edge and between the note and the wrapped
+    /// element.  The default gap is 3 mm.
+    pub fn set_gap(&mut self, gap: impl Into<Mm>) {
+        self.gap = gap.into();
+    }
+
+    /// Sets the gap between the note and the page edge and between the note and the wrapped
+    /// element and returns the wrapper.
+    pub fn with_gap(mut self, gap: impl Into<Mm>) -> Self {
+        self.set_gap(gap);
+        self
+    }
+
+    fn render_note(
+        &mut self,
+        context: &Context,
+        area: &render::Area<'_>,
+        style: Style,
+    ) -> Result<(), Error> {
+        let mut note_area = area.page_area();
+        let page_width = note_area.size().width;
+        let origin = area.origin();
+        let (content_origin, content_size) =
+            context.content_area().unwrap_or((origin, area.size()));
+        let (left, right) = if self.side.is_left(area.page_number()) {
+            (self.gap, content_origin.x - self.gap)
+        } else {
+            (
+                content_origin.x + content_size.width + self.gap,
+                page_width - self.gap,
+            )
+        };
+        if right <= left {
+            return Ok(());
+        }
+        note_area.add_offset(Position::new(left, origin.y));
+        note_area.set_width(right - left);
+        context.render_element(self.note.as_mut(), note_area, style)?;
+        Ok(())
+    }
+}
+
+impl<E: Element> Element for MarginNote<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let result = context.render_element(&mut self.element, area.clone(), style)?;
+        // If nothing has been rendered, the element is moved to the next page.
+        let is_rendered = result.size != Size::default() || !result.has_more;
+        if is_rendered && !self.is_rendered && !context.is_dry_run() {
+            self.render_note(context, &area, style)?;
+            self.is_rendered = true;
+        }
+        Ok(result)
+    }
+
+    fn is_spacing(&self) -> bool {
+        self.element.is_spacing()
+    }
+
+    fn cell_metrics(&self, context: &Context, style: Style) -> CellMetrics {
+        self.element.cell_metrics(context, style)
+    }
+}
+
+/// Adds a named destination at the current position, so that PDF viewers can jump to it.
+///
+/// This element does not render anything and does not take up space.  The destination points at
+/// the upper left corner of the area that this element is rendered in.  It can be used as the
+/// initial view of the document, see [`Document::set_open_destination`][].
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::elements;
+/// let destination = elements::NamedDestination::new("chapter-2");
+/// ```
+///
+/// [`Document::set_open_destination`]: ../struct.Document.html#method.set_open_destination
+#[derive(Clone, Debug, Default)]
+pub struct NamedDestination {
+    name: String,
+}
+
+impl NamedDestination {
+    /// Creates a new named destination with the given name.
+    pub fn new(name: impl Into<String>) -> NamedDestination {
+        NamedDestination { name: name.into() }
+    }
+}
+
+impl Element for NamedDestination {
+    fn render(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        area.add_named_destination(self.name.clone(), Position::default());
+        Ok(RenderResult::default())
+    }
+}
+
+/// An unsigned signature form field with a name and a fixed size.
+///
+/// The field is added to the interactive form of the document at the position of this element, so
+/// that signing tools can sign the document in this field.  The field itself is invisible, so it
+/// is typically combined with a label or a line, for example in a [`LinearLayout`][].  If the
+/// field does not fit into the remaining space of the page, it is moved to the next page.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::elements;
+/// let field = elements::SignatureField::new("customer", (60, 20));
+/// ```
+///
+/// [`LinearLayout`]: struct.LinearLayout.html
+#[derive(Clone, Debug, Default)]
+pub struct SignatureField {
+    name: String,
+    size: Size,
+}
+
+impl SignatureField {
+    /// Creates a new signature field with the given name and size.
+    pub fn new(name: impl Into<String>, size: impl Into<Size>) -> SignatureField {
+        SignatureField {
+            name: name.into(),
+            size: size.into(),
+        }
+    }
+}
+
+impl Element for SignatureField {
+    fn render(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        if self.size.height > area.size().height {
+            return Ok(RenderResult {
+                size: Size::new(0, 0),
+                has_more: true,
+            });
+        }
+        area.add_signature_field(self.name.clone(), Position::default(), self.size);
+        Ok(RenderResult {
+            size: self.size,
+            has_more: false,
+        })
+    }
+}
+
+/// Renders the wrapped element at a fixed position on the page, independent of the flow layout.
+///
+/// The position is relative to the upper left corner of the content area of the page, i. e. the
+/// area returned by the page decorator (see [`Context::content_area`][]).  If the element is not
+/// rendered by a [`Document`][], the position is relative to the upper left corner of the page.
+/// The wrapped element is rendered into the rest of the content area below and to the right of
+/// the position, and it is only rendered on the page that this element is rendered on.  This
+/// element does not take up space in the flow layout, so the following elements are not
+/// displaced.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::{elements, Position};
+/// let stamp = elements::Positioned::new(elements::Text::new("PAID"), Position::new(120, 50));
+/// ```
+///
+/// [`Context::content_area`]: ../struct.Context.html#method.content_area
+/// [`Document`]: ../struct.Document.html
+#[derive(Clone, Debug, Default)]
+pub struct Positioned<E: Element> {
+    element: E,
+    position: Position,
+}
+
+impl<E: Element> Positioned<E> {
+    /// Creates a new wrapper that renders the given element at the given position.
+    pub fn new(element: E, position: impl Into<Position>) -> Positioned<E> {
+        Positioned {
+            element,
+            position: position.into(),
+        }
+    }
+}
+
+impl<E: Element> Element for Positioned<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut element_area = area.page_area();
+        if let Some((origin, size)) = context.content_area() {
+            element_area.add_offset(origin);
+            element_area.set_size(size);
+        }
+        element_area.add_offset(self.position);
+        context.render_element(&mut self.element, element_area, style)?;
+        Ok(RenderResult::default())
+    }
+}
+
+/// Renders the wrapped element rotated by 90 degrees clockwise.
+///
+/// The wrapped element is rendered into an area with the width and the height of the available
+/// area swapped, so that its lines run from the top to the bottom of the page.  This can be used to
+/// print a wide table sideways on a portrait page.  The height of this element is the width of the
+/// wrapped element, and the wrapped element is aligned with the right edge of the available area.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::elements;
+/// let table = elements::TableLayout::new(vec![1, 1, 1, 1, 1, 1]);
+/// let rotated = elements::Rotated::new(table);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Rotated<E: Element> {
+    element: E,
+}
+
+impl<E: Element> Rotated<E> {
+    /// Creates a new wrapper that rotates the given element.
+    pub fn new(element: E) -> Rotated<E> {
+        Rotated { element }
+    }
+}
+
+impl<E: Element> Element for Rotated<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        // The upper right corner of the available area is the upper left corner of the rotated
+        // area.
+        let size = area.size();
+        let mut area = area;
+        area.add_offset(Position::new(size.width, 0));
+        area.set_size(Size::new(size.height, size.width));
+        let mut result = area.rotated(90.0, |area| {
+            context.render_element(&mut self.element, area, style)
+        })?;
+        result.size = Size::new(result.size.height, result.size.width);
+        Ok(result)
+    }
+}
+
+/// The parity of a page number, see [`OnPageParity`][].
+///
+/// [`OnPageParity`]: struct.OnPageParity.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PageParity {
+    /// Odd pages, i. e. the right-hand pages of a duplex document.
+    Odd,
+    /// Even pages, i. e. the left-hand pages of a duplex document.
+    Even,
+}
+
+impl PageParity {
+    /// Returns whether the page with the given number (starting at 1) has this parity.
+    pub fn matches(&self, page_number: usize) -> bool {
+        (page_number % 2 == 1) == (*self == PageParity::Odd)
+    }
+}
+
+/// Only renders the wrapped element on pages with the given parity.
+///
+/// If this element is rendered on a page with a different parity, it renders nothing and does not
+/// take up space, and it is not rendered again on the next page.  The parity is only checked for
+/// the page that the wrapped element starts on:  If the wrapped element does not fit on that page,
+/// the remaining content is rendered on the next page like for any other element.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::elements;
+/// let logo = elements::OnPageParity::new(
+///     elements::Text::new("Logo"),
+///     elements::PageParity::Odd,
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct OnPageParity<E: Element> {
+    element: E,
+    parity: PageParity,
+    is_started: bool,
+}
+
+impl<E: Element> OnPageParity<E> {
+    /// Creates a new wrapper that only renders the given element on pages with the given parity.
+    pub fn new(element: E, parity: PageParity) -> OnPageParity<E> {
+        OnPageParity {
+            element,
+            parity,
+            is_started: false,
+        }
+    }
+}
+
+impl<E: Element> Element for OnPageParity<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        if !self.is_started && !self.parity.matches(area.page_number()) {
+            return Ok(RenderResult::default());
+        }
+        let result = context.render_element(&mut self.element, area, style)?;
+        // If nothing has been rendered, the element is moved to the next page.
+        if result.size != Size::default() || !result.has_more {
+            self.is_started = true;
+        }
+        Ok(result)
+    }
+
+    fn is_spacing(&self) -> bool {
+        self.element.is_spacing()
+    }
+
+    fn cell_metrics(&self, context: &Context, style: Style) -> CellMetrics {
+        self.element.cell_metrics(context, style)
+    }
+}
+
+/// The kind of a captioned element, see [`Figure`][] and [`Table`][].
+///
+/// Figures and tables are numbered separately.
+///
+/// [`Figure`]: struct.Figure.html
+/// [`Table`]: struct.Table.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CaptionKind {
+    /// A figure with a caption below the content.
+    Figure,
+    /// A table with a caption above the content.
+    Table,
+}
+
+impl CaptionKind {
+    /// Returns the label that is printed before the number in the caption.
+    pub fn label(&self) -> &'static str {
+        match self {
+            CaptionKind::Figure => "Figure",
+            CaptionKind::Table => "Table",
+        }
+    }
+
+    fn is_caption_above(&self) -> bool {
+        *self == CaptionKind::Table
+    }
+}
+
+/// A handle for retrieving the number and the [`Destination`][] of a [`Figure`][] or a
+/// [`Table`][] after the document has been rendered.
+///
+/// Handles are created by [`Figure::handle`][] and [`Table::handle`][] and can be added to a
+/// [`ListOfFigures`][].  Before the element has been rendered, [`number`][] and
+/// [`destination`][] return `None`.
+///
+/// [`Destination`]: ../struct.Destination.html
+/// [`Figure`]: struct.Figure.html
+/// [`Table`]: struct.Table.html
+/// [`Figure::handle`]: struct.Figure.html#method.handle
+/// [`Table::handle`]: struct.Table.html#method.handle
+/// [`ListOfFigures`]: struct.ListOfFigures.html
+/// [`number`]: #method.number
+/// [`destination`]: #method.destination
+#[derive(Clone, Debug)]
+pub struct CaptionHandle {
+    kind: CaptionKind,
+    caption: rc::Rc<str>,
+    number: rc::Rc<cell::Cell<Option<usize>>>,
+    element: ElementHandle,
+}
+
+impl CaptionHandle {
+    fn new(kind: CaptionKind, caption: impl Into<String>) -> CaptionHandle {
+        CaptionHandle {
+            kind,
+            caption: caption.into().into(),
+            number: Default::default(),
+            element: ElementHandle::default(),
+        }
+    }
+
+    /// Returns the kind of the captioned element.
+    pub fn kind(&self) -> CaptionKind {
+        self.kind
+    }
+
+    /// Returns the caption text without the label and the number.
+    pub fn caption(&self) -> &str {
+        &self.caption
+    }
+
+    /// Returns the number of the element, or `None` if it has not been rendered yet.
+    pub fn number(&self) -> Option<usize> {
+        self.number.get()
+    }
+
+    /// Returns the destination of the element, or `None` if it has not been rendered yet.
+    pub fn destination(&self) -> Option<Destination> {
+        self.element.destination()
+    }
+
+    /// Returns the full caption consisting of the label, the number and the caption text, or
+    /// `None` if the element has not been rendered yet.
+    pub fn label(&self) -> Option<String> {
+        self.number().map(|number| self.format_label(number))
+    }
+
+    fn format_label(&self, number: usize) -> String {
+        format!("{} {}: {}", self.kind.label(), number, self.caption)
+    }
+}
+
+/// The shared implementation of [`Figure`][] and [`Table`][].
+///
+/// [`Figure`]: struct.Figure.html
+/// [`Table`]: struct.Table.html
+#[derive(Clone, Debug)]
+struct CaptionedElement<E: Element> {
+    element: E,
+    handle: CaptionHandle,
+    paragraph: Option<Paragraph>,
+    render_idx: usize,
+}
+
+impl<E: Element> CaptionedElement<E> {
+    fn new(element: E, kind: CaptionKind, caption: impl Into<String>) -> CaptionedElement<E> {
+        CaptionedElement {
+            element,
+            handle: CaptionHandle::new(kind, caption),
+            paragraph: None,
+            render_idx: 0,
+        }
+    }
+
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        // The number is assigned when the element is rendered for the first time so that the
+        // numbers follow the order of the elements in the document.  Dry runs only use the number
+        // that would be assigned.
+        let handle = &self.handle;
+        let number = handle.number().unwrap_or_else(|| {
+            let number = context.next_caption_number(handle.kind);
+            if !context.is_dry_run() {
+                handle.number.set(Some(number));
+            }
+            number
+        });
+        let paragraph = self
+            .paragraph
+            .get_or_insert_with(|| Paragraph::new(handle.format_label(number)));
+        let destination = Destination::new(area.page_number(), area.origin());
+
+        let mut result = RenderResult::default();
+        while self.render_idx < 2 {
+            let element: &mut dyn Element =
+                if (self.render_idx == 0) == handle.kind.is_caption_above() {
+                    paragraph
+                } else {
+                    &mut self.element
+                };
+            let element_result = context.render_element(element, area.clone(), style)?;
+            area.add_offset(Position::new(0, element_result.size.height));
+            result.size = result.size.stack_vertical(element_result.size);
+            if element_result.has_more {
+                result.has_more = true;
+                break;
+            }
+            self.render_idx += 1;
+        }
+        // If nothing has been rendered, the element is moved to the next page.
+        if (result.size != Size::default() || !result.has_more) && !context.is_dry_run() {
+            handle.element.set_destination(destination);
+        }
+        Ok(result)
+    }
+}
+
+/// Adds a numbered caption below the wrapped element.
+///
+/// The figures of a document are numbered in the order in which they are rendered, starting at 1.
+/// The caption consists of the label, the number and the given text, for example “Figure 1: A
+/// chart”.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::elements;
+/// let figure = elements::Figure::new(elements::Paragraph::new("content"), "A chart");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Figure<E: Element> {
+    inner: CaptionedElement<E>,
+}
+
+impl<E: Element> Figure<E> {
+    /// Creates a new figure with the given element and caption text.
+    pub fn new(element: E, caption: impl Into<String>) -> Figure<E> {
+        Figure {
+            inner: CaptionedElement::new(element, CaptionKind::Figure, caption),
+        }
+    }
+
+    /// Returns a handle that can be used to retrieve the number and the destination of this
+    /// figure after it has been rendered, for example to add it to a [`ListOfFigures`][].
+    ///
+    /// [`ListOfFigures`]: struct.ListOfFigures.html
+    pub fn handle(&self) -> CaptionHandle {
+        self.inner.handle.clone()
+    }
+}
+
+impl<E: Element> Element for Figure<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.inner.render(context, area, style)
+    }
+}
+
+/// Adds a numbered caption above the wrapped element, typically a [`TableLayout`][].
+///
+/// The tables of a document are numbered in the order in which they are rendered, starting at 1,
+/// independently of the figures.  The caption consists of the label, the number and the given
+/// text, for example “Table 1: Results”.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::elements;
+/// let mut layout = elements::TableLayout::new(vec![1, 1]);
+/// layout
+///     .row()
+///     .element(elements::Paragraph::new("a"))
+///     .element(elements::Paragraph::new("b"))
+///     .push()
+///     .expect("Invalid table row");
+/// let table = elements::Table::new(layout, "Results");
+/// ```
+///
+/// [`TableLayout`]: struct.TableLayout.html
+#[derive(Clone, Debug)]
+pub struct Table<E: Element> {
+    inner: CaptionedElement<E>,
+}
+
+impl<E: Element> Table<E> {
+    /// Creates a new table with the given element and caption text.
+    pub fn new(element: E, caption: impl Into<String>) -> Table<E> {
+        Table {
+            inner: CaptionedElement::new(element, CaptionKind::Table, caption),
+        }
+    }
+
+    /// Returns a handle that can be used to retrieve the number and the destination of this
+    /// table after it has been rendered, for example to add it to a [`ListOfFigures`][].
+    ///
+    /// [`ListOfFigures`]: struct.ListOfFigures.html
+    pub fn handle(&self) -> CaptionHandle {
+        self.inner.handle.clone()
+    }
+}
+
+impl<E: Element> Element for Table<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.inner.render(context, area, style)
+    }
+}
+
+/// A list of the captions of [`Figure`][] and [`Table`][] elements with their page numbers.
+///
+/// The entries are added with the handles returned by [`Figure::handle`][] and
+/// [`Table::handle`][].  Every entry takes one line with the full caption on the left, truncated
+/// with an ellipsis if necessary, and the page number on the right.  As the list is typically
+/// placed before the elements that it refers to, the lines are only reserved when the list is
+/// rendered and filled in after all pages of the document have been rendered.  Entries for
+/// elements that have not been rendered stay empty.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::elements;
+/// let figure = elements::Figure::new(elements::Paragraph::new("content"), "A chart");
+/// let mut list = elements::ListOfFigures::new();
+/// list.push(figure.handle());
+/// // push the list and then the figure to the document
+/// ```
+///
+/// [`Figure`]: struct.Figure.html
+/// [`Table`]: struct.Table.html
+/// [`Figure::handle`]: struct.Figure.html#method.handle
+/// [`Table::handle`]: struct.Table.html#method.handle
+#[derive(Clone, Debug, Default)]
+pub struct ListOfFigures {
+    entries: Vec<CaptionHandle>,
+    render_idx: usize,
+}
+
+impl ListOfFigures {
+    /// Creates a new empty list of figures.
+    pub fn new() -> ListOfFigures {
+        ListOfFigures::default()
+    }
+
+    /// Adds an entry for the element with the given handle to this list.
+    pub fn push(&mut self, handle: CaptionHandle) {
+        self.entries.push(handle);
+    }
+
+    /// Adds an entry for the element with the given handle to this list and returns the list.
+    pub fn entry(mut self, handle: CaptionHandle) -> ListOfFigures {
+        self.push(handle);
+        self
+    }
+}
+
+impl Element for ListOfFigures {
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        let line_height = style.line_height(&context.font_cache);
+        while let Some(handle) = self.entries.get(self.render_idx) {
+            if area.size().height < line_height {
+                result.has_more = true;
+                break;
+            }
+            let mut line_area = area.clone();
+            line_area.set_height(line_height);
+            let handle = handle.clone();
+            context.defer(&line_area, style, move |context, area, style| {
+                let (label, destination) = match (handle.label(), handle.destination()) {
+                    (Some(label), Some(destination)) => (label, destination),
+                    _ => return Ok(()),
+                };
+                let font_cache = &context.font_cache;
+                let page = destination.page.to_string();
+                let page_width = style.str_width(font_cache, &page);
+                let max_width = area.size().width - page_width - style.char_width(font_cache, ' ');
+                let label = ellipsize(
+                    font_cache,
+                    label.into(),
+                    style,
+                    max_width,
+                    "…",
+                    Truncation::End,
+                );
+                area.print_str(font_cache, Position::default(), style, label)?;
+                area.print_str(
+                    font_cache,
+                    Position::new(area.size().width - page_width, 0),
+                    style,
+                    page,
+                )?;
+                Ok(())
+            });
+            area.add_offset(Position::new(0, line_height));
+            result.size.width = result.size.width.max(line_area.size().width);
+            result.size.height += line_height;
+            self.render_idx += 1;
+        }
+        Ok(result)
+    }
+}
+
+/// Adds a padding to the wrapped element.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use rckive_genpdf::elements;
+/// let p = elements::PaddedElement::new(
+///     elements::Paragraph::new("text"),
+///     rckive_genpdf::Margins::trbl(5, 2, 5, 10),
+/// );
+/// ```
+///
+/// Using [`Element::padded`][]:
+/// ```
+/// use rckive_genpdf::{elements, Element as _};
+/// let p = elements::Paragraph::new("text")
+///     .padded(rckive_genpdf::Margins::trbl(5, 2, 5, 10));
+/// ```
+///
+/// [`Element::padded`]: ../trait.Element.html#method.padded
+#[derive(Clone, Debug, Default)]
+pub struct PaddedElement<E: Element> {
+    element: E,
+    padding: Margins,
+}
+
+impl<E: Element> PaddedElement<E> {
+    /// Creates a new padded element that wraps the given element with the given padding.
+    pub fn new(element: E, padding: impl Into<Margins>) -> PaddedElement<E> {
+        PaddedElement {
+            element,
+            padding: padding.into(),
+        }
+    }
+}
+
+impl<E: Element> Element for PaddedElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        area.add_margins(Margins {
+            bottom: Mm(0.0),
+            ..self.padding
+        });
+        let mut result = context.render_element(&mut self.element, area, style)?;
+        result.size.width += self.padding.left + self.padding.right;
+        result.size.height += self.padding.top + self.padding.bottom;
+        Ok(result)
+    }
+
+    fn cell_metrics(&self, context: &Context, style: Style) -> CellMetrics {
+        let metrics = self.element.cell_metrics(context, style);
+        let inner = metrics.padding.unwrap_or_default();
+        CellMetrics {
+            text_widths: metrics.text_widths.map(|(integer, fraction)| {
+                (integer + self.padding.left, fraction + self.padding.right)
+            }),
+            padding: Some(Margins::trbl(
+                self.padding.top + inner.top,
+                self.padding.right + inner.right,
+                self.padding.bottom + inner.bottom,
+                self.padding.left + inner.left,
+            )),
+        }
+    }
+}
+
+/// Adds a default style to the wrapped element and its children.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use rckive_genpdf::{elements, style};
+/// let p = elements::StyledElement::new(
+///     elements::Paragraph::new("text"),
+///     style::Effect::Bold,
+/// );
+/// ```
+///
+/// Using [`Element::styled`][]:
+/// ```
+/// use rckive_genpdf::{elements, style, Element as _};
+/// let p = elements::Paragraph::new("text")
+///     .styled(style::Effect::Bold);
+/// ```
+///
+/// [`Element::styled`]: ../trait.Element.html#method.styled
+#[derive(Clone, Debug, Default)]
+pub struct StyledElement<E: Element> {
+    element: E,
+    style: Style,
+}
+
+impl<E: Element> StyledElement<E> {
+    /// Creates a new styled element that wraps the given element with the given style.
+    pub fn new(element: E, style: impl Into<Style>) -> StyledElement<E> {
+        StyledElement {
+            element,
+            style: style.into(),
+        }
+    }
+}
+
+impl<E: Element> Element for StyledElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        mut style: Style,
+    ) -> Result<RenderResult, Error> {
+        style.merge(self.style);
+        context.render_element(&mut self.element, area, style)
+    }
+
+    fn is_spacing(&self) -> bool {
+        self.element.is_spacing()
+    }
+
+    fn cell_metrics(&self, context: &Context, mut style: Style) -> CellMetrics {
+        style.merge(self.style);
+        self.element.cell_metrics(context, style)
+    }
+}
+
+/// Adds a frame around the wrapped element.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use rckive_genpdf::elements;
+/// let p = elements::FramedElement::new(
+///     elements::Paragraph::new("text"),
+/// );
+/// ```
+///
+/// Using [`Element::framed`][]:
+/// ```
+/// use rckive_genpdf::{elements, style, Element as _};
+/// let p = elements::Paragraph::new("text").framed(style::LineStyle::new());
+/// ```
+///
+/// [`Element::framed`]: ../trait.Element.html#method.framed
+#[derive(Clone, Debug, Default)]
+pub struct FramedElement<E: Element> {
+    element: E,
+    is_first: bool,
+    line_style: Option<LineStyle>,
+}
+
+impl<E: Element> FramedElement<E> {
+    /// Creates a new framed element that wraps the given element.
+    ///
+    /// The frame is drawn with the default line style of the document, see
+    /// [`Document::set_default_line_style`][].
+    ///
+    /// [`Document::set_default_line_style`]: ../struct.Document.html#method.set_default_line_style
+    pub fn new(element: E) -> FramedElement<E> {
+        Self {
+            is_first: true,
+            element,
+            line_style: None,
+        }
+    }
+
+    /// Creates a new framed element that wraps the given element,
+    /// and with the given line style.
+    pub fn with_line_style(element: E, line_style: impl Into<LineStyle>) -> FramedElement<E> {
+        Self {
+            is_first: true,
+            element,
+            line_style: Some(line_style.into()),
+        }
+    }
+}
+
+impl<E: Element> Element for FramedElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let line_style = self
+            .line_style
+            .unwrap_or_else(|| context.default_line_style());
+        // For the element area calculations, we have to take into account the full line thickness.
+        // For the frame area, we only need half because we specify the center of the line.
+        let line_thickness = line_style.thickness();
+        let line_offset = line_thickness / 2.0;
+
+        // Calculate the areas in which to draw the element and the frame.
+        let mut element_area = area.clone();
+        let mut frame_area = area.clone();
+        element_area.add_margins(Margins::trbl(
+            0,
+            line_thickness,
+            line_thickness,
+            line_thickness,
+        ));
+        frame_area.add_margins(Margins::trbl(0, line_offset, 0, line_offset));
+        if self.is_first {
+            element_area.add_margins(Margins::trbl(line_thickness, 0, 0, 0));
+            frame_area.add_margins(Margins::trbl(line_offset, 0, 0, 0));
+        }
+
+        // Draw the element.
+        let mut result = context.render_element(&mut self.element, element_area, style)?;
+        result.size.width = area.size().width;
+        if result.has_more {
+            frame_area.set_height(result.size.height + line_offset);
+        } else {
+            frame_area.set_height(result.size.height + line_thickness);
+        }
+
+        // Draw the frame.
+        let top_left = Position::default();
+        let top_right = Position::new(frame_area.size().width, 0);
+        let bottom_left = Position::new(0, frame_area.size().height);
+        let bottom_right = Position::new(frame_area.size().width, frame_area.size().height);
+
+        if self.is_first {
+            result.size.height += line_thickness;
+            frame_area.draw_line(
+                vec![bottom_right, top_right, top_left, bottom_left],
+                line_style,
+            );
+        }
+        if !result.has_more {
+            result.size.height += line_thickness;
+            frame_area.draw_line(
+                vec![top_left, bottom_left, bottom_right, top_right],
+                line_style,
+            );
+        } else {
+            frame_area.draw_line(vec![top_left, bottom_left], line_style);
+            frame_area.draw_line(vec![top_right, bottom_right], line_style);
+        }
+
+        self.is_first = false;
+
+        Ok(result)
+    }
+
+    fn cell_metrics(&self, context: &Context, style: Style) -> CellMetrics {
+        let line_thickness = self
+            .line_style
+            .unwrap_or_else(|| context.default_line_style())
+            .thickness();
+        let mut metrics = self.element.cell_metrics(context, style);
+        metrics.text_widths = metrics
+            .text_widths
+            .map(|(integer, fraction)| (integer + line_thickness, fraction + line_thickness));
+        metrics
+    }
+}
+
+/// Adds a margin, a border, a padding and a background color to the wrapped element.
+///
+/// The decorations are applied like in the CSS box model:  The margin is the outermost space
+/// around the element.  It is followed by the border and the padding, and then by the wrapped
+/// element.  The background color fills the area inside of the border, including the padding.
+///
+/// If the wrapped element is split across multiple pages, the top margin, border and padding are
+/// only added on the first page and the bottom margin, border and padding are only added on the
+/// last page.
+///
+/// # Examples
+///
+/// With setters:
+/// ```
+/// use rckive_genpdf::{elements, style};
+/// let mut p = elements::DecoratedElement::new(elements::Paragraph::new("text"));
+/// p.set_margins(2);
+/// p.set_border(style::LineStyle::new());
+/// p.set_padding(1);
+/// p.set_background(style::Color::Greyscale(220));
+/// ```
+///
+/// Chained:
+/// ```
+/// use rckive_genpdf::{elements, style, Element as _};
+/// let p = elements::Paragraph::new("text")
+///     .decorated()
+///     .with_margins(2)
+///     .with_border(style::LineStyle::new())
+///     .with_padding(1)
+///     .with_background(style::Color::Greyscale(220));
+/// ```
+#[derive(Clone, Debug)]
+pub struct DecoratedElement<E: Element> {
+    element: E,
+    margins: Margins,
+    border: Option<LineStyle>,
+    padding: Margins,
+    background: Option<Color>,
+    is_first: bool,
+}
+
+impl<E: Element> DecoratedElement<E> {
+    /// Creates a new decorated element that wraps the given element without any decorations.
+    pub fn new(element: E) -> DecoratedElement<E> {
+        DecoratedElement {
+            element,
+            margins: Margins::default(),
+            border: None,
+            padding: Margins::default(),
+            background: None,
+            is_first: true,
+        }
+    }
+
+    /// Sets the margins outside of the border.
+    pub fn set_margins(&mut self, margins: impl Into<Margins>) {
+        self.margins = margins.into();
+    }
+
+    /// Sets the margins outside of the border and returns the element.
+    pub fn with_margins(mut self, margins: impl Into<Margins>) -> Self {
+        self.set_margins(margins);
+        self
+    }
+
+    /// Sets the line style of the border.
+    pub fn set_border(&mut self, line_style: impl Into<LineStyle>) {
+        self.border = Some(line_style.into());
+    }
+
+    /// Sets the line style of the border and returns the element.
+    pub fn with_border(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_border(line_style);
+        self
+    }
+
+    /// Sets the padding between the border and the wrapped element.
+    pub fn set_padding(&mut self, padding: impl Into<Margins>) {
+        self.padding = padding.into();
+    }
+
+    /// Sets the padding between the border and the wrapped element and returns the element.
+    pub fn with_padding(mut self, padding: impl Into<Margins>) -> Self {
+        self.set_padding(padding);
+        self
+    }
+
+    /// Sets the background color.
+    pub fn set_background(&mut self, color: Color) {
+        self.background = Some(color);
+    }
+
+    /// Sets the background color and returns the element.
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.set_background(color);
+        self
+    }
+}
+
+impl<E: Element> Element for DecoratedElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let thickness = self
+            .border
+            .map(|line_style| line_style.thickness())
+            .unwrap_or_default();
+        let margin_top = if self.is_first {
+            self.margins.top
+        } else {
+            Mm(0.0)
+        };
+        let inner_top = if self.is_first {
+            thickness + self.padding.top
+        } else {
+            Mm(0.0)
+        };
+        let inner_bottom = self.padding.bottom + thickness;
+
+        // Calculate the area inside the margins and the area inside the padding.  If we draw a
+        // background, the element is drawn on the next layer so that it is not hidden by the
+        // background.
+        let mut border_area = area.clone();
+        border_area.add_margins(Margins::trbl(
+            margin_top,
+            self.margins.right,
+            0,
+            self.margins.left,
+        ));
+        let mut element_area = if self.background.is_some() {
+            border_area.next_layer()
+        } else {
+            border_area.clone()
+        };
+        element_area.add_margins(Margins::trbl(
+            inner_top,
+            thickness + self.padding.right,
+            inner_bottom + self.margins.bottom,
+            thickness + self.padding.left,
+        ));
+
+        let mut result = context.render_element(&mut self.element, element_area, style)?;
+        if self.is_first && result.has_more && result.size.height == Mm(0.0) {
+            // Nothing has been rendered, so we try again on the next page.
+            result.size = Size::default();
+            return Ok(result);
+        }
+
+        let mut height = inner_top + result.size.height;
+        if !result.has_more {
+            height += inner_bottom;
+        }
+        let width = border_area.size().width;
+
+        if let Some(color) = self.background {
+            let top = if self.is_first { thickness } else { Mm(0.0) };
+            let bottom = if result.has_more { Mm(0.0) } else { thickness };
+            border_area.fill_rect(
+                Position::new(thickness, top),
+                Size::new(width - thickness * 2.0, height - top - bottom),
+                color,
+            );
+        }
+
+        if let Some(line_style) = self.border {
+            // The position of the line is the center of the line.
+            let offset = thickness / 2.0;
+            let top = if self.is_first { offset } else { Mm(0.0) };
+            let bottom = if result.has_more {
+                height
+            } else {
+                height - offset
+            };
+            let top_left = Position::new(offset, top);
+            let top_right = Position::new(width - offset, top);
+            let bottom_left = Position::new(offset, bottom);
+            let bottom_right = Position::new(width - offset, bottom);
+
+            if self.is_first {
+                border_area.draw_line(
+                    vec![bottom_right, top_right, top_left, bottom_left],
+                    line_style,
+                );
+            }
+            if !result.has_more {
+                border_area.draw_line(
+                    vec![top_left, bottom_left, bottom_right, top_right],
+                    line_style,
+                );
+            } else if !self.is_first {
+                border_area.draw_line(vec![top_left, bottom_left], line_style);
+                border_area.draw_line(vec![top_right, bottom_right], line_style);
+            }
         }
 
-        // Draw the element.
-        let mut result = self.element.render(context, element_area, style)?;
         result.size.width = area.size().width;
-        if result.has_more {
-            frame_area.set_height(result.size.height + line_offset);
+        result.size.height = margin_top + height;
+        if !result.has_more {
+            result.size.height += self.margins.bottom;
+        }
+        self.is_first = false;
+        Ok(result)
+    }
+
+    fn cell_metrics(&self, context: &Context, style: Style) -> CellMetrics {
+        let thickness = self
+            .border
+            .map(|line_style| line_style.thickness())
+            .unwrap_or_default();
+        let mut metrics = self.element.cell_metrics(context, style);
+        metrics.text_widths = metrics.text_widths.map(|(integer, fraction)| {
+            (
+                integer + self.margins.left + thickness + self.padding.left,
+                fraction + self.padding.right + thickness + self.margins.right,
+            )
+        });
+        metrics
+    }
+}
+
+/// An unordered list of elements with bullet points.
+///
+/// # Examples
+///
+/// With setters:
+/// ```
+/// use rckive_genpdf::elements;
+/// let mut list = elements::UnorderedList::new();
+/// list.push(elements::Paragraph::new("first"));
+/// list.push(elements::Paragraph::new("second"));
+/// list.push(elements::Paragraph::new("third"));
+/// ```
+///
+/// With setters and a custom bullet symbol:
+/// ```
+/// use rckive_genpdf::elements;
+/// let mut list = elements::UnorderedList::with_bullet("*");
+/// list.push(elements::Paragraph::new("first"));
+/// list.push(elements::Paragraph::new("second"));
+/// list.push(elements::Paragraph::new("third"));
+/// ```
+///
+/// Chained:
+/// ```
+/// use rckive_genpdf::elements;
+/// let list = elements::UnorderedList::new()
+///     .element(elements::Paragraph::new("first"))
+///     .element(elements::Paragraph::new("second"))
+///     .element(elements::Paragraph::new("third"));
+/// ```
+///
+/// Nested list using a [`LinearLayout`][]:
+/// ```
+/// use rckive_genpdf::elements;
+/// let list = elements::UnorderedList::new()
+///     .element(
+///         elements::OrderedList::new()
+///             .element(elements::Paragraph::new("Sublist with bullet point"))
+///     )
+///     .element(
+///         elements::LinearLayout::vertical()
+///             .element(elements::Paragraph::new("Sublist without bullet point:"))
+///             .element(
+///                 elements::OrderedList::new()
+///                     .element(elements::Paragraph::new("first"))
+///                     .element(elements::Paragraph::new("second"))
+///             )
+///     );
+/// ```
+///
+/// [`LinearLayout`]: struct.LinearLayout.html
+pub struct UnorderedList {
+    layout: LinearLayout,
+    bullet: Option<String>,
+}
+
+impl UnorderedList {
+    /// Creates a new unordered list with the default bullet point symbol.
+    pub fn new() -> UnorderedList {
+        UnorderedList {
+            layout: LinearLayout::vertical(),
+            bullet: None,
+        }
+    }
+
+    /// Creates a new unordered list with the given bullet point symbol.
+    pub fn with_bullet(bullet: impl Into<String>) -> UnorderedList {
+        UnorderedList {
+            layout: LinearLayout::vertical(),
+            bullet: Some(bullet.into()),
+        }
+    }
+
+    /// Adds an element to this list.
+    pub fn push<E: Element + 'static>(&mut self, element: E) {
+        let mut point = BulletPoint::new(element);
+        if let Some(bullet) = &self.bullet {
+            point.set_bullet(bullet.clone());
+        }
+        self.layout.push(point);
+    }
+
+    /// Adds an element to this list and returns the list.
+    pub fn element<E: Element + 'static>(mut self, element: E) -> Self {
+        self.push(element);
+        self
+    }
+}
+
+impl Element for UnorderedList {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        context.render_element(&mut self.layout, area, style)
+    }
+}
+
+impl Default for UnorderedList {
+    fn default() -> UnorderedList {
+        UnorderedList::new()
+    }
+}
+
+impl<E: Element + 'static> iter::Extend<E> for UnorderedList {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        for element in iter {
+            self.push(element);
+        }
+    }
+}
+
+impl<E: Element + 'static> iter::FromIterator<E> for UnorderedList {
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        let mut list = Self::default();
+        list.extend(iter);
+        list
+    }
+}
+
+/// An ordered list of elements with arabic numbers.
+///
+/// # Examples
+///
+/// With setters:
+/// ```
+/// use rckive_genpdf::elements;
+/// let mut list = elements::OrderedList::new();
+/// list.push(elements::Paragraph::new("first"));
+/// list.push(elements::Paragraph::new("second"));
+/// list.push(elements::Paragraph::new("third"));
+/// ```
+///
+/// With setters and a custom start number:
+/// ```
+/// use rckive_genpdf::elements;
+/// let mut list = elements::OrderedList::with_start(5);
+/// list.push(elements::Paragraph::new("first"));
+/// list.push(elements::Paragraph::new("second"));
+/// list.push(elements::Paragraph::new("third"));
+/// ```
+///
+/// Chained:
+/// ```
+/// use rckive_genpdf::elements;
+/// let list = elements::OrderedList::new()
+///     .element(elements::Paragraph::new("first"))
+///     .element(elements::Paragraph::new("second"))
+///     .element(elements::Paragraph::new("third"));
+/// ```
+///
+/// Nested list using a [`LinearLayout`][]:
+/// ```
+/// use rckive_genpdf::elements;
+/// let list = elements::OrderedList::new()
+///     .element(
+///         elements::UnorderedList::new()
+///             .element(elements::Paragraph::new("Sublist with number"))
+///     )
+///     .element(
+///         elements::LinearLayout::vertical()
+///             .element(elements::Paragraph::new("Sublist without number:"))
+///             .element(
+///                 elements::UnorderedList::new()
+///                     .element(elements::Paragraph::new("first"))
+///                     .element(elements::Paragraph::new("second"))
+///             )
+///     );
+/// ```
+///
+/// [`LinearLayout`]: struct.LinearLayout.html
+pub struct OrderedList {
+    layout: LinearLayout,
+    number: usize,
+}
+
+impl OrderedList {
+    /// Creates a new ordered list starting at 1.
+    pub fn new() -> OrderedList {
+        OrderedList::with_start(1)
+    }
+
+    /// Creates a new ordered list with the given start number.
+    pub fn with_start(start: usize) -> OrderedList {
+        OrderedList {
+            layout: LinearLayout::vertical(),
+            number: start,
+        }
+    }
+
+    /// Adds an element to this list.
+    pub fn push<E: Element + 'static>(&mut self, element: E) {
+        let mut point = BulletPoint::new(element);
+        point.set_bullet(format!("{}.", self.number));
+        self.layout.push(point);
+        self.number += 1;
+    }
+
+    /// Adds an element to this list and returns the list.
+    pub fn element<E: Element + 'static>(mut self, element: E) -> Self {
+        self.push(element);
+        self
+    }
+}
+
+impl Element for OrderedList {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        context.render_element(&mut self.layout, area, style)
+    }
+}
+
+impl Default for OrderedList {
+    fn default() -> OrderedList {
+        OrderedList::new()
+    }
+}
+
+impl<E: Element + 'static> iter::Extend<E> for OrderedList {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        for element in iter {
+            self.push(element);
+        }
+    }
+}
+
+impl<E: Element + 'static> iter::FromIterator<E> for OrderedList {
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        let mut list = Self::default();
+        list.extend(iter);
+        list
+    }
+}
+
+/// A bullet point in a list.
+///
+/// This is a helper element for the [`OrderedList`][] and [`UnorderedList`][] types, but you can
+/// also use it directly if you have special requirements.
+///
+/// # Example
+///
+/// ```
+/// use rckive_genpdf::elements;
+/// let layout = elements::LinearLayout::vertical()
+///     .element(elements::BulletPoint::new(elements::Paragraph::new("first"))
+///         .with_bullet("a)"))
+///     .element(elements::BulletPoint::new(elements::Paragraph::new("second"))
+///         .with_bullet("b)"));
+/// ```
+///
+/// [`OrderedList`]: struct.OrderedList.html
+/// [`UnorderedList`]: struct.UnorderedList.html
+pub struct BulletPoint<E: Element> {
+    element: E,
+    indent: Mm,
+    bullet_space: Mm,
+    bullet: String,
+    bullet_rendered: bool,
+}
+
+impl<E: Element> BulletPoint<E> {
+    /// Creates a new bullet point with the given element.
+    pub fn new(element: E) -> BulletPoint<E> {
+        BulletPoint {
+            element,
+            indent: Mm::from(10),
+            bullet_space: Mm::from(2),
+            bullet: String::from("–"),
+            bullet_rendered: false,
+        }
+    }
+
+    /// Sets the bullet point symbol for this bullet point.
+    pub fn set_bullet(&mut self, bullet: impl Into<String>) {
+        self.bullet = bullet.into();
+    }
+
+    /// Sets the bullet point symbol for this bullet point and returns the bullet point.
+    pub fn with_bullet(mut self, bullet: impl Into<String>) -> Self {
+        self.set_bullet(bullet);
+        self
+    }
+}
+
+impl<E: Element> Element for BulletPoint<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut element_area = area.clone();
+        element_area.add_offset(Position::new(self.indent, 0));
+        let mut result = context.render_element(&mut self.element, element_area, style)?;
+        result.size.width += self.indent;
+        if !self.bullet_rendered {
+            let bullet_width = style.str_width(&context.font_cache, &self.bullet);
+            area.print_str(
+                &context.font_cache,
+                Position::new(self.indent - bullet_width - self.bullet_space, 0),
+                style,
+                self.bullet.as_str(),
+            )?;
+            self.bullet_rendered = true;
+        }
+        Ok(result)
+    }
+}
+
+/// A decorator for table cells.
+///
+/// Implementations of this trait can be used to style cells of a [`TableLayout`][].
+///
+/// [`TableLayout`]: struct.TableLayout.html
+pub trait CellDecorator {
+    /// Sets the size of the table.
+    ///
+    /// This function is called before the first call to [`prepare_cell`][] or
+    /// [`decorate_cell`][].  If the rows of the table are read from an iterator (see
+    /// [`TableLayout::extend_rows`][]), it is called again before each row with the number of
+    /// rows that is known so far.  The number of rows is only exact once the last row is rendered.
+    ///
+    /// [`prepare_cell`]: #tymethod.prepare_cell
+    /// [`decorate_cell`]: #tymethod.decorate_cell
+    /// [`TableLayout::extend_rows`]: struct.TableLayout.html#method.extend_rows
+    fn set_table_size(&mut self, num_columns: usize, num_rows: usize) {
+        let _ = (num_columns, num_rows);
+    }
+
+    /// Sets the default line style of the document, see [`Document::set_default_line_style`][].
+    ///
+    /// This function is called once before the first call to [`prepare_cell`][] or
+    /// [`decorate_cell`][].
+    ///
+    /// [`Document::set_default_line_style`]: ../struct.Document.html#method.set_default_line_style
+    /// [`prepare_cell`]: #tymethod.prepare_cell
+    /// [`decorate_cell`]: #tymethod.decorate_cell
+    fn set_default_line_style(&mut self, line_style: LineStyle) {
+        let _ = line_style;
+    }
+
+    /// Prepares the cell with the given indizes and returns the area for rendering the cell.
+    fn prepare_cell<'p>(
+        &self,
+        column: usize,
+        row: usize,
+        area: render::Area<'p>,
+    ) -> render::Area<'p> {
+        let _ = (column, row);
+        area
+    }
+
+    /// Styles the cell with the given indizes thas has been rendered within the given area and the
+    /// given row height and return the total row height.
+    fn decorate_cell(
+        &mut self,
+        column: usize,
+        row: usize,
+        has_more: bool,
+        area: render::Area<'_>,
+        row_height: Mm,
+    ) -> Mm;
+}
+
+/// A cell decorator that draws frames around table cells.
+///
+/// This decorator draws frames around the cells of a [`TableLayout`][].  You can configure whether
+/// inner, outer and continuation borders are drawn.  A continuation border is a border between a
+/// cell and the page margin that occurs if a cell has to be wrapped to a new page.
+///
+/// Alternatively, you can set a line style for every edge of the cells with
+/// [`set_edge_styles`][], for example to only draw horizontal rules below the cells.
+///
+/// [`TableLayout`]: struct.TableLayout.html
+/// [`set_edge_styles`]: #method.set_edge_styles
+#[derive(Clone, Debug, Default)]
+pub struct FrameCellDecorator {
+    inner: bool,
+    outer: bool,
+    cont: bool,
+    line_style: Option<LineStyle>,
+    default_line_style: LineStyle,
+    // the line styles for the top, right, bottom and left edges of every cell
+    edge_styles: Option<[Option<LineStyle>; 4]>,
+    num_columns: usize,
+    num_rows: usize,
+    last_row: Option<usize>,
+}
+
+impl FrameCellDecorator {
+    /// Creates a new frame cell decorator with the given settings for inner, outer and
+    /// continuation borders.
+    ///
+    /// The borders are drawn with the default line style of the document, see
+    /// [`Document::set_default_line_style`][].
+    ///
+    /// [`Document::set_default_line_style`]: ../struct.Document.html#method.set_default_line_style
+    pub fn new(inner: bool, outer: bool, cont: bool) -> FrameCellDecorator {
+        FrameCellDecorator {
+            inner,
+            outer,
+            cont,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a new frame cell decorator with the given border settings, as well as a line style.
+    pub fn with_line_style(
+        inner: bool,
+        outer: bool,
+        cont: bool,
+        line_style: impl Into<LineStyle>,
+    ) -> FrameCellDecorator {
+        Self {
+            inner,
+            outer,
+            cont,
+            line_style: Some(line_style.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the line styles for the top, right, bottom and left edges of every cell.
+    ///
+    /// If the edge styles are set, the inner, outer and continuation border settings and the line
+    /// style of this decorator are ignored.  Instead, every cell draws a line with the given style
+    /// at each of its edges that has a style, and no line at the edges without a style.  Note
+    /// that adjacent cells both draw a line at their shared edge if both edges have a style.
+    pub fn set_edge_styles(
+        &mut self,
+        top: Option<LineStyle>,
+        right: Option<LineStyle>,
+        bottom: Option<LineStyle>,
+        left: Option<LineStyle>,
+    ) {
+        self.edge_styles = Some([top, right, bottom, left]);
+    }
+
+    /// Sets the line styles for the top, right, bottom and left edges of every cell and returns
+    /// the decorator.
+    ///
+    /// See [`set_edge_styles`][] for more information.
+    ///
+    /// [`set_edge_styles`]: #method.set_edge_styles
+    pub fn with_edge_styles(
+        mut self,
+        top: Option<LineStyle>,
+        right: Option<LineStyle>,
+        bottom: Option<LineStyle>,
+        left: Option<LineStyle>,
+    ) -> Self {
+        self.set_edge_styles(top, right, bottom, left);
+        self
+    }
+
+    /// Resets the edge styles so that the inner, outer and continuation border settings are used
+    /// again.
+    pub fn reset_edge_styles(&mut self) {
+        self.edge_styles = None;
+    }
+
+    fn edge_style(&self, idx: usize, print: bool) -> Option<LineStyle> {
+        if let Some(edge_styles) = self.edge_styles {
+            edge_styles[idx]
+        } else if print {
+            Some(self.line_style.unwrap_or(self.default_line_style))
+        } else {
+            None
+        }
+    }
+
+    fn left_style(&self, column: usize) -> Option<LineStyle> {
+        let print = if column == 0 { self.outer } else { self.inner };
+        self.edge_style(3, print)
+    }
+
+    fn right_style(&self, column: usize) -> Option<LineStyle> {
+        let print = if column + 1 == self.num_columns {
+            self.outer
+        } else {
+            false
+        };
+        self.edge_style(1, print)
+    }
+
+    fn top_style(&self, row: usize) -> Option<LineStyle> {
+        let print = if self.last_row.map(|last_row| row > last_row).unwrap_or(true) {
+            if row == 0 {
+                self.outer
+            } else {
+                self.inner
+            }
+        } else {
+            self.cont
+        };
+        self.edge_style(0, print)
+    }
+
+    fn bottom_style(&self, row: usize, has_more: bool) -> Option<LineStyle> {
+        let print = if has_more {
+            self.cont
+        } else if row + 1 == self.num_rows {
+            self.outer
+        } else {
+            false
+        };
+        self.edge_style(2, print)
+    }
+}
+
+/// Returns the thickness of the given line style, or zero if it is not set.
+fn thickness(line_style: Option<LineStyle>) -> Mm {
+    line_style.map(|s| s.thickness()).unwrap_or_default()
+}
+
+impl CellDecorator for FrameCellDecorator {
+    fn set_table_size(&mut self, num_columns: usize, num_rows: usize) {
+        self.num_columns = num_columns;
+        self.num_rows = num_rows;
+    }
+
+    fn set_default_line_style(&mut self, line_style: LineStyle) {
+        self.default_line_style = line_style;
+    }
+
+    fn prepare_cell<'p>(
+        &self,
+        column: usize,
+        row: usize,
+        mut area: render::Area<'p>,
+    ) -> render::Area<'p> {
+        let margins = Margins::trbl(
+            thickness(self.top_style(row)),
+            thickness(self.right_style(column)),
+            thickness(self.bottom_style(row, false)),
+            thickness(self.left_style(column)),
+        );
+        area.add_margins(margins);
+        area
+    }
+
+    fn decorate_cell(
+        &mut self,
+        column: usize,
+        row: usize,
+        has_more: bool,
+        area: render::Area<'_>,
+        row_height: Mm,
+    ) -> Mm {
+        let top_style = self.top_style(row);
+        let bottom_style = self.bottom_style(row, has_more);
+        let left_style = self.left_style(column);
+        let right_style = self.right_style(column);
+
+        let size = area.size();
+
+        let left = Mm::from(0);
+        let right = size.width;
+        let top = Mm::from(0);
+        let bottom = row_height + thickness(bottom_style) + thickness(top_style);
+
+        let mut total_height = row_height;
+
+        if let Some(line_style) = top_style {
+            let line_offset = line_style.thickness() / 2.0;
+            area.draw_line(
+                vec![
+                    Position::new(left, top + line_offset),
+                    Position::new(right, top + line_offset),
+                ],
+                line_style,
+            );
+            total_height += line_style.thickness();
+        }
+
+        if let Some(line_style) = right_style {
+            let line_offset = line_style.thickness() / 2.0;
+            area.draw_line(
+                vec![
+                    Position::new(right - line_offset, top),
+                    Position::new(right - line_offset, bottom),
+                ],
+                line_style,
+            );
+        }
+
+        if let Some(line_style) = bottom_style {
+            let line_offset = line_style.thickness() / 2.0;
+            area.draw_line(
+                vec![
+                    Position::new(left, bottom - line_offset),
+                    Position::new(right, bottom - line_offset),
+                ],
+                line_style,
+            );
+            total_height += line_style.thickness();
+        }
+
+        if let Some(line_style) = left_style {
+            let line_offset = line_style.thickness() / 2.0;
+            area.draw_line(
+                vec![
+                    Position::new(left + line_offset, top),
+                    Position::new(left + line_offset, bottom),
+                ],
+                line_style,
+            );
+        }
+
+        if column + 1 == self.num_columns {
+            self.last_row = Some(row);
+        }
+
+        total_height
+    }
+}
+
+/// A row of a table layout.
+///
+/// This is a helper struct for populating a [`TableLayout`][].  After you have added all elements
+/// to the row using [`push_element`][] or [`element`][], you can append the row to the table
+/// layout by calling [`push`][].
+///
+/// # Examples
+///
+/// With setters:
+/// ```
+/// use rckive_genpdf::elements;
+/// let mut table = elements::TableLayout::new(vec![1, 1]);
+/// let mut row = table.row();
+/// row.push_element(elements::Paragraph::new("Cell 1"));
+/// row.push_element(elements::Paragraph::new("Cell 2"));
+/// row.push().expect("Invalid table row");
+/// ```
+///
+/// Chained:
+/// ```
+/// use rckive_genpdf::elements;
+/// let table = elements::TableLayout::new(vec![1, 1])
+///     .row()
+///     .element(elements::Paragraph::new("Cell 1"))
+///     .element(elements::Paragraph::new("Cell 2"))
+///     .push()
+///     .expect("Invalid table row");
+/// ```
+///
+/// [`TableLayout`]: struct.TableLayout.html
+/// [`push`]: #method.push
+/// [`push_element`]: #method.push_element
+/// [`element`]: #method.element
+pub struct TableLayoutRow<'a> {
+    table_layout: &'a mut TableLayout,
+    elements: Vec<Box<dyn Element>>,
+}
+
+impl<'a> TableLayoutRow<'a> {
+    fn new(table_layout: &'a mut TableLayout) -> TableLayoutRow<'a> {
+        TableLayoutRow {
+            table_layout,
+            elements: Vec::new(),
+        }
+    }
+
+    /// Adds the given element to this row.
+    pub fn push_element<E: IntoBoxedElement>(&mut self, element: E) {
+        self.elements.push(element.into_boxed_element());
+    }
+
+    /// Adds the given element to this row and returns the row.
+    #[must_use]
+    pub fn element<E: IntoBoxedElement>(mut self, element: E) -> Self {
+        self.push_element(element);
+        self
+    }
+
+    /// Tries to append this row to the table.
+    ///
+    /// This method fails if the number of elements in this row does not match the number of
+    /// columns in the table.
+    pub fn push(self) -> Result<(), Error> {
+        self.table_layout.push_row(self.elements)
+    }
+}
+
+impl<'a, E: IntoBoxedElement> iter::Extend<E> for TableLayoutRow<'a> {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        self.elements
+            .extend(iter.into_iter().map(|e| e.into_boxed_element()))
+    }
+}
+
+/// The horizontal alignment of the cells of a table column, see
+/// [`TableLayout::set_column_alignment`][].
+///
+/// The alignment is only applied to cells with elements that render a single run of text, for
+/// example [`Text`][] and single-line [`Paragraph`][]s (see [`Element::cell_metrics`][]).  Other
+/// elements are always rendered at the left edge of the cell.
+///
+/// [`TableLayout::set_column_alignment`]: struct.TableLayout.html#method.set_column_alignment
+/// [`Text`]: struct.Text.html
+/// [`Paragraph`]: struct.Paragraph.html
+/// [`Element::cell_metrics`]: ../trait.Element.html#method.cell_metrics
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Default)]
+pub enum ColumnAlignment {
+    /// Align the cells with the left edge of the column.
+    #[default]
+    Left,
+    /// Center the cells in the column.
+    Center,
+    /// Align the cells with the right edge of the column.
+    Right,
+    /// Align the decimal separators (`.`) of the cells.
+    ///
+    /// The separators are placed so that the widest fractional part of the column is aligned with
+    /// the right edge of the column.  Numbers without a separator are aligned as if the separator
+    /// followed the last digit.
+    Decimal,
+}
+
+/// The handling of cells that are wider than their table column, see
+/// [`TableLayout::set_column_overflow`][].
+///
+/// [`TableLayout::set_column_overflow`]: struct.TableLayout.html#method.set_column_overflow
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Default)]
+pub enum CellOverflow {
+    /// Render the cell unchanged, so that its content may overflow the cell border.
+    #[default]
+    Visible,
+    /// Reduce the font size so that the cell fits the column width.
+    ///
+    /// This is only applied to cells with elements that render a single run of text, see
+    /// [`Element::cell_metrics`][], and only if the elements do not set their own font size.
+    /// The font size is not reduced below the minimum font size of the style, see
+    /// [`Style::set_min_font_size`][].
+    ///
+    /// [`Element::cell_metrics`]: ../trait.Element.html#method.cell_metrics
+    /// [`Style::set_min_font_size`]: ../style/struct.Style.html#method.set_min_font_size
+    Shrink,
+    /// Break words that are longer than the column width at an arbitrary character.
+    ///
+    /// This is only applied to [`Paragraph`][]s that do not set their own word break, see
+    /// [`Paragraph::set_word_break`][].  Single lines of text like [`Text`][] are not wrapped.
+    ///
+    /// [`Paragraph`]: struct.Paragraph.html
+    /// [`Paragraph::set_word_break`]: struct.Paragraph.html#method.set_word_break
+    /// [`Text`]: struct.Text.html
+    Wrap,
+    /// Clip the content of the cell at the cell border.
+    Clip,
+}
+
+type PendingRows = Box<dyn Iterator<Item = Vec<Box<dyn Element>>>>;
+
+/// Arranges elements in columns and rows.
+///
+/// This struct can be used to layout arbitrary elements in columns in rows, or to draw typical
+/// tables.  You can customize the cell style by providing a [`CellDecorator`][] implementation.
+/// If you want to print a typical table with borders around the cells, use the
+/// [`FrameCellDecorator`][].
+///
+/// The column widths are determined by the weights that have been set in the constructor.  The
+/// table always uses the full width of the provided area.  Per default, the cells are aligned with
+/// the left edge of their column.  Use [`set_column_alignment`][] to align numbers with the right
+/// edge or on the decimal separator instead.  Content that is wider than its column may overflow
+/// the cell border unless a different policy is set with [`set_column_overflow`][].  A padding
+/// for all cells that do not set their own padding can be set with [`set_cell_padding`][].  The
+/// labels in the first row can be printed at an angle, see [`set_header_angle`][].
+///
+/// If the content of a cell does not fit on the current page, the row is continued on the next
+/// page.  Only the cells that have not been rendered completely are rendered again on the next
+/// page.
+///
+/// For large tables, the rows can be read from an iterator while the table is rendered, see
+/// [`extend_rows`][].
+///
+/// # Examples
+///
+/// With setters:
+/// ```
+/// use rckive_genpdf::elements;
+/// let mut table = elements::TableLayout::new(vec![1, 1]);
+/// table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+/// let mut row = table.row();
+/// row.push_element(elements::Paragraph::new("Cell 1"));
+/// row.push_element(elements::Paragraph::new("Cell 2"));
+/// row.push().expect("Invalid table row");
+/// ```
+///
+/// Chained:
+/// ```
+/// use rckive_genpdf::elements;
+/// let table = elements::TableLayout::new(vec![1, 1])
+///     .row()
+///     .element(elements::Paragraph::new("Cell 1"))
+///     .element(elements::Paragraph::new("Cell 2"))
+///     .push()
+///     .expect("Invalid table row");
+/// ```
+///
+/// [`CellDecorator`]: trait.CellDecorator.html
+/// [`FrameCellDecorator`]: struct.FrameCellDecorator.html
+/// [`set_column_alignment`]: #method.set_column_alignment
+/// [`set_column_overflow`]: #method.set_column_overflow
+/// [`extend_rows`]: #method.extend_rows
+/// [`set_cell_padding`]: #method.set_cell_padding
+/// [`set_header_angle`]: #method.set_header_angle
+pub struct TableLayout {
+    column_weights: Vec<usize>,
+    column_alignments: Vec<ColumnAlignment>,
+    column_overflows: Vec<CellOverflow>,
+    cell_padding: Margins,
+    header_angle: f32,
+    // the width of the widest fractional part of the decimal-aligned columns
+    decimal_fractions: Option<Vec<Mm>>,
+    rows: Vec<Vec<Box<dyn Element>>>,
+    // the rows that have not been read yet, see extend_rows
+    pending_rows: Option<PendingRows>,
+    render_idx: usize,
+    // the cells of the current row that have been rendered completely
+    finished_cells: Vec<bool>,
+    cell_decorator: Option<Box<dyn CellDecorator>>,
+    structure: Option<render::StructureElement>,
+    // the structure elements of the current row and its cells
+    row_structure: Option<(render::StructureElement, Vec<render::StructureElement>)>,
+}
+
+impl TableLayout {
+    /// Creates a new table layout with the given column weights.
+    ///
+    /// The column weights are used to determine the relative width of the columns.  The number of
+    /// column weights determines the number of columns in the table.
+    pub fn new(column_weights: Vec<usize>) -> TableLayout {
+        TableLayout {
+            column_weights,
+            column_alignments: Vec::new(),
+            column_overflows: Vec::new(),
+            cell_padding: Margins::default(),
+            header_angle: 0.0,
+            decimal_fractions: None,
+            rows: Vec::new(),
+            pending_rows: None,
+            render_idx: 0,
+            finished_cells: Vec::new(),
+            cell_decorator: None,
+            structure: None,
+            row_structure: None,
+        }
+    }
+
+    /// Sets the cell decorator for this table.
+    pub fn set_cell_decorator(&mut self, decorator: impl CellDecorator + 'static) {
+        self.cell_decorator = Some(Box::from(decorator));
+    }
+
+    /// Sets the horizontal alignment of the cells in the column with the given index (starting at
+    /// zero).
+    ///
+    /// See [`ColumnAlignment`][] for more information.  Alignments for columns that do not exist
+    /// are ignored.
+    ///
+    /// [`ColumnAlignment`]: enum.ColumnAlignment.html
+    pub fn set_column_alignment(&mut self, column: usize, alignment: ColumnAlignment) {
+        if self.column_alignments.len() <= column {
+            self.column_alignments
+                .resize(column + 1, ColumnAlignment::default());
+        }
+        self.column_alignments[column] = alignment;
+    }
+
+    /// Sets the horizontal alignment of the cells in the column with the given index and returns
+    /// the table.
+    pub fn with_column_alignment(mut self, column: usize, alignment: ColumnAlignment) -> Self {
+        self.set_column_alignment(column, alignment);
+        self
+    }
+
+    /// Sets the handling of cells that are wider than the column with the given index (starting
+    /// at zero).
+    ///
+    /// See [`CellOverflow`][] for more information.  Policies for columns that do not exist are
+    /// ignored.
+    ///
+    /// [`CellOverflow`]: enum.CellOverflow.html
+    pub fn set_column_overflow(&mut self, column: usize, overflow: CellOverflow) {
+        if self.column_overflows.len() <= column {
+            self.column_overflows
+                .resize(column + 1, CellOverflow::default());
+        }
+        self.column_overflows[column] = overflow;
+    }
+
+    /// Sets the handling of cells that are wider than the column with the given index and returns
+    /// the table.
+    pub fn with_column_overflow(mut self, column: usize, overflow: CellOverflow) -> Self {
+        self.set_column_overflow(column, overflow);
+        self
+    }
+
+    /// Sets the default padding of the cells of this table.
+    ///
+    /// The padding is added to all cells that do not set their own padding, for example with
+    /// [`Element::padded`][].  A padding that is set for a cell replaces the default padding.  The
+    /// default padding is zero.
+    ///
+    /// [`Element::padded`]: ../trait.Element.html#method.padded
+    pub fn set_cell_padding(&mut self, padding: impl Into<Margins>) {
+        self.cell_padding = padding.into();
+    }
+
+    /// Sets the default padding of the cells of this table and returns the table.
+    pub fn with_cell_padding(mut self, padding: impl Into<Margins>) -> Self {
+        self.set_cell_padding(padding);
+        self
+    }
+
+    /// Sets the angle in degrees by which the labels in the header of this table are rotated
+    /// counterclockwise.
+    ///
+    /// The first row of the table is used as its header.  The cells of this row that render a
+    /// single run of text, for example [`Text`][] elements, are rotated around the lower left
+    /// corner of their cell, and the height of the row is increased so that the rotated text fits
+    /// into it.  The rotated text may extend over the following columns, so this is useful for
+    /// long labels of narrow columns.  The angle is clamped to the range from 0 to 90 degrees.
+    /// Per default, the header is not rotated.
+    ///
+    /// [`Text`]: struct.Text.html
+    pub fn set_header_angle(&mut self, degrees: f32) {
+        self.header_angle = degrees.clamp(0.0, 90.0);
+    }
+
+    /// Sets the angle in degrees by which the labels in the header of this table are rotated
+    /// counterclockwise and returns the table.
+    pub fn with_header_angle(mut self, degrees: f32) -> Self {
+        self.set_header_angle(degrees);
+        self
+    }
+
+    /// Adds a row to this table using the [`TableLayoutRow`][] helper struct.
+    ///
+    /// [`TableLayoutRow`]: struct.TableLayoutRow.html
+    pub fn row(&mut self) -> TableLayoutRow<'_> {
+        TableLayoutRow::new(self)
+    }
+
+    /// Adds a row to this table.
+    ///
+    /// The number of elements in the given vector must match the number of columns.  Otherwise, an
+    /// error with the [`InvalidTableRow`][] kind is returned.
+    ///
+    /// [`InvalidTableRow`]: ../error/enum.ErrorKind.html#variant.InvalidTableRow
+    pub fn push_row(&mut self, row: Vec<Box<dyn Element>>) -> Result<(), Error> {
+        self.check_row(&row)?;
+        if let Some(pending_rows) = self.pending_rows.take() {
+            self.pending_rows = Some(Box::new(pending_rows.chain(iter::once(row))));
         } else {
-            frame_area.set_height(result.size.height + line_thickness);
+            self.rows.push(row);
         }
+        Ok(())
+    }
 
-        // Draw the frame.
-        let top_left = Position::default();
-        let top_right = Position::new(frame_area.size().width, 0);
-        let bottom_left = Position::new(0, frame_area.size().height);
-        let bottom_right = Position::new(frame_area.size().width, frame_area.size().height);
+    /// Adds the rows of the given iterator to this table.
+    ///
+    /// The rows are not read from the iterator when this method is called.  Instead, each row is
+    /// read shortly before it is rendered, and its elements are dropped after it has been rendered
+    /// completely.  This makes it possible to render large tables without keeping all rows in
+    /// memory.
+    ///
+    /// The number of elements in each row must match the number of columns.  Otherwise, rendering
+    /// the table fails with an error with the [`InvalidTableRow`][] kind.  The rows added with this
+    /// method are not considered when determining the position of the decimal separator for
+    /// columns with the [`ColumnAlignment::Decimal`][] alignment.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rckive_genpdf::elements::{self, IntoBoxedElement as _};
+    /// let mut table = elements::TableLayout::new(vec![1, 3]);
+    /// table.extend_rows((1..=1000).map(|i| {
+    ///     vec![
+    ///         elements::Text::new(i.to_string()).into_boxed_element(),
+    ///         elements::Text::new(format!("Item {}", i)).into_boxed_element(),
+    ///     ]
+    /// }));
+    /// ```
+    ///
+    /// [`InvalidTableRow`]: ../error/enum.ErrorKind.html#variant.InvalidTableRow
+    /// [`ColumnAlignment::Decimal`]: enum.ColumnAlignment.html#variant.Decimal
+    pub fn extend_rows<I>(&mut self, rows: I)
+    where
+        I: IntoIterator<Item = Vec<Box<dyn Element>>>,
+        I::IntoIter: 'static,
+    {
+        let rows = rows.into_iter();
+        self.pending_rows = Some(if let Some(pending_rows) = self.pending_rows.take() {
+            Box::new(pending_rows.chain(rows))
+        } else {
+            Box::new(rows)
+        });
+    }
 
-        if self.is_first {
-            result.size.height += line_thickness;
-            frame_area.draw_line(
-                vec![bottom_right, top_right, top_left, bottom_left],
-                self.line_style,
-            );
+    /// Checks that the given row has one element per column.
+    fn check_row(&self, row: &[Box<dyn Element>]) -> Result<(), Error> {
+        if row.len() == self.column_weights.len() {
+            Ok(())
+        } else {
+            Err(Error::new(
+                format!(
+                    "Expected {} elements in table row, received {}",
+                    self.column_weights.len(),
+                    row.len()
+                ),
+                ErrorKind::InvalidTableRow {
+                    expected: self.column_weights.len(),
+                    actual: row.len(),
+                },
+            ))
         }
-        if !result.has_more {
-            result.size.height += line_thickness;
-            frame_area.draw_line(
-                vec![top_left, bottom_left, bottom_right, top_right],
-                self.line_style,
-            );
+    }
+
+    /// Reads the current and the next row from the pending rows if necessary and updates the table
+    /// size of the cell decorator.
+    fn load_rows(&mut self) -> Result<(), Error> {
+        while self.rows.len() < self.render_idx + 2 {
+            let row = self.pending_rows.as_mut().and_then(|rows| rows.next());
+            if let Some(row) = row {
+                self.check_row(&row)?;
+                self.rows.push(row);
+            } else {
+                self.pending_rows = None;
+                break;
+            }
+        }
+        if let Some(decorator) = &mut self.cell_decorator {
+            // If there are pending rows, the next row is not the last row.
+            let num_rows = self.rows.len() + usize::from(self.pending_rows.is_some());
+            decorator.set_table_size(self.column_weights.len(), num_rows);
+        }
+        Ok(())
+    }
+
+    /// Returns the width of the widest fractional part of each column.
+    fn get_decimal_fractions(&self, context: &Context, style: Style) -> Vec<Mm> {
+        let mut fractions = vec![Mm(0.0); self.column_weights.len()];
+        for row in &self.rows {
+            for (fraction, element) in fractions.iter_mut().zip(row) {
+                if let Some((_, width)) = element.cell_metrics(context, style).text_widths {
+                    *fraction = fraction.max(width);
+                }
+            }
+        }
+        fractions
+    }
+
+    fn render_row(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+
+        // the text widths of the header cells that are rotated, see set_header_angle
+        let angled_cells: Vec<_> = if self.render_idx == 0 && self.header_angle > 0.0 {
+            self.rows[0]
+                .iter()
+                .map(|element| {
+                    element
+                        .cell_metrics(context, style)
+                        .text_widths
+                        .map(|(integer, fraction)| integer + fraction)
+                })
+                .collect()
         } else {
-            frame_area.draw_line(vec![top_left, bottom_left], self.line_style);
-            frame_area.draw_line(vec![top_right, bottom_right], self.line_style);
+            Vec::new()
+        };
+        let line_height = style.line_height(&context.font_cache);
+        let (sin, cos) = self.header_angle.to_radians().sin_cos();
+        let header_height = angled_cells
+            .iter()
+            .flatten()
+            .map(|width| *width * sin + line_height * cos)
+            .fold(Mm(0.0), Mm::max);
+        if header_height + self.cell_padding.top + self.cell_padding.bottom > area.size().height {
+            // The rotated header does not fit on this page.
+            result.has_more = true;
+            return Ok(result);
         }
 
-        self.is_first = false;
+        let areas = area.split_horizontally(&self.column_weights);
+        let mut cell_areas: Vec<_> = if let Some(decorator) = &self.cell_decorator {
+            areas
+                .iter()
+                .enumerate()
+                .map(|(i, area)| decorator.prepare_cell(i, self.render_idx, area.clone()))
+                .collect()
+        } else {
+            areas.clone()
+        };
+
+        if self.row_structure.is_none() {
+            if let Some(row) = area.add_structure_element(render::Tag::TableRow) {
+                let mut row_area = area.clone();
+                row_area.set_structure_parent(row);
+                let cells = cell_areas
+                    .iter()
+                    .filter_map(|_| row_area.add_structure_element(render::Tag::TableCell))
+                    .collect();
+                self.row_structure = Some((row, cells));
+            }
+        }
+        if let Some((_, cells)) = &self.row_structure {
+            for (area, cell) in cell_areas.iter_mut().zip(cells) {
+                area.set_structure_parent(*cell);
+            }
+        }
+
+        let mut row_height = Mm::from(0);
+        self.finished_cells.resize(self.column_weights.len(), false);
+        let cells = cell_areas
+            .iter()
+            .zip(self.rows[self.render_idx].iter_mut())
+            .zip(self.finished_cells.iter_mut())
+            .enumerate();
+        for (i, ((area, element), is_finished)) in cells {
+            if *is_finished {
+                continue;
+            }
+            let cell_area = area;
+            let mut area = area.clone();
+            let metrics = element.cell_metrics(context, style);
+            let padding = if metrics.padding.is_some() {
+                Margins::default()
+            } else {
+                self.cell_padding
+            };
+            area.add_margins(Margins {
+                bottom: Mm(0.0),
+                ..padding
+            });
+            if let Some(Some(width)) = angled_cells.get(i) {
+                // The text line is rotated around its upper left corner, which is placed at the
+                // left edge of the cell so that the rotated lower left corner touches the bottom
+                // of the header.
+                let mut text_area = area.clone();
+                text_area.add_offset(Position::new(0, header_height - line_height * cos));
+                text_area.set_size(Size::new(text_area.size().width.max(*width), line_height));
+                let element_result = text_area.rotated(-self.header_angle, |area| {
+                    context.render_element(element.as_mut(), area, style)
+                })?;
+                *is_finished = !element_result.has_more;
+                result.has_more |= element_result.has_more;
+                row_height = row_height.max(header_height + padding.top + padding.bottom);
+                continue;
+            }
+            let mut style = style;
+            let overflow = self.column_overflows.get(i).copied().unwrap_or_default();
+            if overflow == CellOverflow::Shrink {
+                if let Some((integer, fraction)) = metrics.text_widths {
+                    let width = integer + fraction;
+                    if width > area.size().width && width > Mm(0.0) {
+                        shrink_font_size(&mut style, area.size().width.0 / width.0);
+                    }
+                }
+            }
+            let alignment = self.column_alignments.get(i).copied().unwrap_or_default();
+            if alignment != ColumnAlignment::Left {
+                if let Some((integer, fraction)) = element.cell_metrics(context, style).text_widths
+                {
+                    let free_width = area.size().width - integer - fraction;
+                    let offset = match alignment {
+                        ColumnAlignment::Left => Mm(0.0),
+                        ColumnAlignment::Center => free_width / 2.0,
+                        ColumnAlignment::Right => free_width,
+                        ColumnAlignment::Decimal => {
+                            let max_fraction = self
+                                .decimal_fractions
+                                .as_ref()
+                                .and_then(|fractions| fractions.get(i).copied())
+                                .unwrap_or(fraction);
+                            free_width - (max_fraction - fraction)
+                        }
+                    };
+                    area.add_offset(Position::new(offset.max(Mm(0.0)), 0));
+                }
+            }
+            let element_result = match overflow {
+                CellOverflow::Wrap => context.render_with_word_break(
+                    element.as_mut(),
+                    area,
+                    style,
+                    WordBreak::Anywhere,
+                )?,
+                CellOverflow::Clip => {
+                    cell_area.clipped(|_| context.render_element(element.as_mut(), area, style))?
+                }
+                CellOverflow::Visible | CellOverflow::Shrink => {
+                    context.render_element(element.as_mut(), area, style)?
+                }
+            };
+            *is_finished = !element_result.has_more;
+            result.has_more |= element_result.has_more;
+            let height = element_result.size.height + padding.top + padding.bottom;
+            row_height = row_height.max(height);
+        }
+        result.size.height = row_height;
+
+        if let Some(decorator) = &mut self.cell_decorator {
+            for (i, area) in areas.into_iter().enumerate() {
+                let height =
+                    decorator.decorate_cell(i, self.render_idx, result.has_more, area, row_height);
+                result.size.height = result.size.height.max(height);
+            }
+        }
 
         Ok(result)
     }
 }
 
-/// An unordered list of elements with bullet points.
-///
-/// # Examples
-///
-/// With setters:
-/// ```
-/// use rckive_genpdf::elements;
-/// let mut list = elements::UnorderedList::new();
-/// list.push(elements::Paragraph::new("first"));
-/// list.push(elements::Paragraph::new("second"));
-/// list.push(elements::Paragraph::new("third"));
-/// ```
-///
-/// With setters and a custom bullet symbol:
-/// ```
-/// use rckive_genpdf::elements;
-/// let mut list = elements::UnorderedList::with_bullet("*");
-/// list.push(elements::Paragraph::new("first"));
-/// list.push(elements::Paragraph::new("second"));
-/// list.push(elements::Paragraph::new("third"));
-/// ```
-///
-/// Chained:
-/// ```
-/// use rckive_genpdf::elements;
-/// let list = elements::UnorderedList::new()
-///     .element(elements::Paragraph::new("first"))
-///     .element(elements::Paragraph::new("second"))
-///     .element(elements::Paragraph::new("third"));
-/// ```
-///
-/// Nested list using a [`LinearLayout`][]:
-/// ```
-/// use rckive_genpdf::elements;
-/// let list = elements::UnorderedList::new()
-///     .element(
-///         elements::OrderedList::new()
-///             .element(elements::Paragraph::new("Sublist with bullet point"))
-///     )
-///     .element(
-///         elements::LinearLayout::vertical()
-///             .element(elements::Paragraph::new("Sublist without bullet point:"))
-///             .element(
-///                 elements::OrderedList::new()
-///                     .element(elements::Paragraph::new("first"))
-///                     .element(elements::Paragraph::new("second"))
-///             )
-///     );
-/// ```
-///
-/// [`LinearLayout`]: struct.LinearLayout.html
-pub struct UnorderedList {
-    layout: LinearLayout,
-    bullet: Option<String>,
+impl Element for TableLayout {
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.column_weights.is_empty() {
+            return Ok(result);
+        }
+        if let Some(decorator) = &mut self.cell_decorator {
+            decorator.set_default_line_style(context.default_line_style());
+        }
+        if self.decimal_fractions.is_none()
+            && self.column_alignments.contains(&ColumnAlignment::Decimal)
+        {
+            self.decimal_fractions = Some(self.get_decimal_fractions(context, style));
+        }
+        self.load_rows()?;
+        if self.structure.is_none() {
+            self.structure = area.add_structure_element(render::Tag::Table);
+        }
+        if let Some(table) = self.structure {
+            area.set_structure_parent(table);
+        }
+        result.size.width = area.size().width;
+        while self.render_idx < self.rows.len() {
+            let row_result = self.render_row(context, area.clone(), style)?;
+            result.size.height += row_result.size.height;
+            area.add_offset(Position::new(0, row_result.size.height));
+            if row_result.has_more {
+                break;
+            }
+            // The row has been rendered completely, so we can drop its elements.
+            self.rows[self.render_idx].clear();
+            self.render_idx += 1;
+            self.finished_cells.clear();
+            self.row_structure = None;
+            self.load_rows()?;
+        }
+        result.has_more = self.render_idx < self.rows.len();
+        Ok(result)
+    }
 }
 
-impl UnorderedList {
-    /// Creates a new unordered list with the default bullet point symbol.
-    pub fn new() -> UnorderedList {
-        UnorderedList {
-            layout: LinearLayout::vertical(),
-            bullet: None,
+#[cfg(test)]
+mod tests {
+    use std::collections;
+    use std::iter;
+
+    use float_cmp::approx_eq;
+
+    use super::{
+        Break, CellOverflow, ColumnAlignment, ColumnLayout, DecoratedElement, Figure,
+        FrameCellDecorator, FromFn, HorizontalRule, LinearLayout, ListOfFigures, NumberCell,
+        NumberFormat, OnPageParity, Overlay, PageBreak, PageParity, Paragraph, PdfPage, Positioned,
+        Rotated, SignatureField, Table, TableLayout, Text, TrackedElement,
+    };
+    use crate::error::ErrorKind;
+    use crate::style::{Color, LineStyle, Style};
+    use crate::tests::{
+        as_f32, document, find_operations, page_operations, render_document, FillPage,
+    };
+    use crate::{
+        Alignment, Element, Margins, Mm, Position, RenderResult, Size, Truncation, Whitespace,
+        WordBreak,
+    };
+
+    /// Returns the x coordinates in millimeters of the paths on the given page that are painted
+    /// with the given operator.
+    fn path_x_coordinates(doc: &lopdf::Document, page: usize, paint: &str) -> Vec<Vec<f32>> {
+        let mut paths = Vec::new();
+        let mut points = Vec::new();
+        for op in page_operations(doc, page) {
+            match op.operator.as_str() {
+                "m" | "l" => {
+                    let x: Mm = printpdf::Pt(as_f32(&op.operands[0])).into();
+                    points.push(x.0);
+                }
+                "f" | "S" | "s" | "h" => {
+                    if op.operator == paint {
+                        paths.push(points.clone());
+                    }
+                    if op.operator != "h" {
+                        points.clear();
+                    }
+                }
+                _ => {}
+            }
         }
+        paths
     }
 
-    /// Creates a new unordered list with the given bullet point symbol.
-    pub fn with_bullet(bullet: impl Into<String>) -> UnorderedList {
-        UnorderedList {
-            layout: LinearLayout::vertical(),
-            bullet: Some(bullet.into()),
-        }
+    #[test]
+    fn test_decorated_element() {
+        let mut doc = document();
+        doc.push(
+            DecoratedElement::new(Text::new("text"))
+                .with_margins(2)
+                .with_border(LineStyle::new().with_thickness(1))
+                .with_padding(3)
+                .with_background(Color::Rgb(255, 0, 0)),
+        );
+        let lsb = Style::new().char_left_side_bearing(doc.font_cache(), 't');
+        let doc = render_document(doc).unwrap();
+
+        // The background fills the area inside the border.
+        let fills = path_x_coordinates(&doc, 0, "f");
+        assert_eq!(1, fills.len());
+        let fill_left = fills[0].iter().cloned().fold(f32::MAX, f32::min);
+        assert!(approx_eq!(f32, 3.0, fill_left, epsilon = 0.01));
+
+        // The border is drawn outside of the padding, centered on the edge of the background.
+        let borders = path_x_coordinates(&doc, 0, "S");
+        assert!(!borders.is_empty());
+        let border_left = borders.iter().flatten().cloned().fold(f32::MAX, f32::min);
+        assert!(approx_eq!(f32, 2.5, border_left, epsilon = 0.01));
+
+        // The text starts after the padding, moved by the left side bearing of the first glyph.
+        let cursors = find_operations(&doc, 0, "Td");
+        assert_eq!(1, cursors.len());
+        let text_left: Mm = printpdf::Pt(as_f32(&cursors[0][0])).into();
+        assert!(approx_eq!(f32, 6.0 - lsb.0, text_left.0, epsilon = 0.01));
     }
 
-    /// Adds an element to this list.
-    pub fn push<E: Element + 'static>(&mut self, element: E) {
-        let mut point = BulletPoint::new(element);
-        if let Some(bullet) = &self.bullet {
-            point.set_bullet(bullet.clone());
+    /// Returns the glyph IDs of the characters of the given string in the default font of the
+    /// given document.
+    fn glyph_chars(doc: &crate::Document, s: &str) -> collections::HashMap<u16, char> {
+        let font_cache = doc.font_cache();
+        let font = font_cache.default_font_family().regular;
+        font.glyph_ids(font_cache, s.chars())
+            .into_iter()
+            .zip(s.chars())
+            .collect()
+    }
+
+    /// Returns the text of the text objects on the given page, decoded with the given glyph IDs.
+    fn text_objects(
+        doc: &lopdf::Document,
+        page: usize,
+        chars: &collections::HashMap<u16, char>,
+    ) -> Vec<String> {
+        let mut texts = Vec::new();
+        for op in page_operations(doc, page) {
+            match op.operator.as_str() {
+                "BT" => texts.push(String::new()),
+                "TJ" => texts.last_mut().unwrap().extend(
+                    op.operands[0]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .filter_map(|object| object.as_str().ok())
+                        .flat_map(|bytes| bytes.chunks(2).map(|b| u16::from_be_bytes([b[0], b[1]])))
+                        .map(|glyph_id| chars[&glyph_id]),
+                ),
+                _ => {}
+            }
         }
-        self.layout.push(point);
+        texts
     }
 
-    /// Adds an element to this list and returns the list.
-    pub fn element<E: Element + 'static>(mut self, element: E) -> Self {
-        self.push(element);
-        self
+    #[test]
+    fn test_captions() {
+        let mut doc = document();
+        let chars = glyph_chars(&doc, "Figure 1: First Table 2: Data Second abc");
+        doc.push(Figure::new(Text::new("a"), "First"));
+        doc.push(Table::new(Text::new("b"), "Data"));
+        doc.push(Figure::new(Text::new("c"), "Second"));
+        let doc = render_document(doc).unwrap();
+
+        // Figures and tables are numbered separately, and the caption of a table is printed
+        // above its content.
+        assert_eq!(
+            vec![
+                "a",
+                "Figure 1: First",
+                "Table 1: Data",
+                "b",
+                "c",
+                "Figure 2: Second"
+            ],
+            text_objects(&doc, 0, &chars)
+        );
     }
-}
 
-impl Element for UnorderedList {
-    fn render(
-        &mut self,
-        context: &Context,
-        area: render::Area<'_>,
-        style: Style,
-    ) -> Result<RenderResult, Error> {
-        self.layout.render(context, area, style)
+    #[test]
+    fn test_list_of_figures() {
+        let mut doc = document();
+        let chars = glyph_chars(&doc, "Figure 12: First Second abc");
+        let first = Figure::new(Text::new("a"), "First");
+        let second = Figure::new(Text::new("b"), "Second");
+        doc.push(
+            ListOfFigures::new()
+                .entry(first.handle())
+                .entry(second.handle()),
+        );
+        doc.push(first);
+        doc.push(PageBreak::new());
+        doc.push(second);
+        let doc = render_document(doc).unwrap();
+
+        // The entries are filled in after the figures have been rendered.
+        let texts = text_objects(&doc, 0, &chars);
+        assert_eq!(
+            vec![
+                "a",
+                "Figure 1: First",
+                "Figure 1: First",
+                "1",
+                "Figure 2: Second",
+                "2"
+            ],
+            texts
+        );
+        assert_eq!(vec!["b", "Figure 2: Second"], text_objects(&doc, 1, &chars));
     }
-}
 
-impl Default for UnorderedList {
-    fn default() -> UnorderedList {
-        UnorderedList::new()
+    #[test]
+    fn test_ellipsize() {
+        let doc = document();
+        let font_cache = doc.font_cache();
+        let style = Style::new();
+        let text = "Very long name";
+
+        let max_width = style.str_width(font_cache, "Very long na…");
+        let truncated = super::ellipsize(
+            font_cache,
+            text.into(),
+            style,
+            max_width,
+            "…",
+            Truncation::End,
+        );
+        assert_eq!("Very long na…", truncated);
+        assert!(style.str_width(font_cache, &truncated) <= max_width);
+
+        // Whitespace before the ellipsis is removed.
+        let max_width = style.str_width(font_cache, "Very …");
+        let truncated = super::ellipsize(
+            font_cache,
+            text.into(),
+            style,
+            max_width,
+            "…",
+            Truncation::End,
+        );
+        assert_eq!("Very…", truncated);
+
+        let max_width = style.str_width(font_cache, text);
+        assert_eq!(
+            text,
+            super::ellipsize(
+                font_cache,
+                text.into(),
+                style,
+                max_width,
+                "…",
+                Truncation::End
+            )
+        );
+        assert_eq!(
+            "",
+            super::ellipsize(
+                font_cache,
+                text.into(),
+                style,
+                Mm(0.1),
+                "…",
+                Truncation::End
+            )
+        );
     }
-}
 
-impl<E: Element + 'static> iter::Extend<E> for UnorderedList {
-    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
-        for element in iter {
-            self.push(element);
+    #[test]
+    fn test_ellipsize_truncation() {
+        let doc = document();
+        let font_cache = doc.font_cache();
+        let style = Style::new();
+        let path = "/very/long/path/to/file.txt";
+        let ellipsize = |max_width: Mm, ellipsis: &str, truncation: Truncation| {
+            let truncated = super::ellipsize(
+                font_cache,
+                path.into(),
+                style,
+                max_width,
+                ellipsis,
+                truncation,
+            );
+            assert!(style.str_width(font_cache, &truncated) <= max_width);
+            truncated.into_owned()
+        };
+
+        let max_width = style.str_width(font_cache, "/very/l…ile.txt");
+        assert_eq!(
+            "/very/l…ile.txt",
+            ellipsize(max_width, "…", Truncation::Middle)
+        );
+        assert_eq!(
+            "…th/to/file.txt",
+            ellipsize(max_width, "…", Truncation::Start)
+        );
+
+        // The head and the tail around the ellipsis have a similar length.
+        let max_width = style.str_width(font_cache, "/very/…file.txt");
+        let truncated = ellipsize(max_width, "...", Truncation::Middle);
+        let (head, tail) = truncated.split_once("...").unwrap();
+        assert!(path.starts_with(head) && path.ends_with(tail));
+        assert!(head.chars().count().abs_diff(tail.chars().count()) <= 1);
+        assert_eq!(
+            path,
+            ellipsize(style.str_width(font_cache, path), "...", Truncation::Middle)
+        );
+    }
+
+    #[test]
+    fn test_word_break() {
+        // Renders the string in a paragraph with a width of 60 mm and returns the printed lines.
+        let render_lines = |s: &str, word_break: WordBreak| {
+            let mut doc = document();
+            let font_cache = doc.font_cache();
+            let font = font_cache.default_font_family().regular;
+            let chars: collections::HashMap<_, _> = font
+                .glyph_ids(font_cache, s.chars().chain(iter::once('-')))
+                .into_iter()
+                .zip(s.chars().chain(iter::once('-')))
+                .collect();
+            doc.push(
+                Paragraph::new(s)
+                    .with_word_break(word_break)
+                    .padded(Margins::trbl(0, 150, 0, 0)),
+            );
+            let doc = render_document(doc)?;
+            let lines: Vec<String> = find_operations(&doc, 0, "TJ")
+                .iter()
+                .map(|operands| {
+                    operands[0]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .filter_map(|object| object.as_str().ok())
+                        .flat_map(|bytes| bytes.chunks(2).map(|b| u16::from_be_bytes([b[0], b[1]])))
+                        .map(|glyph_id| chars[&glyph_id])
+                        .collect()
+                })
+                .collect();
+            Ok::<_, crate::error::Error>(lines)
+        };
+
+        let s = "abcdefghij".repeat(8);
+        assert!(render_lines(&s, WordBreak::Never).is_err());
+
+        let doc = document();
+        let style = Style::new();
+        for (word_break, mark) in [
+            (WordBreak::Anywhere, ""),
+            (WordBreak::AnywhereWithHyphen, "-"),
+        ] {
+            let lines = render_lines(&s, word_break).unwrap();
+            assert!(lines.len() > 1);
+            for line in &lines {
+                assert!(style.str_width(doc.font_cache(), line) <= Mm(60.0));
+            }
+            let (last, lines) = lines.split_last().unwrap();
+            let mut printed = String::new();
+            for line in lines {
+                printed.push_str(line.strip_suffix(mark).unwrap());
+            }
+            printed.push_str(last);
+            assert_eq!(s, printed);
         }
     }
-}
 
-impl<E: Element + 'static> iter::FromIterator<E> for UnorderedList {
-    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
-        let mut list = Self::default();
-        list.extend(iter);
-        list
+    #[test]
+    fn test_justify_last_line() {
+        // Renders a justified paragraph with the given number of words and a width of 60 mm and
+        // returns the number of word spaces that have been stretched in each line.
+        let stretched_spaces = |words: usize| {
+            let mut doc = document();
+            doc.push(
+                Paragraph::new("word ".repeat(words).trim_end())
+                    .aligned(Alignment::Justify)
+                    .with_justify_last_line(0.5)
+                    .padded(Margins::trbl(0, 150, 0, 0)),
+            );
+            let doc = render_document(doc).unwrap();
+            let mut lines = Vec::new();
+            for op in page_operations(&doc, 0) {
+                match op.operator.as_str() {
+                    "BT" => lines.push(0),
+                    // Additional space is inserted with a TJ operation without strings.
+                    "TJ" if op.operands[0]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .all(|object| object.as_str().is_err()) =>
+                    {
+                        *lines.last_mut().unwrap() += 1;
+                    }
+                    _ => {}
+                }
+            }
+            lines
+        };
+
+        // A short last line is left-flushed.
+        let lines = stretched_spaces(11);
+        assert_eq!(vec![4, 4, 0], lines);
+
+        // A last line that fills more than half of the width is justified.
+        let lines = stretched_spaces(14);
+        assert_eq!(vec![4, 4, 3], lines);
     }
-}
 
-/// An ordered list of elements with arabic numbers.
-///
-/// # Examples
-///
-/// With setters:
-/// ```
-/// use rckive_genpdf::elements;
-/// let mut list = elements::OrderedList::new();
-/// list.push(elements::Paragraph::new("first"));
-/// list.push(elements::Paragraph::new("second"));
-/// list.push(elements::Paragraph::new("third"));
-/// ```
-///
-/// With setters and a custom start number:
-/// ```
-/// use rckive_genpdf::elements;
-/// let mut list = elements::OrderedList::with_start(5);
-/// list.push(elements::Paragraph::new("first"));
-/// list.push(elements::Paragraph::new("second"));
-/// list.push(elements::Paragraph::new("third"));
-/// ```
-///
-/// Chained:
-/// ```
-/// use rckive_genpdf::elements;
-/// let list = elements::OrderedList::new()
-///     .element(elements::Paragraph::new("first"))
-///     .element(elements::Paragraph::new("second"))
-///     .element(elements::Paragraph::new("third"));
-/// ```
-///
-/// Nested list using a [`LinearLayout`][]:
-/// ```
-/// use rckive_genpdf::elements;
-/// let list = elements::OrderedList::new()
-///     .element(
-///         elements::UnorderedList::new()
-///             .element(elements::Paragraph::new("Sublist with number"))
-///     )
-///     .element(
-///         elements::LinearLayout::vertical()
-///             .element(elements::Paragraph::new("Sublist without number:"))
-///             .element(
-///                 elements::UnorderedList::new()
-///                     .element(elements::Paragraph::new("first"))
-///                     .element(elements::Paragraph::new("second"))
-///             )
-///     );
-/// ```
-///
-/// [`LinearLayout`]: struct.LinearLayout.html
-pub struct OrderedList {
-    layout: LinearLayout,
-    number: usize,
-}
+    #[test]
+    fn test_paragraph_alignment() {
+        // Returns the x coordinates of the lines of a paragraph with a width of 60 mm.
+        let render = |paragraph: Paragraph| {
+            let mut doc = document();
+            doc.push(paragraph.padded(Margins::trbl(0, 150, 0, 0)));
+            let doc = render_document(doc).unwrap();
+            find_operations(&doc, 0, "Td")
+                .iter()
+                .map(|operands| Mm::from(printpdf::Pt(as_f32(&operands[0]))))
+                .collect::<Vec<_>>()
+        };
+        let text = "word ".repeat(11);
+        let text = text.trim_end();
 
-impl OrderedList {
-    /// Creates a new ordered list starting at 1.
-    pub fn new() -> OrderedList {
-        OrderedList::with_start(1)
+        let left = render(Paragraph::new(text));
+        assert_eq!(3, left.len());
+        assert!(left.iter().all(|x| *x == left[0]));
+
+        let mut paragraph = Paragraph::new(text);
+        paragraph.set_alignment(Alignment::Right);
+        let right = render(paragraph);
+        assert_eq!(3, right.len());
+        assert!(right.iter().zip(&left).all(|(right, left)| right > left));
+
+        // The last line is centered while the other lines are right-aligned.
+        let mut paragraph = Paragraph::new(text).with_last_line_alignment(Alignment::Center);
+        paragraph.set_alignment(Alignment::Right);
+        let centered = render(paragraph);
+        assert_eq!(right[..2], centered[..2]);
+        assert!(centered[2] > left[2] && centered[2] < right[2]);
     }
 
-    /// Creates a new ordered list with the given start number.
-    pub fn with_start(start: usize) -> OrderedList {
-        OrderedList {
-            layout: LinearLayout::vertical(),
-            number: start,
-        }
+    #[test]
+    fn test_paragraph_kerning_across_strings() {
+        let doc = document();
+        let width = Style::new().str_width(doc.font_cache(), "AVAV");
+        let kerning = Style::new().kerning_after(doc.font_cache(), (Style::new(), 'V'), "A");
+        assert!(kerning < Mm(0.0));
+
+        // Renders the paragraph in an area that is just wide enough for the kerned string and
+        // returns the x coordinates of its lines.
+        let render = |paragraph: Paragraph| {
+            let mut doc = document();
+            let margin = doc.paper_size().width - width - Mm(0.01);
+            doc.push(
+                paragraph
+                    .aligned(Alignment::Right)
+                    .padded(Margins::trbl(0, margin, 0, 0)),
+            );
+            let doc = render_document(doc).unwrap();
+            find_operations(&doc, 0, "Td")
+                .iter()
+                .map(|operands| as_f32(&operands[0]))
+                .collect::<Vec<_>>()
+        };
+
+        // The kerning between the strings is included in the line width, so the split string fits
+        // into a single line and is aligned like the single string.
+        let single = render(Paragraph::new("AVAV"));
+        let split = render(Paragraph::default().string("AV").string("AV"));
+        assert_eq!(1, single.len());
+        assert_eq!(1, split.len());
+        assert!(approx_eq!(f32, single[0], split[0], epsilon = 0.001));
     }
 
-    /// Adds an element to this list.
-    pub fn push<E: Element + 'static>(&mut self, element: E) {
-        let mut point = BulletPoint::new(element);
-        point.set_bullet(format!("{}.", self.number));
-        self.layout.push(point);
-        self.number += 1;
+    #[test]
+    fn test_preserve_spaces() {
+        // Returns the x coordinate of the right-aligned paragraph and the number of glyphs.
+        let render = |paragraph: Paragraph| {
+            let mut doc = document();
+            doc.push(paragraph.aligned(Alignment::Right));
+            let doc = render_document(doc).unwrap();
+            let x = Mm::from(printpdf::Pt(as_f32(&find_operations(&doc, 0, "Td")[0][0])));
+            let glyphs: usize = find_operations(&doc, 0, "TJ")
+                .iter()
+                .flat_map(|operands| operands[0].as_array().unwrap().clone())
+                .filter_map(|object| object.as_str().ok().map(|s| s.len() / 2))
+                .sum();
+            (x, glyphs)
+        };
+        let space = Style::new().char_width(document().font_cache(), ' ');
+
+        let (preserved, preserved_glyphs) = render(Paragraph::new("a    b"));
+        assert_eq!(6, preserved_glyphs);
+        let explicit = render(Paragraph::new("a    b").with_preserve_spaces(true));
+        assert_eq!((preserved, preserved_glyphs), explicit);
+
+        // The collapsed paragraph is three space advances shorter.
+        let (collapsed, collapsed_glyphs) =
+            render(Paragraph::new("a    b").with_preserve_spaces(false));
+        assert_eq!(3, collapsed_glyphs);
+        assert!(approx_eq!(
+            f32,
+            (preserved + space * 3.0).0,
+            collapsed.0,
+            epsilon = 0.01
+        ));
     }
 
-    /// Adds an element to this list and returns the list.
-    pub fn element<E: Element + 'static>(mut self, element: E) -> Self {
-        self.push(element);
-        self
+    #[test]
+    fn test_max_word_stretch() {
+        // Renders a justified paragraph with a width of 60 mm and returns the number of word
+        // spaces that have been stretched.
+        let stretched_spaces = |max_ratio: Option<f32>| {
+            let mut paragraph = Paragraph::new("a few words Abcdefghijklmnopqrstuvwxyz")
+                .aligned(Alignment::Justify);
+            if let Some(max_ratio) = max_ratio {
+                paragraph.set_max_word_stretch(max_ratio);
+            }
+            let mut doc = document();
+            doc.push(paragraph.padded(Margins::trbl(0, 150, 0, 0)));
+            let doc = render_document(doc).unwrap();
+            find_operations(&doc, 0, "TJ")
+                .iter()
+                .filter(|operands| {
+                    operands[0]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .all(|object| object.as_str().is_err())
+                })
+                .count()
+        };
+
+        // The first line has to be stretched a lot because the long word does not fit.
+        assert_eq!(2, stretched_spaces(None));
+        assert_eq!(2, stretched_spaces(Some(100.0)));
+        // If the stretch exceeds the limit, the line is left-flushed.
+        assert_eq!(0, stretched_spaces(Some(2.0)));
     }
-}
 
-impl Element for OrderedList {
-    fn render(
-        &mut self,
-        context: &Context,
-        area: render::Area<'_>,
-        style: Style,
-    ) -> Result<RenderResult, Error> {
-        self.layout.render(context, area, style)
+    #[test]
+    fn test_invalid_table_row() {
+        let mut table = TableLayout::new(vec![1, 1]);
+        let err = table.push_row(vec![Box::new(Text::new("a"))]).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::InvalidTableRow {
+                expected: 2,
+                actual: 1
+            }
+        ));
     }
-}
 
-impl Default for OrderedList {
-    fn default() -> OrderedList {
-        OrderedList::new()
+    #[test]
+    fn test_frame_cell_decorator_edge_styles() {
+        let mut doc = document();
+        let line_height = Style::new().line_height(doc.font_cache());
+        let mut table = TableLayout::new(vec![1]);
+        table.set_cell_decorator(FrameCellDecorator::new(true, true, true).with_edge_styles(
+            None,
+            None,
+            Some(LineStyle::new().with_thickness(1)),
+            None,
+        ));
+        table.push_row(vec![Box::new(Text::new("Cell"))]).unwrap();
+        doc.push(table);
+        let doc = render_document(doc).unwrap();
+
+        // Only the bottom edge is drawn, below the cell content.
+        let mut lines = Vec::new();
+        for op in page_operations(&doc, 0) {
+            let point = || {
+                let x = Mm::from(printpdf::Pt(as_f32(&op.operands[0])));
+                let y = Mm::from(297) - Mm::from(printpdf::Pt(as_f32(&op.operands[1])));
+                (x.0, y.0)
+            };
+            match op.operator.as_str() {
+                "m" => lines.push(vec![point()]),
+                "l" => lines.last_mut().unwrap().push(point()),
+                _ => {}
+            }
+        }
+        assert_eq!(1, lines.len());
+        let line = &lines[0];
+        assert_eq!(2, line.len());
+        assert!(approx_eq!(f32, 0.0, line[0].0, epsilon = 0.01));
+        assert!(approx_eq!(f32, 210.0, line[1].0, epsilon = 0.01));
+        for (_, y) in line {
+            assert!(approx_eq!(f32, line_height.0 + 0.5, *y, epsilon = 0.01));
+        }
     }
-}
 
-impl<E: Element + 'static> iter::Extend<E> for OrderedList {
-    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
-        for element in iter {
-            self.push(element);
+    #[test]
+    fn test_table_cell_split() {
+        let mut doc = document();
+        doc.set_paper_size(Size::new(60, 40));
+        let mut table = TableLayout::new(vec![1, 1]);
+        table.set_cell_decorator(FrameCellDecorator::new(true, true, false));
+        table
+            .row()
+            .element(Text::new("Short"))
+            .element(Paragraph::new("word ".repeat(40)))
+            .push()
+            .unwrap();
+        table
+            .row()
+            .element(Text::new("Next"))
+            .element(Text::new("Row"))
+            .push()
+            .unwrap();
+        doc.push(table);
+        let doc = render_document(doc).unwrap();
+
+        let pages = doc.get_pages().len();
+        assert!(pages >= 2);
+        let strings: Vec<_> = (0..pages)
+            .map(|page| {
+                find_operations(&doc, page, "TJ")
+                    .into_iter()
+                    .map(|operands| format!("{:?}", operands))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        // The long paragraph is continued on every page ...
+        assert!(strings.iter().all(|page| !page.is_empty()));
+        // ... but the short cell is only printed once.
+        let short = &strings[0][0];
+        let count = strings.iter().flatten().filter(|s| *s == short).count();
+        assert_eq!(1, count);
+    }
+
+    #[test]
+    fn test_table_row_height_wrapped_paragraph() {
+        let mut doc = document();
+        let line_height = Style::new().line_height(doc.font_cache());
+        let mut table = TableLayout::new(vec![1, 5]);
+        table.set_cell_decorator(FrameCellDecorator::new(true, true, false));
+        table
+            .push_row(vec![
+                Box::new(Paragraph::new("word ".repeat(9).trim_end())),
+                Box::new(Text::new("short")),
+            ])
+            .unwrap();
+        table
+            .push_row(vec![
+                Box::new(Text::new("next")),
+                Box::new(Text::new("row")),
+            ])
+            .unwrap();
+        doc.push(table);
+        let doc = render_document(doc).unwrap();
+
+        // The y coordinates of the text cursors, measured from the top of the page
+        let cursors: Vec<_> = find_operations(&doc, 0, "Td")
+            .iter()
+            .map(|operands| Mm(297.0) - Mm::from(printpdf::Pt(as_f32(&operands[1]))))
+            .collect();
+        // The paragraph is wrapped at the width of the first column into three lines.
+        assert_eq!(6, cursors.len());
+        let (first_row, second_row) = cursors.split_at(4);
+        for (line, y) in first_row[..3].iter().enumerate() {
+            assert!(approx_eq!(
+                f32,
+                first_row[0].0 + line_height.0 * line as f32,
+                y.0,
+                epsilon = 0.01
+            ));
+        }
+        // The second row starts below the third line of the paragraph.
+        for y in second_row {
+            assert!(y.0 >= first_row[2].0 + line_height.0);
         }
     }
-}
 
-impl<E: Element + 'static> iter::FromIterator<E> for OrderedList {
-    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
-        let mut list = Self::default();
-        list.extend(iter);
-        list
+    #[test]
+    fn test_table_column_alignment() {
+        let mut doc = document();
+        let width = |s: &str| Style::new().str_width(doc.font_cache(), s);
+        let (short, long) = (width("1.5"), width("123.45"));
+        let (short_int, long_int) = (width("1"), width("123"));
+        let mut table = TableLayout::new(vec![1, 1, 1])
+            .with_column_alignment(1, ColumnAlignment::Right)
+            .with_column_alignment(2, ColumnAlignment::Decimal);
+        for value in ["1.5", "123.45"] {
+            table
+                .push_row(vec![
+                    Box::new(Text::new(value)),
+                    Box::new(Text::new(value)),
+                    Box::new(Paragraph::new(value)),
+                ])
+                .unwrap();
+        }
+        doc.push(table);
+        let doc = render_document(doc).unwrap();
+
+        // The x coordinates of the text cursors
+        let cursors: Vec<_> = find_operations(&doc, 0, "Td")
+            .iter()
+            .map(|operands| Mm::from(printpdf::Pt(as_f32(&operands[0]))))
+            .collect();
+        assert_eq!(6, cursors.len());
+        let (first, second) = cursors.split_at(3);
+        // The left-aligned cells start at the same position.
+        assert!(approx_eq!(f32, first[0].0, second[0].0, epsilon = 0.01));
+        // The right-aligned cells end at the same position.
+        assert!(approx_eq!(
+            f32,
+            (first[1] + short).0,
+            (second[1] + long).0,
+            epsilon = 0.01
+        ));
+        // The decimal-aligned cells are aligned on the separator.
+        assert!(approx_eq!(
+            f32,
+            (first[2] + short_int).0,
+            (second[2] + long_int).0,
+            epsilon = 0.01
+        ));
+        assert!(first[2] + short < second[2] + long);
     }
-}
 
-/// A bullet point in a list.
-///
-/// This is a helper element for the [`OrderedList`][] and [`UnorderedList`][] types, but you can
-/// also use it directly if you have special requirements.
-///
-/// # Example
-///
-/// ```
-/// use rckive_genpdf::elements;
-/// let layout = elements::LinearLayout::vertical()
-///     .element(elements::BulletPoint::new(elements::Paragraph::new("first"))
-///         .with_bullet("a)"))
-///     .element(elements::BulletPoint::new(elements::Paragraph::new("second"))
-///         .with_bullet("b)"));
-/// ```
-///
-/// [`OrderedList`]: struct.OrderedList.html
-/// [`UnorderedList`]: struct.UnorderedList.html
-pub struct BulletPoint<E: Element> {
-    element: E,
-    indent: Mm,
-    bullet_space: Mm,
-    bullet: String,
-    bullet_rendered: bool,
-}
+    #[test]
+    fn test_table_padded_cell_alignment() {
+        // Returns the x coordinates of the text cursors.
+        let render = |padded: bool| {
+            let mut doc = document();
+            let mut table = TableLayout::new(vec![1, 1])
+                .with_column_alignment(0, ColumnAlignment::Right)
+                .with_column_alignment(1, ColumnAlignment::Decimal);
+            for value in ["1.5", "123.45"] {
+                let cell = |value| -> Box<dyn Element> {
+                    if padded {
+                        Box::new(Text::new(value).padded(Margins::trbl(0, 2, 0, 1)))
+                    } else {
+                        Box::new(Text::new(value))
+                    }
+                };
+                table.push_row(vec![cell(value), cell(value)]).unwrap();
+            }
+            doc.push(table);
+            let doc = render_document(doc).unwrap();
+            find_operations(&doc, 0, "Td")
+                .iter()
+                .map(|operands| Mm::from(printpdf::Pt(as_f32(&operands[0]))).0)
+                .collect::<Vec<_>>()
+        };
+
+        let cursors = render(false);
+        let padded_cursors = render(true);
+        assert_eq!(4, padded_cursors.len());
+        // The padded cells are aligned like the other cells, but keep their right padding.
+        for (cursor, padded_cursor) in cursors.iter().zip(&padded_cursors) {
+            assert!(approx_eq!(
+                f32,
+                cursor - 2.0,
+                *padded_cursor,
+                epsilon = 0.01
+            ));
+        }
+    }
 
-impl<E: Element> BulletPoint<E> {
-    /// Creates a new bullet point with the given element.
-    pub fn new(element: E) -> BulletPoint<E> {
-        BulletPoint {
-            element,
-            indent: Mm::from(10),
-            bullet_space: Mm::from(2),
-            bullet: String::from("–"),
-            bullet_rendered: false,
+    #[test]
+    fn test_number_cell() {
+        let format = NumberFormat::new();
+        assert_eq!("1,234,567.50", format.format(1234567.5));
+        assert_eq!("-1,000.00", format.format(-999.999));
+        assert_eq!("0.00", format.format(-0.001));
+        assert_eq!("123", format.with_decimals(0).format(123.4));
+        let format = format
+            .with_thousands_separator(Some('.'))
+            .with_decimal_separator(',');
+        assert_eq!("1.234.567,50", format.format(1234567.5));
+        assert_eq!(
+            "1234567",
+            NumberFormat::new()
+                .with_decimals(0)
+                .with_thousands_separator(None)
+                .format(1234567.0)
+        );
+
+        let mut doc = document();
+        let width = |s: &str| Style::new().str_width(doc.font_cache(), s);
+        let (short_int, long_int) = (width("1"), width("1.234.567"));
+        let mut table =
+            TableLayout::new(vec![1]).with_column_alignment(0, ColumnAlignment::Decimal);
+        for value in [1.25, 1234567.5] {
+            table
+                .push_row(vec![Box::new(NumberCell::new(value, format))])
+                .unwrap();
         }
+        doc.push(table);
+        let doc = render_document(doc).unwrap();
+
+        // The x coordinates of the text cursors
+        let cursors: Vec<_> = find_operations(&doc, 0, "Td")
+            .iter()
+            .map(|operands| Mm::from(printpdf::Pt(as_f32(&operands[0]))))
+            .collect();
+        assert_eq!(2, cursors.len());
+        // The cells are aligned on the decimal separator of the format.
+        assert!(approx_eq!(
+            f32,
+            (cursors[0] + short_int).0,
+            (cursors[1] + long_int).0,
+            epsilon = 0.01
+        ));
     }
 
-    /// Sets the bullet point symbol for this bullet point.
-    pub fn set_bullet(&mut self, bullet: impl Into<String>) {
-        self.bullet = bullet.into();
+    #[test]
+    fn test_positioned() {
+        // Returns the text cursors in millimeters, measured from the upper left corner of the page.
+        let render = |positioned: bool| {
+            let mut doc = document();
+            let mut decorator = crate::SimplePageDecorator::new();
+            decorator.set_margins(10);
+            doc.set_page_decorator(decorator);
+            doc.push(Paragraph::new("first"));
+            if positioned {
+                doc.push(Positioned::new(Text::new("stamp"), Position::new(50, 100)));
+            }
+            doc.push(Paragraph::new("second"));
+            let doc = render_document(doc).unwrap();
+            find_operations(&doc, 0, "Td")
+                .iter()
+                .map(|operands| {
+                    let x = Mm::from(printpdf::Pt(as_f32(&operands[0])));
+                    let y = Mm(297.0) - Mm::from(printpdf::Pt(as_f32(&operands[1])));
+                    (x, y)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let expected = render(false);
+        let cursors = render(true);
+        assert_eq!(3, cursors.len());
+        // The following element is not displaced.
+        assert_eq!(expected, [cursors[0], cursors[2]]);
+        // The stamp is rendered relative to the content area, and its first line starts at the
+        // same distance from the top of the area as the first paragraph.
+        let (x, y) = cursors[1];
+        assert!(approx_eq!(f32, 60.0, x.0, epsilon = 0.5));
+        assert!(approx_eq!(
+            f32,
+            (expected[0].1 + Mm(100.0)).0,
+            y.0,
+            epsilon = 0.01
+        ));
     }
 
-    /// Sets the bullet point symbol for this bullet point and returns the bullet point.
-    pub fn with_bullet(mut self, bullet: impl Into<String>) -> Self {
-        self.set_bullet(bullet);
-        self
+    #[test]
+    fn test_signature_field() {
+        let mut doc = document();
+        let mut decorator = crate::SimplePageDecorator::new();
+        decorator.set_margins(10);
+        doc.set_page_decorator(decorator);
+        doc.push(SignatureField::new("customer", (60, 20)));
+        let doc = render_document(doc).unwrap();
+
+        let page_id = doc.page_iter().next().unwrap();
+        let annotations = doc
+            .get_dictionary(page_id)
+            .unwrap()
+            .get(b"Annots")
+            .and_then(lopdf::Object::as_array)
+            .unwrap();
+        assert_eq!(1, annotations.len());
+        let field_id = annotations[0].as_reference().unwrap();
+        let field = doc.get_dictionary(field_id).unwrap();
+        assert_eq!(
+            b"Widget".as_ref(),
+            field.get(b"Subtype").unwrap().as_name().unwrap()
+        );
+        assert_eq!(
+            b"Sig".as_ref(),
+            field.get(b"FT").unwrap().as_name().unwrap()
+        );
+        assert_eq!(
+            b"customer".as_ref(),
+            field.get(b"T").unwrap().as_str().unwrap()
+        );
+        // The rectangle in millimeters, measured from the lower left corner of the page
+        let rect: Vec<_> = field
+            .get(b"Rect")
+            .and_then(lopdf::Object::as_array)
+            .unwrap()
+            .iter()
+            .map(|value| Mm::from(printpdf::Pt(as_f32(value))).0)
+            .collect();
+        let expected = [10.0, 297.0 - 30.0, 70.0, 297.0 - 10.0];
+        for (expected, value) in expected.iter().zip(&rect) {
+            assert!(approx_eq!(f32, *expected, *value, epsilon = 0.01));
+        }
+
+        let fields = doc
+            .catalog()
+            .unwrap()
+            .get(b"AcroForm")
+            .and_then(lopdf::Object::as_dict)
+            .and_then(|form| form.get(b"Fields"))
+            .and_then(lopdf::Object::as_array)
+            .unwrap();
+        assert_eq!(1, fields.len());
+        assert_eq!(field_id, fields[0].as_reference().unwrap());
     }
-}
 
-impl<E: Element> Element for BulletPoint<E> {
-    fn render(
-        &mut self,
-        context: &Context,
-        area: render::Area<'_>,
-        style: Style,
-    ) -> Result<RenderResult, Error> {
-        let mut element_area = area.clone();
-        element_area.add_offset(Position::new(self.indent, 0));
-        let mut result = self.element.render(context, element_area, style)?;
-        result.size.width += self.indent;
-        if !self.bullet_rendered {
-            let bullet_width = style.str_width(&context.font_cache, &self.bullet);
+    #[test]
+    fn test_signature_field_duplicate_name() {
+        let mut doc = document();
+        doc.push(SignatureField::new("customer", (60, 20)));
+        doc.push(PageBreak::new());
+        doc.push(SignatureField::new("customer", (60, 20)));
+        let err = render_document(doc).unwrap_err();
+        assert!(matches!(err.kind(), crate::error::ErrorKind::InvalidData));
+    }
+
+    #[test]
+    fn test_rotated() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let child_size = Rc::new(Cell::new(None));
+        let mut doc = document();
+        let mut decorator = crate::SimplePageDecorator::new();
+        decorator.set_margins(10);
+        doc.set_page_decorator(decorator);
+        let size = child_size.clone();
+        doc.push(Rotated::new(super::FromFn::new(move |context, area, _| {
+            size.set(Some(area.size()));
             area.print_str(
                 &context.font_cache,
-                Position::new(self.indent - bullet_width - self.bullet_space, 0),
-                style,
-                self.bullet.as_str(),
+                Position::default(),
+                Style::new(),
+                "rotated",
             )?;
-            self.bullet_rendered = true;
-        }
-        Ok(result)
-    }
-}
+            Ok(RenderResult {
+                size: Size::new(50, 20),
+                has_more: false,
+            })
+        })));
+        doc.push(Paragraph::new("below"));
+        let doc = render_document(doc).unwrap();
+        assert_eq!(Some(Size::new(277, 190)), child_size.get());
 
-/// A decorator for table cells.
-///
-/// Implementations of this trait can be used to style cells of a [`TableLayout`][].
-///
-/// [`TableLayout`]: struct.TableLayout.html
-pub trait CellDecorator {
-    /// Sets the size of the table.
-    ///
-    /// This function is called once before the first call to [`prepare_cell`][] or
-    /// [`decorate_cell`][].
-    ///
-    /// [`prepare_cell`]: #tymethod.prepare_cell
-    /// [`decorate_cell`]: #tymethod.decorate_cell
-    fn set_table_size(&mut self, num_columns: usize, num_rows: usize) {
-        let _ = (num_columns, num_rows);
-    }
+        let transforms = find_operations(&doc, 0, "cm");
+        assert_eq!(1, transforms.len());
+        let matrix: Vec<_> = transforms[0].iter().map(as_f32).collect();
+        assert_eq!(vec![0.0, -1.0, 1.0, 0.0], matrix[..4]);
+        // The coordinate system is rotated around the upper right corner of the content area,
+        // which is the upper left corner of the rotated area.
+        let offset = |value: f32| Mm::from(printpdf::Pt(value)).0;
+        assert!(approx_eq!(
+            f32,
+            10.0 + 190.0 - 287.0,
+            offset(matrix[4]),
+            epsilon = 0.01
+        ));
+        assert!(approx_eq!(
+            f32,
+            10.0 + 190.0 + 287.0,
+            offset(matrix[5]),
+            epsilon = 0.01
+        ));
 
-    /// Prepares the cell with the given indizes and returns the area for rendering the cell.
-    fn prepare_cell<'p>(
-        &self,
-        column: usize,
-        row: usize,
-        area: render::Area<'p>,
-    ) -> render::Area<'p> {
-        let _ = (column, row);
-        area
+        // The following paragraph is rendered below the rotated element, which has the width of
+        // the child as its height.
+        let cursors = find_operations(&doc, 0, "Td");
+        assert_eq!(2, cursors.len());
+        let y = Mm(297.0) - Mm::from(printpdf::Pt(as_f32(&cursors[1][1])));
+        assert!(y > Mm(60.0) && y < Mm(70.0));
     }
 
-    /// Styles the cell with the given indizes thas has been rendered within the given area and the
-    /// given row height and return the total row height.
-    fn decorate_cell(
-        &mut self,
-        column: usize,
-        row: usize,
-        has_more: bool,
-        area: render::Area<'_>,
-        row_height: Mm,
-    ) -> Mm;
-}
-
-/// A cell decorator that draws frames around table cells.
-///
-/// This decorator draws frames around the cells of a [`TableLayout`][].  You can configure whether
-/// inner, outer and continuation borders are drawn.  A continuation border is a border between a
-/// cell and the page margin that occurs if a cell has to be wrapped to a new page.
-///
-/// [`TableLayout`]: struct.TableLayout.html
-#[derive(Clone, Debug, Default)]
-pub struct FrameCellDecorator {
-    inner: bool,
-    outer: bool,
-    cont: bool,
-    line_style: LineStyle,
-    num_columns: usize,
-    num_rows: usize,
-    last_row: Option<usize>,
-}
+    #[test]
+    fn test_table_extend_rows() {
+        let row = |i: usize| -> Vec<Box<dyn crate::Element>> {
+            vec![
+                Box::new(Text::new(i.to_string())),
+                Box::new(Text::new(format!("Item {}", i))),
+            ]
+        };
+        let render_table = |table: TableLayout| {
+            let mut doc = document();
+            doc.push(table);
+            let doc = render_document(doc).unwrap();
+            (0..doc.get_pages().len())
+                .map(|page| format!("{:?}", page_operations(&doc, page)))
+                .collect::<Vec<_>>()
+        };
 
-impl FrameCellDecorator {
-    /// Creates a new frame cell decorator with the given settings for inner, outer and
-    /// continuation borders.
-    pub fn new(inner: bool, outer: bool, cont: bool) -> FrameCellDecorator {
-        FrameCellDecorator {
-            inner,
-            outer,
-            cont,
-            ..Default::default()
+        let mut eager_table = TableLayout::new(vec![1, 3]);
+        eager_table.set_cell_decorator(FrameCellDecorator::new(true, true, true));
+        for i in 0..1000 {
+            eager_table.push_row(row(i)).unwrap();
         }
+        let expected = render_table(eager_table);
+
+        let mut lazy_table = TableLayout::new(vec![1, 3]);
+        lazy_table.set_cell_decorator(FrameCellDecorator::new(true, true, true));
+        lazy_table.push_row(row(0)).unwrap();
+        lazy_table.extend_rows((1..500).map(row));
+        lazy_table.extend_rows((500..999).map(row));
+        lazy_table.push_row(row(999)).unwrap();
+        let pages = render_table(lazy_table);
+
+        // The lazily read rows are paginated and decorated like the rows pushed before rendering.
+        assert!(pages.len() > 1);
+        assert_eq!(expected, pages);
+        let cells: usize = pages.iter().map(|page| page.matches("Td").count()).sum();
+        assert_eq!(2000, cells);
+
+        let mut invalid_table = TableLayout::new(vec![1, 3]);
+        invalid_table.extend_rows((0..10).map(move |i| row(i).into_iter().take(1).collect()));
+        let mut doc = document();
+        doc.push(invalid_table);
+        let err = render_document(doc).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::InvalidTableRow {
+                expected: 2,
+                actual: 1
+            }
+        ));
     }
 
-    /// Creates a new frame cell decorator with the given border settings, as well as a line style.
-    pub fn with_line_style(
-        inner: bool,
-        outer: bool,
-        cont: bool,
-        line_style: impl Into<LineStyle>,
-    ) -> FrameCellDecorator {
-        Self {
-            inner,
-            outer,
-            cont,
-            line_style: line_style.into(),
-            ..Default::default()
+    #[test]
+    fn test_table_cell_padding() {
+        // Returns the text cursors in millimeters, measured from the upper left corner of the page.
+        let render = |padding: Option<Margins>| {
+            let mut table = TableLayout::new(vec![1, 1]);
+            if let Some(padding) = padding {
+                table.set_cell_padding(padding);
+            }
+            table
+                .row()
+                .element(Text::new("a"))
+                .element(Text::new("b").padded(1).styled(Color::Rgb(0, 0, 0)))
+                .push()
+                .unwrap();
+            table
+                .row()
+                .element(Text::new("c"))
+                .element(Text::new("d"))
+                .push()
+                .unwrap();
+            let mut doc = document();
+            doc.push(table);
+            let doc = render_document(doc).unwrap();
+            find_operations(&doc, 0, "Td")
+                .iter()
+                .map(|operands| {
+                    let x = Mm::from(printpdf::Pt(as_f32(&operands[0])));
+                    let y = Mm(297.0) - Mm::from(printpdf::Pt(as_f32(&operands[1])));
+                    (x, y)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let expected = render(None);
+        let cursors = render(Some(Margins::trbl(5, 3, 5, 4)));
+        assert_eq!(4, expected.len());
+        assert_eq!(4, cursors.len());
+        let offsets: Vec<_> = expected
+            .iter()
+            .zip(&cursors)
+            .map(|(expected, cursor)| ((cursor.0 - expected.0).0, (cursor.1 - expected.1).0))
+            .collect();
+        // The cells without an explicit padding are inset by the default padding, the padded cell
+        // keeps its own padding even though it is wrapped in a styled element, and the first row is higher by the additional vertical padding.
+        let row_offset = 5.0 + 5.0 - 2.0;
+        let expected_offsets = [
+            (4.0, 5.0),
+            (0.0, 0.0),
+            (4.0, row_offset + 5.0),
+            (4.0, row_offset + 5.0),
+        ];
+        for (offset, expected_offset) in offsets.iter().zip(&expected_offsets) {
+            assert!(approx_eq!(f32, expected_offset.0, offset.0, epsilon = 0.01));
+            assert!(approx_eq!(f32, expected_offset.1, offset.1, epsilon = 0.01));
         }
     }
 
-    fn print_left(&self, column: usize) -> bool {
-        if column == 0 {
-            self.outer
-        } else {
-            self.inner
+    #[test]
+    fn test_table_header_angle() {
+        let render = |angle: f32| {
+            let mut table = TableLayout::new(vec![1, 1]).with_header_angle(angle);
+            table
+                .row()
+                .element(Text::new("A long header label"))
+                .element(Text::new("B"))
+                .push()
+                .unwrap();
+            table
+                .row()
+                .element(Text::new("1"))
+                .element(Text::new("2"))
+                .push()
+                .unwrap();
+            let mut doc = document();
+            doc.push(table);
+            render_document(doc).unwrap()
+        };
+        // Returns the y coordinates of the text cursors in millimeters, measured from the top of
+        // the page.
+        let cursors = |doc: &lopdf::Document| {
+            find_operations(doc, 0, "Td")
+                .iter()
+                .map(|operands| Mm(297.0) - Mm::from(printpdf::Pt(as_f32(&operands[1]))))
+                .collect::<Vec<_>>()
+        };
+
+        let doc = render(0.0);
+        assert!(find_operations(&doc, 0, "cm").is_empty());
+        let expected = cursors(&doc);
+
+        let doc = render(45.0);
+        let transforms = find_operations(&doc, 0, "cm");
+        assert_eq!(2, transforms.len());
+        let (sin, cos) = 45f32.to_radians().sin_cos();
+        for transform in &transforms {
+            let matrix: Vec<_> = transform.iter().map(as_f32).collect();
+            assert!(approx_eq!(f32, cos, matrix[0], epsilon = 0.001));
+            assert!(approx_eq!(f32, sin, matrix[1], epsilon = 0.001));
+            assert!(approx_eq!(f32, -sin, matrix[2], epsilon = 0.001));
+            assert!(approx_eq!(f32, cos, matrix[3], epsilon = 0.001));
         }
+
+        // The header row is high enough for the bounding box of the rotated label, so the second
+        // row is moved down.
+        let doc_cursors = cursors(&doc);
+        assert_eq!(4, doc_cursors.len());
+        let line_height = expected[2] - expected[0];
+        let label_width = Style::new().str_width(document().font_cache(), "A long header label");
+        let header_height = label_width * sin + line_height * cos;
+        assert!(approx_eq!(
+            f32,
+            (expected[2] + header_height - line_height).0,
+            doc_cursors[2].0,
+            epsilon = 0.01
+        ));
     }
 
-    fn print_right(&self, column: usize) -> bool {
-        if column + 1 == self.num_columns {
-            self.outer
-        } else {
-            false
+    #[test]
+    fn test_table_column_overflow() {
+        let word = "a".repeat(40);
+        let render_table = |element: Box<dyn crate::Element>, overflow: CellOverflow| {
+            let mut doc = document();
+            let mut table = TableLayout::new(vec![1, 9]).with_column_overflow(0, overflow);
+            table
+                .push_row(vec![element, Box::new(Text::new("next"))])
+                .unwrap();
+            doc.push(table);
+            render_document(doc)
+        };
+
+        // Without a policy, the long word cannot be wrapped.
+        let result = render_table(Box::new(Paragraph::new(&word)), CellOverflow::Visible);
+        assert!(result.is_err());
+
+        // With the wrap policy, the word is broken within the first column (21 mm).
+        let doc = render_table(Box::new(Paragraph::new(&word)), CellOverflow::Wrap).unwrap();
+        let cursors = find_operations(&doc, 0, "Td");
+        assert!(cursors.len() > 2);
+        let (lines, next) = cursors.split_at(cursors.len() - 1);
+        for operands in lines {
+            assert!(Mm::from(printpdf::Pt(as_f32(&operands[0]))) < Mm(1.0));
         }
+        assert!(Mm::from(printpdf::Pt(as_f32(&next[0][0]))) > Mm(20.0));
+
+        // With the clip policy, the text is clipped at the border of the first column.
+        let doc = render_table(Box::new(Text::new(&word)), CellOverflow::Clip).unwrap();
+        let operators: Vec<_> = page_operations(&doc, 0)
+            .into_iter()
+            .map(|op| op.operator)
+            .collect();
+        assert!(operators.windows(3).any(|ops| ops == ["re", "W", "n"]));
+        let rect = &find_operations(&doc, 0, "re")[0];
+        assert!(approx_eq!(f32, 0.0, as_f32(&rect[0]), epsilon = 0.01));
+        let width = Mm::from(printpdf::Pt(as_f32(&rect[2])));
+        assert!(approx_eq!(f32, 21.0, width.0, epsilon = 0.01));
+
+        // With the shrink policy, the font size is reduced.
+        let doc = render_table(Box::new(Text::new(&word)), CellOverflow::Shrink).unwrap();
+        let font_sizes: Vec<_> = find_operations(&doc, 0, "Tf")
+            .iter()
+            .map(|operands| as_f32(&operands[1]))
+            .collect();
+        assert!(font_sizes[0] < 12.0);
+        assert!(approx_eq!(f32, 12.0, font_sizes[1], epsilon = 0.01));
     }
 
-    fn print_top(&self, row: usize) -> bool {
-        if self.last_row.map(|last_row| row > last_row).unwrap_or(true) {
-            if row == 0 {
-                self.outer
-            } else {
-                self.inner
-            }
-        } else {
-            self.cont
-        }
+    #[test]
+    fn test_margin_note() {
+        let mut doc = document();
+        let mut decorator = crate::SimplePageDecorator::new();
+        decorator.set_margins(30);
+        doc.set_page_decorator(decorator);
+        doc.push(Paragraph::new("first"));
+        doc.push(Paragraph::new("anchor").with_margin_note(Text::new("note")));
+        doc.push(PageBreak::new());
+        doc.push(Paragraph::new("anchor").with_margin_note(Text::new("note")));
+        let doc = render_document(doc).unwrap();
+
+        // The text cursors in millimeters, measured from the upper left corner of the page.  The x
+        // coordinates are shifted by the left side bearing of the first character.
+        let cursors = |page| -> Vec<_> {
+            find_operations(&doc, page, "Td")
+                .iter()
+                .map(|operands| {
+                    let x = Mm::from(printpdf::Pt(as_f32(&operands[0])));
+                    let y = Mm(297.0) - Mm::from(printpdf::Pt(as_f32(&operands[1])));
+                    (x, y)
+                })
+                .collect()
+        };
+
+        // On odd pages, the note is placed in the right margin.
+        let first_page = cursors(0);
+        assert_eq!(3, first_page.len());
+        let (anchor, note) = (first_page[1], first_page[2]);
+        assert!(approx_eq!(f32, 30.0, anchor.0 .0, epsilon = 0.5));
+        assert!(approx_eq!(f32, 183.0, note.0 .0, epsilon = 0.5));
+        assert!(approx_eq!(f32, anchor.1 .0, note.1 .0, epsilon = 0.01));
+
+        // On even pages, the note is placed in the left margin.
+        let second_page = cursors(1);
+        assert_eq!(2, second_page.len());
+        let (anchor, note) = (second_page[0], second_page[1]);
+        assert!(approx_eq!(f32, 3.0, note.0 .0, epsilon = 0.5));
+        assert!(approx_eq!(f32, anchor.1 .0, note.1 .0, epsilon = 0.01));
     }
 
-    fn print_bottom(&self, row: usize, has_more: bool) -> bool {
-        if has_more {
-            self.cont
-        } else if row + 1 == self.num_rows {
-            self.outer
-        } else {
-            false
-        }
+    #[test]
+    fn test_margin_note_in_table() {
+        let mut doc = document();
+        let mut decorator = crate::SimplePageDecorator::new();
+        decorator.set_margins(30);
+        doc.set_page_decorator(decorator);
+        let mut table = TableLayout::new(vec![1, 1]);
+        table
+            .row()
+            .element(Paragraph::new("anchor").with_margin_note(Text::new("note")))
+            .element(Paragraph::new("cell"))
+            .push()
+            .unwrap();
+        doc.push(table);
+        let doc = render_document(doc).unwrap();
+
+        // The x coordinates of the text cursors in millimeters, shifted by the left side bearing
+        // of the first character.
+        let cursors: Vec<_> = find_operations(&doc, 0, "Td")
+            .iter()
+            .map(|operands| Mm::from(printpdf::Pt(as_f32(&operands[0]))).0)
+            .collect();
+        assert_eq!(3, cursors.len());
+        // The note is placed in the right page margin, not in the next column.
+        assert!(approx_eq!(f32, 183.0, cursors[1], epsilon = 0.5));
+        assert!(approx_eq!(f32, 105.0, cursors[2], epsilon = 0.5));
     }
-}
 
-impl CellDecorator for FrameCellDecorator {
-    fn set_table_size(&mut self, num_columns: usize, num_rows: usize) {
-        self.num_columns = num_columns;
-        self.num_rows = num_rows;
+    #[test]
+    fn test_balanced_columns() {
+        // Returns the number of lines in the left and in the right column.
+        let render_columns = |balanced: bool| {
+            let mut doc = document();
+            let text = ["Lorem ipsum dolor sit amet."; 10].join(" ");
+            doc.push(
+                ColumnLayout::new(Paragraph::new(text), 2)
+                    .with_gap(10)
+                    .with_balanced(balanced),
+            );
+            let doc = render_document(doc).unwrap();
+            assert_eq!(1, doc.get_pages().len());
+            let (left, right): (Vec<_>, Vec<_>) = find_operations(&doc, 0, "Td")
+                .iter()
+                .map(|operands| Mm::from(printpdf::Pt(as_f32(&operands[0]))))
+                .partition(|x| x.0 < 105.0);
+            (left.len(), right.len())
+        };
+
+        let (left, right) = render_columns(false);
+        assert!(left > 2);
+        assert_eq!(0, right);
+
+        let (balanced_left, balanced_right) = render_columns(true);
+        assert_eq!(left, balanced_left + balanced_right);
+        assert!(balanced_left >= balanced_right);
+        assert!(balanced_left - balanced_right <= 1);
     }
 
-    fn prepare_cell<'p>(
-        &self,
-        column: usize,
-        row: usize,
-        mut area: render::Area<'p>,
-    ) -> render::Area<'p> {
-        let margin = self.line_style.thickness();
-        let margins = Margins::trbl(
-            if self.print_top(row) {
-                margin
-            } else {
-                0.into()
-            },
-            if self.print_right(column) {
-                margin
-            } else {
-                0.into()
-            },
-            if self.print_bottom(row, false) {
-                margin
-            } else {
-                0.into()
-            },
-            if self.print_left(column) {
-                margin
-            } else {
-                0.into()
-            },
+    #[test]
+    fn test_balanced_columns_destination() {
+        let mut doc = document();
+        let mut decorator = crate::SimplePageDecorator::new();
+        decorator.set_margins(10);
+        doc.set_page_decorator(decorator);
+        doc.push(Paragraph::new("first"));
+        let text = ["Lorem ipsum dolor sit amet."; 10].join(" ");
+        let element = TrackedElement::new(Paragraph::new(text));
+        let handle = element.handle();
+        doc.push(ColumnLayout::new(element, 2).with_balanced(true));
+        let line_height = Style::new().metrics(doc.font_cache()).line_height;
+        render_document(doc).unwrap();
+
+        // The measurement of the balanced columns does not record a destination.
+        let destination = handle.destination().unwrap();
+        assert_eq!(1, destination.page);
+        assert!(approx_eq!(
+            f32,
+            10.0,
+            destination.position.x.0,
+            epsilon = 0.01
+        ));
+        assert!(approx_eq!(
+            f32,
+            10.0 + line_height.0,
+            destination.position.y.0,
+            epsilon = 0.01
+        ));
+    }
+
+    #[test]
+    fn test_font_family_runs() {
+        let mut doc = document();
+        let default_family = doc.font_cache().default_font_family();
+        let code_family = doc.add_font_family(crate::tests::font_family());
+        assert_ne!(
+            crate::fonts::FontFamilyId::from(default_family),
+            code_family
         );
-        area.add_margins(margins);
-        area
+
+        let mut p = Paragraph::default();
+        p.push("Call ");
+        p.push_styled("render", Style::new().with_font_family(code_family));
+        p.push(" now.");
+        doc.push(p);
+        let doc = render_document(doc).unwrap();
+
+        let fonts: Vec<_> = find_operations(&doc, 0, "Tf")
+            .iter()
+            .map(|operands| operands[0].as_name().unwrap().to_vec())
+            .collect();
+        assert_eq!(3, fonts.len());
+        assert_ne!(fonts[0], fonts[1]);
+        assert_eq!(fonts[0], fonts[2]);
     }
 
-    fn decorate_cell(
-        &mut self,
-        column: usize,
-        row: usize,
-        has_more: bool,
-        area: render::Area<'_>,
-        row_height: Mm,
-    ) -> Mm {
-        let print_top = self.print_top(row);
-        let print_bottom = self.print_bottom(row, has_more);
-        let print_left = self.print_left(column);
-        let print_right = self.print_right(column);
+    #[test]
+    fn test_paragraph_link() {
+        let mut doc = document();
+        let mut decorator = crate::SimplePageDecorator::new();
+        decorator.set_margins(10);
+        doc.set_page_decorator(decorator);
+        let width = |s: &str, style: Style| style.str_width(doc.font_cache(), s);
+        let offset = width("See ", Style::new())
+            + width("this", Style::new().bold())
+            + width(" and ", Style::new());
+        let link_width = width("that", Style::new());
+        doc.push(
+            Paragraph::default()
+                .string("See ")
+                .styled_string("this", Style::new().bold())
+                .string(" and ")
+                .link("that", "https://example.org")
+                .string(" page."),
+        );
+        let doc = render_document(doc).unwrap();
 
-        let size = area.size();
-        let line_offset = self.line_style.thickness() / 2.0;
+        // The bold word is printed inline with its own font.
+        let fonts: Vec<_> = find_operations(&doc, 0, "Tf")
+            .iter()
+            .map(|operands| operands[0].as_name().unwrap().to_vec())
+            .collect();
+        assert_eq!(3, fonts.len());
+        assert_ne!(fonts[0], fonts[1]);
+        assert_eq!(fonts[0], fonts[2]);
+        let cursors = find_operations(&doc, 0, "Td");
+        assert_eq!(1, cursors.len());
 
-        let left = Mm::from(0);
-        let right = size.width;
-        let top = Mm::from(0);
-        let bottom = row_height
-            + if print_bottom {
-                self.line_style.thickness()
-            } else {
-                0.into()
-            }
-            + if print_top {
-                self.line_style.thickness()
-            } else {
-                0.into()
-            };
+        // The line starts at the text cursor, which is moved to the left of the margin by the
+        // left side bearing of the first glyph.
+        let line_start = Mm::from(printpdf::Pt(as_f32(&cursors[0][0])));
+        assert!(line_start < Mm(10.0));
+        let start = line_start + offset;
+        let end = start + link_width;
 
-        let mut total_height = row_height;
+        let page_id = doc.page_iter().next().unwrap();
+        let annotations = doc
+            .get_dictionary(page_id)
+            .unwrap()
+            .get(b"Annots")
+            .and_then(lopdf::Object::as_array)
+            .unwrap();
+        assert_eq!(1, annotations.len());
+        let link = doc
+            .get_dictionary(annotations[0].as_reference().unwrap())
+            .unwrap();
+        assert_eq!(
+            b"Link".as_ref(),
+            link.get(b"Subtype").unwrap().as_name().unwrap()
+        );
+        let action = link.get(b"A").and_then(lopdf::Object::as_dict).unwrap();
+        assert_eq!(
+            b"https://example.org".as_ref(),
+            action.get(b"URI").unwrap().as_str().unwrap()
+        );
+        // The rectangle in millimeters, measured from the lower left corner of the page
+        let rect: Vec<_> = link
+            .get(b"Rect")
+            .and_then(lopdf::Object::as_array)
+            .unwrap()
+            .iter()
+            .map(|value| Mm::from(printpdf::Pt(as_f32(value))).0)
+            .collect();
+        assert!(approx_eq!(f32, start.0, rect[0], epsilon = 0.01));
+        assert!(approx_eq!(f32, end.0, rect[2], epsilon = 0.01));
+        assert!(rect[1] < rect[3]);
+        assert!(approx_eq!(f32, 297.0 - 10.0, rect[3], epsilon = 0.01));
+    }
 
-        if print_top {
-            area.draw_line(
-                vec![
-                    Position::new(left, top + line_offset),
-                    Position::new(right, top + line_offset),
-                ],
-                self.line_style,
-            );
-            total_height += self.line_style.thickness();
-        }
+    #[test]
+    fn test_paragraph_link_collapsed_spaces() {
+        let mut doc = document();
+        let mut decorator = crate::SimplePageDecorator::new();
+        decorator.set_margins(10);
+        doc.set_page_decorator(decorator);
+        let style = Style::new();
+        let width = style.str_width(doc.font_cache(), "Some spaces ");
+        let link_width = style.str_width(doc.font_cache(), "link");
+        let lsb = style.char_left_side_bearing(doc.font_cache(), 'l');
+        let line_height = style.metrics(doc.font_cache()).line_height;
+        doc.set_paper_size(Size::new(Mm(21.0) + width, 100));
+        doc.push(
+            Paragraph::new("Some    spaces ")
+                .with_preserve_spaces(false)
+                .link("link", "https://example.org"),
+        );
+        let doc = render_document(doc).unwrap();
 
-        if print_right {
-            area.draw_line(
-                vec![
-                    Position::new(right - line_offset, top),
-                    Position::new(right - line_offset, bottom),
-                ],
-                self.line_style,
-            );
-        }
+        // The link is wrapped to the second line after the spaces have been collapsed.
+        let page_id = doc.page_iter().next().unwrap();
+        let annotations = doc
+            .get_dictionary(page_id)
+            .unwrap()
+            .get(b"Annots")
+            .and_then(lopdf::Object::as_array)
+            .unwrap();
+        assert_eq!(1, annotations.len());
+        let rect: Vec<_> = doc
+            .get_dictionary(annotations[0].as_reference().unwrap())
+            .unwrap()
+            .get(b"Rect")
+            .and_then(lopdf::Object::as_array)
+            .unwrap()
+            .iter()
+            .map(|value| Mm::from(printpdf::Pt(as_f32(value))).0)
+            .collect();
+        assert!(approx_eq!(f32, (Mm(10.0) - lsb).0, rect[0], epsilon = 0.01));
+        assert!(approx_eq!(
+            f32,
+            (Mm(10.0) - lsb + link_width).0,
+            rect[2],
+            epsilon = 0.01
+        ));
+        assert!(approx_eq!(
+            f32,
+            (Mm(100.0 - 10.0) - line_height).0,
+            rect[3],
+            epsilon = 0.01
+        ));
+    }
+
+    #[test]
+    fn test_paragraph_hanging_indent() {
+        let mut doc = document();
+        let mut decorator = crate::SimplePageDecorator::new();
+        decorator.set_margins(10);
+        doc.set_page_decorator(decorator);
+        doc.push(Paragraph::new("lorem ".repeat(50)).with_hanging_indent(15));
+        let lsb = Style::new().char_left_side_bearing(doc.font_cache(), 'l');
+        let doc = render_document(doc).unwrap();
 
-        if print_bottom {
-            area.draw_line(
-                vec![
-                    Position::new(left, bottom - line_offset),
-                    Position::new(right, bottom - line_offset),
-                ],
-                self.line_style,
-            );
-            total_height += self.line_style.thickness();
+        // The x coordinates of the text cursors.  All lines start with the same glyph, so the
+        // side bearing is the same for all lines.
+        let cursors: Vec<_> = find_operations(&doc, 0, "Td")
+            .iter()
+            .map(|operands| Mm::from(printpdf::Pt(as_f32(&operands[0]))).0)
+            .collect();
+        assert!(cursors.len() > 2);
+        assert!(approx_eq!(f32, 10.0 - lsb.0, cursors[0], epsilon = 0.01));
+        for cursor in &cursors[1..] {
+            assert!(approx_eq!(f32, cursors[0] + 15.0, *cursor, epsilon = 0.01));
         }
 
-        if print_left {
+        // A hanging indent that is wider than the area is only an error if the paragraph does
+        // not fit on its first line.
+        let mut doc = document();
+        doc.push(Paragraph::new("lorem").with_hanging_indent(300));
+        assert!(render_document(doc).is_ok());
+        let mut doc = document();
+        doc.push(Paragraph::new("lorem ".repeat(50)).with_hanging_indent(300));
+        let err = render_document(doc).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::AreaTooSmall));
+    }
+
+    #[test]
+    fn test_paragraph_line_count() {
+        let mut doc = document();
+        let width = Style::new().str_width(doc.font_cache(), "lorem ipsum ");
+        let mut paragraph = Paragraph::new("lorem ipsum ".repeat(3));
+        assert_eq!(0, paragraph.line_count());
+        let line_count = std::rc::Rc::new(std::cell::Cell::new(0));
+        let paragraph_line_count = line_count.clone();
+        doc.push(FromFn(move |context, mut area, style| {
+            // Each line only fits one repetition of the text.
+            area.set_width(width + Mm(1.0));
+            let result = context.render_element(&mut paragraph, area, style)?;
+            paragraph_line_count.set(paragraph.line_count());
+            Ok(result)
+        }));
+        let doc = render_document(doc).unwrap();
+
+        assert_eq!(3, line_count.get());
+        assert_eq!(3, find_operations(&doc, 0, "Td").len());
+    }
+
+    #[test]
+    fn test_zero_width_area() {
+        let mut doc = document();
+        doc.push(Paragraph::new("Lorem ipsum").padded(Margins::trbl(0, 105, 0, 105)));
+        let err = render_document(doc).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::AreaTooSmall));
+
+        let mut doc = document();
+        doc.push(
+            Paragraph::new("Lorem ipsum")
+                .with_word_break(WordBreak::Anywhere)
+                .padded(Margins::trbl(0, 120, 0, 120)),
+        );
+        let err = render_document(doc).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::AreaTooSmall));
+
+        let mut doc = document();
+        doc.push(Text::new("Lorem ipsum").padded(Margins::trbl(0, 105, 0, 105)));
+        let err = render_document(doc).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::AreaTooSmall));
+
+        // Elements that do not print text can be rendered in an empty area.
+        let mut doc = document();
+        doc.push(Break::new(1.0).padded(Margins::trbl(0, 105, 0, 105)));
+        assert!(render_document(doc).is_ok());
+    }
+
+    #[test]
+    fn test_whitespace() {
+        // Returns the x coordinate of the text cursor and the number of printed glyphs.
+        let render = |element: Box<dyn crate::Element>| {
+            let mut doc = document();
+            doc.push(element);
+            let doc = render_document(doc).unwrap();
+            let cursors = find_operations(&doc, 0, "Td");
+            assert_eq!(1, cursors.len());
+            let x = Mm::from(printpdf::Pt(as_f32(&cursors[0][0])));
+            let glyphs: usize = find_operations(&doc, 0, "TJ")
+                .iter()
+                .flat_map(|operands| operands[0].as_array().unwrap())
+                .filter_map(|object| object.as_str().ok())
+                .map(|bytes| bytes.len() / 2)
+                .sum();
+            (x, glyphs)
+        };
+
+        let (_, preserved) = render(Box::new(Text::new("  spaced  ")));
+        let (_, trimmed) = render(Box::new(
+            Text::new("  spaced  ").with_whitespace(Whitespace::Trim),
+        ));
+        assert_eq!(10, preserved);
+        assert_eq!(6, trimmed);
+
+        let (preserved_x, preserved) = render(Box::new(
+            Paragraph::new("  spaced  ").aligned(Alignment::Right),
+        ));
+        let (trimmed_x, trimmed) = render(Box::new(
+            Paragraph::new("  spaced  ")
+                .aligned(Alignment::Right)
+                .with_whitespace(Whitespace::Trim),
+        ));
+        assert_eq!(10, preserved);
+        assert_eq!(6, trimmed);
+        assert!(trimmed_x > preserved_x);
+    }
+
+    #[test]
+    fn test_from_fn() {
+        let mut doc = document();
+        let mut calls = 0;
+        doc.push(FromFn(move |_context, area, _style| {
+            calls += 1;
             area.draw_line(
-                vec![
-                    Position::new(left + line_offset, top),
-                    Position::new(left + line_offset, bottom),
-                ],
-                self.line_style,
+                vec![Position::new(0, 0), Position::new(area.size().width, 0)],
+                LineStyle::new(),
             );
+            Ok(RenderResult {
+                size: Size::new(area.size().width, 1),
+                has_more: calls < 3,
+            })
+        }));
+        let doc = render_document(doc).unwrap();
+        assert_eq!(3, doc.get_pages().len());
+        for page in 0..3 {
+            let ops = page_operations(&doc, page);
+            assert_eq!(1, ops.iter().filter(|op| op.operator == "S").count());
         }
+    }
 
-        if column + 1 == self.num_columns {
-            self.last_row = Some(row);
+    #[test]
+    fn test_on_page_parity() {
+        let mut doc = document();
+        for page in 0..4 {
+            if page > 0 {
+                doc.push(PageBreak::new());
+            }
+            doc.push(OnPageParity::new(Text::new("logo"), PageParity::Odd));
+            doc.push(Text::new("body"));
         }
+        let doc = render_document(doc).unwrap();
+        assert_eq!(4, doc.get_pages().len());
 
-        total_height
+        // The y coordinates of the text cursors on each page
+        let cursors = |page| -> Vec<_> {
+            find_operations(&doc, page, "Td")
+                .iter()
+                .map(|operands| as_f32(&operands[1]))
+                .collect()
+        };
+        let odd = cursors(0);
+        assert_eq!(2, odd.len());
+        assert_eq!(odd, cursors(2));
+        // On even pages, the logo is not rendered and the body starts at the top of the page.
+        let even = cursors(1);
+        assert_eq!(vec![odd[0]], even);
+        assert_eq!(even, cursors(3));
     }
-}
 
-/// A row of a table layout.
-///
-/// This is a helper struct for populating a [`TableLayout`][].  After you have added all elements
-/// to the row using [`push_element`][] or [`element`][], you can append the row to the table
-/// layout by calling [`push`][].
-///
-/// # Examples
-///
-/// With setters:
-/// ```
-/// use rckive_genpdf::elements;
-/// let mut table = elements::TableLayout::new(vec![1, 1]);
-/// let mut row = table.row();
-/// row.push_element(elements::Paragraph::new("Cell 1"));
-/// row.push_element(elements::Paragraph::new("Cell 2"));
-/// row.push().expect("Invalid table row");
-/// ```
-///
-/// Chained:
-/// ```
-/// use rckive_genpdf::elements;
-/// let table = elements::TableLayout::new(vec![1, 1])
-///     .row()
-///     .element(elements::Paragraph::new("Cell 1"))
-///     .element(elements::Paragraph::new("Cell 2"))
-///     .push()
-///     .expect("Invalid table row");
-/// ```
-///
-/// [`TableLayout`]: struct.TableLayout.html
-/// [`push`]: #method.push
-/// [`push_element`]: #method.push_element
-/// [`element`]: #method.element
-pub struct TableLayoutRow<'a> {
-    table_layout: &'a mut TableLayout,
-    elements: Vec<Box<dyn Element>>,
-}
+    #[test]
+    fn test_overlay() {
+        // Draws a horizontal line with the given width and returns the given size.
+        let line = |width: i32, height: i32| {
+            FromFn(move |_context, area, _style| {
+                area.draw_line(
+                    vec![Position::new(0, 0), Position::new(width, 0)],
+                    LineStyle::new(),
+                );
+                Ok(RenderResult {
+                    size: Size::new(width, height),
+                    has_more: false,
+                })
+            })
+        };
+        let mut doc = document();
+        let mut overlay = Overlay::new().element(line(30, 10)).element(line(20, 25));
+        let size = std::rc::Rc::new(std::cell::Cell::new(Size::default()));
+        let overlay_size = size.clone();
+        doc.push(FromFn(move |context, area, style| {
+            let result = context.render_element(&mut overlay, area, style)?;
+            overlay_size.set(result.size);
+            Ok(result)
+        }));
+        doc.push(Text::new("after"));
+        let doc = render_document(doc).unwrap();
 
-impl<'a> TableLayoutRow<'a> {
-    fn new(table_layout: &'a mut TableLayout) -> TableLayoutRow<'a> {
-        TableLayoutRow {
-            table_layout,
-            elements: Vec::new(),
+        // The size is the maximum of the sizes of the elements.
+        assert_eq!(Size::new(30, 25), size.get());
+
+        // Both lines start at the upper left corner of the same area.
+        let starts = find_operations(&doc, 0, "m");
+        let ends = find_operations(&doc, 0, "l");
+        assert_eq!(2, starts.len());
+        for start in &starts {
+            assert_eq!(0.0, as_f32(&start[0]));
+            assert!(approx_eq!(
+                f32,
+                printpdf::Pt::from(Mm(297.0)).0,
+                as_f32(&start[1]),
+                epsilon = 0.01
+            ));
         }
-    }
+        let width = |end: &Vec<lopdf::Object>| Mm::from(printpdf::Pt(as_f32(&end[0])));
+        assert!(approx_eq!(f32, 30.0, width(&ends[0]).0, epsilon = 0.01));
+        assert!(approx_eq!(f32, 20.0, width(&ends[1]).0, epsilon = 0.01));
 
-    /// Adds the given element to this row.
-    pub fn push_element<E: IntoBoxedElement>(&mut self, element: E) {
-        self.elements.push(element.into_boxed_element());
+        // The following element is placed below the higher element.
+        let cursor = &find_operations(&doc, 0, "Td")[0];
+        let y = Mm(297.0) - Mm::from(printpdf::Pt(as_f32(&cursor[1])));
+        assert!(y > Mm(25.0));
     }
 
-    /// Adds the given element to this row and returns the row.
-    #[must_use]
-    pub fn element<E: IntoBoxedElement>(mut self, element: E) -> Self {
-        self.push_element(element);
-        self
-    }
+    #[test]
+    fn test_default_line_style() {
+        let mut doc = document();
+        let thickness = Mm::from(printpdf::Pt(0.5));
+        doc.set_default_line_style(
+            LineStyle::new()
+                .with_thickness(thickness)
+                .with_color(Color::Greyscale(128)),
+        );
+        doc.push(HorizontalRule::new());
+        doc.push(HorizontalRule::with_line_style(LineStyle::new()));
+        let doc = render_document(doc).unwrap();
 
-    /// Tries to append this row to the table.
-    ///
-    /// This method fails if the number of elements in this row does not match the number of
-    /// columns in the table.
-    pub fn push(self) -> Result<(), Error> {
-        self.table_layout.push_row(self.elements)
-    }
-}
+        let widths: Vec<_> = find_operations(&doc, 0, "w")
+            .iter()
+            .map(|operands| as_f32(&operands[0]))
+            .collect();
+        assert_eq!(2, widths.len());
+        // The rule without a line style uses the document default.
+        assert!(approx_eq!(f32, 0.5, widths[0], epsilon = 0.01));
+        let colors = find_operations(&doc, 0, "G");
+        assert_eq!(1, colors.len());
+        assert!(approx_eq!(
+            f32,
+            128.0 / 255.0,
+            as_f32(&colors[0][0]),
+            epsilon = 0.01
+        ));
+        // The rule with a line style ignores the document default.
+        let explicit = printpdf::Pt::from(LineStyle::new().thickness()).0;
+        assert!(approx_eq!(f32, explicit, widths[1], epsilon = 0.01));
 
-impl<'a, E: IntoBoxedElement> iter::Extend<E> for TableLayoutRow<'a> {
-    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
-        self.elements
-            .extend(iter.into_iter().map(|e| e.into_boxed_element()))
+        // The second rule is placed below the first rule.
+        let starts: Vec<_> = find_operations(&doc, 0, "m")
+            .iter()
+            .map(|operands| Mm(297.0) - Mm::from(printpdf::Pt(as_f32(&operands[1]))))
+            .collect();
+        assert!(approx_eq!(
+            f32,
+            (thickness / 2.0).0,
+            starts[0].0,
+            epsilon = 0.01
+        ));
+        assert!(starts[1] > thickness);
     }
-}
 
-/// Arranges elements in columns and rows.
-///
-/// This struct can be used to layout arbitrary elements in columns in rows, or to draw typical
-/// tables.  You can customize the cell style by providing a [`CellDecorator`][] implementation.
-/// If you want to print a typical table with borders around the cells, use the
-/// [`FrameCellDecorator`][].
-///
-/// The column widths are determined by the weights that have been set in the constructor.  The
-/// table always uses the full width of the provided area.
-///
-/// # Examples
-///
-/// With setters:
-/// ```
-/// use rckive_genpdf::elements;
-/// let mut table = elements::TableLayout::new(vec![1, 1]);
-/// table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
-/// let mut row = table.row();
-/// row.push_element(elements::Paragraph::new("Cell 1"));
-/// row.push_element(elements::Paragraph::new("Cell 2"));
-/// row.push().expect("Invalid table row");
-/// ```
-///
-/// Chained:
-/// ```
-/// use rckive_genpdf::elements;
-/// let table = elements::TableLayout::new(vec![1, 1])
-///     .row()
-///     .element(elements::Paragraph::new("Cell 1"))
-///     .element(elements::Paragraph::new("Cell 2"))
-///     .push()
-///     .expect("Invalid table row");
-/// ```
-///
-/// [`CellDecorator`]: trait.CellDecorator.html
-/// [`FrameCellDecorator`]: struct.FrameCellDecorator.html
-pub struct TableLayout {
-    column_weights: Vec<usize>,
-    rows: Vec<Vec<Box<dyn Element>>>,
-    render_idx: usize,
-    cell_decorator: Option<Box<dyn CellDecorator>>,
-}
+    #[test]
+    fn test_break_with_height() {
+        // Returns the vertical distance between the two text sections in millimeters.
+        let distance = |element: Option<Break>| {
+            let mut layout = LinearLayout::vertical();
+            layout.push(Text::new("a"));
+            if let Some(element) = element {
+                layout.push(element);
+            }
+            layout.push(Text::new("b"));
+            let mut doc = document();
+            doc.push(layout.styled(Style::new().with_font_size(20)));
+            let doc = render_document(doc).unwrap();
+            let cursors = find_operations(&doc, 0, "Td");
+            assert_eq!(2, cursors.len());
+            let distance = as_f32(&cursors[0][1]) - as_f32(&cursors[1][1]);
+            Mm::from(printpdf::Pt(distance)).0
+        };
 
-impl TableLayout {
-    /// Creates a new table layout with the given column weights.
-    ///
-    /// The column weights are used to determine the relative width of the columns.  The number of
-    /// column weights determines the number of columns in the table.
-    pub fn new(column_weights: Vec<usize>) -> TableLayout {
-        TableLayout {
-            column_weights,
-            rows: Vec::new(),
-            render_idx: 0,
-            cell_decorator: None,
-        }
+        let without_break = distance(None);
+        let with_break = distance(Some(Break::with_height(12.5)));
+        assert!(approx_eq!(
+            f32,
+            12.5,
+            with_break - without_break,
+            epsilon = 0.01
+        ));
     }
 
-    /// Sets the cell decorator for this table.
-    pub fn set_cell_decorator(&mut self, decorator: impl CellDecorator + 'static) {
-        self.cell_decorator = Some(Box::from(decorator));
+    #[test]
+    fn test_pdf_page_next_page() {
+        let mut template = document();
+        template.set_paper_size(Size::new(100, 100));
+        template.push(Paragraph::new("Template"));
+        let mut data = Vec::new();
+        template.render(&mut data).unwrap();
+        let page = PdfPage::from_bytes(&data, 0).unwrap();
+
+        // The page does not fit into the remaining 20 mm, so it is moved to the next page instead
+        // of being scaled down.
+        let mut doc = document();
+        doc.push(FillPage(Mm(20.0)));
+        doc.push(page);
+        let doc = render_document(doc).unwrap();
+        assert_eq!(2, doc.get_pages().len());
+        assert!(find_operations(&doc, 0, "Do").is_empty());
+        assert_eq!(1, find_operations(&doc, 1, "Do").len());
+        let transform = find_operations(&doc, 1, "cm").pop().unwrap();
+        assert!(approx_eq!(f32, 1.0, as_f32(&transform[0])));
+        assert!(approx_eq!(f32, 1.0, as_f32(&transform[3])));
     }
 
-    /// Adds a row to this table using the [`TableLayoutRow`][] helper struct.
-    ///
-    /// [`TableLayoutRow`]: struct.TableLayoutRow.html
-    pub fn row(&mut self) -> TableLayoutRow<'_> {
-        TableLayoutRow::new(self)
+    #[test]
+    fn test_trailing_break() {
+        let mut doc = document();
+        doc.push(FillPage(Mm(0.0)));
+        doc.push(Break::new(2.0));
+        doc.push(Break::with_height(10));
+        let doc = render_document(doc).unwrap();
+        assert_eq!(1, doc.get_pages().len());
     }
 
-    /// Adds a row to this table.
-    ///
-    /// The number of elements in the given vector must match the number of columns.  Otherwise, an
-    /// error is returned.
-    pub fn push_row(&mut self, row: Vec<Box<dyn Element>>) -> Result<(), Error> {
-        if row.len() == self.column_weights.len() {
-            self.rows.push(row);
-            Ok(())
-        } else {
-            Err(Error::new(
-                format!(
-                    "Expected {} elements in table row, received {}",
-                    self.column_weights.len(),
-                    row.len()
-                ),
-                ErrorKind::InvalidData,
-            ))
+    #[test]
+    fn test_page_break() {
+        let text_count = |doc: &lopdf::Document, page| find_operations(doc, page, "BT").len();
+
+        let mut doc = document();
+        doc.push(Paragraph::new("a"));
+        doc.push(PageBreak::new());
+        doc.push(Paragraph::new("b"));
+        let doc = render_document(doc).unwrap();
+        assert_eq!(2, doc.get_pages().len());
+        assert_eq!(1, text_count(&doc, 0));
+        assert_eq!(1, text_count(&doc, 1));
+
+        // Page breaks at the start of a page are ignored.
+        let mut doc = document();
+        doc.push(PageBreak::new());
+        doc.push(Paragraph::new("a").with_page_break_before());
+        doc.push(Paragraph::new("b").with_page_break_before());
+        doc.push(PageBreak::new());
+        doc.push(Paragraph::new("c").with_page_break_before());
+        let doc = render_document(doc).unwrap();
+        assert_eq!(3, doc.get_pages().len());
+        for page in 0..3 {
+            assert_eq!(1, text_count(&doc, page));
         }
     }
 
-    fn render_row(
-        &mut self,
-        context: &Context,
-        area: render::Area<'_>,
-        style: Style,
-    ) -> Result<RenderResult, Error> {
-        let mut result = RenderResult::default();
+    #[test]
+    fn test_keep_with_next() {
+        let text_count = |doc: &lopdf::Document, page| find_operations(doc, page, "BT").len();
 
-        let areas = area.split_horizontally(&self.column_weights);
-        let cell_areas = if let Some(decorator) = &self.cell_decorator {
-            areas
-                .iter()
-                .enumerate()
-                .map(|(i, area)| decorator.prepare_cell(i, self.render_idx, area.clone()))
-                .collect()
-        } else {
-            areas.clone()
-        };
+        let mut doc = document();
+        doc.push(FillPage(Mm(15.0)));
+        doc.push(Paragraph::new("Heading").keep_with_next(20));
+        doc.push(Paragraph::new("Text"));
+        let doc = render_document(doc).unwrap();
+        assert_eq!(2, doc.get_pages().len());
+        assert_eq!(0, text_count(&doc, 0));
+        assert_eq!(2, text_count(&doc, 1));
 
-        let mut row_height = Mm::from(0);
-        for (area, element) in cell_areas.iter().zip(self.rows[self.render_idx].iter_mut()) {
-            let element_result = element.render(context, area.clone(), style)?;
-            result.has_more |= element_result.has_more;
-            row_height = row_height.max(element_result.size.height);
-        }
-        result.size.height = row_height;
+        // If there is enough space, the heading stays on the first page.
+        let mut doc = document();
+        doc.push(FillPage(Mm(25.0)));
+        doc.push(Paragraph::new("Heading").keep_with_next(20));
+        doc.push(Paragraph::new("Text"));
+        let doc = render_document(doc).unwrap();
+        assert_eq!(1, doc.get_pages().len());
+        assert_eq!(2, text_count(&doc, 0));
 
-        if let Some(decorator) = &mut self.cell_decorator {
-            for (i, area) in areas.into_iter().enumerate() {
-                let height =
-                    decorator.decorate_cell(i, self.render_idx, result.has_more, area, row_height);
-                result.size.height = result.size.height.max(height);
-            }
-        }
+        // If only the first line of the heading fits above the reserved space, the complete
+        // heading is moved to the next page instead of being split.
+        let mut doc = document();
+        doc.set_paper_size(Size::new(25, 297));
+        let line_height = Style::new().metrics(doc.font_cache()).line_height;
+        doc.push(FillPage(Mm(20.0) + line_height * 1.5));
+        doc.push(Paragraph::new("Long heading").keep_with_next(20));
+        doc.push(Paragraph::new("Text"));
+        let doc = render_document(doc).unwrap();
+        assert_eq!(2, doc.get_pages().len());
+        assert_eq!(0, text_count(&doc, 0));
+        assert_eq!(3, text_count(&doc, 1));
 
-        Ok(result)
+        // In a padded area, the element is never at the start of a page.  If it does not fit
+        // together with the reserved space, it is only moved once.
+        let mut doc = document();
+        doc.push(
+            Paragraph::new("Heading")
+                .keep_with_next(400)
+                .padded(Margins::trbl(5, 0, 0, 0)),
+        );
+        doc.push(Paragraph::new("Text"));
+        let doc = render_document(doc).unwrap();
+        assert_eq!(2, doc.get_pages().len());
+        assert_eq!(0, text_count(&doc, 0));
+        assert_eq!(2, text_count(&doc, 1));
     }
-}
 
-impl Element for TableLayout {
-    fn render(
-        &mut self,
-        context: &Context,
-        mut area: render::Area<'_>,
-        style: Style,
-    ) -> Result<RenderResult, Error> {
-        let mut result = RenderResult::default();
-        if self.column_weights.is_empty() {
-            return Ok(result);
-        }
-        if let Some(decorator) = &mut self.cell_decorator {
-            decorator.set_table_size(self.column_weights.len(), self.rows.len());
-        }
-        result.size.width = area.size().width;
-        while self.render_idx < self.rows.len() {
-            let row_result = self.render_row(context, area.clone(), style)?;
-            result.size.height += row_result.size.height;
-            area.add_offset(Position::new(0, row_result.size.height));
-            if row_result.has_more {
-                break;
+    #[test]
+    fn test_pdf_page() {
+        struct TemplateDecorator(PdfPage);
+
+        impl crate::PageDecorator for TemplateDecorator {
+            fn decorate_page<'a>(
+                &mut self,
+                context: &crate::Context,
+                area: crate::render::Area<'a>,
+                style: Style,
+            ) -> Result<crate::render::Area<'a>, crate::error::Error> {
+                self.0.clone().render(context, area.clone(), style)?;
+                Ok(area)
             }
-            self.render_idx += 1;
         }
-        result.has_more = self.render_idx < self.rows.len();
-        Ok(result)
+
+        let mut template = document();
+        template.set_paper_size(Size::new(100, 50));
+        template.push(Paragraph::new("Template"));
+        let mut data = Vec::new();
+        template.render(&mut data).unwrap();
+        let page = PdfPage::from_bytes(&data, 0).unwrap();
+        assert!(PdfPage::from_bytes(&data, 1).is_err());
+
+        let mut doc = document();
+        doc.set_page_decorator(TemplateDecorator(page));
+        doc.push(Paragraph::new("On top"));
+        let doc = render_document(doc).unwrap();
+
+        // The imported page is drawn before the document content.
+        let operations = page_operations(&doc, 0);
+        let form = operations
+            .iter()
+            .position(|op| {
+                op.operator == "Do" && op.operands[0].as_name().ok() == Some(&b"Fm0"[..])
+            })
+            .unwrap();
+        let text = operations
+            .iter()
+            .position(|op| op.operator == "BT")
+            .unwrap();
+        assert!(form < text);
+
+        let page_id = doc.page_iter().next().unwrap();
+        let (resources, _) = doc.get_page_resources(page_id);
+        let xobjects = resources
+            .unwrap()
+            .get(b"XObject")
+            .unwrap()
+            .as_dict()
+            .unwrap();
+        let form_id = xobjects.get(b"Fm0").unwrap().as_reference().unwrap();
+        let form = doc.get_object(form_id).unwrap().as_stream().unwrap();
+        assert_eq!(
+            b"Form",
+            form.dict.get(b"Subtype").unwrap().as_name().unwrap()
+        );
+        let bbox = form.dict.get(b"BBox").unwrap().as_array().unwrap();
+        let width: Mm = printpdf::Pt(as_f32(&bbox[2]) - as_f32(&bbox[0])).into();
+        assert!((width.0 - 100.0).abs() < 0.01);
+        // The form keeps the content and the fonts of the imported page.
+        let content = form
+            .decompressed_content()
+            .unwrap_or_else(|_| form.content.clone());
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        assert!(content.operations.iter().any(|op| op.operator == "TJ"));
+        let form_resources = form.dict.get(b"Resources").unwrap();
+        let form_resources = match form_resources {
+            lopdf::Object::Reference(id) => doc.get_dictionary(*id).unwrap(),
+            object => object.as_dict().unwrap(),
+        };
+        assert!(form_resources.get(b"Font").is_ok());
     }
 }
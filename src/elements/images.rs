@@ -8,6 +8,32 @@ use crate::error::{Context as _, Error, ErrorKind};
 use crate::{render, style};
 use crate::{Alignment, Context, Element, Mm, Position, RenderResult, Rotation, Scale, Size};
 
+/// The handling of images that are larger than the area they are rendered in.
+///
+/// *Only available if the `images` feature is enabled.*
+///
+/// See [`Image::set_fit`][].  The default behavior is to shrink the image.
+///
+/// [`Image::set_fit`]: struct.Image.html#method.set_fit
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Default)]
+pub enum ImageFit {
+    /// Scale the image down, preserving its aspect ratio, so that it fits into the area.
+    #[default]
+    Shrink,
+    /// Render the image with its original size, even if it exceeds the area.
+    Overflow,
+}
+
+/// The width of an image, see [`Image::set_width`][] and [`Image::set_width_percent`][].
+///
+/// [`Image::set_width`]: struct.Image.html#method.set_width
+/// [`Image::set_width_percent`]: struct.Image.html#method.set_width_percent
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ImageWidth {
+    Absolute(Mm),
+    Percent(f32),
+}
+
 /// An image to embed in the PDF.
 ///
 /// *Only available if the `images` feature is enabled.*
@@ -51,31 +77,64 @@ pub struct Image {
     position: Option<Position>,
 
     /// Scaling of the image, default is 1:1.
-    scale: Scale,
+    ///
+    /// If no scale is set, the image may be shrunk to fit the area, see `fit`.
+    scale: Option<Scale>,
+
+    /// The handling of images that are larger than the area.
+    fit: ImageFit,
+
+    /// The width of the image that overrides the scale and the DPI.
+    width: Option<ImageWidth>,
+
+    /// The height of the image that overrides the scale and the DPI.
+    height: Option<Mm>,
 
     /// The number of degrees of clockwise rotation.
     rotation: Rotation,
 
-    /// DPI override if you know better. Defaults to `printpdf`’s default of 300 dpi.
+    /// DPI override if you know better. Defaults to the document’s image DPI or `printpdf`’s
+    /// default of 300 dpi.
     dpi: Option<f32>,
+
+    /// The alternate text describing the image.
+    alt_text: Option<String>,
+
+    /// The structure element of the image if the document is tagged.
+    structure: Option<render::StructureElement>,
+
+    /// Whether the image has been moved to the next page because it did not fit into the
+    /// remaining space of the previous page.
+    is_deferred: bool,
 }
 
 impl Image {
     /// Creates a new image from an already loaded image.
+    ///
+    /// Images with an alpha channel are not supported and cause an error with the
+    /// [`UnsupportedAlphaChannel`][] kind.
+    ///
+    /// [`UnsupportedAlphaChannel`]: ../error/enum.ErrorKind.html#variant.UnsupportedAlphaChannel
     pub fn from_dynamic_image(data: printpdf::image_crate::DynamicImage) -> Result<Self, Error> {
         if data.color().has_alpha() {
             Err(Error::new(
                 "Images with an alpha channel are not supported",
-                ErrorKind::InvalidData,
+                ErrorKind::UnsupportedAlphaChannel,
             ))
         } else {
             Ok(Image {
                 data,
                 alignment: Alignment::default(),
                 position: None,
-                scale: Scale::default(),
+                scale: None,
+                fit: ImageFit::default(),
+                width: None,
+                height: None,
                 rotation: Rotation::default(),
                 dpi: None,
+                alt_text: None,
+                structure: None,
+                is_deferred: false,
             })
         }
     }
@@ -90,7 +149,7 @@ impl Image {
             .with_guessed_format()
             .context("Could not determine image format")?
             .decode()
-            .context("Could not decode image")?;
+            .map_err(|err| Error::new("Could not decode image", ErrorKind::ImageDecode(err)))?;
         Self::from_dynamic_image(image)
     }
 
@@ -124,8 +183,12 @@ impl Image {
     }
 
     /// Scales the image.
+    ///
+    /// If a scale is set, the image is never shrunk to fit the area, see [`set_fit`][].
+    ///
+    /// [`set_fit`]: #method.set_fit
     pub fn set_scale(&mut self, scale: impl Into<Scale>) {
-        self.scale = scale.into();
+        self.scale = Some(scale.into());
     }
 
     /// Scales the image and returns it.
@@ -134,6 +197,86 @@ impl Image {
         self
     }
 
+    /// Sets the width of the image.
+    ///
+    /// If no height is set (see [`set_height`][]), the height is calculated from the aspect ratio
+    /// of the image.  The width overrides the scale (see [`set_scale`][]) and the DPI (see
+    /// [`set_dpi`][]), and the image is not shrunk to fit the area.
+    ///
+    /// [`set_height`]: #method.set_height
+    /// [`set_scale`]: #method.set_scale
+    /// [`set_dpi`]: #method.set_dpi
+    pub fn set_width(&mut self, width: impl Into<Mm>) {
+        self.width = Some(ImageWidth::Absolute(width.into()));
+    }
+
+    /// Sets the width of the image and returns it.
+    pub fn with_width(mut self, width: impl Into<Mm>) -> Self {
+        self.set_width(width);
+        self
+    }
+
+    /// Sets the width of the image as a percentage of the width of the area it is rendered in.
+    ///
+    /// For example, `50.0` means that the image is half as wide as its area.  See [`set_width`][]
+    /// for more information.
+    ///
+    /// [`set_width`]: #method.set_width
+    pub fn set_width_percent(&mut self, percent: f32) {
+        self.width = Some(ImageWidth::Percent(percent));
+    }
+
+    /// Sets the width of the image as a percentage of the width of the area it is rendered in and
+    /// returns it.
+    pub fn with_width_percent(mut self, percent: f32) -> Self {
+        self.set_width_percent(percent);
+        self
+    }
+
+    /// Sets the height of the image.
+    ///
+    /// If no width is set (see [`set_width`][]), the width is calculated from the aspect ratio of
+    /// the image.  If both the width and the height are set, the image is stretched to this size.
+    /// The height overrides the scale (see [`set_scale`][]) and the DPI (see [`set_dpi`][]), and
+    /// the image is not shrunk to fit the area.
+    ///
+    /// [`set_width`]: #method.set_width
+    /// [`set_scale`]: #method.set_scale
+    /// [`set_dpi`]: #method.set_dpi
+    pub fn set_height(&mut self, height: impl Into<Mm>) {
+        self.height = Some(height.into());
+    }
+
+    /// Sets the height of the image and returns it.
+    pub fn with_height(mut self, height: impl Into<Mm>) -> Self {
+        self.set_height(height);
+        self
+    }
+
+    /// Sets the handling of images that are larger than the area they are rendered in.
+    ///
+    /// Per default, an image that is wider than its area is scaled down so that it fits into the
+    /// area, preserving its aspect ratio.  An image that is higher than the remaining space on
+    /// the page is moved to the next page, and only scaled down if it is higher than the area
+    /// there.  This only applies if neither a scale (see
+    /// [`set_scale`][]), a width (see [`set_width`][]), a height (see [`set_height`][]) nor a
+    /// position (see [`set_position`][]) has been set.
+    ///
+    /// [`set_scale`]: #method.set_scale
+    /// [`set_width`]: #method.set_width
+    /// [`set_height`]: #method.set_height
+    /// [`set_position`]: #method.set_position
+    pub fn set_fit(&mut self, fit: ImageFit) {
+        self.fit = fit;
+    }
+
+    /// Sets the handling of images that are larger than the area they are rendered in and returns
+    /// the image.
+    pub fn with_fit(mut self, fit: ImageFit) -> Self {
+        self.set_fit(fit);
+        self
+    }
+
     /// Sets the alignment to use for this image.
     pub fn set_alignment(&mut self, alignment: impl Into<Alignment>) {
         self.alignment = alignment.into();
@@ -148,23 +291,34 @@ impl Image {
     /// Determines the offset from left-side based on provided Alignment.
     fn get_offset(&self, width: Mm, max_width: Mm) -> Position {
         let horizontal_offset = match self.alignment {
-            Alignment::Left => Mm::default(),
+            Alignment::Left | Alignment::Justify => Mm::default(),
             Alignment::Center => (max_width - width) / 2.0,
             Alignment::Right => max_width - width,
         };
         Position::new(horizontal_offset, 0)
     }
 
+    /// Returns the DPI of this image, falling back to the document’s default image DPI.
+    fn get_dpi(&self, context: &Context) -> Option<f32> {
+        self.dpi.or_else(|| context.image_dpi())
+    }
+
     /// Calculates a guess for the size of the image based on the dpi/pixel-count/scale.
-    fn get_size(&self) -> Size {
+    fn get_size(&self, context: &Context) -> Size {
+        let size = self.get_unscaled_size(context);
+        let scale = self.scale.unwrap_or_default();
+        Size::new(size.width * scale.x, size.height * scale.y)
+    }
+
+    /// Calculates a guess for the size of the image based on the dpi/pixel-count.
+    fn get_unscaled_size(&self, context: &Context) -> Size {
         let mmpi: f32 = 25.4; // millimeters per inch
                               // Assume 300 DPI to be consistent with printpdf.
-        let dpi: f32 = self.dpi.unwrap_or(300.0);
+        let dpi: f32 = self.get_dpi(context).unwrap_or(300.0);
         let (px_width, px_height) = self.data.dimensions();
-        let (scale_width, scale_height): (f32, f32) = (self.scale.x, self.scale.y);
         Size::new(
-            mmpi * ((scale_width * px_width as f32) / dpi),
-            mmpi * ((scale_height * px_height as f32) / dpi),
+            mmpi * (px_width as f32 / dpi),
+            mmpi * (px_height as f32 / dpi),
         )
     }
 
@@ -181,6 +335,11 @@ impl Image {
     }
 
     /// Sets the expected DPI of the encoded image.
+    ///
+    /// If this method is not called, the default DPI of the document is used, see
+    /// [`Document::set_image_dpi`][].
+    ///
+    /// [`Document::set_image_dpi`]: ../struct.Document.html#method.set_image_dpi
     pub fn set_dpi(&mut self, dpi: f32) {
         self.dpi = Some(dpi);
     }
@@ -190,17 +349,86 @@ impl Image {
         self.set_dpi(dpi);
         self
     }
+
+    /// Sets the alternate text that describes the image, for example for screen readers.
+    ///
+    /// If tagging is enabled for the document (see [`Document::enable_tagging`][]), the alternate
+    /// text is stored in the structure element of the image.  Otherwise, it is stored in the `Alt`
+    /// entry of the image XObject.
+    ///
+    /// [`Document::enable_tagging`]: ../struct.Document.html#method.enable_tagging
+    pub fn set_alt_text(&mut self, alt_text: impl Into<String>) {
+        self.alt_text = Some(alt_text.into());
+    }
+
+    /// Sets the alternate text that describes the image and returns it.
+    pub fn with_alt_text(mut self, alt_text: impl Into<String>) -> Self {
+        self.set_alt_text(alt_text);
+        self
+    }
 }
 
 impl Element for Image {
     fn render(
         &mut self,
-        _context: &Context,
+        context: &Context,
         area: render::Area<'_>,
         _style: style::Style,
     ) -> Result<RenderResult, Error> {
         let mut result = RenderResult::default();
-        let true_size = self.get_size();
+        let mut scale = self.scale.unwrap_or_default();
+        let mut true_size = self.get_size(context);
+        if self.width.is_some() || self.height.is_some() {
+            let unscaled_size = self.get_unscaled_size(context);
+            let x_factor = self.width.map(|width| {
+                let width = match width {
+                    ImageWidth::Absolute(width) => width,
+                    ImageWidth::Percent(percent) => area.size().width * (percent / 100.0),
+                };
+                width.0 / unscaled_size.width.0
+            });
+            let y_factor = self.height.map(|height| height.0 / unscaled_size.height.0);
+            // If only one dimension is set, the aspect ratio is preserved.
+            let x_factor = x_factor.or(y_factor).unwrap_or(1.0);
+            let y_factor = y_factor.unwrap_or(x_factor);
+            scale = Scale::new(x_factor, y_factor);
+            true_size = Size::new(
+                unscaled_size.width * x_factor,
+                unscaled_size.height * y_factor,
+            );
+        } else if self.fit == ImageFit::Shrink && self.scale.is_none() && self.position.is_none() {
+            // The bounding box scales linearly with the image, so we can shrink the image by the
+            // factor that makes the bounding box fit into the area.
+            let (_, bb_size) = bounding_box_offset_and_size(&self.rotation, &true_size);
+            let mut factor = (area.size().width.0 / bb_size.width.0).min(1.0);
+            if bb_size.height * factor > area.size().height {
+                // The image might fit into the area on the next page, so we only shrink it to the
+                // remaining height if it is already at the start of a page or has been moved.
+                if !area.is_page_start() && !self.is_deferred {
+                    self.is_deferred = true;
+                    result.has_more = true;
+                    return Ok(result);
+                }
+                factor = factor.min(area.size().height.0 / bb_size.height.0);
+            }
+            if factor <= 0.0 {
+                result.has_more = true;
+                return Ok(result);
+            }
+            scale = Scale::new(scale.x * factor, scale.y * factor);
+            true_size = Size::new(true_size.width * factor, true_size.height * factor);
+        }
+        self.is_deferred = false;
+
+        if self.structure.is_none() {
+            self.structure = area.add_structure_element(render::Tag::Figure);
+            if let (Some(element), Some(alt_text)) = (self.structure, &self.alt_text) {
+                area.set_alt_text(element, alt_text.clone());
+            }
+        }
+        let _marked_content = self
+            .structure
+            .map(|element| area.begin_marked_content(element));
         let (bb_origin, bb_size) = bounding_box_offset_and_size(&self.rotation, &true_size);
 
         let mut position: Position = if let Some(position) = self.position {
@@ -219,7 +447,14 @@ impl Element for Image {
         position += bb_origin;
 
         // Insert/render the image with the overridden/calculated position.
-        area.add_image(&self.data, position, self.scale, self.rotation, self.dpi);
+        let dpi = self.get_dpi(context);
+        // If the structure element carries the alternate text, we don't need another
+        // marked-content sequence for it.
+        if let (Some(alt_text), None) = (&self.alt_text, self.structure) {
+            area.add_image_with_alt_text(&self.data, position, scale, self.rotation, dpi, alt_text);
+        } else {
+            area.add_image(&self.data, position, scale, self.rotation, dpi);
+        }
 
         // Always false as we can't safely do this unless we want to try to do "sub-images".
         // This is technically possible with the `image` package, but it is potentially more
@@ -276,8 +511,12 @@ fn bounding_box_offset_and_size(rotation: &Rotation, size: &Size) -> (Position,
 
 #[cfg(test)]
 mod tests {
-    use super::bounding_box_offset_and_size;
-    use crate::{Position, Rotation, Size};
+    use super::{bounding_box_offset_and_size, Image, ImageFit};
+    use crate::fonts::FontCache;
+    use crate::tests::{
+        as_f32, document, find_operations, font_family, render_document, render_scale, FillPage,
+    };
+    use crate::{Context, Element as _, Mm, Position, Rotation, Scale, Size};
     use float_cmp::approx_eq;
 
     macro_rules! assert_approx_eq {
@@ -509,4 +748,204 @@ mod tests {
         test_position(size, 90.0, Position::new(100, 200));
         test_position(size, 180.0, Position::new(200, 0));
     }
+
+    #[test]
+    fn test_default_dpi() {
+        let mut context = Context::new(FontCache::new(font_family()));
+        let image = printpdf::image_crate::DynamicImage::new_rgb8(300, 150);
+        let image = Image::from_dynamic_image(image).unwrap();
+        assert_approx_eq!(Size, Size::new(25.4, 12.7), image.get_size(&context));
+
+        context.image_dpi = Some(150.0);
+        assert_approx_eq!(Size, Size::new(50.8, 25.4), image.get_size(&context));
+
+        let image = image.with_dpi(600.0);
+        assert_approx_eq!(Size, Size::new(12.7, 6.35), image.get_size(&context));
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        // Renders the image in an area with a width of 100 mm.
+        let render_scale =
+            |image: Image| render_scale(image.padded(crate::Margins::trbl(0, 110, 0, 0)));
+
+        // 6000 × 3000 pixels at 300 dpi are 508 × 254 mm.
+        let image = printpdf::image_crate::DynamicImage::new_rgb8(6000, 3000);
+        let image = Image::from_dynamic_image(image).unwrap();
+        let (width, height) = render_scale(image.clone());
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(100.0)).0, width);
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(50.0)).0, height);
+
+        for image in [
+            image.clone().with_fit(ImageFit::Overflow),
+            image.clone().with_scale(Scale::new(1.0, 1.0)),
+        ] {
+            let (width, _) = render_scale(image);
+            assert_approx_eq!(f32, printpdf::Pt::from(Mm(508.0)).0, width);
+        }
+    }
+
+    #[test]
+    fn test_move_to_next_page() {
+        // 1200 × 1200 pixels at 300 dpi are 101.6 × 101.6 mm.
+        let image = printpdf::image_crate::DynamicImage::new_rgb8(1200, 1200);
+        let image = Image::from_dynamic_image(image).unwrap().with_dpi(300.0);
+
+        // The image does not fit into the remaining 20 mm, so it is moved to the next page
+        // instead of being scaled down.
+        let mut doc = document();
+        doc.push(FillPage(Mm(20.0)));
+        doc.push(image.clone());
+        let doc = render_document(doc).unwrap();
+        assert_eq!(2, doc.get_pages().len());
+        assert!(find_operations(&doc, 0, "cm").is_empty());
+        let transform = find_operations(&doc, 1, "cm").pop().unwrap();
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(101.6)).0, as_f32(&transform[0]));
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(101.6)).0, as_f32(&transform[3]));
+
+        // An image that is higher than a page is scaled down to the height of the page.
+        let image = printpdf::image_crate::DynamicImage::new_rgb8(1200, 6000);
+        let image = Image::from_dynamic_image(image).unwrap().with_dpi(300.0);
+        let mut doc = document();
+        doc.push(image);
+        let doc = render_document(doc).unwrap();
+        assert_eq!(1, doc.get_pages().len());
+        let transform = find_operations(&doc, 0, "cm").pop().unwrap();
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(297.0)).0, as_f32(&transform[3]));
+    }
+
+    #[test]
+    fn test_width() {
+        // Renders the image in an area with a width of 100 mm.
+        let render_size =
+            |image: Image| render_scale(image.padded(crate::Margins::trbl(0, 110, 0, 0)));
+
+        // 600 × 300 pixels at 300 dpi are 50.8 × 25.4 mm.
+        let image = printpdf::image_crate::DynamicImage::new_rgb8(600, 300);
+        let image = Image::from_dynamic_image(image).unwrap().with_dpi(300.0);
+
+        let (width, height) = render_size(image.clone().with_width_percent(50.0));
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(50.0)).0, width);
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(25.0)).0, height);
+
+        // The width overrides the scale.
+        let (width, height) =
+            render_size(image.with_scale(Scale::new(3.0, 1.0)).with_width(Mm(30.0)));
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(30.0)).0, width);
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(15.0)).0, height);
+    }
+
+    #[test]
+    fn test_height() {
+        // 600 × 300 pixels at 300 dpi are 50.8 × 25.4 mm.
+        let image = printpdf::image_crate::DynamicImage::new_rgb8(600, 300);
+        let image = Image::from_dynamic_image(image).unwrap().with_dpi(300.0);
+
+        // The height alone preserves the aspect ratio.
+        let (width, height) = render_scale(image.clone().with_height(Mm(20.0)));
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(40.0)).0, width);
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(20.0)).0, height);
+
+        // The width and the height stretch the image.
+        let (width, height) = render_scale(image.with_width(Mm(30.0)).with_height(Mm(30.0)));
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(30.0)).0, width);
+        assert_approx_eq!(f32, printpdf::Pt::from(Mm(30.0)).0, height);
+    }
+
+    #[test]
+    fn test_image_decode_error() {
+        let data = std::io::Cursor::new(b"\x89PNG\r\n\x1a\ncorrupt".to_vec());
+        let err = Image::from_reader(data).err().unwrap();
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::ImageDecode(_)
+        ));
+    }
+
+    #[test]
+    fn test_alpha_channel_error() {
+        let image = printpdf::image_crate::DynamicImage::new_rgba8(4, 4);
+        let err = Image::from_dynamic_image(image).err().unwrap();
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::UnsupportedAlphaChannel
+        ));
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn test_image_alt_text() {
+        let image = |size| {
+            let image = printpdf::image_crate::DynamicImage::new_rgb8(size, size);
+            Image::from_dynamic_image(image).unwrap()
+        };
+        let mut doc = document();
+        doc.push(image(4).with_alt_text("A small square"));
+        doc.push(image(8));
+        doc.push(image(12).with_alt_text("A large square"));
+        let doc = render_document(doc).unwrap();
+
+        // The alternate texts are attached to the marked-content sequences of the images.
+        let spans: Vec<_> = find_operations(&doc, 0, "BDC")
+            .into_iter()
+            .filter(|operands| operands[0].as_name().ok() == Some(b"Span"))
+            .collect();
+        assert_eq!(2, spans.len());
+
+        let mut alt_texts: Vec<_> = doc
+            .objects
+            .values()
+            .filter_map(|object| object.as_stream().ok())
+            .filter(|stream| {
+                stream
+                    .dict
+                    .get(b"Subtype")
+                    .and_then(lopdf::Object::as_name)
+                    .ok()
+                    == Some(b"Image")
+            })
+            .map(|stream| {
+                let width = stream.dict.get(b"Width").unwrap().as_i64().unwrap();
+                let alt_text = stream
+                    .dict
+                    .get(b"Alt")
+                    .and_then(lopdf::Object::as_str)
+                    .ok()
+                    .map(|s| String::from_utf8_lossy(s).into_owned());
+                (width, alt_text)
+            })
+            .collect();
+        alt_texts.sort();
+        assert_eq!(
+            vec![
+                (4, Some("A small square".to_owned())),
+                (8, None),
+                (12, Some("A large square".to_owned())),
+            ],
+            alt_texts
+        );
+
+        // In a tagged document, the alternate text is stored in the structure element, so the
+        // image is not wrapped in a nested marked-content sequence.
+        let mut doc = document();
+        doc.enable_tagging();
+        doc.push(image(4).with_alt_text("A small square"));
+        let doc = render_document(doc).unwrap();
+        let tags: Vec<_> = find_operations(&doc, 0, "BDC")
+            .into_iter()
+            .map(|operands| operands[0].as_name_str().unwrap().to_owned())
+            .collect();
+        assert!(tags.contains(&"Figure".to_owned()));
+        assert!(!tags.contains(&"Span".to_owned()));
+        let figure = doc
+            .objects
+            .values()
+            .filter_map(|object| object.as_dict().ok())
+            .find(|dict| dict.get(b"S").and_then(lopdf::Object::as_name).ok() == Some(b"Figure"))
+            .unwrap();
+        assert_eq!(
+            b"A small square",
+            figure.get(b"Alt").unwrap().as_str().unwrap()
+        );
+    }
 }
@@ -3,6 +3,7 @@
 use std::error;
 use std::fmt;
 use std::io;
+use std::path;
 
 /// Helper trait for creating [`Error`][] instances.
 ///
@@ -78,6 +79,14 @@ impl error::Error for Error {
             ErrorKind::InvalidFont => None,
             ErrorKind::PageSizeExceeded => None,
             ErrorKind::UnsupportedEncoding => None,
+            ErrorKind::FontNotFound(_) => None,
+            ErrorKind::GlyphMissing { .. } => None,
+            ErrorKind::InvalidTableRow { .. } => None,
+            ErrorKind::AreaTooSmall => None,
+            #[cfg(feature = "images")]
+            ErrorKind::UnsupportedAlphaChannel => None,
+            #[cfg(feature = "images")]
+            ErrorKind::ImageDecode(err) => Some(err),
             ErrorKind::IoError(err) => Some(err),
             ErrorKind::PdfError(err) => Some(err),
             ErrorKind::PdfIndexError(err) => Some(err),
@@ -102,6 +111,34 @@ pub enum ErrorKind {
     PageSizeExceeded,
     /// A string with unsupported characters was used with a built-in font.
     UnsupportedEncoding,
+    /// A font file with the given path could not be found.
+    FontNotFound(path::PathBuf),
+    /// A font does not contain a glyph for the given character.
+    GlyphMissing {
+        /// The missing character.
+        ch: char,
+    },
+    /// A table row with the wrong number of elements was added to a table.
+    InvalidTableRow {
+        /// The number of columns of the table.
+        expected: usize,
+        /// The number of elements in the row.
+        actual: usize,
+    },
+    /// An element was rendered into an area with a width that is not positive, for example
+    /// because the margins are larger than the page.
+    AreaTooSmall,
+    /// An image with an alpha channel was used.  Transparent images are not supported.
+    ///
+    /// *Only available if the `images` feature is enabled.*
+    #[cfg(feature = "images")]
+    UnsupportedAlphaChannel,
+    /// An image could not be decoded, for example because its format is not supported or its
+    /// data is corrupt.
+    ///
+    /// *Only available if the `images` feature is enabled.*
+    #[cfg(feature = "images")]
+    ImageDecode(printpdf::image_crate::ImageError),
     /// An IO error.
     IoError(io::Error),
     /// An error caused by invalid data in `printpdf`.
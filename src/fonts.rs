@@ -2,7 +2,8 @@
 //!
 //! Before you can use a font in a PDF document, you have to load the [`FontData`][] for it, either
 //! from a file ([`FontData::load`][]) or from bytes ([`FontData::new`][]).  See the [`rusttype`][]
-//! crate for the supported data formats.  Use the [`from_files`][] function to load a font family
+//! crate for the supported data formats.  Both TrueType fonts and OpenType fonts with CFF outlines
+//! can be used.  Use the [`from_files`][] function to load a font family
 //! from a set of files following the default naming conventions.
 //!
 //! The [`FontCache`][] caches all loaded fonts.  A [`Font`][] is a reference to a cached font in
@@ -10,8 +11,9 @@
 //! bold italic font (raw data or cached).
 //!
 //! Add fonts to a document’s font cache by calling [`Document::add_font_family`][].  This method
-//! returns a reference to the cached data that you then can use with the [`Style`][] struct to
-//! change the font family of an element.
+//! returns a handle ([`FontFamilyId`][]) for the cached data that you then can use with the
+//! [`Style`][] struct to change the font family of an element or of a single run of text.  A
+//! document can use any number of font families.
 //!
 //! There are two methods for using fonts in a PDF font:  You can either embed the font data into
 //! the PDF file.  Or you can use one of the three built-in font families ([`Builtin`][]) that PDF
@@ -55,6 +57,7 @@
 //! [`FontData::load`]: struct.FontData.html#method.load
 //! [`Font`]: struct.Font.html
 //! [`FontFamily`]: struct.FontFamily.html
+//! [`FontFamilyId`]: struct.FontFamilyId.html
 //! [`rusttype`]: https://docs.rs/rusttype
 //! [`rusttype::Font`]: https://docs.rs/rusttype/0.8.3/rusttype/struct.Font.html
 //! [`printpdf`]: https://docs.rs/printpdf
@@ -78,7 +81,6 @@ use crate::Mm;
 /// [`Document`]: ../struct.Document.html
 /// [`Font`]: struct.Font.html
 /// [`FontFamily`]: struct.FontFamily.html
-#[derive(Debug)]
 pub struct FontCache {
     fonts: Vec<FontData>,
     pdf_fonts: Vec<printpdf::IndirectFontRef>,
@@ -86,8 +88,13 @@ pub struct FontCache {
     // a font, but the default font is always loaded in new, so this options is always some
     // (outside of new).
     default_font_family: Option<FontFamily<Font>>,
+    #[cfg(feature = "images")]
+    emoji_images: std::collections::HashMap<char, printpdf::image_crate::DynamicImage>,
+    missing_glyph_callback: Option<MissingGlyphCallback>,
 }
 
+type MissingGlyphCallback = Box<dyn Fn(char, Font)>;
+
 impl FontCache {
     /// Creates a new font cache with the given default font family.
     pub fn new(default_font_family: FontFamily<FontData>) -> FontCache {
@@ -95,8 +102,15 @@ impl FontCache {
             fonts: Vec::new(),
             pdf_fonts: Vec::new(),
             default_font_family: None,
+            #[cfg(feature = "images")]
+            emoji_images: std::collections::HashMap::new(),
+            missing_glyph_callback: None,
         };
-        font_cache.default_font_family = Some(font_cache.add_font_family(default_font_family));
+        font_cache.default_font_family = Some(
+            font_cache
+                .add_font_family(default_font_family)
+                .font_family(),
+        );
         font_cache
     }
 
@@ -111,13 +125,65 @@ impl FontCache {
         font
     }
 
-    /// Adds the given font family to the cache and returns a reference to it.
-    pub fn add_font_family(&mut self, family: FontFamily<FontData>) -> FontFamily<Font> {
-        FontFamily {
+    /// Adds the given font family to the cache and returns a handle for it.
+    pub fn add_font_family(&mut self, family: FontFamily<FontData>) -> FontFamilyId {
+        FontFamilyId(FontFamily {
             regular: self.add_font(family.regular),
             bold: self.add_font(family.bold),
             italic: self.add_font(family.italic),
             bold_italic: self.add_font(family.bold_italic),
+        })
+    }
+
+    /// Adds an image that is printed instead of the glyph for the given character.
+    ///
+    /// *Only available if the `images` feature is enabled.*
+    ///
+    /// This can be used to render emoji and other characters that are not contained in the loaded
+    /// fonts, for example with images extracted from a color font.  When a text section prints a
+    /// string that contains the character, the image is inserted at the position of the character.
+    /// It is scaled to the glyph height of the current font (see [`Font::glyph_height`][]), placed
+    /// on the descent line and keeps its aspect ratio.  The character itself is used as the
+    /// alternate text of the image.
+    ///
+    /// [`Font::glyph_height`]: struct.Font.html#method.glyph_height
+    #[cfg(feature = "images")]
+    pub fn add_emoji_image(&mut self, c: char, image: printpdf::image_crate::DynamicImage) {
+        self.emoji_images.insert(c, image);
+    }
+
+    /// Returns the image that has been registered for the given character with
+    /// [`add_emoji_image`][], if any.
+    ///
+    /// *Only available if the `images` feature is enabled.*
+    ///
+    /// [`add_emoji_image`]: #method.add_emoji_image
+    #[cfg(feature = "images")]
+    pub fn emoji_image(&self, c: char) -> Option<&printpdf::image_crate::DynamicImage> {
+        self.emoji_images.get(&c)
+    }
+
+    /// Returns whether any emoji images have been added to this font cache.
+    #[cfg(feature = "images")]
+    pub(crate) fn has_emoji_images(&self) -> bool {
+        !self.emoji_images.is_empty()
+    }
+
+    /// Returns the ratio of the width to the height of the emoji image for the given character.
+    fn emoji_aspect_ratio(&self, c: char) -> Option<f32> {
+        #[cfg(feature = "images")]
+        {
+            use printpdf::image_crate::GenericImageView as _;
+
+            self.emoji_image(c).map(|image| {
+                let (width, height) = image.dimensions();
+                width as f32 / height.max(1) as f32
+            })
+        }
+        #[cfg(not(feature = "images"))]
+        {
+            let _ = c;
+            None
         }
     }
 
@@ -161,6 +227,91 @@ impl FontCache {
     pub fn get_rt_font(&self, font: Font) -> &rusttype::Font<'static> {
         &self.fonts[font.idx].rt_font
     }
+
+    /// Returns whether the given font contains a glyph for the given character.
+    ///
+    /// Characters that are mapped to the `.notdef` glyph are treated as missing.  This method may
+    /// only be called with [`Font`][] instances that have been created by this font cache.
+    ///
+    /// [`Font`]: struct.Font.html
+    pub fn has_glyph(&self, font: Font, c: char) -> bool {
+        self.get_rt_font(font).glyph(c).id().0 != 0
+    }
+
+    /// Sets a callback that is invoked whenever a character is printed that is not contained in
+    /// the font used to print it.
+    ///
+    /// The callback is called with the missing character and the font every time a text section
+    /// prints it, so it may be called multiple times for the same character.  Control characters
+    /// are ignored.  The text is still printed, typically with the `.notdef` glyph of the font.
+    pub fn set_missing_glyph_callback<F: Fn(char, Font) + 'static>(&mut self, cb: F) {
+        self.missing_glyph_callback = Some(Box::new(cb));
+    }
+
+    /// Invokes the missing glyph callback, if set, for all characters of the given string that
+    /// are not contained in the given font.
+    pub(crate) fn report_missing_glyphs(&self, font: Font, s: &str) {
+        if let Some(cb) = &self.missing_glyph_callback {
+            for c in s.chars() {
+                if !c.is_control() && !self.has_glyph(font, c) {
+                    cb(c, font);
+                }
+            }
+        }
+    }
+
+    /// Returns the characters of the given string that are not contained in the given font.
+    ///
+    /// Every missing character is only returned once, in the order of its first occurrence.  This
+    /// method may only be called with [`Font`][] instances that have been created by this font
+    /// cache.
+    ///
+    /// [`Font`]: struct.Font.html
+    pub fn missing_glyphs(&self, font: Font, s: &str) -> Vec<char> {
+        let mut missing = Vec::new();
+        for c in s.chars() {
+            if !self.has_glyph(font, c) && !missing.contains(&c) {
+                missing.push(c);
+            }
+        }
+        missing
+    }
+
+    /// Checks that the given font contains glyphs for all characters of the given string.
+    ///
+    /// If a character is missing, an error with the [`GlyphMissing`][] kind is returned for the
+    /// first missing character, see [`missing_glyphs`][].  Control characters are ignored.  This
+    /// method may only be called with [`Font`][] instances that have been created by this font
+    /// cache.
+    ///
+    /// [`GlyphMissing`]: ../error/enum.ErrorKind.html#variant.GlyphMissing
+    /// [`missing_glyphs`]: #method.missing_glyphs
+    /// [`Font`]: struct.Font.html
+    pub fn check_glyphs(&self, font: Font, s: &str) -> Result<(), Error> {
+        match s
+            .chars()
+            .find(|c| !c.is_control() && !self.has_glyph(font, *c))
+        {
+            Some(ch) => Err(Error::new(
+                format!(
+                    "The font does not contain a glyph for the character {:?}",
+                    ch
+                ),
+                ErrorKind::GlyphMissing { ch },
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for FontCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FontCache")
+            .field("fonts", &self.fonts)
+            .field("pdf_fonts", &self.pdf_fonts)
+            .field("default_font_family", &self.default_font_family)
+            .finish_non_exhaustive()
+    }
 }
 
 /// The data for a font that is cached by a [`FontCache`][].
@@ -215,6 +366,12 @@ impl FontData {
             .with_context(|| format!("Failed to open font file {}", path.as_ref().display()))?;
         FontData::new(data, builtin)
     }
+
+    /// Returns whether this font is a monospaced font, i. e. if all printable ASCII characters
+    /// have the same advance width.
+    pub fn is_monospace(&self) -> bool {
+        is_monospace(&self.rt_font)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -319,6 +476,34 @@ impl<T: Clone + Copy + fmt::Debug + PartialEq> FontFamily<T> {
     }
 }
 
+/// A handle for a font family that has been added to a [`FontCache`][].
+///
+/// Font family handles are returned by [`Document::add_font_family`][] and
+/// [`FontCache::add_font_family`][] and can be used with [`Style::with_font_family`][] to render
+/// text with the font family.  Any number of font families can be added to a font cache, and all
+/// of them are embedded into the PDF document.  A handle is only valid for the font cache that
+/// created it.
+///
+/// [`FontCache`]: struct.FontCache.html
+/// [`FontCache::add_font_family`]: struct.FontCache.html#method.add_font_family
+/// [`Document::add_font_family`]: ../struct.Document.html#method.add_font_family
+/// [`Style::with_font_family`]: ../style/struct.Style.html#method.with_font_family
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FontFamilyId(FontFamily<Font>);
+
+impl FontFamilyId {
+    /// Returns the cached fonts of the font family for this handle.
+    pub fn font_family(&self) -> FontFamily<Font> {
+        self.0
+    }
+}
+
+impl From<FontFamily<Font>> for FontFamilyId {
+    fn from(font_family: FontFamily<Font>) -> FontFamilyId {
+        FontFamilyId(font_family)
+    }
+}
+
 /// A reference to a font cached by a [`FontCache`][].
 ///
 /// See the [module documentation](index.html) for details on the internals.
@@ -332,6 +517,10 @@ pub struct Font {
     line_height: Mm,
     glyph_height: Mm,
     ascent: Mm,
+    descent: Mm,
+    line_gap: Mm,
+    cap_height: Mm,
+    x_height: Mm,
 }
 
 impl Font {
@@ -345,7 +534,23 @@ impl Font {
         let scale = rusttype::Scale::uniform(glyph_height);
 
         let ascent = v_metrics.ascent / units_per_em;
-        let line_height = glyph_height + v_metrics.line_gap / units_per_em;
+        let descent = v_metrics.descent / units_per_em;
+        let line_gap = v_metrics.line_gap / units_per_em;
+        let line_height = glyph_height + line_gap;
+
+        // The cap height and the x-height are measured using the outlines of H and x.
+        let glyph_top = |c| {
+            let glyph = rt_font.glyph(c);
+            if glyph.id().0 == 0 {
+                return None;
+            }
+            glyph
+                .scaled(scale)
+                .exact_bounding_box()
+                .map(|rect| -rect.min.y)
+        };
+        let cap_height = glyph_top('H').unwrap_or(ascent);
+        let x_height = glyph_top('x').unwrap_or(ascent);
 
         Font {
             idx,
@@ -354,6 +559,10 @@ impl Font {
             line_height: printpdf::Pt(line_height).into(),
             glyph_height: printpdf::Pt(glyph_height).into(),
             ascent: printpdf::Pt(ascent).into(),
+            descent: printpdf::Pt(descent).into(),
+            line_gap: printpdf::Pt(line_gap).into(),
+            cap_height: printpdf::Pt(cap_height).into(),
+            x_height: printpdf::Pt(x_height).into(),
         }
     }
 
@@ -377,16 +586,65 @@ impl Font {
         self.ascent * f32::from(font_size)
     }
 
+    /// Returns the descent for text with this font and the given font size.
+    ///
+    /// The descent is measured from the baseline, so it is negative for most fonts.
+    pub fn descent(&self, font_size: u8) -> Mm {
+        self.descent * f32::from(font_size)
+    }
+
+    /// Returns the line gap for text with this font and the given font size.
+    ///
+    /// The line height is the sum of the glyph height and the line gap.
+    pub fn line_gap(&self, font_size: u8) -> Mm {
+        self.line_gap * f32::from(font_size)
+    }
+
+    /// Returns the cap height, i. e. the height of capital letters, for text with this font and
+    /// the given font size.
+    ///
+    /// The cap height is measured using the character `H`.  If the font does not contain this
+    /// character, the ascent is used instead.
+    pub fn cap_height(&self, font_size: u8) -> Mm {
+        self.cap_height * f32::from(font_size)
+    }
+
+    /// Returns the x-height, i. e. the height of lowercase letters, for text with this font and
+    /// the given font size.
+    ///
+    /// The x-height is measured using the character `x`.  If the font does not contain this
+    /// character, the ascent is used instead.
+    pub fn x_height(&self, font_size: u8) -> Mm {
+        self.x_height * f32::from(font_size)
+    }
+
     /// Returns the width of a character with this font and the given font size.
     ///
+    /// If an emoji image has been added for the character (see [`FontCache::add_emoji_image`][]),
+    /// the width of the scaled image is returned instead.
+    ///
     /// The given [`FontCache`][] must be the font cache that loaded this font.
     ///
     /// [`FontCache`]: struct.FontCache.html
+    /// [`FontCache::add_emoji_image`]: struct.FontCache.html#method.add_emoji_image
     pub fn char_width(&self, font_cache: &FontCache, c: char, font_size: u8) -> Mm {
+        if let Some(aspect_ratio) = font_cache.emoji_aspect_ratio(c) {
+            return self.glyph_height(font_size) * aspect_ratio;
+        }
         let advance_width = self.char_h_metrics(font_cache, c).advance_width;
         Mm::from(printpdf::Pt(advance_width * f32::from(font_size)))
     }
 
+    /// Returns whether this font is a monospaced font, i. e. if all printable ASCII characters
+    /// have the same advance width.
+    ///
+    /// The given [`FontCache`][] must be the font cache that loaded this font.
+    ///
+    /// [`FontCache`]: struct.FontCache.html
+    pub fn is_monospace(&self, font_cache: &FontCache) -> bool {
+        is_monospace(font_cache.get_rt_font(*self))
+    }
+
     /// Returns the width of the empty space between the origin of the glyph bounding
     /// box and the leftmost edge of the character, for a given font and font size.
     ///
@@ -412,11 +670,9 @@ impl Font {
     ///
     /// [`FontCache`]: struct.FontCache.html
     pub fn str_width(&self, font_cache: &FontCache, s: &str, font_size: u8) -> Mm {
-        let str_width: Mm = font_cache
-            .get_rt_font(*self)
-            .glyphs_for(s.chars())
-            .map(|g| g.scaled(self.scale).h_metrics().advance_width)
-            .map(|w| Mm::from(printpdf::Pt(w * f32::from(font_size))))
+        let str_width: Mm = s
+            .chars()
+            .map(|c| self.char_width(font_cache, c, font_size))
             .sum();
         let kerning_width: Mm = self
             .kerning(font_cache, s.chars())
@@ -478,6 +734,23 @@ impl Font {
     }
 }
 
+fn is_monospace(font: &rusttype::Font<'_>) -> bool {
+    let mut advance_widths = (' '..='~')
+        .map(|c| font.glyph(c))
+        .filter(|glyph| glyph.id().0 != 0)
+        .map(|glyph| {
+            glyph
+                .scaled(rusttype::Scale::uniform(1.0))
+                .h_metrics()
+                .advance_width
+        });
+    if let Some(first) = advance_widths.next() {
+        advance_widths.all(|width| width == first)
+    } else {
+        false
+    }
+}
+
 fn from_file(
     dir: impl AsRef<path::Path>,
     name: &str,
@@ -485,10 +758,18 @@ fn from_file(
     builtin: Option<Builtin>,
 ) -> Result<FontData, Error> {
     let builtin = builtin.map(|b| b.style(style));
-    FontData::load(
-        dir.as_ref().join(format!("{}-{}.ttf", name, style)),
-        builtin,
-    )
+    let path = dir.as_ref().join(format!("{}-{}.ttf", name, style));
+    let otf_path = path.with_extension("otf");
+    if path.exists() {
+        FontData::load(path, builtin)
+    } else if otf_path.exists() {
+        FontData::load(otf_path, builtin)
+    } else {
+        Err(Error::new(
+            format!("Could not find font file {}", path.display()),
+            ErrorKind::FontNotFound(path),
+        ))
+    }
 }
 
 /// Loads the font family at the given path with the given name.
@@ -499,9 +780,15 @@ fn from_file(
 /// - `{name}-Italic.ttf`
 /// - `{name}-BoldItalic.ttf`
 ///
+/// If one of these files does not exist, the OpenType font with the same name and the extension
+/// `.otf` is used instead.  If neither file exists, an error with the
+/// [`FontNotFound`][] kind is returned.
+///
 /// If `builtin` is set, built-in PDF fonts are used instead of embedding the fonts in the PDF file
 /// (see the [module documentation](index.html) for more information).  In this case, the given
 /// fonts must be metrically identical to the built-in fonts.
+///
+/// [`FontNotFound`]: ../error/enum.ErrorKind.html#variant.FontNotFound
 pub fn from_files(
     dir: impl AsRef<path::Path>,
     name: &str,
@@ -546,3 +833,73 @@ impl Metrics {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+
+    use std::path;
+
+    use super::FontCache;
+    use crate::error::ErrorKind;
+    use crate::style::Style;
+    use crate::tests::font_family;
+
+    #[test]
+    fn test_vertical_metrics() {
+        let font_cache = FontCache::new(font_family());
+        let style = Style::new().with_font_size(20);
+        let metrics = style.metrics(&font_cache);
+
+        let ascent = style.ascent(&font_cache);
+        let descent = style.descent(&font_cache);
+        assert!(ascent.0 > 0.0);
+        assert!(descent.0 < 0.0);
+        assert!(approx_eq!(
+            f32,
+            metrics.glyph_height.0,
+            ascent.0 - descent.0,
+            epsilon = 0.001
+        ));
+        assert!(approx_eq!(
+            f32,
+            metrics.line_height.0,
+            ascent.0 - descent.0 + style.line_gap(&font_cache).0,
+            epsilon = 0.001
+        ));
+
+        let cap_height = style.cap_height(&font_cache);
+        let x_height = style.x_height(&font_cache);
+        assert!(x_height.0 > 0.0);
+        assert!(x_height < cap_height);
+        assert!(cap_height < ascent);
+    }
+
+    #[test]
+    fn test_missing_glyphs() {
+        let font_cache = FontCache::new(font_family());
+        let font = font_cache.default_font_family().regular;
+        assert!(font_cache.has_glyph(font, 'A'));
+        assert!(!font_cache.has_glyph(font, '漢'));
+        assert_eq!(
+            vec!['漢', '字'],
+            font_cache.missing_glyphs(font, "A漢字 and 漢")
+        );
+        assert!(font_cache.missing_glyphs(font, "Hello").is_empty());
+
+        let err = font_cache.check_glyphs(font, "A漢字").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::GlyphMissing { ch: '漢' }));
+        assert!(font_cache.check_glyphs(font, "Hello\n").is_ok());
+    }
+
+    #[test]
+    fn test_font_not_found() {
+        let err = super::from_files("tests/files", "Missing", None).unwrap_err();
+        match err.kind() {
+            ErrorKind::FontNotFound(path) => {
+                assert_eq!(path::Path::new("tests/files/Missing-Regular.ttf"), path)
+            }
+            kind => panic!("Unexpected error kind: {:?}", kind),
+        }
+    }
+}
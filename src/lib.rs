@@ -158,9 +158,12 @@ pub mod fonts;
 pub mod render;
 pub mod style;
 
+use std::cell;
+use std::fmt;
 use std::fs;
 use std::io;
 use std::path;
+use std::rc;
 
 use derive_more::{
     Add, AddAssign, Div, DivAssign, From, Into, Mul, MulAssign, Sub, SubAssign, Sum,
@@ -204,6 +207,11 @@ impl Mm {
     pub fn max(self, other: Mm) -> Mm {
         Mm(self.0.max(other.0))
     }
+
+    /// Returns the minimum of this value and the given value.
+    pub fn min(self, other: Mm) -> Mm {
+        Mm(self.0.min(other.0))
+    }
 }
 
 impl From<i8> for Mm {
@@ -282,6 +290,109 @@ pub enum Alignment {
     Right,
     /// Centered.
     Center,
+    /// Justified, i. e. the space between the words is stretched so that the lines fill the
+    /// available width.
+    ///
+    /// The last line of a paragraph is left-flushed unless configured otherwise with
+    /// [`Paragraph::set_justify_last_line`][].  Elements that do not consist of words, like
+    /// [`Image`][], treat this alignment like [`Left`][].
+    ///
+    /// [`Paragraph::set_justify_last_line`]: elements/struct.Paragraph.html#method.set_justify_last_line
+    /// [`Image`]: elements/struct.Image.html
+    /// [`Left`]: #variant.Left
+    Justify,
+}
+
+/// The fallback for a line of text that does not even fit on a new page.
+///
+/// If a line of text is higher than the area of an empty page, it cannot be rendered and the
+/// rendering process is cancelled.  You can use [`Document::set_line_overflow`][] to render such a
+/// line anyway.  The fallback is only applied to the first line that is printed on the new page.
+///
+/// The default behavior is to cancel the rendering process with an error.
+///
+/// [`Document::set_line_overflow`]: struct.Document.html#method.set_line_overflow
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Default)]
+pub enum LineOverflow {
+    /// Cancel the rendering process with a [`PageSizeExceeded`][] error.
+    ///
+    /// [`PageSizeExceeded`]: error/enum.ErrorKind.html#variant.PageSizeExceeded
+    #[default]
+    Error,
+    /// Reduce the font size of the line so that it fits the available height.
+    ///
+    /// The font size is not reduced below the minimum font size of the style, see
+    /// [`Style::set_min_font_size`][].  If the line does not fit even with the minimum font size,
+    /// it is clipped at the border of the page area.
+    ///
+    /// [`Style::set_min_font_size`]: style/struct.Style.html#method.set_min_font_size
+    Shrink,
+    /// Print the line with its original font size and clip it at the border of the page area.
+    Clip,
+}
+
+/// The fallback for words that are longer than a line.
+///
+/// Paragraphs are wrapped at whitespace and, if hyphenation is enabled, at hyphenation points.  A
+/// word that is longer than a line and cannot be split otherwise, for example a long URL, cannot
+/// be rendered and the rendering process is cancelled.  You can use
+/// [`Paragraph::set_word_break`][] to break such words at an arbitrary character instead.
+///
+/// The default behavior is to cancel the rendering process with an error.
+///
+/// [`Paragraph::set_word_break`]: elements/struct.Paragraph.html#method.set_word_break
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Default)]
+pub enum WordBreak {
+    /// Never break words at arbitrary characters.
+    #[default]
+    Never,
+    /// Break words that do not fit into a line at an arbitrary character.
+    Anywhere,
+    /// Break words that do not fit into a line at an arbitrary character and insert a hyphen at
+    /// the end of the line.
+    AnywhereWithHyphen,
+}
+
+/// The handling of leading and trailing whitespace in a line of text.
+///
+/// Per default, [`Text`][] and [`Paragraph`][] render whitespace like any other character, so
+/// leading and trailing spaces take up space and are considered when aligning a line.  This can be
+/// used to align text in fixed layouts.  With [`Whitespace::Trim`][], the whitespace at the start
+/// and at the end of every line is removed before the line is aligned and rendered.  Whitespace
+/// between words is preserved unless the consecutive spaces of a paragraph are collapsed with
+/// [`Paragraph::set_preserve_spaces`][].
+///
+/// See [`Text::set_whitespace`][] and [`Paragraph::set_whitespace`][].
+///
+/// [`Text`]: elements/struct.Text.html
+/// [`Paragraph`]: elements/struct.Paragraph.html
+/// [`Whitespace::Trim`]: #variant.Trim
+/// [`Text::set_whitespace`]: elements/struct.Text.html#method.set_whitespace
+/// [`Paragraph::set_whitespace`]: elements/struct.Paragraph.html#method.set_whitespace
+/// [`Paragraph::set_preserve_spaces`]: elements/struct.Paragraph.html#method.set_preserve_spaces
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Default)]
+pub enum Whitespace {
+    /// Render leading and trailing whitespace.
+    #[default]
+    Preserve,
+    /// Remove leading and trailing whitespace from every line.
+    Trim,
+}
+
+/// The part of a text that is removed if the text is truncated with an ellipsis.
+///
+/// See [`Text::set_truncation`][].
+///
+/// [`Text::set_truncation`]: elements/struct.Text.html#method.set_truncation
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Default)]
+pub enum Truncation {
+    /// Remove the start of the text and keep its end, for example `…file.txt`.
+    Start,
+    /// Remove the middle of the text and keep its start and end, for example `/home/…/file.txt`.
+    Middle,
+    /// Remove the end of the text and keep its start, for example `/home/user/…`.
+    #[default]
+    End,
 }
 
 /// A position on a PDF layer, measured in millimeters.
@@ -415,6 +526,37 @@ impl Size {
         self.height += other.height;
         self
     }
+
+    /// Scales this size so that it fits within the given bounds while keeping its aspect ratio.
+    ///
+    /// The returned size is the largest size with the aspect ratio of this size that is not wider
+    /// or higher than the given bounds.  If this size has a width or height of zero, it is
+    /// returned unchanged.
+    #[must_use]
+    pub fn fit_within(self, bounds: Size) -> Size {
+        self.scale_to(bounds, f32::min)
+    }
+
+    /// Scales this size so that it covers the given bounds while keeping its aspect ratio.
+    ///
+    /// The returned size is the smallest size with the aspect ratio of this size that is at least
+    /// as wide and as high as the given bounds.  If this size has a width or height of zero, it is
+    /// returned unchanged.
+    #[must_use]
+    pub fn cover(self, bounds: Size) -> Size {
+        self.scale_to(bounds, f32::max)
+    }
+
+    fn scale_to(self, bounds: Size, select: impl Fn(f32, f32) -> f32) -> Size {
+        if self.width <= Mm(0.0) || self.height <= Mm(0.0) {
+            return self;
+        }
+        let factor = select(
+            bounds.width.0 / self.width.0,
+            bounds.height.0 / self.height.0,
+        );
+        Size::new(self.width * factor, self.height * factor)
+    }
 }
 
 impl<W: Into<Mm>, H: Into<Mm>> From<(W, H)> for Size {
@@ -544,7 +686,7 @@ impl<T: Into<Mm>> From<T> for Margins {
 /// [`push`]: #method.push
 /// [`render`]: #method.render
 /// [`render_to_file`]: #method.render_to_file
-/// [`set_hyphenation`]: #method.set_hyphenation
+/// [`set_hyphenator`]: #method.set_hyphenator
 /// [`set_page_decorator`]: #method.set_page_decorator
 /// [`PageDecorator`]: trait.PageDecorator.html
 /// [`SimplePageDecorator`]: struct.SimplePageDecorator.html
@@ -556,9 +698,23 @@ pub struct Document {
     style: style::Style,
     paper_size: Size,
     decorator: Option<Box<dyn PageDecorator>>,
+    content_rect: Option<(Position, Size)>,
+    page_callback: Option<PageCallback>,
+    crop_marks: Option<(Mm, Mm)>,
+    debug_grid: Option<(Mm, style::Color)>,
+    line_overflow: LineOverflow,
+    baseline_grid: Option<Mm>,
+    layer_name: Option<String>,
+    tagging: bool,
     conformance: Option<printpdf::PdfConformance>,
     creation_date: Option<printpdf::OffsetDateTime>,
     modification_date: Option<printpdf::OffsetDateTime>,
+    xmp_metadata: Option<String>,
+    output_intent: Option<(Vec<u8>, String)>,
+    open_destination: Option<String>,
+    producer: Option<String>,
+    creator: Option<String>,
+    appended_documents: Vec<Document>,
 }
 
 impl Document {
@@ -572,26 +728,69 @@ impl Document {
             style: style::Style::new(),
             paper_size: PaperSize::A4.into(),
             decorator: None,
+            content_rect: None,
+            page_callback: None,
+            crop_marks: None,
+            debug_grid: None,
+            line_overflow: LineOverflow::default(),
+            baseline_grid: None,
+            layer_name: None,
+            tagging: false,
             conformance: None,
             creation_date: None,
             modification_date: None,
+            xmp_metadata: None,
+            output_intent: None,
+            open_destination: None,
+            producer: None,
+            creator: None,
+            appended_documents: Vec::new(),
         }
     }
 
-    /// Adds the given font family to the font cache for this document and returns a reference to
+    /// Adds the given font family to the font cache for this document and returns a handle for
     /// it.
     ///
-    /// Note that the returned font reference may only be used for this document.  It cannot be
-    /// shared with other `Document` or [`FontCache`][] instances.
+    /// The handle can be used with [`Style::with_font_family`][] to use the font family for an
+    /// element or for a single run of a paragraph.  All font families that have been added to the
+    /// document are embedded into the PDF file.
+    ///
+    /// Note that the returned handle may only be used for this document.  It cannot be shared
+    /// with other `Document` or [`FontCache`][] instances.
     ///
     /// [`FontCache`]: fonts/struct.FontCache.html
+    /// [`Style::with_font_family`]: style/struct.Style.html#method.with_font_family
     pub fn add_font_family(
         &mut self,
         font_family: fonts::FontFamily<fonts::FontData>,
-    ) -> fonts::FontFamily<fonts::Font> {
+    ) -> fonts::FontFamilyId {
         self.context.font_cache.add_font_family(font_family)
     }
 
+    /// Adds the given monospaced font family to the font cache for this document and returns a
+    /// style that uses it.
+    ///
+    /// This is a helper for code listings and other text that has to be aligned in columns.  It
+    /// can be used with the built-in [`Courier`][] font family to avoid embedding a font.  If the
+    /// regular font of the family is not monospaced (see [`FontData::is_monospace`][]), an error
+    /// is returned and the font family is not added.
+    ///
+    /// [`Courier`]: fonts/enum.Builtin.html#variant.Courier
+    /// [`FontData::is_monospace`]: fonts/struct.FontData.html#method.is_monospace
+    pub fn add_monospace_font_family(
+        &mut self,
+        font_family: fonts::FontFamily<fonts::FontData>,
+    ) -> Result<style::Style, error::Error> {
+        if font_family.regular.is_monospace() {
+            Ok(self.add_font_family(font_family).into())
+        } else {
+            Err(error::Error::new(
+                "The font family is not monospaced",
+                error::ErrorKind::InvalidFont,
+            ))
+        }
+    }
+
     /// Returns the font cache used by this document.
     ///
     /// You can use the font cache to get the default font and to query glyph metrics for a font.
@@ -602,14 +801,50 @@ impl Document {
         &self.context.font_cache
     }
 
-    /// Activates hyphenation and sets the hyphentor to use.
+    /// Activates hyphenation and sets the hyphenator to use.
     ///
     /// *Only available if the `hyphenation` feature is enabled.*
+    ///
+    /// The hyphenator can be any prepared [`hyphenation::Standard`][] instance, for example a
+    /// dictionary that has been loaded once and is cloned for every document of a batch job, or a
+    /// dictionary with custom exceptions that have been added with
+    /// [`hyphenation::Hyphenator::add_exception`][].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use hyphenation::{Hyphenator as _, Load as _};
+    ///
+    /// let mut hyphenator = hyphenation::Standard::from_embedded(hyphenation::Language::EnglishUS)
+    ///     .expect("Failed to load hyphenation dictionary");
+    /// hyphenator.add_exception("rckive".to_owned(), vec![2]);
+    ///
+    /// let font_family = rckive_genpdf::fonts::from_files("./fonts", "LiberationSans", None)
+    ///     .expect("Failed to load font family");
+    /// let mut doc = rckive_genpdf::Document::new(font_family);
+    /// doc.set_hyphenator(hyphenator);
+    /// ```
+    ///
+    /// [`hyphenation::Standard`]: https://docs.rs/hyphenation/latest/hyphenation/struct.Standard.html
+    /// [`hyphenation::Hyphenator::add_exception`]: https://docs.rs/hyphenation/latest/hyphenation/trait.Hyphenator.html#method.add_exception
     #[cfg(feature = "hyphenation")]
     pub fn set_hyphenator(&mut self, hyphenator: hyphenation::Standard) {
         self.context.hyphenator = Some(hyphenator);
     }
 
+    /// Sets the number of letters that a word must exceed to be hyphenated.
+    ///
+    /// *Only available if the `hyphenation` feature is enabled.*
+    ///
+    /// Only words that are longer than the given number of letters are hyphenated.  Shorter words
+    /// are moved to the next line instead, which avoids many breaks in short words.  Punctuation is
+    /// not counted, and a word that consists of runs with different styles is counted as a whole.
+    /// Per default, all words can be hyphenated.
+    #[cfg(feature = "hyphenation")]
+    pub fn set_hyphenation_min_word_length(&mut self, min_word_length: usize) {
+        self.context.hyphenation_min_word_length = min_word_length;
+    }
+
     /// Sets the title of the PDF document.
     ///
     /// If this method is not called, the PDF title will be empty.
@@ -624,6 +859,17 @@ impl Document {
         self.style.set_font_size(font_size);
     }
 
+    /// Sets the default minimum font size in points for text that is shrunk to fit the page.
+    ///
+    /// See [`Style::set_min_font_size`][] and [`LineOverflow::Shrink`][].  If this method is not
+    /// called, the default value of 1 point is used.
+    ///
+    /// [`Style::set_min_font_size`]: style/struct.Style.html#method.set_min_font_size
+    /// [`LineOverflow::Shrink`]: enum.LineOverflow.html#variant.Shrink
+    pub fn set_min_font_size(&mut self, min_font_size: u8) {
+        self.style.set_min_font_size(min_font_size);
+    }
+
     /// Sets the default line spacing factor for this document.
     ///
     /// If this method is not called, the default value of 1 is used.
@@ -631,11 +877,31 @@ impl Document {
         self.style.set_line_spacing(line_spacing);
     }
 
+    /// Sets the default extra leading for this document.
+    ///
+    /// The extra leading is added to the line height of the text after it has been multiplied
+    /// with the line spacing factor, see [`set_line_spacing`][].  If this method is not called, no
+    /// extra leading is used.
+    ///
+    /// [`set_line_spacing`]: #method.set_line_spacing
+    pub fn set_extra_leading(&mut self, extra_leading: impl Into<Mm>) {
+        self.style.set_extra_leading(extra_leading);
+    }
+
+    /// Returns the paper size for the pages of this document, see [`set_paper_size`][].
+    ///
+    /// [`set_paper_size`]: #method.set_paper_size
+    pub fn paper_size(&self) -> Size {
+        self.paper_size
+    }
+
     /// Sets the paper size for all pages of this document.
     ///
-    /// If this method is not called, the default size [`A4`][] is used.
+    /// If this method is not called, the default size [`A4`][] is used.  To change the paper size
+    /// of the pages after a specific element, use [`PageBreak::with_paper_size`][].
     ///
     /// [`A4`]: enum.PaperSize.html#variant.A4
+    /// [`PageBreak::with_paper_size`]: elements/struct.PageBreak.html#method.with_paper_size
     pub fn set_paper_size(&mut self, paper_size: impl Into<Size>) {
         self.paper_size = paper_size.into();
     }
@@ -652,6 +918,180 @@ impl Document {
         self.decorator = Some(Box::new(decorator));
     }
 
+    /// Restricts the content of every page to the rectangle with the given position and size.
+    ///
+    /// The position is measured from the upper left corner of the page.  The document content is
+    /// rendered into the intersection of this rectangle and the area returned by the page
+    /// decorator, so headers and margins set by the decorator are still applied.  This can be
+    /// used to keep a region of the page free, for example for a sidebar that is drawn by the
+    /// page decorator.
+    pub fn set_content_rect(&mut self, position: impl Into<Position>, size: impl Into<Size>) {
+        self.content_rect = Some((position.into(), size.into()));
+    }
+
+    /// Draws crop marks at the corners of the trim box of every page.
+    ///
+    /// The trim box of each page is the page inset by the sum of the given offset and length on
+    /// every side, see [`Page::set_trim_box`][].  At each corner of the trim box, a horizontal and
+    /// a vertical line with the given length are drawn outside of the trim box, with the given
+    /// offset from the corner.  The crop marks are drawn after the document content has been
+    /// rendered, so you should use a page decorator with margins of at least the sum of offset and
+    /// length to keep the content inside of the trim box.
+    ///
+    /// [`Page::set_trim_box`]: render/struct.Page.html#method.set_trim_box
+    pub fn add_crop_marks(&mut self, offset: impl Into<Mm>, length: impl Into<Mm>) {
+        self.crop_marks = Some((offset.into(), length.into()));
+    }
+
+    /// Draws a grid with the given spacing and color and guides for the page margins on every
+    /// page to help debugging the layout.
+    ///
+    /// The grid lines start at the upper left corner of the page.  The margin guides are drawn
+    /// around the area that is returned by the page decorator, see [`set_page_decorator`][].
+    /// The grid is drawn on an optional layer above the document content, so it can be hidden in
+    /// PDF viewers.  The spacing must be positive.
+    ///
+    /// [`set_page_decorator`]: #method.set_page_decorator
+    pub fn set_debug_grid(&mut self, spacing: impl Into<Mm>, color: style::Color) {
+        self.debug_grid = Some((spacing.into(), color));
+    }
+
+    /// Sets whether the bounding box of every element is outlined to help debugging the layout.
+    ///
+    /// If enabled, a rectangle with the size returned by the render method of each element is
+    /// drawn at the origin of its area.  The color of the rectangle depends on the nesting level
+    /// of the element.  Custom elements have to use [`Context::render_element`][] to render their
+    /// children so that the bounding boxes of the children are drawn.
+    ///
+    /// [`Context::render_element`]: struct.Context.html#method.render_element
+    pub fn set_debug_boxes(&mut self, debug_boxes: bool) {
+        self.context.debug_boxes = debug_boxes;
+    }
+
+    /// Sets a callback that is called for every page after the document content has been
+    /// rendered.
+    ///
+    /// The callback receives the render context, an area that covers the full page, the page
+    /// number (starting at 1) and the total number of pages.  It can be used to draw overlays that
+    /// depend on the total number of pages, for example page numbers in the form “1/10”.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let font_family = rckive_genpdf::fonts::from_files("./fonts", "LiberationSans", None).unwrap();
+    /// let mut doc = rckive_genpdf::Document::new(font_family);
+    /// doc.for_each_page(|context, area, page, total| {
+    ///     let style = rckive_genpdf::style::Style::new();
+    ///     let position = rckive_genpdf::Position::new(10, 10);
+    ///     area.print_str(&context.font_cache, position, style, format!("{}/{}", page, total))?;
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn for_each_page<F>(&mut self, cb: F)
+    where
+        F: FnMut(&Context, render::Area<'_>, usize, usize) -> Result<(), error::Error> + 'static,
+    {
+        self.page_callback = Some(Box::new(cb));
+    }
+
+    /// Sets the fallback for lines of text that do not fit on a new page.
+    ///
+    /// If this method is not called, the rendering process is cancelled with an error if a line
+    /// does not fit on a new page.  See [`LineOverflow`][] for the available options.
+    ///
+    /// [`LineOverflow`]: enum.LineOverflow.html
+    pub fn set_line_overflow(&mut self, line_overflow: LineOverflow) {
+        self.line_overflow = line_overflow;
+    }
+
+    /// Sets the spacing of the baseline grid for all pages of this document.
+    ///
+    /// If a baseline grid is set, the baselines of all lines of text are moved down to the next
+    /// line of the grid, starting at the top edge of the page.  This makes sure that text in
+    /// different columns is aligned.  If the spacing is not positive, an error with the
+    /// [`InvalidData`][] kind is returned and the baseline grid is not changed.
+    ///
+    /// If this method is not called, no baseline grid is used.
+    ///
+    /// [`InvalidData`]: error/enum.ErrorKind.html#variant.InvalidData
+    pub fn set_baseline_grid(&mut self, spacing: impl Into<Mm>) -> Result<(), error::Error> {
+        let spacing = spacing.into();
+        render::check_grid_spacing(spacing)?;
+        self.baseline_grid = Some(spacing);
+        Ok(())
+    }
+
+    /// Sets the name of the layer that contains the content of each page of this document.
+    ///
+    /// PDF viewers show the layer names in their layers panel.  If this method is not called,
+    /// the layer is named “Layer 1”.
+    pub fn set_layer_name(&mut self, name: impl Into<String>) {
+        self.layer_name = Some(name.into());
+    }
+
+    /// Sets the default resolution in dots per inch for all images of this document.
+    ///
+    /// *Only relevant if the `images` feature is enabled.*
+    ///
+    /// The default resolution is used for all images that do not have their own resolution set
+    /// with [`Image::set_dpi`][].  If this method is not called, a resolution of 300 dpi is used.
+    ///
+    /// [`Image::set_dpi`]: elements/struct.Image.html#method.set_dpi
+    pub fn set_image_dpi(&mut self, dpi: f32) {
+        self.context.image_dpi = Some(dpi);
+    }
+
+    /// Sets the default line style for frames and rules of this document.
+    ///
+    /// The default line style is used by elements that draw lines without an explicit line style,
+    /// for example [`FramedElement::new`][], [`FrameCellDecorator::new`][] and
+    /// [`HorizontalRule::new`][].  If this method is not called, the default [`LineStyle`][] is
+    /// used.
+    ///
+    /// [`FramedElement::new`]: elements/struct.FramedElement.html#method.new
+    /// [`FrameCellDecorator::new`]: elements/struct.FrameCellDecorator.html#method.new
+    /// [`HorizontalRule::new`]: elements/struct.HorizontalRule.html#method.new
+    /// [`LineStyle`]: style/struct.LineStyle.html
+    pub fn set_default_line_style(&mut self, line_style: impl Into<style::LineStyle>) {
+        self.context.default_line_style = line_style.into();
+    }
+
+    /// Adds an image that is printed instead of the glyph for the given character, for example to
+    /// render emoji that are not contained in the document fonts.
+    ///
+    /// *Only available if the `images` feature is enabled.*
+    ///
+    /// See [`FontCache::add_emoji_image`][] for more information.
+    ///
+    /// [`FontCache::add_emoji_image`]: fonts/struct.FontCache.html#method.add_emoji_image
+    #[cfg(feature = "images")]
+    pub fn add_emoji_image(&mut self, c: char, image: printpdf::image_crate::DynamicImage) {
+        self.context.font_cache.add_emoji_image(c, image);
+    }
+
+    /// Sets a callback that is invoked with the character and the font whenever a character is
+    /// rendered that is not contained in the font, for example to log or collect missing glyphs.
+    ///
+    /// See [`FontCache::set_missing_glyph_callback`][] for more information.
+    ///
+    /// [`FontCache::set_missing_glyph_callback`]: fonts/struct.FontCache.html#method.set_missing_glyph_callback
+    pub fn on_missing_glyph<F: Fn(char, fonts::Font) + 'static>(&mut self, cb: F) {
+        self.context.font_cache.set_missing_glyph_callback(cb);
+    }
+
+    /// Enables the generation of a tagged PDF document for accessibility.
+    ///
+    /// If tagging is enabled, the elements add structure information to the generated document:
+    /// Paragraphs and text are tagged as paragraphs or headings (see [`Paragraph::with_tag`][]),
+    /// tables as tables with rows and cells and images as figures with an optional alternate text
+    /// (see [`Image::with_alt_text`][]).
+    ///
+    /// [`Paragraph::with_tag`]: elements/struct.Paragraph.html#method.with_tag
+    /// [`Image::with_alt_text`]: elements/struct.Image.html#method.with_alt_text
+    pub fn enable_tagging(&mut self) {
+        self.tagging = true;
+    }
+
     /// Sets the PDF conformance settings for this document.
     pub fn set_conformance(&mut self, conformance: printpdf::PdfConformance) {
         self.conformance = Some(conformance);
@@ -681,6 +1121,78 @@ impl Document {
         self.modification_date = Some(date);
     }
 
+    /// Sets the producer of the PDF file, for example the name of the generating application.
+    ///
+    /// If this method is not called, the default producer set by `printpdf` is used.
+    pub fn set_producer(&mut self, producer: impl Into<String>) {
+        self.producer = Some(producer.into());
+    }
+
+    /// Sets the creator of the PDF file, i. e. the application that created the original content.
+    ///
+    /// If this method is not called, the default creator set by `printpdf` is used.
+    pub fn set_creator(&mut self, creator: impl Into<String>) {
+        self.creator = Some(creator.into());
+    }
+
+    /// Sets a custom XMP metadata packet for the PDF file, for example to add Dublin Core or
+    /// custom properties for asset management systems.
+    ///
+    /// The packet must be a complete XMP packet.  It replaces the XMP metadata that is generated
+    /// from the document title and the dates and is written to the `Metadata` stream of the PDF
+    /// file without modifications.  If the packet is not well-formed XML, an error with the
+    /// [`InvalidData`][] kind is returned and the metadata is not changed.
+    ///
+    /// Note that only a basic well-formedness check is performed:  The tags must be balanced and
+    /// there must be exactly one root element.
+    ///
+    /// [`InvalidData`]: error/enum.ErrorKind.html#variant.InvalidData
+    pub fn set_xmp_metadata(&mut self, xmp: impl Into<String>) -> Result<(), error::Error> {
+        let xmp = xmp.into();
+        check_xml(&xmp).map_err(|msg| {
+            error::Error::new(
+                format!("Invalid XMP metadata: {}", msg),
+                error::ErrorKind::InvalidData,
+            )
+        })?;
+        self.xmp_metadata = Some(xmp);
+        Ok(())
+    }
+
+    /// Sets the output intent of the PDF file, using the given ICC profile and the given output
+    /// condition identifier, for example `"FOGRA39"`.
+    ///
+    /// The profile is embedded in the PDF file and replaces the default output intent that is
+    /// generated for PDF/X conformance, see [`Renderer::with_output_intent`][].  If the header of
+    /// the profile is invalid or does not declare a gray, RGB, Lab or CMYK color space, an error
+    /// with the [`InvalidData`][] kind is returned and the output intent is not changed.
+    ///
+    /// [`Renderer::with_output_intent`]: render/struct.Renderer.html#method.with_output_intent
+    /// [`InvalidData`]: error/enum.ErrorKind.html#variant.InvalidData
+    pub fn set_output_intent(
+        &mut self,
+        icc_profile: impl Into<Vec<u8>>,
+        name: impl Into<String>,
+    ) -> Result<(), error::Error> {
+        let icc_profile = icc_profile.into();
+        render::icc_components(&icc_profile)?;
+        self.output_intent = Some((icc_profile, name.into()));
+        Ok(())
+    }
+
+    /// Sets the named destination that PDF viewers display when the PDF file is opened, for
+    /// example the start of a section.
+    ///
+    /// The destination is added with a [`NamedDestination`][] element.  If the document does not
+    /// contain a destination with the given name, rendering fails with an error with the
+    /// [`InvalidData`][] kind.
+    ///
+    /// [`NamedDestination`]: elements/struct.NamedDestination.html
+    /// [`InvalidData`]: error/enum.ErrorKind.html#variant.InvalidData
+    pub fn set_open_destination(&mut self, name: impl Into<String>) {
+        self.open_destination = Some(name.into());
+    }
+
     /// Adds the given element to the document.
     ///
     /// The given element is appended to the list of elements that is rendered by the root
@@ -693,12 +1205,83 @@ impl Document {
         self.root.push(element);
     }
 
+    /// Adds the given element to the document and returns a handle for retrieving its destination
+    /// after the document has been rendered.
+    ///
+    /// The element is wrapped in a [`TrackedElement`][], see its documentation for more
+    /// information.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use rckive_genpdf::elements;
+    /// let font_family = rckive_genpdf::fonts::from_files("./fonts", "LiberationSans", None)
+    ///     .expect("Failed to load font family");
+    /// let mut doc = rckive_genpdf::Document::new(font_family);
+    /// let handle = doc.push_with_handle(elements::Paragraph::new("Introduction"));
+    /// doc.render_to_file("output.pdf").expect("Failed to render document");
+    /// let page = handle.destination().expect("Heading was not rendered").page;
+    /// ```
+    ///
+    /// [`TrackedElement`]: elements/struct.TrackedElement.html
+    pub fn push_with_handle<E: Element + 'static>(&mut self, element: E) -> ElementHandle {
+        let element = elements::TrackedElement::new(element);
+        let handle = element.handle();
+        self.root.push(element);
+        handle
+    }
+
+    /// Appends the pages of the given document to this document.
+    ///
+    /// The given document is rendered with its own settings, for example its paper size, page
+    /// decorator and fonts, when this document is rendered, and its pages are added after the
+    /// pages of this document.  Resources that are used by both documents, for example embedded
+    /// fonts, are only written once.  See [`Renderer::append_pdf`][] for more information.
+    ///
+    /// [`Renderer::append_pdf`]: render/struct.Renderer.html#method.append_pdf
+    pub fn append(&mut self, other: Document) {
+        self.appended_documents.push(other);
+    }
+
     /// Renders this document into a PDF file and writes it to the given writer.
     ///
     /// The given writer is always wrapped in a buffered writer.  For details on the rendering
     /// process, see the [Rendering Process section of the crate
     /// documentation](index.html#rendering-process).
-    pub fn render(mut self, w: impl io::Write) -> Result<(), error::Error> {
+    pub fn render(self, w: impl io::Write) -> Result<(), error::Error> {
+        self.render_pages()?.write(w)
+    }
+
+    /// Renders this document and writes a PDF file with the pages with the given page numbers to
+    /// the given writer, for example to generate a preview of a document.
+    ///
+    /// The page numbers start at 1.  The complete document is rendered, but only the given pages
+    /// and the resources they use are written to the PDF file.  See
+    /// [`Renderer::write_page_range`][] for more information.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let font_family = rckive_genpdf::fonts::from_files("./fonts", "LiberationSans", None)
+    ///     .expect("Failed to load font family");
+    /// let doc = rckive_genpdf::Document::new(font_family);
+    /// // Write the pages 2, 3 and 4
+    /// let file = std::fs::File::create("preview.pdf").expect("Failed to create file");
+    /// doc.render_page_range(2..=4, file)
+    ///     .expect("Failed to render document");
+    /// ```
+    ///
+    /// [`Renderer::write_page_range`]: render/struct.Renderer.html#method.write_page_range
+    pub fn render_page_range(
+        self,
+        pages: impl std::ops::RangeBounds<usize>,
+        w: impl io::Write,
+    ) -> Result<(), error::Error> {
+        self.render_pages()?.write_page_range(pages, w)
+    }
+
+    /// Renders the elements of this document and returns the renderer with the rendered pages.
+    fn render_pages(mut self) -> Result<render::Renderer, error::Error> {
         let mut renderer = render::Renderer::new(self.paper_size, &self.title)?;
         if let Some(conformance) = self.conformance {
             renderer = renderer.with_conformance(conformance);
@@ -709,13 +1292,67 @@ impl Document {
         if let Some(modification_date) = self.modification_date {
             renderer = renderer.with_modification_date(modification_date);
         }
+        if let Some(baseline_grid) = self.baseline_grid {
+            renderer = renderer.with_baseline_grid(baseline_grid)?;
+        }
+        if let Some(layer_name) = self.layer_name.take() {
+            renderer = renderer.with_layer_name(layer_name);
+        }
+        if self.tagging {
+            renderer = renderer.with_tagging();
+        }
+        if let Some(producer) = self.producer.take() {
+            renderer = renderer.with_producer(producer);
+        }
+        if let Some(creator) = self.creator.take() {
+            renderer = renderer.with_creator(creator);
+        }
+        if let Some(xmp) = self.xmp_metadata.take() {
+            renderer = renderer.with_xmp_metadata(xmp);
+        }
+        if let Some((icc_profile, name)) = self.output_intent.take() {
+            renderer = renderer.with_output_intent(icc_profile, name)?;
+        }
+        if let Some(name) = self.open_destination.take() {
+            renderer = renderer.with_open_destination(name);
+        }
         self.context.font_cache.load_pdf_fonts(&renderer)?;
+        let mut content_areas = Vec::new();
         loop {
             let mut area = renderer.last_page().last_layer().area();
             if let Some(decorator) = &mut self.decorator {
                 area = decorator.decorate_page(&self.context, area, self.style)?;
             }
-            let result = self.root.render(&self.context, area, self.style)?;
+            if let Some((position, size)) = self.content_rect {
+                area.intersect(position, size);
+            }
+            content_areas.push((area.origin(), area.size()));
+            self.context
+                .content_area
+                .set(Some((area.origin(), area.size())));
+            area.set_page_start();
+            let deferred = self.context.deferred.borrow().len();
+            let annotations = renderer.last_page().annotation_counts();
+            let mut result =
+                self.context
+                    .render_element(&mut self.root, area.clone(), self.style)?;
+            if result.has_more
+                && result.size == Size::new(0, 0)
+                && self.line_overflow != LineOverflow::Error
+            {
+                // Nothing fit on the new page, so we try again and let the text elements apply
+                // the fallback to their first line.  The elements may already have registered
+                // deferred draws and annotations for this page, so we discard them to avoid
+                // duplicates.  Caption numbers are kept because they are stored in the handles.
+                self.context.deferred.borrow_mut().truncate(deferred);
+                renderer.last_page().truncate_annotations(annotations);
+                self.context.line_overflow = self.line_overflow;
+                let retry = self
+                    .context
+                    .render_element(&mut self.root, area, self.style);
+                self.context.line_overflow = LineOverflow::Error;
+                result = retry?;
+            }
             if result.has_more {
                 if result.size == Size::new(0, 0) {
                     return Err(error::Error::new(
@@ -723,12 +1360,61 @@ impl Document {
                         error::ErrorKind::PageSizeExceeded,
                     ));
                 }
+                if let Some(paper_size) = self.context.next_paper_size.take() {
+                    self.paper_size = paper_size;
+                }
                 renderer.add_page(self.paper_size);
             } else {
                 break;
             }
         }
-        renderer.write(w)
+        self.context.draw_deferred(&renderer)?;
+        if let Some(cb) = &mut self.page_callback {
+            let total = renderer.page_count();
+            for idx in 0..total {
+                if let Some(page) = renderer.get_page(idx) {
+                    cb(&self.context, page.last_layer().area(), idx + 1, total)?;
+                }
+            }
+        }
+        if let Some((spacing, color)) = self.debug_grid {
+            for (idx, content_area) in content_areas.into_iter().enumerate() {
+                if let Some(page) = renderer.get_page_mut(idx) {
+                    draw_debug_grid(page, spacing, color, content_area);
+                }
+            }
+        }
+        if let Some((offset, length)) = self.crop_marks {
+            for idx in 0..renderer.page_count() {
+                if let Some(page) = renderer.get_page_mut(idx) {
+                    draw_crop_marks(page, offset, length);
+                }
+            }
+        }
+        for other in self.appended_documents {
+            let mut data = Vec::new();
+            other.render(&mut data)?;
+            renderer.append_pdf(&data)?;
+        }
+        Ok(renderer)
+    }
+
+    /// Renders this document and rasterizes its pages to images with the given resolution in dots
+    /// per inch, for example to generate thumbnails or previews.
+    ///
+    /// *Only available if the `raster` feature is enabled.*
+    ///
+    /// The rasterization is delegated to the `pdftoppm` tool of the Poppler library, which must be
+    /// installed and available in the search path.  If it cannot be executed or fails, an error is
+    /// returned.  The returned vector contains one image per page.
+    #[cfg(feature = "raster")]
+    pub fn render_to_images(
+        self,
+        dpi: u32,
+    ) -> Result<Vec<printpdf::image_crate::DynamicImage>, error::Error> {
+        let mut data = Vec::new();
+        self.render(&mut data)?;
+        rasterize(&data, dpi)
     }
 
     /// Renders this document into a PDF file at the given path.
@@ -751,6 +1437,240 @@ impl<E: elements::IntoBoxedElement> std::iter::Extend<E> for Document {
     }
 }
 
+/// Rasterizes the given PDF document with `pdftoppm` and returns an image for every page.
+#[cfg(feature = "raster")]
+fn rasterize(
+    data: &[u8],
+    dpi: u32,
+) -> Result<Vec<printpdf::image_crate::DynamicImage>, error::Error> {
+    use std::sync::atomic;
+
+    static COUNTER: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
+
+    let dir = std::env::temp_dir().join(format!(
+        "rckive-genpdf-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, atomic::Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir).context("Failed to create temporary directory")?;
+    let result = (|| {
+        let input = dir.join("document.pdf");
+        fs::write(&input, data).context("Failed to write temporary file")?;
+        let output = std::process::Command::new("pdftoppm")
+            .arg("-r")
+            .arg(dpi.to_string())
+            .arg(&input)
+            .arg(dir.join("page"))
+            .output()
+            .context("Failed to execute pdftoppm")?;
+        if !output.status.success() {
+            return Err(error::Error::new(
+                format!(
+                    "pdftoppm failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                error::ErrorKind::Internal,
+            ));
+        }
+
+        // pdftoppm writes one file per page with zero-padded page numbers, e. g. page-01.ppm.
+        let mut pages = Vec::new();
+        for entry in fs::read_dir(&dir).context("Failed to read temporary directory")? {
+            let path = entry.context("Failed to read temporary directory")?.path();
+            if path.extension().map(|ext| ext == "ppm").unwrap_or_default() {
+                pages.push(path);
+            }
+        }
+        pages.sort();
+        pages
+            .into_iter()
+            .map(|path| printpdf::image_crate::open(path).context("Failed to read page image"))
+            .collect()
+    })();
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+/// Checks that the given string is a well-formed XML document, i. e. that its tags are balanced
+/// and that it has exactly one root element.
+///
+/// This is not a full XML parser:  Entities, names and attributes are not validated.
+fn check_xml(xml: &str) -> Result<(), String> {
+    let mut stack = Vec::new();
+    let mut has_root = false;
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        if stack.is_empty() && !rest[..start].trim().is_empty() {
+            return Err("text outside of the root element".to_owned());
+        }
+        rest = &rest[start..];
+
+        // Processing instructions, comments, CDATA sections and declarations
+        let special_end = [
+            ("<?", "?>"),
+            ("<!--", "-->"),
+            ("<![CDATA[", "]]>"),
+            ("<!", ">"),
+        ]
+        .iter()
+        .find(|(start, _)| rest.starts_with(start))
+        .map(|(_, end)| *end);
+        if let Some(end) = special_end {
+            let idx = rest.find(end).ok_or_else(|| format!("missing {}", end))?;
+            rest = &rest[idx + end.len()..];
+            continue;
+        }
+
+        // Find the end of the tag, skipping quoted attribute values
+        let mut quote = None;
+        let end = rest
+            .char_indices()
+            .find(|(_, c)| match quote {
+                Some(q) => {
+                    if *c == q {
+                        quote = None;
+                    }
+                    false
+                }
+                None => {
+                    if *c == '"' || *c == '\'' {
+                        quote = Some(*c);
+                    }
+                    *c == '>'
+                }
+            })
+            .map(|(idx, _)| idx)
+            .ok_or_else(|| "unterminated tag".to_owned())?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            match stack.pop() {
+                Some(open) if open == name => {}
+                Some(open) => return Err(format!("expected </{}>, found </{}>", open, name)),
+                None => return Err(format!("unexpected </{}>", name)),
+            }
+        } else {
+            let name = tag
+                .trim_end_matches('/')
+                .split(char::is_whitespace)
+                .next()
+                .unwrap_or_default();
+            if name.is_empty() {
+                return Err("missing tag name".to_owned());
+            }
+            if stack.is_empty() {
+                if has_root {
+                    return Err("multiple root elements".to_owned());
+                }
+                has_root = true;
+            }
+            if !tag.ends_with('/') {
+                stack.push(name);
+            }
+        }
+    }
+
+    if let Some(open) = stack.pop() {
+        Err(format!("missing </{}>", open))
+    } else if !rest.trim().is_empty() {
+        Err("text outside of the root element".to_owned())
+    } else if !has_root {
+        Err("missing root element".to_owned())
+    } else {
+        Ok(())
+    }
+}
+
+/// Sets the trim box of the given page and draws crop marks at its corners, see
+/// [`Document::add_crop_marks`][].
+///
+/// [`Document::add_crop_marks`]: struct.Document.html#method.add_crop_marks
+fn draw_crop_marks(page: &mut render::Page, offset: Mm, length: Mm) {
+    let margin = offset + length;
+    // The crop marks are part of the page content, so they must not be drawn on an optional
+    // layer like the debug grid.
+    let area = page.first_layer().area();
+    let size = area.size();
+    let trim_size = Size::new(size.width - margin * 2.0, size.height - margin * 2.0);
+    let left = margin;
+    let right = size.width - margin;
+    let top = margin;
+    let bottom = size.height - margin;
+    let line_style = style::LineStyle::new();
+    for (x, dx) in [(left, -1.0), (right, 1.0)] {
+        for (y, dy) in [(top, -1.0), (bottom, 1.0)] {
+            area.draw_line(
+                vec![
+                    Position::new(x + offset * dx, y),
+                    Position::new(x + margin * dx, y),
+                ],
+                line_style,
+            );
+            area.draw_line(
+                vec![
+                    Position::new(x, y + offset * dy),
+                    Position::new(x, y + margin * dy),
+                ],
+                line_style,
+            );
+        }
+    }
+    page.set_trim_box(Position::new(left, top), trim_size);
+}
+
+/// Draws a grid with the given spacing and the guides for the given content area on a new layer of
+/// the given page, see [`Document::set_debug_grid`][].
+///
+/// [`Document::set_debug_grid`]: struct.Document.html#method.set_debug_grid
+fn draw_debug_grid(
+    page: &mut render::Page,
+    spacing: Mm,
+    color: style::Color,
+    content_area: (Position, Size),
+) {
+    page.add_optional_layer("Debug grid", true);
+    let area = page.last_layer().area();
+    let size = area.size();
+    let line_style = style::LineStyle::from(color);
+    if spacing > Mm(0.0) {
+        let xs = (1..)
+            .map(|i| spacing * i as f32)
+            .take_while(|x| *x < size.width);
+        for x in xs {
+            area.draw_line(
+                vec![Position::new(x, 0), Position::new(x, size.height)],
+                line_style,
+            );
+        }
+        let ys = (1..)
+            .map(|i| spacing * i as f32)
+            .take_while(|y| *y < size.height);
+        for y in ys {
+            area.draw_line(
+                vec![Position::new(0, y), Position::new(size.width, y)],
+                line_style,
+            );
+        }
+    }
+
+    let (origin, content_size) = content_area;
+    if origin != Position::default() || content_size != size {
+        let bottom_right = origin + Position::new(content_size.width, content_size.height);
+        area.draw_line(
+            vec![
+                origin,
+                Position::new(bottom_right.x, origin.y),
+                bottom_right,
+                Position::new(origin.x, bottom_right.y),
+                origin,
+            ],
+            line_style.with_thickness(Mm::from(0.3)),
+        );
+    }
+}
+
 /// The result of the rendering process.
 ///
 /// This struct is returned by implementations of the [`Element::render`][] method.  It contains
@@ -771,32 +1691,146 @@ pub struct RenderResult {
     pub has_more: bool,
 }
 
-/// Prepares a page of a document.
+/// The metrics of an element that a [`TableLayout`][] uses to place it in a table cell.
 ///
-/// If you set an implementation of this trait for a [`Document`][] using the
-/// [`set_page_decorator`][] method, its [`decorate_page`][] method is called every time a new page
-/// is added to the document.  The decorator can prepare the page before it is filled with the
-/// actual content.  See [`SimplePageDecorator`][] for a basic implementation.
+/// This struct is returned by implementations of the [`Element::cell_metrics`][] method.
 ///
-/// [`Document`]: struct.Document.html
-/// [`set_page_decorator`]: struct.Document.html#method.set_page_decorator
-/// [`SimplePageDecorator`]: struct.SimplePageDecorator.html
-/// [`decorate_page`]: #tymethod.decorate_page
-pub trait PageDecorator {
-    /// Prepares the page with the given area before it is filled with the document content and
-    /// returns the writable area of the page.
+/// [`TableLayout`]: elements/struct.TableLayout.html
+/// [`Element::cell_metrics`]: trait.Element.html#method.cell_metrics
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CellMetrics {
+    /// The width of the text before the decimal separator (`.`) and the width of the separator
+    /// and the following text if the element renders a single run of text.
     ///
-    /// The returned area will be passed to the document content.
-    fn decorate_page<'a>(
-        &mut self,
-        context: &Context,
-        area: render::Area<'a>,
-        style: style::Style,
-    ) -> Result<render::Area<'a>, error::Error>;
-}
-
+    /// The widths include the horizontal space that the element adds around the text, for example
+    /// the padding of a [`PaddedElement`][].  Tables use them to align the cells of a column (see
+    /// [`ColumnAlignment`][]), to shrink cells (see [`CellOverflow`][]) and to rotate header
+    /// cells.  If the text does not contain a decimal separator, the complete text is treated as
+    /// the part before the separator.
+    ///
+    /// [`PaddedElement`]: elements/struct.PaddedElement.html
+    /// [`ColumnAlignment`]: elements/enum.ColumnAlignment.html
+    /// [`CellOverflow`]: elements/enum.CellOverflow.html
+    pub text_widths: Option<(Mm, Mm)>,
+    /// The padding that the element adds around its content if it sets one explicitly, like a
+    /// [`PaddedElement`][].
+    ///
+    /// Tables only apply their default cell padding (see [`TableLayout::set_cell_padding`][]) to
+    /// cells that do not set their own padding.
+    ///
+    /// [`PaddedElement`]: elements/struct.PaddedElement.html
+    /// [`TableLayout::set_cell_padding`]: elements/struct.TableLayout.html#method.set_cell_padding
+    pub padding: Option<Margins>,
+}
+
+/// The final location of an element in the rendered document.
+///
+/// See [`ElementHandle`][] for more information.
+///
+/// [`ElementHandle`]: struct.ElementHandle.html
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Destination {
+    /// The number of the page, starting at 1.
+    pub page: usize,
+    /// The position of the element relative to the upper left corner of the page.
+    pub position: Position,
+}
+
+impl Destination {
+    /// Creates a new destination with the given page number and position.
+    pub fn new(page: usize, position: impl Into<Position>) -> Destination {
+        Destination {
+            page,
+            position: position.into(),
+        }
+    }
+}
+
+/// A handle for retrieving the [`Destination`][] of an element after the document has been
+/// rendered.
+///
+/// Handles are created by [`TrackedElement`][] and [`Document::push_with_handle`][].  They can be
+/// cloned and kept after the document has been consumed by the rendering process.  Before the
+/// element has been rendered, [`destination`][] returns `None`.
+///
+/// [`Destination`]: struct.Destination.html
+/// [`TrackedElement`]: elements/struct.TrackedElement.html
+/// [`Document::push_with_handle`]: struct.Document.html#method.push_with_handle
+/// [`destination`]: #method.destination
+#[derive(Clone, Debug, Default)]
+pub struct ElementHandle {
+    destination: rc::Rc<cell::Cell<Option<Destination>>>,
+}
+
+impl ElementHandle {
+    /// Returns the destination of the element, or `None` if it has not been rendered yet.
+    pub fn destination(&self) -> Option<Destination> {
+        self.destination.get()
+    }
+
+    /// Sets the destination of the element unless it has already been set.
+    fn set_destination(&self, destination: Destination) {
+        if self.destination.get().is_none() {
+            self.destination.set(Some(destination));
+        }
+    }
+}
+
+/// Prepares a page of a document.
+///
+/// If you set an implementation of this trait for a [`Document`][] using the
+/// [`set_page_decorator`][] method, its [`decorate_page`][] method is called every time a new page
+/// is added to the document.  The decorator can prepare the page before it is filled with the
+/// actual content.  See [`SimplePageDecorator`][] for a basic implementation.
+///
+/// [`Document`]: struct.Document.html
+/// [`set_page_decorator`]: struct.Document.html#method.set_page_decorator
+/// [`SimplePageDecorator`]: struct.SimplePageDecorator.html
+/// [`decorate_page`]: #tymethod.decorate_page
+pub trait PageDecorator {
+    /// Prepares the page with the given area before it is filled with the document content and
+    /// returns the writable area of the page.
+    ///
+    /// The returned area will be passed to the document content.
+    fn decorate_page<'a>(
+        &mut self,
+        context: &Context,
+        area: render::Area<'a>,
+        style: style::Style,
+    ) -> Result<render::Area<'a>, error::Error>;
+}
+
 type HeaderCallback = Box<dyn Fn(usize) -> Box<dyn Element>>;
 
+type PageCallback =
+    Box<dyn FnMut(&Context, render::Area<'_>, usize, usize) -> Result<(), error::Error>>;
+
+type DeferredCallback =
+    Box<dyn FnOnce(&Context, render::Area<'_>, style::Style) -> Result<(), error::Error>>;
+
+/// A drawing operation that is executed after all pages of a document have been rendered, see
+/// [`Context::defer`][].
+///
+/// [`Context::defer`]: struct.Context.html#method.defer
+struct Deferred {
+    page: usize,
+    origin: Position,
+    size: Size,
+    style: style::Style,
+    cb: DeferredCallback,
+}
+
+impl fmt::Debug for Deferred {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Deferred")
+            .field("page", &self.page)
+            .field("origin", &self.origin)
+            .field("size", &self.size)
+            .field("style", &self.style)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Prepares a page of a document with margins and a header.
 ///
 /// Per default, this decorator does not modify the page.  If margins have been set with the
@@ -854,7 +1888,7 @@ impl PageDecorator for SimplePageDecorator {
         }
         if let Some(cb) = &self.header_cb {
             let mut element = cb(self.page);
-            let result = element.render(context, area.clone(), style)?;
+            let result = context.render_element(element.as_mut(), area.clone(), style)?;
             area.add_offset(Position::new(0, result.size.height));
         }
         Ok(area)
@@ -865,13 +1899,15 @@ impl PageDecorator for SimplePageDecorator {
 ///
 /// This trait is implemented by all elements that can be added to a [`Document`][].  Implementors
 /// have to define the [`render`][] method that writes the content of this element to the generated
-/// PDF document.
+/// PDF document.  Elements that contain other elements should render them using
+/// [`Context::render_element`][].
 ///
 /// See the [Rendering Process section of the crate documentation](index.html#rendering-process)
 /// for more information on the rendering process.
 ///
 /// [`Document`]: struct.Document.html
 /// [`render`]: #tymethod.render
+/// [`Context::render_element`]: struct.Context.html#method.render_element
 pub trait Element {
     /// Renders this element to the given area using the given style and font cache.
     ///
@@ -913,6 +1949,66 @@ pub trait Element {
         style: style::Style,
     ) -> Result<RenderResult, error::Error>;
 
+    /// Returns whether this element only inserts vertical space, like a [`Break`][].
+    ///
+    /// If there is no space left on a page, layouts discard the following spacing elements
+    /// instead of moving them to the next page.  The default implementation returns `false`.
+    ///
+    /// [`Break`]: elements/struct.Break.html
+    fn is_spacing(&self) -> bool {
+        false
+    }
+
+    /// Returns the metrics that [`TableLayout`][] uses to place this element in a table cell.
+    ///
+    /// See [`CellMetrics`][] for more information.  Wrapper elements should forward this method
+    /// to the wrapped element and adjust the metrics for the space that they add around it.  The
+    /// default implementation returns the default metrics, so the element is not aligned and the
+    /// default cell padding of the table is applied.
+    ///
+    /// [`TableLayout`]: elements/struct.TableLayout.html
+    /// [`CellMetrics`]: struct.CellMetrics.html
+    fn cell_metrics(&self, context: &Context, style: style::Style) -> CellMetrics {
+        let _ = (context, style);
+        CellMetrics::default()
+    }
+
+    /// Inserts a page break before this element.
+    ///
+    /// See [`PageBreakBefore`][] for more information.
+    ///
+    /// [`PageBreakBefore`]: elements/struct.PageBreakBefore.html
+    fn with_page_break_before(self) -> elements::PageBreakBefore<Self>
+    where
+        Self: Sized,
+    {
+        elements::PageBreakBefore::new(self)
+    }
+
+    /// Keeps at least the given space below this element on the same page.
+    ///
+    /// See [`KeepWithNext`][] for more information.
+    ///
+    /// [`KeepWithNext`]: elements/struct.KeepWithNext.html
+    fn keep_with_next(self, space: impl Into<Mm>) -> elements::KeepWithNext<Self>
+    where
+        Self: Sized + Clone,
+    {
+        elements::KeepWithNext::new(self, space)
+    }
+
+    /// Adds a note in the outer page margin next to this element.
+    ///
+    /// See [`MarginNote`][] for more information.
+    ///
+    /// [`MarginNote`]: elements/struct.MarginNote.html
+    fn with_margin_note(self, note: impl elements::IntoBoxedElement) -> elements::MarginNote<Self>
+    where
+        Self: Sized,
+    {
+        elements::MarginNote::new(self, note)
+    }
+
     /// Draws a frame around this element using the given line style.
     fn framed(self, line_style: impl Into<style::LineStyle>) -> elements::FramedElement<Self>
     where
@@ -929,6 +2025,17 @@ pub trait Element {
         elements::PaddedElement::new(self, padding)
     }
 
+    /// Wraps this element in a [`DecoratedElement`][] that can add a margin, a border, a padding
+    /// and a background color.
+    ///
+    /// [`DecoratedElement`]: elements/struct.DecoratedElement.html
+    fn decorated(self) -> elements::DecoratedElement<Self>
+    where
+        Self: Sized,
+    {
+        elements::DecoratedElement::new(self)
+    }
+
     /// Sets the default style for this element and its children.
     fn styled(self, style: impl Into<style::Style>) -> elements::StyledElement<Self>
     where
@@ -938,6 +2045,19 @@ pub trait Element {
     }
 }
 
+/// The colors of the bounding boxes of the elements, see [`Document::set_debug_boxes`][], indexed
+/// by the nesting level of the element.
+///
+/// [`Document::set_debug_boxes`]: struct.Document.html#method.set_debug_boxes
+const DEBUG_BOX_COLORS: [style::Color; 6] = [
+    style::Color::Rgb(255, 0, 0),
+    style::Color::Rgb(0, 160, 0),
+    style::Color::Rgb(0, 0, 255),
+    style::Color::Rgb(255, 128, 0),
+    style::Color::Rgb(192, 0, 192),
+    style::Color::Rgb(0, 160, 160),
+];
+
 /// The context for a rendering process.
 ///
 /// This struct stores data that is shared between all elements during the rendering process.
@@ -953,12 +2073,40 @@ pub struct Context {
     /// If this field is `None`, hyphenation is disabled.
     #[cfg(feature = "hyphenation")]
     pub hyphenator: Option<hyphenation::Standard>,
+    #[cfg(feature = "hyphenation")]
+    hyphenation_min_word_length: usize,
+    line_overflow: LineOverflow,
+    image_dpi: Option<f32>,
+    default_line_style: style::LineStyle,
+    figure_count: cell::Cell<usize>,
+    table_count: cell::Cell<usize>,
+    deferred: cell::RefCell<Vec<Deferred>>,
+    debug_boxes: bool,
+    debug_depth: cell::Cell<usize>,
+    dry_run: cell::Cell<bool>,
+    word_break: cell::Cell<Option<WordBreak>>,
+    content_area: cell::Cell<Option<(Position, Size)>>,
+    next_paper_size: cell::Cell<Option<Size>>,
 }
 
 impl Context {
     #[cfg(not(feature = "hyphenation"))]
     fn new(font_cache: fonts::FontCache) -> Context {
-        Context { font_cache }
+        Context {
+            font_cache,
+            line_overflow: LineOverflow::Error,
+            image_dpi: None,
+            default_line_style: style::LineStyle::new(),
+            figure_count: cell::Cell::new(0),
+            table_count: cell::Cell::new(0),
+            deferred: Default::default(),
+            debug_boxes: false,
+            debug_depth: cell::Cell::new(0),
+            dry_run: cell::Cell::new(false),
+            word_break: cell::Cell::new(None),
+            content_area: cell::Cell::new(None),
+            next_paper_size: cell::Cell::new(None),
+        }
     }
 
     #[cfg(feature = "hyphenation")]
@@ -966,12 +2114,338 @@ impl Context {
         Context {
             font_cache,
             hyphenator: None,
+            hyphenation_min_word_length: 0,
+            line_overflow: LineOverflow::Error,
+            image_dpi: None,
+            default_line_style: style::LineStyle::new(),
+            figure_count: cell::Cell::new(0),
+            table_count: cell::Cell::new(0),
+            deferred: Default::default(),
+            debug_boxes: false,
+            debug_depth: cell::Cell::new(0),
+            dry_run: cell::Cell::new(false),
+            word_break: cell::Cell::new(None),
+            content_area: cell::Cell::new(None),
+            next_paper_size: cell::Cell::new(None),
+        }
+    }
+
+    /// Returns the fallback that should be applied to the first line of text if it does not fit
+    /// in the area provided to the element.
+    ///
+    /// This is [`LineOverflow::Error`][] unless the document could not fit any content on a new
+    /// page and retries the rendering with the fallback set with
+    /// [`Document::set_line_overflow`][].
+    ///
+    /// [`LineOverflow::Error`]: enum.LineOverflow.html#variant.Error
+    /// [`Document::set_line_overflow`]: struct.Document.html#method.set_line_overflow
+    pub fn line_overflow(&self) -> LineOverflow {
+        self.line_overflow
+    }
+
+    /// Returns the default resolution for images in dots per inch that has been set with
+    /// [`Document::set_image_dpi`][], if any.
+    ///
+    /// [`Document::set_image_dpi`]: struct.Document.html#method.set_image_dpi
+    pub fn image_dpi(&self) -> Option<f32> {
+        self.image_dpi
+    }
+
+    /// Returns the line style that should be used for lines without an explicit line style, see
+    /// [`Document::set_default_line_style`][].
+    ///
+    /// [`Document::set_default_line_style`]: struct.Document.html#method.set_default_line_style
+    pub fn default_line_style(&self) -> style::LineStyle {
+        self.default_line_style
+    }
+
+    /// Renders the given element into the given area and returns the result.
+    ///
+    /// Elements should use this method instead of calling [`Element::render`][] directly to
+    /// render their child elements.  If debug boxes are enabled with
+    /// [`Document::set_debug_boxes`][], this method also outlines the bounding box of the rendered
+    /// element, using a different color for every nesting level.
+    ///
+    /// [`Element::render`]: trait.Element.html#tymethod.render
+    /// [`Document::set_debug_boxes`]: struct.Document.html#method.set_debug_boxes
+    pub fn render_element<E: Element + ?Sized>(
+        &self,
+        element: &mut E,
+        area: render::Area<'_>,
+        style: style::Style,
+    ) -> Result<RenderResult, error::Error> {
+        if !self.debug_boxes {
+            return element.render(self, area, style);
+        }
+
+        let depth = self.debug_depth.get();
+        self.debug_depth.set(depth + 1);
+        let result = element.render(self, area.clone(), style);
+        self.debug_depth.set(depth);
+        let result = result?;
+        if result.size != Size::new(0, 0) {
+            let Size { width, height } = result.size;
+            let color = DEBUG_BOX_COLORS[depth % DEBUG_BOX_COLORS.len()];
+            area.draw_line(
+                vec![
+                    Position::new(0, 0),
+                    Position::new(width, 0),
+                    Position::new(width, height),
+                    Position::new(0, height),
+                    Position::new(0, 0),
+                ],
+                color.into(),
+            );
         }
+        Ok(result)
+    }
+
+    /// Renders the given element into the given area without changing the generated document and
+    /// returns the result, for example to measure the size of the element before rendering it.
+    ///
+    /// During the dry run, [`is_dry_run`][] returns true and the drawing methods of the area
+    /// (see [`Area::is_dry_run`][]) do not emit any PDF operators.  Elements that draw on the
+    /// layer directly, for example using [`Area::with_raw_layer`][], or that have other side
+    /// effects should check the flag.
+    ///
+    /// Rendering changes the state of most elements, so you should only perform a dry run with a
+    /// clone of an element that is rendered later.
+    ///
+    /// [`is_dry_run`]: #method.is_dry_run
+    /// [`Area::is_dry_run`]: render/struct.Area.html#method.is_dry_run
+    /// [`Area::with_raw_layer`]: render/struct.Area.html#method.with_raw_layer
+    pub fn measure<E: Element + ?Sized>(
+        &self,
+        element: &mut E,
+        mut area: render::Area<'_>,
+        style: style::Style,
+    ) -> Result<RenderResult, error::Error> {
+        area.set_dry_run();
+        let dry_run = self.dry_run.replace(true);
+        let result = self.render_element(element, area, style);
+        self.dry_run.set(dry_run);
+        result
+    }
+
+    /// Returns whether the current rendering process is a dry run that must not change the
+    /// generated document, see [`measure`][].
+    ///
+    /// [`measure`]: #method.measure
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.get()
+    }
+
+    /// Renders the given element, breaking words that are longer than a line with the given
+    /// setting unless the paragraphs set a different word break, see [`TableLayout`][].
+    ///
+    /// [`TableLayout`]: elements/struct.TableLayout.html
+    pub(crate) fn render_with_word_break<E: Element + ?Sized>(
+        &self,
+        element: &mut E,
+        area: render::Area<'_>,
+        style: style::Style,
+        word_break: WordBreak,
+    ) -> Result<RenderResult, error::Error> {
+        let previous = self.word_break.replace(Some(word_break));
+        let result = self.render_element(element, area, style);
+        self.word_break.set(previous);
+        result
+    }
+
+    /// Returns the origin and the size of the content area of the current page, i. e. the area
+    /// returned by the page decorator, if the element is rendered by a [`Document`][].
+    ///
+    /// The origin is relative to the upper left corner of the page.
+    ///
+    /// [`Document`]: struct.Document.html
+    pub fn content_area(&self) -> Option<(Position, Size)> {
+        self.content_area.get()
+    }
+
+    /// Sets the paper size for the pages that are added to the document after the current page.
+    ///
+    /// In a dry run, the paper size is not changed.
+    pub(crate) fn set_next_paper_size(&self, paper_size: Size) {
+        if !self.is_dry_run() {
+            self.next_paper_size.set(Some(paper_size));
+        }
+    }
+
+    /// Returns the word break that should be used by paragraphs that do not set a word break.
+    pub(crate) fn word_break(&self) -> Option<WordBreak> {
+        self.word_break.get()
+    }
+
+    /// Returns the next number for a caption of the given kind, starting at 1.
+    ///
+    /// In a dry run, the number is not consumed.
+    pub(crate) fn next_caption_number(&self, kind: elements::CaptionKind) -> usize {
+        let count = match kind {
+            elements::CaptionKind::Figure => &self.figure_count,
+            elements::CaptionKind::Table => &self.table_count,
+        };
+        if self.is_dry_run() {
+            return count.get() + 1;
+        }
+        count.set(count.get() + 1);
+        count.get()
+    }
+
+    /// Registers a callback that draws on the given area after all pages of the document have
+    /// been rendered.
+    ///
+    /// This can be used to fill in content that depends on the rest of the document, for example
+    /// page numbers of elements that have not been rendered yet.  The callback receives an area
+    /// with the same page, position and size as the given area.  In a dry run, the callback is
+    /// discarded.
+    pub(crate) fn defer<F>(&self, area: &render::Area<'_>, style: style::Style, cb: F)
+    where
+        F: FnOnce(&Context, render::Area<'_>, style::Style) -> Result<(), error::Error> + 'static,
+    {
+        if self.is_dry_run() {
+            return;
+        }
+        self.deferred.borrow_mut().push(Deferred {
+            page: area.page_number(),
+            origin: area.origin(),
+            size: area.size(),
+            style,
+            cb: Box::new(cb),
+        });
+    }
+
+    /// Executes the callbacks registered with [`defer`][] on the pages of the given renderer.
+    ///
+    /// [`defer`]: #method.defer
+    fn draw_deferred(&self, renderer: &render::Renderer) -> Result<(), error::Error> {
+        for deferred in self.deferred.take() {
+            if let Some(page) = renderer.get_page(deferred.page - 1) {
+                let mut area = page.last_layer().area();
+                area.add_offset(deferred.origin);
+                area.set_size(deferred.size);
+                (deferred.cb)(self, area, deferred.style)?;
+            }
+        }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::cell;
+    use std::rc;
+
+    use float_cmp::approx_eq;
+
+    use super::{error, fonts, render, style, Context, Element, Mm, Position, RenderResult, Size};
+
+    const FONT_DIR: &str = "./tests/files/liberation";
+    const FONT_NAME: &str = "LiberationSans";
+
+    /// Loads the Liberation Sans font family from the test files.
+    pub(crate) fn font_family() -> fonts::FontFamily<fonts::FontData> {
+        fonts::from_files(FONT_DIR, FONT_NAME, None).expect("Failed to load test font family")
+    }
+
+    /// Creates a font cache with the test font family that has been loaded into the given
+    /// renderer.
+    pub(crate) fn font_cache(renderer: &render::Renderer) -> fonts::FontCache {
+        let mut font_cache = fonts::FontCache::new(font_family());
+        font_cache
+            .load_pdf_fonts(renderer)
+            .expect("Failed to load PDF fonts");
+        font_cache
+    }
+
+    /// Creates a document with the test font family and minimal conformance.
+    pub(crate) fn document() -> super::Document {
+        let mut doc = super::Document::new(font_family());
+        doc.set_minimal_conformance();
+        doc
+    }
+
+    /// An element that fills the remaining space of the page except for the given height.
+    pub(crate) struct FillPage(pub(crate) Mm);
+
+    impl Element for FillPage {
+        fn render(
+            &mut self,
+            _context: &Context,
+            area: render::Area<'_>,
+            _style: style::Style,
+        ) -> Result<RenderResult, error::Error> {
+            let mut size = area.size();
+            size.height = (size.height - self.0).max(Mm(0.0));
+            Ok(RenderResult {
+                size,
+                has_more: false,
+            })
+        }
+    }
+
+    /// Renders the given document and parses the generated PDF file.
+    pub(crate) fn render_document(
+        doc: super::Document,
+    ) -> Result<lopdf::Document, super::error::Error> {
+        let mut data = Vec::new();
+        doc.render(&mut data)?;
+        Ok(lopdf::Document::load_mem(&data).expect("Failed to parse generated PDF"))
+    }
+
+    /// Writes the document of the given renderer and parses the generated PDF file.
+    pub(crate) fn write_renderer(renderer: render::Renderer) -> lopdf::Document {
+        let mut data = Vec::new();
+        renderer.write(&mut data).expect("Failed to write document");
+        lopdf::Document::load_mem(&data).expect("Failed to parse generated PDF")
+    }
+
+    /// Returns the content stream operations of the page with the given index.
+    pub(crate) fn page_operations(
+        doc: &lopdf::Document,
+        page: usize,
+    ) -> Vec<lopdf::content::Operation> {
+        let page_id = doc.page_iter().nth(page).expect("Missing page");
+        let content = doc
+            .get_page_content(page_id)
+            .expect("Failed to read page content");
+        lopdf::content::Content::decode(&content)
+            .expect("Failed to decode page content")
+            .operations
+    }
+
+    /// Returns the operands of all operations with the given operator on the given page.
+    pub(crate) fn find_operations(
+        doc: &lopdf::Document,
+        page: usize,
+        operator: &str,
+    ) -> Vec<Vec<lopdf::Object>> {
+        page_operations(doc, page)
+            .into_iter()
+            .filter(|op| op.operator == operator)
+            .map(|op| op.operands)
+            .collect()
+    }
+
+    /// Renders the given element and returns the horizontal and vertical scale of the last
+    /// transformation matrix on the first page, for example the size of an image in points.
+    #[cfg(feature = "images")]
+    pub(crate) fn render_scale(element: impl super::elements::IntoBoxedElement) -> (f32, f32) {
+        let mut doc = document();
+        doc.push(element);
+        let doc = render_document(doc).unwrap();
+        let transform = find_operations(&doc, 0, "cm").pop().unwrap();
+        (as_f32(&transform[0]), as_f32(&transform[3]))
+    }
+
+    /// Converts a numeric PDF object into a float.
+    pub(crate) fn as_f32(object: &lopdf::Object) -> f32 {
+        match object {
+            lopdf::Object::Integer(i) => *i as f32,
+            lopdf::Object::Real(r) => *r,
+            _ => panic!("Expected a number, got {:?}", object),
+        }
+    }
+
     impl float_cmp::ApproxEq for super::Mm {
         type Margin = float_cmp::F32Margin;
 
@@ -1018,4 +2492,882 @@ mod tests {
         assert_eq!(Some(-90.0), Rotation::from(-450.0).degrees());
         assert_eq!(Some(-180.0), Rotation::from(-540.0).degrees());
     }
+
+    fn oversized_document(line_overflow: super::LineOverflow) -> super::Document {
+        let mut doc = document();
+        doc.set_paper_size(super::Size::new(100, 10));
+        doc.set_font_size(60);
+        doc.set_line_overflow(line_overflow);
+        doc.push(super::elements::Paragraph::new("Huge"));
+        doc.push(super::elements::Text::new("Text"));
+        doc
+    }
+
+    #[test]
+    fn test_line_overflow_error() {
+        let err = render_document(oversized_document(super::LineOverflow::Error)).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            super::error::ErrorKind::PageSizeExceeded
+        ));
+    }
+
+    #[test]
+    fn test_line_overflow_shrink() {
+        let doc = render_document(oversized_document(super::LineOverflow::Shrink)).unwrap();
+        assert_eq!(2, doc.get_pages().len());
+        for page in 0..2 {
+            let fonts = find_operations(&doc, page, "Tf");
+            assert_eq!(1, fonts.len());
+            let font_size = as_f32(&fonts[0][1]);
+            assert!(font_size > 0.0 && font_size < 60.0);
+            assert!(!find_operations(&doc, page, "TJ").is_empty());
+        }
+    }
+
+    #[test]
+    fn test_line_overflow_retry_annotations() {
+        let mut doc = oversized_document(super::LineOverflow::Shrink);
+        let mut paragraph = super::elements::Paragraph::new("Link");
+        doc.push(super::elements::FromFn::new(move |context, area, style| {
+            area.add_link(Position::default(), Size::new(10, 5), "https://example.org");
+            paragraph.render(context, area, style)
+        }));
+        let doc = render_document(doc).unwrap();
+        assert_eq!(3, doc.get_pages().len());
+
+        // The link is only added once although the element is rendered twice.
+        let page_id = doc.page_iter().nth(2).unwrap();
+        let annotations = doc
+            .get_dictionary(page_id)
+            .unwrap()
+            .get(b"Annots")
+            .and_then(lopdf::Object::as_array)
+            .unwrap();
+        assert_eq!(1, annotations.len());
+    }
+
+    #[test]
+    fn test_line_overflow_shrink_min_font_size() {
+        let mut doc = oversized_document(super::LineOverflow::Shrink);
+        doc.set_min_font_size(40);
+        let doc = render_document(doc).unwrap();
+        assert_eq!(2, doc.get_pages().len());
+        for page in 0..2 {
+            let fonts = find_operations(&doc, page, "Tf");
+            assert_eq!(1, fonts.len());
+            assert!(approx_eq!(f32, 40.0, as_f32(&fonts[0][1])));
+            assert_eq!(1, find_operations(&doc, page, "W").len());
+            assert!(!find_operations(&doc, page, "TJ").is_empty());
+        }
+    }
+
+    #[test]
+    fn test_line_overflow_clip() {
+        let doc = render_document(oversized_document(super::LineOverflow::Clip)).unwrap();
+        assert_eq!(2, doc.get_pages().len());
+        for page in 0..2 {
+            let fonts = find_operations(&doc, page, "Tf");
+            assert_eq!(1, fonts.len());
+            assert!(approx_eq!(f32, 60.0, as_f32(&fonts[0][1])));
+            assert_eq!(1, find_operations(&doc, page, "W").len());
+            assert!(!find_operations(&doc, page, "TJ").is_empty());
+        }
+    }
+
+    #[test]
+    fn test_baseline_grid() {
+        let mut doc = document();
+        doc.set_paper_size(super::Size::new(50, 100));
+        assert!(doc.set_baseline_grid(0).is_err());
+        assert!(doc.set_baseline_grid(f32::NAN).is_err());
+        doc.set_baseline_grid(7).unwrap();
+        let mut decorator = super::SimplePageDecorator::new();
+        decorator.set_margins(3);
+        doc.set_page_decorator(decorator);
+        doc.push(super::elements::Text::new("Single line"));
+        doc.push(super::elements::Paragraph::new(
+            "A paragraph with enough text to be wrapped into multiple lines",
+        ));
+        let doc = render_document(doc).unwrap();
+
+        let cursors = find_operations(&doc, 0, "Td");
+        assert!(cursors.len() > 3);
+        let mut last_baseline = 0.0;
+        for operands in cursors {
+            let y: super::Mm = printpdf::Pt(as_f32(&operands[1])).into();
+            let baseline = 100.0 - y.0;
+            let lines = baseline / 7.0;
+            assert!(approx_eq!(f32, lines, lines.round(), epsilon = 0.001));
+            assert!(baseline > last_baseline);
+            last_baseline = baseline;
+        }
+    }
+
+    #[test]
+    fn test_extra_leading() {
+        fn baselines(extra_leading: Option<Mm>) -> Vec<f32> {
+            let mut doc = document();
+            if let Some(extra_leading) = extra_leading {
+                doc.set_extra_leading(extra_leading);
+            }
+            for _ in 0..3 {
+                doc.push(super::elements::Text::new("Line"));
+            }
+            let doc = render_document(doc).unwrap();
+            find_operations(&doc, 0, "Td")
+                .iter()
+                .map(|operands| Mm::from(printpdf::Pt(as_f32(&operands[1]))).0)
+                .collect()
+        }
+
+        let default = baselines(None);
+        let leading = baselines(Some(Mm(2.5)));
+        assert_eq!(3, default.len());
+        assert_eq!(3, leading.len());
+        for i in 1..3 {
+            let default_advance = default[i - 1] - default[i];
+            let leading_advance = leading[i - 1] - leading[i];
+            assert!(approx_eq!(
+                f32,
+                leading_advance - default_advance,
+                2.5,
+                epsilon = 0.001
+            ));
+        }
+    }
+
+    #[test]
+    fn test_tagging() {
+        use super::{elements, render::Tag};
+
+        let mut doc = document();
+        doc.enable_tagging();
+        doc.push(elements::Paragraph::new("Heading").with_tag(Tag::Heading(1)));
+        doc.push(elements::Paragraph::new("Text"));
+        let mut table = elements::TableLayout::new(vec![1]);
+        table
+            .row()
+            .element(elements::Text::new("Cell"))
+            .push()
+            .unwrap();
+        doc.push(table);
+        let doc = render_document(doc).unwrap();
+
+        let get_dict = |object: &lopdf::Object| -> lopdf::Dictionary {
+            match object {
+                lopdf::Object::Reference(id) => doc.get_dictionary(*id).unwrap().clone(),
+                lopdf::Object::Dictionary(dict) => dict.clone(),
+                _ => panic!("Expected a dictionary, got {:?}", object),
+            }
+        };
+        let structure_type =
+            |dict: &lopdf::Dictionary| dict.get(b"S").unwrap().as_name_str().unwrap().to_owned();
+        let kids = |dict: &lopdf::Dictionary| -> Vec<lopdf::Dictionary> {
+            dict.get(b"K")
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(get_dict)
+                .collect()
+        };
+
+        let catalog = doc.catalog().unwrap();
+        assert!(catalog.has(b"MarkInfo"));
+        let root = get_dict(catalog.get(b"StructTreeRoot").unwrap());
+        let elements = kids(&root);
+        let types: Vec<_> = elements.iter().map(structure_type).collect();
+        assert_eq!(vec!["H1", "P", "Table"], types);
+
+        // The paragraph references its marked content sequence on the first page.
+        let content = kids(&elements[1]);
+        assert_eq!(1, content.len());
+        assert_eq!(1, content[0].get(b"MCID").unwrap().as_i64().unwrap());
+        let marked_content = find_operations(&doc, 0, "BDC");
+        assert!(marked_content
+            .iter()
+            .any(|operands| operands[0].as_name_str().ok() == Some("P")));
+
+        let rows = kids(&elements[2]);
+        assert_eq!("TR", structure_type(&rows[0]));
+        let cells = kids(&rows[0]);
+        assert_eq!("TD", structure_type(&cells[0]));
+        assert_eq!("P", structure_type(&kids(&cells[0])[0]));
+    }
+
+    #[test]
+    fn test_push_with_handle() {
+        use super::elements;
+
+        let mut doc = document();
+        doc.push(elements::Paragraph::new("a"));
+        let first = doc.push_with_handle(elements::Paragraph::new("b"));
+        // The heading does not fit on the first page, so it is moved to the second page.
+        doc.push(FillPage(Mm(1.0)));
+        let heading = doc.push_with_handle(elements::Paragraph::new("Heading"));
+        assert_eq!(None, heading.destination());
+        let doc = render_document(doc).unwrap();
+        assert_eq!(2, doc.get_pages().len());
+
+        let first = first.destination().unwrap();
+        assert_eq!(1, first.page);
+        assert_eq!(Mm(0.0), first.position.x);
+        assert!(first.position.y > Mm(0.0));
+        assert_eq!(
+            Some(super::Destination::new(2, Position::default())),
+            heading.destination()
+        );
+    }
+
+    #[test]
+    fn test_size_fit_within_and_cover() {
+        let landscape = Size::new(40, 20);
+        let portrait = Size::new(20, 40);
+        let wide = Size::new(100, 30);
+        let tall = Size::new(30, 100);
+
+        assert_eq!(Size::new(60, 30), landscape.fit_within(wide));
+        assert_eq!(Size::new(30, 15), landscape.fit_within(tall));
+        assert_eq!(Size::new(15, 30), portrait.fit_within(wide));
+        assert_eq!(Size::new(30, 60), portrait.fit_within(tall));
+
+        assert_eq!(Size::new(100, 50), landscape.cover(wide));
+        assert_eq!(Size::new(200, 100), landscape.cover(tall));
+        assert_eq!(Size::new(100, 200), portrait.cover(wide));
+        assert_eq!(Size::new(50, 100), portrait.cover(tall));
+
+        assert_eq!(Size::new(0, 20), Size::new(0, 20).fit_within(wide));
+    }
+
+    #[test]
+    fn test_check_xml() {
+        assert!(super::check_xml("<a/>").is_ok());
+        assert!(super::check_xml(
+            "<?xml version=\"1.0\"?>\n<!-- comment --><a x='>'><b>text</b><![CDATA[<c>]]></a>\n"
+        )
+        .is_ok());
+        assert!(super::check_xml("").is_err());
+        assert!(super::check_xml("text").is_err());
+        assert!(super::check_xml("<a>").is_err());
+        assert!(super::check_xml("<a></b>").is_err());
+        assert!(super::check_xml("<a><b></a></b>").is_err());
+        assert!(super::check_xml("<a/><b/>").is_err());
+        assert!(super::check_xml("<a x=\"1></a>").is_err());
+    }
+
+    #[test]
+    fn test_producer_and_creator() {
+        let mut doc = document();
+        doc.set_producer("Invoice Generator 2.0");
+        doc.set_creator("Accounting Suite");
+        let doc = render_document(doc).unwrap();
+
+        let info = doc.trailer.get(b"Info").unwrap().as_reference().unwrap();
+        let info = doc.get_dictionary(info).unwrap();
+        assert_eq!(
+            b"Invoice Generator 2.0",
+            info.get(b"Producer").unwrap().as_str().unwrap()
+        );
+        assert_eq!(
+            b"Accounting Suite",
+            info.get(b"Creator").unwrap().as_str().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_xmp_metadata() {
+        let xmp = concat!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>",
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">",
+            "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">",
+            "<rdf:Description rdf:about=\"\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">",
+            "<dc:source>asset-1234</dc:source>",
+            "</rdf:Description>",
+            "</rdf:RDF>",
+            "</x:xmpmeta>",
+            "<?xpacket end=\"w\"?>",
+        );
+
+        let mut doc = document();
+        let err = doc.set_xmp_metadata("<x:xmpmeta>").unwrap_err();
+        assert!(matches!(err.kind(), error::ErrorKind::InvalidData));
+        doc.set_xmp_metadata(xmp).unwrap();
+        let doc = render_document(doc).unwrap();
+
+        let metadata_id = doc
+            .catalog()
+            .and_then(|catalog| catalog.get(b"Metadata"))
+            .and_then(lopdf::Object::as_reference)
+            .unwrap();
+        let stream = doc.get_object(metadata_id).unwrap().as_stream().unwrap();
+        assert_eq!(
+            b"Metadata".as_ref(),
+            stream.dict.get(b"Type").unwrap().as_name().unwrap()
+        );
+        assert_eq!(xmp.as_bytes(), stream.content.as_slice());
+    }
+
+    #[test]
+    fn test_output_intent() {
+        let mut doc = document();
+        let err = doc.set_output_intent(vec![0; 128], "Test").unwrap_err();
+        assert!(matches!(err.kind(), error::ErrorKind::InvalidData));
+        let mut icc_profile = vec![0; 128];
+        icc_profile[16..20].copy_from_slice(b"RGB ");
+        icc_profile[36..40].copy_from_slice(b"acsp");
+        doc.set_output_intent(icc_profile, "Test RGB").unwrap();
+        let doc = render_document(doc).unwrap();
+
+        let output_intents = doc
+            .catalog()
+            .unwrap()
+            .get(b"OutputIntents")
+            .and_then(lopdf::Object::as_array)
+            .unwrap();
+        assert_eq!(1, output_intents.len());
+        let profile_id = output_intents[0]
+            .as_dict()
+            .unwrap()
+            .get(b"DestinationOutputProfile")
+            .and_then(lopdf::Object::as_reference)
+            .unwrap();
+        let stream = doc.get_object(profile_id).unwrap().as_stream().unwrap();
+        assert_eq!(3, stream.dict.get(b"N").unwrap().as_i64().unwrap());
+    }
+
+    #[test]
+    fn test_open_destination() {
+        let document = |name: &str| {
+            let mut doc = document();
+            doc.push(super::elements::Paragraph::new("first"));
+            doc.push(super::elements::PageBreak::new());
+            doc.push(super::elements::NamedDestination::new("chapter-2"));
+            doc.push(super::elements::Paragraph::new("second"));
+            doc.set_open_destination(name);
+            doc
+        };
+
+        let err = render_document(document("chapter-3")).unwrap_err();
+        assert!(matches!(err.kind(), error::ErrorKind::InvalidData));
+
+        let doc = render_document(document("chapter-2")).unwrap();
+        let second_page = doc.page_iter().nth(1).unwrap();
+        let catalog = doc.catalog().unwrap();
+        let open_action = catalog
+            .get(b"OpenAction")
+            .and_then(lopdf::Object::as_array)
+            .unwrap();
+        assert_eq!(second_page, open_action[0].as_reference().unwrap());
+        assert_eq!(b"XYZ".as_ref(), open_action[1].as_name().unwrap());
+
+        let dests_id = catalog
+            .get(b"Names")
+            .and_then(lopdf::Object::as_dict)
+            .and_then(|names| names.get(b"Dests"))
+            .and_then(lopdf::Object::as_reference)
+            .unwrap();
+        let names = doc
+            .get_dictionary(dests_id)
+            .unwrap()
+            .get(b"Names")
+            .and_then(lopdf::Object::as_array)
+            .unwrap();
+        assert_eq!(2, names.len());
+        assert_eq!(b"chapter-2".as_ref(), names[0].as_str().unwrap());
+        let target = names[1].as_array().unwrap();
+        assert_eq!(second_page, target[0].as_reference().unwrap());
+    }
+
+    #[test]
+    fn test_render_page_range() {
+        // The n-th page contains n paragraphs.
+        let document = || {
+            let mut doc = document();
+            for page in 1..=5 {
+                if page > 1 {
+                    doc.push(super::elements::PageBreak::new());
+                }
+                for _ in 0..page {
+                    doc.push(super::elements::Paragraph::new("Text"));
+                }
+            }
+            doc
+        };
+
+        let mut data = Vec::new();
+        document().render_page_range(2..=3, &mut data).unwrap();
+        let doc = lopdf::Document::load_mem(&data).unwrap();
+        assert_eq!(2, doc.get_pages().len());
+        assert_eq!(2, find_operations(&doc, 0, "Td").len());
+        assert_eq!(3, find_operations(&doc, 1, "Td").len());
+
+        let err = document()
+            .render_page_range(6.., &mut Vec::new())
+            .unwrap_err();
+        assert!(matches!(err.kind(), error::ErrorKind::InvalidData));
+    }
+
+    #[test]
+    fn test_append() {
+        let mut doc = document();
+        doc.push(super::elements::Paragraph::new("First"));
+        doc.push(super::elements::PageBreak::new());
+        doc.push(super::elements::Paragraph::new("Second"));
+        let mut other = document();
+        other.push(super::elements::Paragraph::new("Third"));
+        other.push(super::elements::Paragraph::new("Fourth"));
+        doc.append(other);
+        let doc = render_document(doc).unwrap();
+
+        assert_eq!(3, doc.get_pages().len());
+        let pages = doc
+            .get_dictionary(
+                doc.catalog()
+                    .unwrap()
+                    .get(b"Pages")
+                    .unwrap()
+                    .as_reference()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(3, pages.get(b"Count").unwrap().as_i64().unwrap());
+        assert_eq!(2, find_operations(&doc, 2, "Td").len());
+
+        // The font files of both documents are identical, so they are only embedded once.
+        let font_files = doc
+            .objects
+            .values()
+            .filter_map(|object| object.as_dict().ok())
+            .filter_map(|dict| dict.get(b"FontFile2").ok())
+            .filter_map(|object| object.as_reference().ok())
+            .collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(4, font_files.len());
+    }
+
+    #[cfg(feature = "raster")]
+    #[test]
+    #[ignore = "requires pdftoppm"]
+    fn test_render_to_images() {
+        use printpdf::image_crate::GenericImageView as _;
+
+        let mut doc = document();
+        // 10 x 5 inches
+        doc.set_paper_size(Size::new(254, 127));
+        doc.push(super::elements::Paragraph::new("First"));
+        doc.push(super::elements::PageBreak::new());
+        doc.push(super::elements::Paragraph::new("Second"));
+        let images = doc.render_to_images(50).expect("Failed to render images");
+        assert_eq!(2, images.len());
+        for image in images {
+            let (width, height) = image.dimensions();
+            assert!((500..=501).contains(&width));
+            assert!((250..=251).contains(&height));
+        }
+    }
+
+    #[test]
+    fn test_debug_grid() {
+        let count_lines = |doc: &lopdf::Document| {
+            let operations = page_operations(doc, 0);
+            let lines = operations.iter().filter(|op| op.operator == "m").count();
+            let segments = operations.iter().filter(|op| op.operator == "l").count();
+            (lines, segments)
+        };
+
+        let mut doc = document();
+        doc.set_paper_size(Size::new(100, 200));
+        doc.set_debug_grid(10, style::Color::Rgb(200, 200, 255));
+        let doc = render_document(doc).unwrap();
+        // 9 vertical and 19 horizontal lines, no margin guides
+        assert_eq!((28, 28), count_lines(&doc));
+
+        let mut doc = document();
+        doc.set_paper_size(Size::new(100, 200));
+        doc.set_debug_grid(10, style::Color::Rgb(200, 200, 255));
+        let mut decorator = super::SimplePageDecorator::new();
+        decorator.set_margins(15);
+        doc.set_page_decorator(decorator);
+        let doc = render_document(doc).unwrap();
+        // the grid and the four edges of the content area
+        assert_eq!((29, 32), count_lines(&doc));
+    }
+
+    #[test]
+    fn test_debug_boxes() {
+        let mut doc = document();
+        doc.set_paper_size(Size::new(100, 200));
+        doc.set_debug_boxes(true);
+        let style = style::Style::new();
+        let width = style.str_width(doc.font_cache(), "Hello");
+        let height = style.line_height(doc.font_cache());
+        doc.push(super::elements::Text::new("Hello"));
+        let doc = render_document(doc).unwrap();
+
+        // Collect the outlines as (color, points) with the points in millimeters, measured from
+        // the upper left corner of the page.
+        let mut outlines = Vec::new();
+        let mut color = Vec::new();
+        for op in page_operations(&doc, 0) {
+            let point = || {
+                let x = Mm::from(printpdf::Pt(as_f32(&op.operands[0])));
+                let y = Mm::from(200) - Mm::from(printpdf::Pt(as_f32(&op.operands[1])));
+                (x, y)
+            };
+            match op.operator.as_str() {
+                "RG" => color = op.operands.iter().map(as_f32).collect(),
+                "m" => outlines.push((color.clone(), vec![point()])),
+                "l" => outlines.last_mut().unwrap().1.push(point()),
+                _ => {}
+            }
+        }
+
+        // The text is outlined first and then the root layout
+        assert_eq!(2, outlines.len());
+        let (text_color, points) = &outlines[0];
+        assert_eq!(&vec![0.0, 160.0 / 255.0, 0.0], text_color);
+        assert_eq!(5, points.len());
+        let (x, y) = points[2];
+        assert!(approx_eq!(f32, width.0, x.0, epsilon = 0.01));
+        assert!(approx_eq!(f32, height.0, y.0, epsilon = 0.01));
+        let (layout_color, _) = &outlines[1];
+        assert_eq!(&vec![1.0, 0.0, 0.0], layout_color);
+    }
+
+    #[test]
+    fn test_on_missing_glyph() {
+        let missing = rc::Rc::new(cell::RefCell::new(Vec::new()));
+        let mut doc = document();
+        let font = doc.font_cache().default_font_family().regular;
+        {
+            let missing = missing.clone();
+            doc.on_missing_glyph(move |c, font| missing.borrow_mut().push((c, font)));
+        }
+        doc.push(super::elements::Paragraph::new("A 漢字 text"));
+        render_document(doc).unwrap();
+        assert_eq!(vec![('漢', font), ('字', font)], *missing.borrow());
+    }
+
+    #[test]
+    fn test_measure() {
+        let element = || {
+            let mut layout = super::elements::LinearLayout::vertical();
+            layout.push(super::elements::Paragraph::new(
+                "A paragraph with enough text to be wrapped into multiple lines",
+            ));
+            layout.push(
+                super::elements::Text::new("Framed text").framed(style::Color::Rgb(255, 0, 0)),
+            );
+            // The background is drawn on a separate layer.
+            layout.push(
+                super::elements::Text::new("Highlighted text")
+                    .decorated()
+                    .with_background(style::Color::Rgb(255, 255, 0)),
+            );
+            layout
+        };
+        let render = |dry_run: bool| {
+            let renderer = render::Renderer::new(Size::new(50, 100), "test").unwrap();
+            let context = Context::new(font_cache(&renderer));
+            let area = renderer.first_page().first_layer().area();
+            let result = if dry_run {
+                let result = context.measure(&mut element(), area, style::Style::new());
+                assert!(!context.is_dry_run());
+                result
+            } else {
+                element().render(&context, area, style::Style::new())
+            };
+            let layer_count = renderer.first_page().layer_count();
+            (result.unwrap(), layer_count, write_renderer(renderer))
+        };
+
+        let (measured, measured_layers, doc) = render(true);
+        let (rendered, rendered_layers, _) = render(false);
+        assert_eq!(1, measured_layers);
+        assert_eq!(2, rendered_layers);
+        assert_eq!(rendered.size, measured.size);
+        assert_eq!(rendered.has_more, measured.has_more);
+        assert!(measured.size.height > Mm(0.0));
+
+        let empty = write_renderer(render::Renderer::new(Size::new(50, 100), "test").unwrap());
+        let operators = |doc: &lopdf::Document| -> Vec<_> {
+            page_operations(doc, 0)
+                .into_iter()
+                .map(|op| op.operator)
+                .collect()
+        };
+        assert_eq!(operators(&empty), operators(&doc));
+    }
+
+    #[test]
+    fn test_content_rect() {
+        let mut doc = document();
+        doc.set_paper_size(Size::new(100, 200));
+        let mut decorator = super::SimplePageDecorator::new();
+        decorator.set_margins(10);
+        doc.set_page_decorator(decorator);
+        doc.set_content_rect(Position::new(40, 5), Size::new(50, 60));
+        for _ in 0..20 {
+            doc.push(super::elements::Paragraph::new(
+                "word ".repeat(20).trim_end(),
+            ));
+        }
+        let doc = render_document(doc).unwrap();
+        assert!(doc.get_pages().len() > 1);
+
+        // The text cursors in millimeters, measured from the upper left corner of the page
+        let cursors: Vec<_> = find_operations(&doc, 0, "Td")
+            .iter()
+            .map(|operands| {
+                let x = Mm::from(printpdf::Pt(as_f32(&operands[0])));
+                let y = Mm(200.0) - Mm::from(printpdf::Pt(as_f32(&operands[1])));
+                (x, y)
+            })
+            .collect();
+        assert!(cursors.len() > 1);
+        // The content starts at the left edge of the rectangle and the top margin of the page.
+        assert!(approx_eq!(f32, 40.0, cursors[0].0 .0, epsilon = 0.01));
+        for (x, y) in cursors {
+            assert!(x >= Mm(40.0) && x < Mm(90.0));
+            assert!(y > Mm(10.0) && y <= Mm(65.0));
+        }
+    }
+
+    #[test]
+    fn test_paper_size() {
+        let mut doc = document();
+        assert_eq!(Size::from(super::PaperSize::A4), doc.paper_size());
+        doc.push(super::elements::Paragraph::new("first"));
+        doc.push(super::elements::PageBreak::with_paper_size(Size::new(
+            100, 150,
+        )));
+        doc.push(super::elements::Paragraph::new("second"));
+        doc.push(super::elements::PageBreak::new());
+        doc.push(super::elements::Paragraph::new("third"));
+        let doc = render_document(doc).unwrap();
+
+        let media_boxes: Vec<Vec<f32>> = doc
+            .page_iter()
+            .map(|page_id| {
+                let page = doc.get_dictionary(page_id).unwrap();
+                page.get(b"MediaBox")
+                    .and_then(lopdf::Object::as_array)
+                    .unwrap()
+                    .iter()
+                    .map(|value| Mm::from(printpdf::Pt(as_f32(value))).0.round())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                vec![0.0, 0.0, 210.0, 297.0],
+                vec![0.0, 0.0, 100.0, 150.0],
+                vec![0.0, 0.0, 100.0, 150.0],
+            ],
+            media_boxes
+        );
+    }
+
+    #[test]
+    fn test_crop_marks_debug_grid() {
+        let mut doc = document();
+        doc.set_paper_size(Size::new(100, 200));
+        doc.set_debug_grid(10, style::Color::Rgb(200, 200, 255));
+        doc.add_crop_marks(3, 5);
+        let doc = render_document(doc).unwrap();
+
+        // Count the lines in the content of each layer.  The eight crop mark lines are drawn on
+        // the first layer, the 9 vertical and 19 horizontal lines of the grid on the debug layer.
+        let mut lines = Vec::new();
+        for op in page_operations(&doc, 0) {
+            match op.operator.as_str() {
+                "BDC" => lines.push(0),
+                "m" => *lines.last_mut().unwrap() += 1,
+                _ => {}
+            }
+        }
+        assert_eq!(vec![8, 28], lines);
+    }
+
+    #[test]
+    fn test_multiple_font_families() {
+        let mut doc = document();
+        let family = doc.add_font_family(font_family());
+        let mut p = super::elements::Paragraph::default();
+        p.push("Default ");
+        p.push_styled("other", style::Style::new().with_font_family(family));
+        doc.push(p);
+        let doc = render_document(doc).unwrap();
+
+        // Both font families are embedded.
+        let embedded_fonts = doc
+            .objects
+            .values()
+            .filter_map(|object| object.as_dict().ok())
+            .filter(|dict| {
+                dict.get(b"Subtype").and_then(lopdf::Object::as_name).ok() == Some(b"Type0")
+            })
+            .count();
+        assert_eq!(8, embedded_fonts);
+
+        // Both runs reference a font from their font family.
+        let names: Vec<_> = find_operations(&doc, 0, "Tf")
+            .iter()
+            .map(|operands| operands[0].as_name().unwrap().to_vec())
+            .collect();
+        assert_eq!(2, names.len());
+        assert_ne!(names[0], names[1]);
+        let page = doc.get_dictionary(doc.page_iter().next().unwrap()).unwrap();
+        let resources = doc.dereference(page.get(b"Resources").unwrap()).unwrap().1;
+        let font_resources = resources.as_dict().unwrap().get(b"Font").unwrap();
+        let font_resources = doc.dereference(font_resources).unwrap().1;
+        for name in names {
+            assert!(font_resources.as_dict().unwrap().has(&name));
+        }
+    }
+
+    #[test]
+    fn test_crop_marks() {
+        let mut doc = document();
+        doc.set_paper_size(Size::new(100, 200));
+        doc.add_crop_marks(3, 5);
+        let doc = render_document(doc).unwrap();
+
+        // Collect the lines in millimeters, rounded to 1/100 mm and measured from the upper left
+        // corner of the page.
+        let mut lines = Vec::new();
+        let mut start = None;
+        for op in page_operations(&doc, 0) {
+            let point = || {
+                let x = Mm::from(printpdf::Pt(as_f32(&op.operands[0]))).0;
+                let y = 200.0 - Mm::from(printpdf::Pt(as_f32(&op.operands[1]))).0;
+                ((x * 100.0).round() as i64, (y * 100.0).round() as i64)
+            };
+            match op.operator.as_str() {
+                "m" => start = Some(point()),
+                "l" => lines.push((start.unwrap(), point())),
+                _ => {}
+            }
+        }
+        lines.sort();
+
+        let mut expected = vec![
+            // upper left corner
+            ((500, 800), (0, 800)),
+            ((800, 500), (800, 0)),
+            // lower left corner
+            ((500, 19200), (0, 19200)),
+            ((800, 19500), (800, 20000)),
+            // upper right corner
+            ((9500, 800), (10000, 800)),
+            ((9200, 500), (9200, 0)),
+            // lower right corner
+            ((9500, 19200), (10000, 19200)),
+            ((9200, 19500), (9200, 20000)),
+        ];
+        expected.sort();
+        assert_eq!(expected, lines);
+
+        let page = doc.get_dictionary(doc.page_iter().next().unwrap()).unwrap();
+        let trim_box: Vec<_> = page
+            .get(b"TrimBox")
+            .and_then(lopdf::Object::as_array)
+            .unwrap()
+            .iter()
+            .map(|value| Mm::from(printpdf::Pt(as_f32(value))).0.round())
+            .collect();
+        assert_eq!(vec![8.0, 8.0, 92.0, 192.0], trim_box);
+    }
+
+    #[test]
+    fn test_for_each_page() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        use super::elements;
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let mut doc = document();
+        doc.push(elements::Paragraph::new("a"));
+        doc.push(elements::PageBreak::new());
+        doc.push(elements::Paragraph::new("b"));
+        let callback_calls = calls.clone();
+        doc.for_each_page(move |context, area, page, total| {
+            let text = format!("{}/{}", page, total);
+            let font = context.font_cache.default_font_family().regular;
+            let glyph_ids = font.glyph_ids(&context.font_cache, text.chars());
+            callback_calls.borrow_mut().push((page, total, glyph_ids));
+            let style = style::Style::new();
+            assert!(area.print_str(&context.font_cache, Position::new(10, 10), style, text)?);
+            Ok(())
+        });
+        let doc = render_document(doc).unwrap();
+
+        let calls = calls.borrow();
+        assert_eq!(2, calls.len());
+        for (page, (number, total, glyph_ids)) in calls.iter().enumerate() {
+            assert_eq!((page + 1, 2), (*number, *total));
+            // The overlay is drawn after the content of the page.
+            let texts = find_operations(&doc, page, "TJ");
+            assert_eq!(2, texts.len());
+            let printed: Vec<_> = texts[1][0]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter_map(|object| object.as_str().ok())
+                .flat_map(|bytes| bytes.chunks(2).map(|b| u16::from_be_bytes([b[0], b[1]])))
+                .collect();
+            assert_eq!(glyph_ids, &printed);
+        }
+    }
+
+    #[test]
+    fn test_monospace_font_family() {
+        use super::elements;
+
+        let data = fonts::FontData::load("./tests/files/dejavu/DejaVuSansMono.ttf", None).unwrap();
+        let font_family = fonts::FontFamily {
+            regular: data.clone(),
+            bold: data.clone(),
+            italic: data.clone(),
+            bold_italic: data,
+        };
+
+        let mut doc = document();
+        assert!(doc
+            .add_monospace_font_family(super::tests::font_family())
+            .is_err());
+        let style = doc.add_monospace_font_family(font_family).unwrap();
+        let width = style.char_width(doc.font_cache(), 'm');
+        for c in "il.MW 0_".chars() {
+            assert_eq!(width, style.char_width(doc.font_cache(), c));
+        }
+
+        doc.push(elements::Text::new("a\tb").styled(style));
+        doc.push(
+            elements::Text::new("abcdefghi\tb")
+                .with_tab_width(4)
+                .styled(style),
+        );
+        let font = style.font(doc.font_cache());
+        let expected: Vec<_> = ["a       b", "abcdefghi   b"]
+            .iter()
+            .map(|s| font.glyph_ids(doc.font_cache(), s.chars()))
+            .collect();
+        let doc = render_document(doc).unwrap();
+        // The embedded font uses the glyph IDs as two-byte character codes.
+        let texts: Vec<Vec<u16>> = find_operations(&doc, 0, "TJ")
+            .into_iter()
+            .map(|operands| {
+                operands[0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .filter_map(|object| object.as_str().ok())
+                    .flat_map(|s| s.chunks(2).map(|b| u16::from_be_bytes([b[0], b[1]])))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(expected, texts);
+    }
 }
@@ -16,17 +16,20 @@
 //! [`TextSection`]: struct.TextSection.html
 
 use std::cell;
+use std::collections;
+use std::hash::{Hash as _, Hasher as _};
 use std::io;
+use std::iter;
 use std::ops;
 use std::rc;
 
 use crate::error::{Context as _, Error, ErrorKind};
 use crate::fonts;
-use crate::style::{Color, LineStyle, Style};
-use crate::{Margins, Mm, Position, Size};
+use crate::style::{Color, LineCap, LineJoin, LineStyle, Style};
+use crate::{Margins, Mm, Position, Rotation, Size};
 
 #[cfg(feature = "images")]
-use crate::{Rotation, Scale};
+use crate::Scale;
 
 /// A position relative to the top left corner of a layer.
 struct LayerPosition(Position);
@@ -72,6 +75,15 @@ pub struct Renderer {
     doc: printpdf::PdfDocumentReference,
     // invariant: pages.len() >= 1
     pages: Vec<Page>,
+    has_cff_fonts: cell::Cell<bool>,
+    baseline_grid: Option<Mm>,
+    structure: Option<rc::Rc<cell::RefCell<StructureTree>>>,
+    xmp_metadata: Option<String>,
+    // the ICC profile and the output condition identifier of the output intent
+    output_intent: Option<(Vec<u8>, String)>,
+    open_destination: Option<String>,
+    appended_documents: Vec<lopdf::Document>,
+    layer_name: String,
 }
 
 impl Renderer {
@@ -91,6 +103,14 @@ impl Renderer {
         Ok(Renderer {
             doc,
             pages: vec![page],
+            has_cff_fonts: Default::default(),
+            baseline_grid: None,
+            structure: None,
+            xmp_metadata: None,
+            output_intent: None,
+            open_destination: None,
+            appended_documents: Vec::new(),
+            layer_name: "Layer 1".to_owned(),
         })
     }
 
@@ -112,15 +132,146 @@ impl Renderer {
         self
     }
 
+    /// Sets the producer of the generated PDF document that is written to the document information
+    /// dictionary.
+    pub fn with_producer(mut self, producer: impl Into<String>) -> Self {
+        self.doc = self.doc.with_producer(producer);
+        self
+    }
+
+    /// Sets the creator of the generated PDF document, i. e. the application that created the
+    /// content, that is written to the document information dictionary.
+    pub fn with_creator(mut self, creator: impl Into<String>) -> Self {
+        self.doc = self.doc.with_creator(creator);
+        self
+    }
+
+    /// Sets the spacing of the baseline grid for all pages of the generated PDF document.
+    ///
+    /// If a baseline grid is set, the baselines of all text sections are moved down to the next
+    /// line of the grid, and the line height of text sections is rounded up to the next multiple
+    /// of the grid spacing.  The grid starts at the top edge of the
+    /// page.  If the spacing is not positive, an error with the [`InvalidData`][] kind is
+    /// returned.
+    ///
+    /// [`InvalidData`]: ../error/enum.ErrorKind.html#variant.InvalidData
+    pub fn with_baseline_grid(mut self, spacing: Mm) -> Result<Self, Error> {
+        check_grid_spacing(spacing)?;
+        self.baseline_grid = Some(spacing);
+        for page in &mut self.pages {
+            page.baseline_grid = Some(spacing);
+        }
+        Ok(self)
+    }
+
+    /// Enables the generation of a tagged PDF document.
+    ///
+    /// If tagging is enabled, the structure elements that are added with
+    /// [`Area::add_structure_element`][] are written to the structure tree of the generated
+    /// document, and the document is marked as a tagged PDF document.
+    ///
+    /// [`Area::add_structure_element`]: struct.Area.html#method.add_structure_element
+    pub fn with_tagging(mut self) -> Self {
+        let structure = rc::Rc::new(cell::RefCell::new(StructureTree::default()));
+        for page in &mut self.pages {
+            page.structure = Some(structure.clone());
+        }
+        self.structure = Some(structure);
+        self
+    }
+
+    /// Sets the XMP metadata packet for the generated PDF document.
+    ///
+    /// The given packet replaces the XMP metadata generated by `printpdf` and is written to the
+    /// `Metadata` stream of the document catalog without modifications.  It is not validated.
+    pub fn with_xmp_metadata(mut self, xmp: impl Into<String>) -> Self {
+        self.xmp_metadata = Some(xmp.into());
+        self
+    }
+
+    /// Sets the output intent for the generated PDF document, using the given ICC profile and the
+    /// given output condition identifier, for example `"FOGRA39"`.
+    ///
+    /// The profile is embedded as an ICC-based stream and referenced by the `OutputIntents` entry
+    /// of the document catalog, as required for PDF/X documents.  It replaces the output intent
+    /// generated by `printpdf`.  The number of color components is read from the header of the
+    /// profile.  If the header is invalid or does not declare a gray, RGB, Lab or CMYK color
+    /// space, an error with the [`InvalidData`][] kind is returned.
+    ///
+    /// [`InvalidData`]: ../error/enum.ErrorKind.html#variant.InvalidData
+    pub fn with_output_intent(
+        mut self,
+        icc_profile: impl Into<Vec<u8>>,
+        name: impl Into<String>,
+    ) -> Result<Self, Error> {
+        let icc_profile = icc_profile.into();
+        icc_components(&icc_profile)?;
+        self.output_intent = Some((icc_profile, name.into()));
+        Ok(self)
+    }
+
+    /// Sets the named destination that PDF viewers display when the generated PDF document is
+    /// opened.
+    ///
+    /// The destination must be added with [`Area::add_named_destination`][] before the document
+    /// is written.  Otherwise, writing the document fails with an error with the
+    /// [`InvalidData`][] kind.
+    ///
+    /// [`Area::add_named_destination`]: struct.Area.html#method.add_named_destination
+    /// [`InvalidData`]: ../error/enum.ErrorKind.html#variant.InvalidData
+    pub fn with_open_destination(mut self, name: impl Into<String>) -> Self {
+        self.open_destination = Some(name.into());
+        self
+    }
+
+    /// Sets the name of the first layer of every page of the generated PDF document.
+    ///
+    /// The layers of a page are written as optional content groups, so PDF viewers show their
+    /// names in the layers panel.  If this method is not called, the first layer is named
+    /// “Layer 1”.  Additional layers can be named with [`Page::add_layer`][].
+    ///
+    /// [`Page::add_layer`]: struct.Page.html#method.add_layer
+    pub fn with_layer_name(mut self, name: impl Into<String>) -> Self {
+        self.layer_name = name.into();
+        for page in &mut self.pages {
+            page.layers_options[0].name = Some(self.layer_name.clone());
+        }
+        self
+    }
+
+    /// Appends the pages of the given PDF document to the generated document.
+    ///
+    /// The pages are added after the pages of this renderer when the document is written.  Streams
+    /// that are contained in both documents, for example embedded fonts, are only written once.
+    /// Only the pages and the resources they use are copied, the other parts of the appended
+    /// document like its metadata, outline and structure tree are discarded.  If the given data
+    /// cannot be parsed, an error with the [`InvalidData`][] kind is returned.
+    ///
+    /// [`InvalidData`]: ../error/enum.ErrorKind.html#variant.InvalidData
+    pub fn append_pdf(&mut self, data: &[u8]) -> Result<(), Error> {
+        let doc = lopdf::Document::load_mem(data).map_err(|err| {
+            Error::new(
+                format!("Failed to parse the appended document: {}", err),
+                ErrorKind::InvalidData,
+            )
+        })?;
+        self.appended_documents.push(doc);
+        Ok(())
+    }
+
     /// Adds a new page with the given size to the document.
     pub fn add_page(&mut self, size: impl Into<Size>) {
         let size = size.into();
         let (page_idx, layer_idx) =
             self.doc
-                .add_page(size.width.into(), size.height.into(), "Layer 1");
+                .add_page(size.width.into(), size.height.into(), &self.layer_name);
         let page_ref = self.doc.get_page(page_idx);
         let layer_ref = page_ref.get_layer(layer_idx);
-        self.pages.push(Page::new(page_ref, layer_ref, size))
+        let mut page = Page::new(page_ref, layer_ref, size);
+        page.index = self.pages.len();
+        page.baseline_grid = self.baseline_grid;
+        page.structure = self.structure.clone();
+        self.pages.push(page)
     }
 
     /// Returns the number of pages in this document.
@@ -172,7 +323,12 @@ impl Renderer {
 
     /// Loads the font from the given data, adds it to the generated document and returns a
     /// reference to it.
+    ///
+    /// The font data may contain a TrueType font or an OpenType font with CFF outlines.
     pub fn add_embedded_font(&self, data: &[u8]) -> Result<printpdf::IndirectFontRef, Error> {
+        if is_cff_font(data) {
+            self.has_cff_fonts.set(true);
+        }
         self.doc
             .add_external_font(data)
             .context("Failed to load PDF font")
@@ -180,9 +336,183 @@ impl Renderer {
 
     /// Writes this PDF document to a writer.
     pub fn write(self, w: impl io::Write) -> Result<(), Error> {
-        self.doc
-            .save(&mut io::BufWriter::new(w))
-            .context("Failed to save document")
+        let mut w = io::BufWriter::new(w);
+        if self.requires_post_processing() {
+            let mut doc = self.into_lopdf_document()?;
+            doc.save_to(&mut w).context("Failed to save document")
+        } else {
+            self.doc.save(&mut w).context("Failed to save document")
+        }
+    }
+
+    /// Writes the pages of this PDF document with the given page numbers to a writer.
+    ///
+    /// The page numbers start at 1.  Page numbers that are out of range are ignored.  The
+    /// resources that are only used by the omitted pages are removed from the generated document.
+    /// If the range does not contain any page of this document, an error with the
+    /// [`InvalidData`][] kind is returned.
+    ///
+    /// [`InvalidData`]: ../error/enum.ErrorKind.html#variant.InvalidData
+    pub fn write_page_range(
+        self,
+        pages: impl ops::RangeBounds<usize>,
+        w: impl io::Write,
+    ) -> Result<(), Error> {
+        let mut doc = self.into_lopdf_document()?;
+        let page_count = doc.get_pages().len();
+        let omitted: Vec<_> = (1..=page_count)
+            .filter(|page| !pages.contains(page))
+            .map(|page| page as u32)
+            .collect();
+        if omitted.len() == page_count {
+            return Err(Error::new(
+                "The page range does not contain any pages of the document",
+                ErrorKind::InvalidData,
+            ));
+        }
+
+        let mut w = io::BufWriter::new(w);
+        if !omitted.is_empty() {
+            doc.delete_pages(&omitted);
+            doc.prune_objects();
+        }
+        doc.save_to(&mut w).context("Failed to save document")
+    }
+
+    /// Returns whether the document generated by `printpdf` has to be modified because it uses
+    /// features that are not supported by `printpdf`.
+    fn requires_post_processing(&self) -> bool {
+        self.has_cff_fonts.get()
+            || self.structure.is_some()
+            || self.xmp_metadata.is_some()
+            || self.output_intent.is_some()
+            || self.open_destination.is_some()
+            || !self.appended_documents.is_empty()
+            || self.pages.iter().any(|page| {
+                page.rotation != 0
+                    || page.layers_options.iter().any(LayerOptions::is_modified)
+                    || !page.boxes().is_empty()
+                    || page.has_image_alt_texts.get()
+                    || !page.patterns.borrow().is_empty()
+                    || !page.imported_pages.borrow().is_empty()
+                    || !page.emoji_images.borrow().is_empty()
+                    || !page.destinations.borrow().is_empty()
+                    || !page.signature_fields.borrow().is_empty()
+                    || !page.links.borrow().is_empty()
+            })
+    }
+
+    /// Saves the document generated by `printpdf`, parses it with `lopdf` and adds the features
+    /// that are not supported by `printpdf`.
+    fn into_lopdf_document(self) -> Result<lopdf::Document, Error> {
+        let has_image_alt_texts: Vec<_> = self
+            .pages
+            .iter()
+            .map(|page| page.has_image_alt_texts.get())
+            .collect();
+        let patterns: Vec<_> = self.pages.iter().map(|page| page.patterns.take()).collect();
+        let imported_pages: Vec<_> = self
+            .pages
+            .iter()
+            .map(|page| page.imported_pages.take())
+            .collect();
+        let emoji_images: Vec<_> = self
+            .pages
+            .iter()
+            .map(|page| page.emoji_images.take())
+            .collect();
+        let layers_options: Vec<_> = self
+            .pages
+            .iter()
+            .map(|page| page.layers_options.clone())
+            .collect();
+        let destinations: Vec<_> = self
+            .pages
+            .iter()
+            .map(|page| page.destinations.take())
+            .collect();
+        let signature_fields: Vec<_> = self
+            .pages
+            .iter()
+            .map(|page| page.signature_fields.take())
+            .collect();
+        let links: Vec<_> = self.pages.iter().map(|page| page.links.take()).collect();
+        let rotations: Vec<_> = self.pages.iter().map(|page| page.rotation).collect();
+        let boxes: Vec<_> = self.pages.iter().map(Page::boxes).collect();
+
+        let data = self
+            .doc
+            .save_to_bytes()
+            .context("Failed to save document")?;
+        let mut doc = lopdf::Document::load_mem(&data).map_err(|err| {
+            Error::new(
+                format!("Failed to parse generated document: {}", err),
+                ErrorKind::Internal,
+            )
+        })?;
+        if self.has_cff_fonts.get() {
+            embed_cff_fonts(&mut doc);
+        }
+        if let Some(structure) = &self.structure {
+            structure.borrow().write(&mut doc);
+        }
+        if emoji_images.iter().any(|images| !images.is_empty()) {
+            set_page_emoji_images(&mut doc, emoji_images);
+        }
+        if has_image_alt_texts
+            .iter()
+            .any(|has_alt_texts| *has_alt_texts)
+        {
+            set_image_alt_texts(&mut doc, &has_image_alt_texts);
+        }
+        if patterns.iter().any(|patterns| !patterns.is_empty()) {
+            set_page_patterns(&mut doc, patterns);
+        }
+        let has_imported_pages = imported_pages.iter().any(|pages| !pages.is_empty());
+        if has_imported_pages {
+            set_page_form_xobjects(&mut doc, imported_pages);
+        }
+        if rotations.iter().any(|rotation| *rotation != 0) {
+            set_page_rotations(&mut doc, &rotations);
+        }
+        if boxes.iter().any(|boxes| !boxes.is_empty()) {
+            set_page_boxes(&mut doc, &boxes);
+        }
+        if let Some(xmp) = &self.xmp_metadata {
+            set_xmp_metadata(&mut doc, xmp);
+        }
+        if let Some((icc_profile, name)) = &self.output_intent {
+            set_output_intent(&mut doc, icc_profile, name);
+        }
+        if destinations
+            .iter()
+            .any(|destinations| !destinations.is_empty())
+            || self.open_destination.is_some()
+        {
+            set_named_destinations(&mut doc, destinations, self.open_destination.as_deref())?;
+        }
+        if signature_fields.iter().any(|fields| !fields.is_empty()) {
+            add_signature_fields(&mut doc, signature_fields)?;
+        }
+        if links.iter().any(|links| !links.is_empty()) {
+            add_links(&mut doc, links);
+        }
+        if layers_options
+            .iter()
+            .flatten()
+            .any(LayerOptions::is_modified)
+        {
+            set_layers_options(&mut doc, &layers_options);
+        }
+        let has_appended_documents = !self.appended_documents.is_empty();
+        for other in self.appended_documents {
+            append_pages(&mut doc, other);
+        }
+        if has_appended_documents || has_imported_pages {
+            deduplicate_streams(&mut doc);
+            doc.prune_objects();
+        }
+        Ok(doc)
     }
 }
 
@@ -195,6 +525,29 @@ pub struct Page {
     page: printpdf::PdfPageReference,
     size: Size,
     layers: Layers,
+    index: usize,
+    baseline_grid: Option<Mm>,
+    structure: Option<rc::Rc<cell::RefCell<StructureTree>>>,
+    // whether an image on this page is wrapped in a marked-content sequence with an alternate text
+    has_image_alt_texts: cell::Cell<bool>,
+    // the tiling patterns of this page, named P0, P1, … in the order they were added
+    patterns: cell::RefCell<Vec<TilingPattern>>,
+    // the imported pages drawn on this page, named Fm0, Fm1, … in the order they were added
+    imported_pages: cell::RefCell<Vec<ImportedPage>>,
+    // the emoji images drawn on this page, see emoji_image_name for their names
+    emoji_images: cell::RefCell<collections::BTreeMap<char, lopdf::Stream>>,
+    // the named destinations on this page with their positions in user space, in points
+    destinations: cell::RefCell<Vec<(String, [f32; 2])>>,
+    // the signature fields on this page with their rectangles in user space, in points
+    signature_fields: cell::RefCell<Vec<(String, [f32; 4])>>,
+    // the link targets on this page with their rectangles in user space, in points
+    links: cell::RefCell<Vec<(String, [f32; 4])>>,
+    // the options of the layers of this page that are not supported by printpdf
+    layers_options: Vec<LayerOptions>,
+    rotation: i64,
+    crop_box: Option<(Position, Size)>,
+    bleed_box: Option<(Position, Size)>,
+    trim_box: Option<(Position, Size)>,
 }
 
 impl Page {
@@ -207,13 +560,231 @@ impl Page {
             page,
             size,
             layers: Layers::new(layer),
+            index: 0,
+            baseline_grid: None,
+            structure: None,
+            has_image_alt_texts: Default::default(),
+            patterns: Default::default(),
+            imported_pages: Default::default(),
+            emoji_images: Default::default(),
+            destinations: Default::default(),
+            signature_fields: Default::default(),
+            links: Default::default(),
+            layers_options: vec![LayerOptions::default()],
+            rotation: 0,
+            crop_box: None,
+            bleed_box: None,
+            trim_box: None,
+        }
+    }
+
+    /// Sets the crop box of this page, i. e. the region that PDF viewers display.
+    ///
+    /// The position of the upper left corner of the box is relative to the upper left corner of
+    /// the page.  If this method is not called, the crop box is the media box, i. e. the complete
+    /// page.
+    pub fn set_crop_box(&mut self, position: impl Into<Position>, size: impl Into<Size>) {
+        self.crop_box = Some((position.into(), size.into()));
+    }
+
+    /// Returns the position and the size of the crop box of this page.
+    ///
+    /// See [`set_crop_box`][] for more information.
+    ///
+    /// [`set_crop_box`]: #method.set_crop_box
+    pub fn crop_box(&self) -> (Position, Size) {
+        self.crop_box.unwrap_or_else(|| self.media_box())
+    }
+
+    /// Sets the bleed box of this page, i. e. the region that the content is clipped to in a
+    /// production environment.
+    ///
+    /// The position of the upper left corner of the box is relative to the upper left corner of
+    /// the page.  If this method is not called, the bleed box is the media box, i. e. the complete
+    /// page.
+    pub fn set_bleed_box(&mut self, position: impl Into<Position>, size: impl Into<Size>) {
+        self.bleed_box = Some((position.into(), size.into()));
+    }
+
+    /// Returns the position and the size of the bleed box of this page.
+    ///
+    /// See [`set_bleed_box`][] for more information.
+    ///
+    /// [`set_bleed_box`]: #method.set_bleed_box
+    pub fn bleed_box(&self) -> (Position, Size) {
+        self.bleed_box.unwrap_or_else(|| self.media_box())
+    }
+
+    /// Sets the trim box of this page, i. e. the intended dimensions of the finished page after
+    /// trimming.
+    ///
+    /// The position of the upper left corner of the box is relative to the upper left corner of
+    /// the page.  If this method is not called, the trim box is the media box, i. e. the complete
+    /// page.
+    pub fn set_trim_box(&mut self, position: impl Into<Position>, size: impl Into<Size>) {
+        self.trim_box = Some((position.into(), size.into()));
+    }
+
+    /// Returns the position and the size of the trim box of this page.
+    ///
+    /// See [`set_trim_box`][] for more information.
+    ///
+    /// [`set_trim_box`]: #method.set_trim_box
+    pub fn trim_box(&self) -> (Position, Size) {
+        self.trim_box.unwrap_or_else(|| self.media_box())
+    }
+
+    fn media_box(&self) -> (Position, Size) {
+        (Position::default(), self.size)
+    }
+
+    /// Returns the number of named destinations, signature fields and links on this page, see
+    /// [`truncate_annotations`][].
+    ///
+    /// [`truncate_annotations`]: #method.truncate_annotations
+    pub(crate) fn annotation_counts(&self) -> [usize; 3] {
+        [
+            self.destinations.borrow().len(),
+            self.signature_fields.borrow().len(),
+            self.links.borrow().len(),
+        ]
+    }
+
+    /// Removes the named destinations, signature fields and links that were added to this page
+    /// after [`annotation_counts`][] returned the given numbers.
+    ///
+    /// [`annotation_counts`]: #method.annotation_counts
+    pub(crate) fn truncate_annotations(&self, [destinations, signature_fields, links]: [usize; 3]) {
+        self.destinations.borrow_mut().truncate(destinations);
+        self.signature_fields
+            .borrow_mut()
+            .truncate(signature_fields);
+        self.links.borrow_mut().truncate(links);
+    }
+
+    /// Returns the page boundaries that have been set explicitly, as PDF rectangles.
+    fn boxes(&self) -> Vec<(&'static str, [f32; 4])> {
+        [
+            ("CropBox", self.crop_box),
+            ("BleedBox", self.bleed_box),
+            ("TrimBox", self.trim_box),
+        ]
+        .iter()
+        .filter_map(|(name, rect)| rect.map(|rect| (*name, self.pdf_rect(rect))))
+        .collect()
+    }
+
+    /// Converts the given rectangle into a PDF rectangle, i. e. the coordinates of the lower left
+    /// and the upper right corner in user space.
+    fn pdf_rect(&self, (position, size): (Position, Size)) -> [f32; 4] {
+        let bottom = self.size.height - position.y - size.height;
+        [
+            position.x,
+            bottom,
+            position.x + size.width,
+            bottom + size.height,
+        ]
+        .map(|mm| printpdf::Pt::from(mm).0)
+    }
+
+    /// Sets the rotation of this page in degrees clockwise.
+    ///
+    /// The rotation is stored in the `Rotate` entry of the page so that PDF viewers display the
+    /// page rotated.  The content of the page is not transformed.  The rotation must be a multiple
+    /// of 90, otherwise an error is returned.  Negative values are converted to the equivalent
+    /// positive rotation.
+    pub fn set_rotation(&mut self, degrees: i32) -> Result<(), Error> {
+        if degrees % 90 == 0 {
+            self.rotation = i64::from(degrees).rem_euclid(360);
+            Ok(())
+        } else {
+            Err(Error::new(
+                format!(
+                    "Page rotation must be a multiple of 90 degrees, received {}",
+                    degrees
+                ),
+                ErrorKind::InvalidData,
+            ))
+        }
+    }
+
+    /// Returns the rotation of this page in degrees clockwise.
+    ///
+    /// The rotation is always one of 0, 90, 180 and 270.
+    pub fn rotation(&self) -> i32 {
+        self.rotation as i32
+    }
+
+    /// Appends the given PDF content stream operators to the last layer of this page.
+    ///
+    /// This is an escape hatch for integrating existing generators of PDF operators.  The
+    /// operators use the PDF coordinate system:  The origin is the lower left corner of the page,
+    /// the y axis points upwards and the unit is one point.  The operators are wrapped in a saved
+    /// graphics state, so changes to the colors, the line style or the transformation matrix do
+    /// not affect the rest of the document.  Resources like fonts or images that are referenced
+    /// by the operators must already be part of the page resources.
+    ///
+    /// If the data cannot be parsed as a content stream, or if the `q`/`Q` and `BT`/`ET` operators
+    /// are not balanced, an error with the [`InvalidData`][] kind is returned and nothing is
+    /// added to the page.  This method must not be called while a [`TextSection`][] for this page
+    /// is active.
+    ///
+    /// [`InvalidData`]: ../error/enum.ErrorKind.html#variant.InvalidData
+    /// [`TextSection`]: struct.TextSection.html
+    pub fn add_content_stream(&self, data: &[u8]) -> Result<(), Error> {
+        let content = lopdf::content::Content::decode(data).map_err(|err| {
+            Error::new(
+                format!("Failed to parse content stream: {}", err),
+                ErrorKind::InvalidData,
+            )
+        })?;
+        check_content_operators(&content.operations)?;
+
+        let layer = self.last_layer();
+        layer.save_graphics_state();
+        for operation in content.operations {
+            layer.data.layer.add_operation(operation);
         }
+        layer.restore_graphics_state();
+        Ok(())
     }
 
     /// Adds a new layer with the given name to the page.
+    ///
+    /// The layers of a page are written as optional content groups, so PDF viewers show their
+    /// names in the layers panel.
     pub fn add_layer(&mut self, name: impl Into<String>) {
         let layer = self.page.add_layer(name);
         self.layers.push(layer);
+        self.layers_options.push(LayerOptions::default());
+    }
+
+    /// Adds a new optional layer with the given name to the page.
+    ///
+    /// Optional layers can be shown and hidden in the layers panel of PDF viewers.  If
+    /// `visible_by_default` is false, the layer is hidden when the document is opened.
+    pub fn add_optional_layer(&mut self, name: impl Into<String>, visible_by_default: bool) {
+        self.add_layer(name);
+        if let Some(options) = self.layers_options.last_mut() {
+            options.hidden = !visible_by_default;
+        }
+    }
+
+    /// Sets the name of the layer with the given index.
+    ///
+    /// If the index is out of range, an error with the [`InvalidData`][] kind is returned.
+    ///
+    /// [`InvalidData`]: ../error/enum.ErrorKind.html#variant.InvalidData
+    pub fn set_layer_name(&mut self, idx: usize, name: impl Into<String>) -> Result<(), Error> {
+        if let Some(options) = self.layers_options.get_mut(idx) {
+            options.name = Some(name.into());
+            Ok(())
+        } else {
+            Err(Error::new(
+                format!("The page does not have a layer with index {}", idx),
+                ErrorKind::InvalidData,
+            ))
+        }
     }
 
     /// Returns the number of layers on this page.
@@ -296,11 +867,17 @@ impl Layers {
 pub struct Layer<'p> {
     page: &'p Page,
     data: rc::Rc<LayerData>,
+    // If set, no operations are added to the layer, see Context::measure.
+    dry_run: bool,
 }
 
 impl<'p> Layer<'p> {
     fn new(page: &'p Page, data: rc::Rc<LayerData>) -> Layer<'p> {
-        Layer { page, data }
+        Layer {
+            page,
+            data,
+            dry_run: false,
+        }
     }
 
     /// Returns the next layer of this page.
@@ -316,6 +893,16 @@ impl<'p> Layer<'p> {
         Area::new(self.clone(), Position::default(), self.page.size)
     }
 
+    /// Calls the given function with the data of this layer to add operations to it, unless this
+    /// is a dry run (see [`Area::is_dry_run`][]).
+    ///
+    /// [`Area::is_dry_run`]: struct.Area.html#method.is_dry_run
+    fn emit(&self, f: impl FnOnce(&LayerData)) {
+        if !self.dry_run {
+            f(&self.data);
+        }
+    }
+
     #[cfg(feature = "images")]
     fn add_image(
         &self,
@@ -324,90 +911,380 @@ impl<'p> Layer<'p> {
         scale: Scale,
         rotation: Rotation,
         dpi: Option<f32>,
+        alt_text: Option<&str>,
     ) {
-        let dynamic_image = printpdf::Image::from_dynamic_image(image);
-        let position = self.transform_position(position);
-        dynamic_image.add_to_layer(
-            self.data.layer.clone(),
-            printpdf::ImageTransform {
-                translate_x: Some(position.x.into()),
-                translate_y: Some(position.y.into()),
-                rotate: Some(printpdf::ImageRotation {
-                    // rotation.degrees() is clockwise, but ImageRotation requires ccw
-                    angle_ccw_degrees: -(rotation
-                        .degrees()
-                        .expect("Could not parse rotation into degrees")),
-                    ..Default::default()
-                }),
-                scale_x: Some(scale.x),
-                scale_y: Some(scale.y),
-                dpi,
-            },
-        );
+        self.emit(|data| {
+            if let Some(alt_text) = alt_text {
+                self.begin_alt_text(alt_text);
+            }
+            let dynamic_image = printpdf::Image::from_dynamic_image(image);
+            let position = self.transform_position(position);
+            dynamic_image.add_to_layer(
+                data.layer.clone(),
+                printpdf::ImageTransform {
+                    translate_x: Some(position.x.into()),
+                    translate_y: Some(position.y.into()),
+                    rotate: rotation.degrees().map(|degrees| printpdf::ImageRotation {
+                        // rotation.degrees() is clockwise, but ImageRotation requires ccw
+                        angle_ccw_degrees: -degrees,
+                        ..Default::default()
+                    }),
+                    scale_x: Some(scale.x),
+                    scale_y: Some(scale.y),
+                    dpi,
+                },
+            );
+            if alt_text.is_some() {
+                data.layer
+                    .add_operation(lopdf::content::Operation::new("EMC", Vec::new()));
+            }
+        });
+    }
+
+    /// Draws the emoji image of the given character with the given size, with its lower left
+    /// corner at the given position.
+    ///
+    /// The image is embedded once per character and page and referenced by its name, so repeated
+    /// emojis do not increase the size of the document.
+    #[cfg(feature = "images")]
+    fn add_emoji_image(
+        &self,
+        c: char,
+        image: &printpdf::image_crate::DynamicImage,
+        position: LayerPosition,
+        size: Size,
+    ) {
+        self.emit(|data| {
+            self.page
+                .emoji_images
+                .borrow_mut()
+                .entry(c)
+                .or_insert_with(|| printpdf::ImageXObject::from_dynamic_image(image).into());
+
+            let position = self.transform_position(position);
+            let operands = [
+                printpdf::Pt::from(size.width).0,
+                0.0,
+                0.0,
+                printpdf::Pt::from(size.height).0,
+                printpdf::Pt::from(position.x).0,
+                printpdf::Pt::from(position.y).0,
+            ]
+            .iter()
+            .map(|value| (*value).into())
+            .collect();
+            self.begin_alt_text(&c.to_string());
+            self.save_graphics_state();
+            let layer = &data.layer;
+            layer.add_operation(lopdf::content::Operation::new("cm", operands));
+            layer.add_operation(lopdf::content::Operation::new(
+                "Do",
+                vec![lopdf::Object::Name(emoji_image_name(c).into_bytes())],
+            ));
+            self.restore_graphics_state();
+            layer.add_operation(lopdf::content::Operation::new("EMC", Vec::new()));
+        });
+    }
+
+    /// Begins a marked-content sequence with the given alternate text for the next image.
+    ///
+    /// printpdf does not tell us the name of the image XObject, so we attach the alternate text
+    /// to the marked-content sequence that contains the image and copy it to the XObject that is
+    /// drawn in it when post-processing the document.
+    #[cfg(feature = "images")]
+    fn begin_alt_text(&self, alt_text: &str) {
+        self.emit(|data| {
+            self.page.has_image_alt_texts.set(true);
+            let properties = lopdf::Dictionary::from_iter(vec![("Alt", text_string(alt_text))]);
+            data.layer.add_operation(lopdf::content::Operation::new(
+                "BDC",
+                vec![
+                    lopdf::Object::Name(b"Span".to_vec()),
+                    lopdf::Object::Dictionary(properties),
+                ],
+            ));
+        });
+    }
+
+    #[cfg(feature = "images")]
+    fn fill_with_image_pattern(
+        &self,
+        image: &printpdf::image_crate::DynamicImage,
+        position: LayerPosition,
+        size: Size,
+        tile_size: Size,
+    ) {
+        self.emit(|data| {
+            // The pattern space is the default user space of the page, so the pattern is moved so
+            // that the first tile starts at the upper left corner of the filled rectangle.
+            let top_left = self.transform_position(position);
+            let name = {
+                let mut patterns = self.page.patterns.borrow_mut();
+                patterns.push(TilingPattern {
+                    image: printpdf::ImageXObject::from_dynamic_image(image).into(),
+                    origin: Position::new(top_left.x, top_left.y - tile_size.height),
+                    tile_size,
+                });
+                format!("P{}", patterns.len() - 1)
+            };
+
+            let operands = [
+                top_left.x,
+                top_left.y - size.height,
+                size.width,
+                size.height,
+            ]
+            .iter()
+            .map(|mm| printpdf::Pt::from(*mm).0.into())
+            .collect();
+            self.save_graphics_state();
+            let layer = &data.layer;
+            layer.add_operation(lopdf::content::Operation::new(
+                "cs",
+                vec![lopdf::Object::Name(b"Pattern".to_vec())],
+            ));
+            layer.add_operation(lopdf::content::Operation::new(
+                "scn",
+                vec![lopdf::Object::Name(name.into_bytes())],
+            ));
+            layer.add_operation(lopdf::content::Operation::new("re", operands));
+            layer.add_operation(lopdf::content::Operation::new("f", Vec::new()));
+            self.restore_graphics_state();
+        });
+    }
+
+    fn add_form_xobject(&self, page: &ImportedPage, position: LayerPosition, scale: f32) {
+        self.emit(|data| {
+            let name = {
+                let mut imported_pages = self.page.imported_pages.borrow_mut();
+                imported_pages.push(page.clone());
+                format!("Fm{}", imported_pages.len() - 1)
+            };
+
+            // The form is placed so that the upper left corner of its bounding box is at the given
+            // position.
+            let top_left = self.transform_position(position);
+            let x = printpdf::Pt::from(top_left.x).0 - page.origin.0 * scale;
+            let y =
+                printpdf::Pt::from(top_left.y - page.size.height * scale).0 - page.origin.1 * scale;
+            let operands = [scale, 0.0, 0.0, scale, x, y]
+                .iter()
+                .map(|value| (*value).into())
+                .collect();
+            self.save_graphics_state();
+            let layer = &data.layer;
+            layer.add_operation(lopdf::content::Operation::new("cm", operands));
+            layer.add_operation(lopdf::content::Operation::new(
+                "Do",
+                vec![lopdf::Object::Name(name.into_bytes())],
+            ));
+            self.restore_graphics_state();
+        });
     }
 
     fn add_line_shape<I>(&self, points: I)
     where
         I: IntoIterator<Item = LayerPosition>,
     {
-        let line_points: Vec<_> = points
-            .into_iter()
-            .map(|pos| (self.transform_position(pos).into(), false))
-            .collect();
-        let line = printpdf::Line {
-            points: line_points,
-            is_closed: false,
-        };
-        self.data.layer.add_line(line);
+        self.emit(|data| {
+            let line_points: Vec<_> = points
+                .into_iter()
+                .map(|pos| (self.transform_position(pos).into(), false))
+                .collect();
+            let line = printpdf::Line {
+                points: line_points,
+                is_closed: false,
+            };
+            data.layer.add_line(line);
+        });
+    }
+
+    fn add_filled_shape<I>(&self, points: I, stroke: bool)
+    where
+        I: IntoIterator<Item = LayerPosition>,
+    {
+        self.emit(|data| {
+            let points: Vec<_> = points
+                .into_iter()
+                .map(|pos| (self.transform_position(pos).into(), false))
+                .collect();
+            let mode = if stroke {
+                printpdf::PolygonMode::FillStroke
+            } else {
+                printpdf::PolygonMode::Fill
+            };
+            let polygon = printpdf::Polygon {
+                rings: vec![points],
+                mode,
+                winding_order: printpdf::WindingOrder::NonZero,
+            };
+            data.layer.add_polygon(polygon);
+        });
     }
 
     fn set_fill_color(&self, color: Option<Color>) {
-        if self.data.update_fill_color(color) {
-            self.data
-                .layer
-                .set_fill_color(color.unwrap_or(Color::Rgb(0, 0, 0)).into());
-        }
+        self.emit(|data| {
+            if data.update_fill_color(color) {
+                data.layer
+                    .set_fill_color(color.unwrap_or(Color::Rgb(0, 0, 0)).into());
+            }
+        });
     }
 
     fn set_outline_thickness(&self, thickness: Mm) {
-        if self.data.update_outline_thickness(thickness) {
-            self.data
-                .layer
-                .set_outline_thickness(printpdf::Pt::from(thickness).0);
-        }
+        self.emit(|data| {
+            if data.update_outline_thickness(thickness) {
+                data.layer
+                    .set_outline_thickness(printpdf::Pt::from(thickness).0);
+            }
+        });
     }
 
     fn set_outline_color(&self, color: Color) {
-        if self.data.update_outline_color(color) {
-            self.data.layer.set_outline_color(color.into());
-        }
+        self.emit(|data| {
+            if data.update_outline_color(color) {
+                data.layer.set_outline_color(color.into());
+            }
+        });
+    }
+
+    fn set_line_cap(&self, line_cap: LineCap) {
+        self.emit(|data| data.layer.set_line_cap_style(line_cap.into()));
+    }
+
+    fn set_line_join(&self, line_join: LineJoin) {
+        self.emit(|data| data.layer.set_line_join_style(line_join.into()));
     }
 
     fn set_text_cursor(&self, cursor: LayerPosition) {
-        let cursor = self.transform_position(cursor);
-        self.data
-            .layer
-            .set_text_cursor(cursor.x.into(), cursor.y.into());
+        self.emit(|data| {
+            let cursor = self.transform_position(cursor);
+            data.layer.set_text_cursor(cursor.x.into(), cursor.y.into());
+        });
+    }
+
+    fn save_graphics_state(&self) {
+        self.emit(|data| {
+            data.save_state();
+            data.layer.save_graphics_state();
+        });
+    }
+
+    fn restore_graphics_state(&self) {
+        self.emit(|data| {
+            data.layer.restore_graphics_state();
+            // Restoring the graphics state also restores the colors and the line width, so we have
+            // to reset the cached values.
+            data.restore_state();
+        });
+    }
+
+    fn set_clip_rect(&self, position: LayerPosition, size: Size) {
+        self.emit(|data| {
+            // The clipping path is defined by its lower left corner in user space.
+            let position = self.transform_position(position);
+            let operands = [
+                position.x,
+                position.y - size.height,
+                size.width,
+                size.height,
+            ]
+            .iter()
+            .map(|mm| printpdf::Pt::from(*mm).0.into())
+            .collect();
+            let layer = &data.layer;
+            layer.add_operation(lopdf::content::Operation::new("re", operands));
+            layer.add_operation(lopdf::content::Operation::new("W", Vec::new()));
+            layer.add_operation(lopdf::content::Operation::new("n", Vec::new()));
+        });
+    }
+
+    /// Rotates the coordinate system by the given angle in degrees clockwise around the given
+    /// position.
+    fn rotate_around(&self, position: LayerPosition, degrees: f32) {
+        let position = self.transform_position(position);
+        let x = printpdf::Pt::from(position.x).0;
+        let y = printpdf::Pt::from(position.y).0;
+        let (mut sin, mut cos) = degrees.to_radians().sin_cos();
+        if degrees % 90.0 == 0.0 {
+            // Avoid rounding errors for right angles, e. g. cos(90°) = -4.37e-8.
+            sin = sin.round();
+            cos = cos.round();
+        }
+        self.transform([
+            cos,
+            -sin,
+            sin,
+            cos,
+            x - cos * x - sin * y,
+            y + sin * x - cos * y,
+        ]);
+    }
+
+    /// Applies the given transformation matrix to the coordinate system.
+    fn transform(&self, matrix: [f32; 6]) {
+        self.emit(|data| {
+            let operands = matrix.iter().map(|value| (*value).into()).collect();
+            data.layer
+                .add_operation(lopdf::content::Operation::new("cm", operands));
+        });
+    }
+
+    fn begin_marked_content(&self, tag: Tag, mcid: usize) {
+        self.emit(|data| {
+            let properties = lopdf::Dictionary::from_iter(vec![("MCID", (mcid as i64).into())]);
+            data.layer.add_operation(lopdf::content::Operation::new(
+                "BDC",
+                vec![
+                    lopdf::Object::Name(tag.name().into_bytes()),
+                    lopdf::Object::Dictionary(properties),
+                ],
+            ));
+        });
+    }
+
+    fn end_marked_content(&self) {
+        self.emit(|data| {
+            data.layer
+                .add_operation(lopdf::content::Operation::new("EMC", Vec::new()));
+        });
     }
 
     fn begin_text_section(&self) {
-        self.data.layer.begin_text_section();
+        self.emit(|data| data.layer.begin_text_section());
     }
 
     fn end_text_section(&self) {
-        self.data.layer.end_text_section();
+        self.emit(|data| data.layer.end_text_section());
     }
 
     fn add_line_break(&self) {
-        self.data.layer.add_line_break();
+        self.emit(|data| data.layer.add_line_break());
+    }
+
+    fn set_text_rise(&self, rise: Mm) {
+        self.emit(|data| data.layer.set_line_offset(printpdf::Pt::from(rise).0));
     }
 
     fn set_line_height(&self, line_height: Mm) {
-        self.data.layer.set_line_height(line_height.0);
+        self.emit(|data| data.layer.set_line_height(line_height.0));
     }
 
     fn set_font(&self, font: &printpdf::IndirectFontRef, font_size: u8) {
-        self.data.layer.set_font(font, font_size.into());
+        self.emit(|data| data.layer.set_font(font, font_size.into()));
+    }
+
+    /// Moves the text cursor by the given horizontal offset without changing the start of the
+    /// line, using an empty `TJ` operation with the given font size.
+    fn move_text_cursor(&self, offset: Mm, font_size: u8) {
+        self.emit(|data| {
+            // TJ offsets are measured in 1/1000 of the font size and move the cursor to the left
+            let offset = -printpdf::Pt::from(offset).0 * 1000.0 / f32::from(font_size);
+            data.layer.add_operation(lopdf::content::Operation::new(
+                "TJ",
+                vec![lopdf::Object::Array(vec![offset.into()])],
+            ));
+        });
     }
 
     fn write_positioned_codepoints<P, C>(&self, positions: P, codepoints: C)
@@ -415,9 +1292,10 @@ impl<'p> Layer<'p> {
         P: IntoIterator<Item = i64>,
         C: IntoIterator<Item = u16>,
     {
-        self.data
-            .layer
-            .write_positioned_codepoints(positions.into_iter().zip(codepoints.into_iter()));
+        self.emit(|data| {
+            data.layer
+                .write_positioned_codepoints(positions.into_iter().zip(codepoints));
+        });
     }
 
     /// Transforms the given position that is relative to the upper left corner of the layer to a
@@ -430,13 +1308,34 @@ impl<'p> Layer<'p> {
 #[derive(Debug)]
 struct LayerData {
     layer: printpdf::PdfLayerReference,
+    // The fill color (used for text and filled shapes) and the outline color (used for lines and
+    // shape outlines) are separate parts of the graphics state, so they are cached separately.
     fill_color: cell::Cell<Color>,
     outline_color: cell::Cell<Color>,
     outline_thickness: cell::Cell<Mm>,
+    saved_states: cell::RefCell<Vec<(Color, Color, Mm)>>,
 }
 
 impl LayerData {
-    pub fn update_fill_color(&self, color: Option<Color>) -> bool {
+    pub fn save_state(&self) {
+        self.saved_states.borrow_mut().push((
+            self.fill_color.get(),
+            self.outline_color.get(),
+            self.outline_thickness.get(),
+        ));
+    }
+
+    pub fn restore_state(&self) {
+        if let Some((fill_color, outline_color, outline_thickness)) =
+            self.saved_states.borrow_mut().pop()
+        {
+            self.fill_color.set(fill_color);
+            self.outline_color.set(outline_color);
+            self.outline_thickness.set(outline_thickness);
+        }
+    }
+
+    pub fn update_fill_color(&self, color: Option<Color>) -> bool {
         let color = color.unwrap_or(Color::Rgb(0, 0, 0));
         self.fill_color.replace(color) != color
     }
@@ -457,6 +1356,7 @@ impl From<printpdf::PdfLayerReference> for LayerData {
             fill_color: Color::Rgb(0, 0, 0).into(),
             outline_color: Color::Rgb(0, 0, 0).into(),
             outline_thickness: Mm::from(printpdf::Pt(1.0)).into(),
+            saved_states: Default::default(),
         }
     }
 }
@@ -472,6 +1372,8 @@ pub struct Area<'p> {
     layer: Layer<'p>,
     origin: Position,
     size: Size,
+    structure_parent: Option<StructureElement>,
+    is_page_start: bool,
 }
 
 impl<'p> Area<'p> {
@@ -480,19 +1382,31 @@ impl<'p> Area<'p> {
             layer,
             origin,
             size,
+            structure_parent: None,
+            is_page_start: false,
         }
     }
 
     /// Returns a copy of this area on the next layer of the page.
     ///
     /// If this area is not on the last layer, the existing next layer is used.  If it is on the
-    /// last layer, a new layer is created and added to the page.
+    /// last layer, a new layer is created and added to the page.  During a dry run (see
+    /// [`is_dry_run`][]), nothing is drawn, so no layer is added and a copy of this area is
+    /// returned.
+    ///
+    /// [`is_dry_run`]: #method.is_dry_run
     pub fn next_layer(&self) -> Self {
-        let layer = self.layer.next();
+        if self.layer.dry_run {
+            return self.clone();
+        }
+        let mut layer = self.layer.next();
+        layer.dry_run = self.layer.dry_run;
         Self {
             layer,
             origin: self.origin,
             size: self.size,
+            structure_parent: self.structure_parent,
+            is_page_start: self.is_page_start,
         }
     }
 
@@ -503,6 +1417,24 @@ impl<'p> Area<'p> {
         self.origin.y += margins.top;
         self.size.width -= margins.left + margins.right;
         self.size.height -= margins.top + margins.bottom;
+        if margins.top > Mm(0.0) {
+            self.is_page_start = false;
+        }
+    }
+
+    /// Reduces the drawable area to its intersection with the rectangle with the given origin and
+    /// size.
+    ///
+    /// The origin is measured from the upper left corner of the page.  If the rectangle does not
+    /// overlap with this area, the size of the area is set to zero.
+    pub fn intersect(&mut self, origin: impl Into<Position>, size: impl Into<Size>) {
+        let (origin, size) = (origin.into(), size.into());
+        let left = self.origin.x.max(origin.x);
+        let top = self.origin.y.max(origin.y);
+        let right = (self.origin.x + self.size.width).min(origin.x + size.width);
+        let bottom = (self.origin.y + self.size.height).min(origin.y + size.height);
+        self.add_offset(Position::new(left - self.origin.x, top - self.origin.y));
+        self.size = Size::new((right - left).max(Mm(0.0)), (bottom - top).max(Mm(0.0)));
     }
 
     /// Returns the size of this area.
@@ -517,6 +1449,37 @@ impl<'p> Area<'p> {
         self.origin.y += offset.y;
         self.size.width -= offset.x;
         self.size.height -= offset.y;
+        if offset.y > Mm(0.0) {
+            self.is_page_start = false;
+        }
+    }
+
+    /// Returns whether this area starts at the beginning of the content of a page, i. e. if
+    /// nothing has been rendered above this area on the current page.
+    ///
+    /// Areas are only marked as the page start by [`Document`][] for the area that is returned by
+    /// the page decorator.
+    ///
+    /// [`Document`]: ../struct.Document.html
+    pub fn is_page_start(&self) -> bool {
+        self.is_page_start
+    }
+
+    /// Returns whether this area is used for a dry run, see [`Context::measure`][].
+    ///
+    /// In a dry run, the drawing methods of this area do not change the generated document.
+    ///
+    /// [`Context::measure`]: ../struct.Context.html#method.measure
+    pub fn is_dry_run(&self) -> bool {
+        self.layer.dry_run
+    }
+
+    pub(crate) fn set_dry_run(&mut self) {
+        self.layer.dry_run = true;
+    }
+
+    pub(crate) fn set_page_start(&mut self) {
+        self.is_page_start = true;
     }
 
     /// Sets the size of this area.
@@ -575,7 +1538,74 @@ impl<'p> Area<'p> {
         dpi: Option<f32>,
     ) {
         self.layer
-            .add_image(image, self.position(position), scale, rotation, dpi);
+            .add_image(image, self.position(position), scale, rotation, dpi, None);
+    }
+
+    /// Inserts an image into the document and sets its alternate text.
+    ///
+    /// *Only available if the `images` feature is enabled.*
+    ///
+    /// The alternate text is stored in the `Alt` entry of the image XObject and of a `Span`
+    /// marked-content sequence around the image so that it can be used by assistive technologies
+    /// even if the document is not tagged.  If the image belongs to a structure element, set the
+    /// alternate text of the structure element instead (see [`set_alt_text`][]) and use
+    /// [`add_image`][].
+    ///
+    /// [`add_image`]: #method.add_image
+    /// [`set_alt_text`]: #method.set_alt_text
+    #[cfg(feature = "images")]
+    pub fn add_image_with_alt_text(
+        &self,
+        image: &printpdf::image_crate::DynamicImage,
+        position: Position,
+        scale: Scale,
+        rotation: Rotation,
+        dpi: Option<f32>,
+        alt_text: &str,
+    ) {
+        self.layer.add_image(
+            image,
+            self.position(position),
+            scale,
+            rotation,
+            dpi,
+            Some(alt_text),
+        );
+    }
+
+    /// Fills this area with copies of the given image that are arranged in a grid, for example to
+    /// draw a textured background.
+    ///
+    /// *Only available if the `images` feature is enabled.*
+    ///
+    /// Every copy of the image is scaled to the given tile size, and the first tile is placed in
+    /// the upper left corner of the area.  The image is only embedded once and repeated with a
+    /// tiling pattern, so this is more efficient than adding the image multiple times with
+    /// [`add_image`][].
+    ///
+    /// [`add_image`]: #method.add_image
+    #[cfg(feature = "images")]
+    pub fn draw_image_tiled(
+        &self,
+        image: &printpdf::image_crate::DynamicImage,
+        tile_size: impl Into<Size>,
+    ) {
+        self.layer.fill_with_image_pattern(
+            image,
+            self.position(Position::default()),
+            self.size,
+            tile_size.into(),
+        );
+    }
+
+    /// Draws the given page of another PDF document at the given position.
+    ///
+    /// The position is the upper left corner of the imported page, relative to the upper left
+    /// corner of the area, and the page is scaled with the given factor.  The page is embedded as
+    /// a form XObject, so it keeps its vector graphics and text.
+    pub fn draw_imported_page(&self, page: &ImportedPage, position: Position, scale: f32) {
+        self.layer
+            .add_form_xobject(page, self.position(position), scale);
     }
 
     /// Draws a line with the given points and the given line style.
@@ -585,17 +1615,298 @@ impl<'p> Area<'p> {
     where
         I: IntoIterator<Item = Position>,
     {
+        self.stroke(line_style, || {
+            self.layer
+                .add_line_shape(points.into_iter().map(|pos| self.position(pos)))
+        });
+    }
+
+    /// Sets the given line style, calls the given function to draw the line and resets the line
+    /// cap and join styles afterwards if necessary.
+    fn stroke(&self, line_style: LineStyle, f: impl FnOnce()) {
         self.layer.set_outline_thickness(line_style.thickness());
         self.layer.set_outline_color(line_style.color());
+        let line_cap = line_style.line_cap();
+        let line_join = line_style.line_join();
+        if line_cap != LineCap::default() {
+            self.layer.set_line_cap(line_cap);
+        }
+        if line_join != LineJoin::default() {
+            self.layer.set_line_join(line_join);
+        }
+        f();
+        if line_cap != LineCap::default() {
+            self.layer.set_line_cap(LineCap::default());
+        }
+        if line_join != LineJoin::default() {
+            self.layer.set_line_join(LineJoin::default());
+        }
+    }
+
+    /// Draws a closed shape with the given points, fills it with the given color and, if a line
+    /// style is set, draws its outline with the line style.
+    ///
+    /// The points are relative to the upper left corner of the area.  The fill color and the
+    /// outline color are set independently, so they do not affect each other.
+    pub fn draw_filled_shape<I>(&self, points: I, fill_color: Color, line_style: Option<LineStyle>)
+    where
+        I: IntoIterator<Item = Position>,
+    {
+        self.layer.set_fill_color(Some(fill_color));
+        let points = points.into_iter().map(|pos| self.position(pos));
+        if let Some(line_style) = line_style {
+            self.stroke(line_style, || self.layer.add_filled_shape(points, true));
+        } else {
+            self.layer.add_filled_shape(points, false);
+        }
+    }
+
+    /// Draws an arrow from the given start position to the given end position.
+    ///
+    /// The arrow consists of a line with the given line style and a filled triangular arrowhead
+    /// at the end position that points in the direction of the line.  The length and the width
+    /// of the arrowhead are set to `head_size`.  The positions are relative to the upper left
+    /// corner of the area.  If the start and the end position are equal, nothing is drawn.
+    pub fn draw_arrow(
+        &self,
+        from: Position,
+        to: Position,
+        line_style: LineStyle,
+        head_size: impl Into<Mm>,
+    ) {
+        let head_size = f32::from(head_size.into());
+        let dx = f32::from(to.x - from.x);
+        let dy = f32::from(to.y - from.y);
+        let length = dx.hypot(dy);
+        if length == 0.0 {
+            return;
+        }
+        // unit vectors along and perpendicular to the line
+        let (ux, uy) = (dx / length, dy / length);
+        let (px, py) = (-uy, ux);
+
+        let head_length = head_size.min(length);
+        let base = Position::new(
+            f32::from(to.x) - ux * head_length,
+            f32::from(to.y) - uy * head_length,
+        );
+        let half_width = head_size / 2.0;
+        let corner = |sign: f32| {
+            Position::new(
+                f32::from(base.x) + sign * px * half_width,
+                f32::from(base.y) + sign * py * half_width,
+            )
+        };
+
+        if head_length < length {
+            self.draw_line(vec![from, base], line_style);
+        }
+        self.draw_filled_shape(
+            vec![to, corner(1.0), corner(-1.0)],
+            line_style.color(),
+            None,
+        );
+    }
+
+    /// Calls the given function with the underlying [`printpdf::PdfLayerReference`][] so that
+    /// custom PDF operations can be added to the layer.
+    ///
+    /// This is an escape hatch for features that are not supported by `rckive_genpdf`.  Note that
+    /// the layer uses the PDF coordinate system:  The origin is the lower left corner of the page
+    /// (not of this area) and the y axis points upwards.  You can use [`origin`][] and
+    /// [`size`][] to calculate the position of this area.  The operations are wrapped in a saved
+    /// graphics state, so changes to the colors, the line style or the transformation matrix do
+    /// not affect the rest of the document.
+    ///
+    /// This method must not be called while a [`TextSection`][] for this layer is active.
+    ///
+    /// [`printpdf::PdfLayerReference`]: https://docs.rs/printpdf/0.3.2/printpdf/types/pdf_layer/struct.PdfLayerReference.html
+    /// [`origin`]: #method.origin
+    /// [`size`]: #method.size
+    /// [`TextSection`]: struct.TextSection.html
+    pub fn with_raw_layer<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&printpdf::PdfLayerReference) -> R,
+    {
+        self.layer.save_graphics_state();
+        let result = f(&self.layer.data.layer);
+        self.layer.restore_graphics_state();
+        result
+    }
+
+    /// Returns the position of the upper left corner of this area relative to the upper left
+    /// corner of the page.
+    pub fn origin(&self) -> Position {
+        self.origin
+    }
+
+    /// Returns an area on the same layer that covers the full page.
+    ///
+    /// This can be used to draw outside of the area that has been assigned to an element, for
+    /// example in the page margins.
+    pub fn page_area(&self) -> Area<'p> {
+        Area {
+            layer: self.layer.clone(),
+            origin: Position::default(),
+            size: self.layer.page.size,
+            structure_parent: self.structure_parent,
+            is_page_start: false,
+        }
+    }
+
+    /// Returns the number of the page of this area, starting at 1.
+    pub fn page_number(&self) -> usize {
+        self.layer.page.index + 1
+    }
+
+    /// Adds a structure element with the given tag to the structure tree of the document.
+    ///
+    /// The element is added as a child of the structure parent of this area (see
+    /// [`set_structure_parent`][]), or as a top-level element if this area does not have a
+    /// structure parent.  Use [`begin_marked_content`][] to associate content with the element.
+    ///
+    /// If tagging is not enabled for the document (see [`Renderer::with_tagging`][]), this method
+    /// returns `None`.
+    ///
+    /// [`set_structure_parent`]: #method.set_structure_parent
+    /// [`begin_marked_content`]: #method.begin_marked_content
+    /// [`Renderer::with_tagging`]: struct.Renderer.html#method.with_tagging
+    pub fn add_structure_element(&self, tag: Tag) -> Option<StructureElement> {
+        if self.layer.dry_run {
+            return None;
+        }
+        self.layer.page.structure.as_ref().map(|structure| {
+            structure
+                .borrow_mut()
+                .add_element(tag, self.structure_parent)
+        })
+    }
+
+    /// Sets the structure parent for structure elements that are added using this area.
+    pub fn set_structure_parent(&mut self, element: StructureElement) {
+        self.structure_parent = Some(element);
+    }
+
+    /// Sets the alternate text for the given structure element, for example a description of an
+    /// image.
+    pub fn set_alt_text(&self, element: StructureElement, alt_text: impl Into<String>) {
+        if self.layer.dry_run {
+            return;
+        }
+        if let Some(structure) = &self.layer.page.structure {
+            structure.borrow_mut().elements[element.0].alt_text = Some(alt_text.into());
+        }
+    }
+
+    /// Adds a named destination at the given position of this area, so that links and PDF viewers
+    /// can jump to this position.
+    ///
+    /// The position is relative to the upper left corner of this area.  If a name is used for
+    /// more than one destination, the last one is used.  See also
+    /// [`Renderer::with_open_destination`][].
+    ///
+    /// [`Renderer::with_open_destination`]: struct.Renderer.html#method.with_open_destination
+    pub fn add_named_destination(&self, name: impl Into<String>, position: Position) {
+        if self.layer.dry_run {
+            return;
+        }
+        let position = self.layer.transform_position(self.position(position));
+        let point = [
+            printpdf::Pt::from(position.x).0,
+            printpdf::Pt::from(position.y).0,
+        ];
+        self.layer
+            .page
+            .destinations
+            .borrow_mut()
+            .push((name.into(), point));
+    }
+
+    /// Adds an unsigned signature form field with the given name and the given position and size
+    /// to this area.
+    ///
+    /// The field is added to the interactive form of the document, so that signing tools can sign
+    /// the document in this field.  The position is relative to the upper left corner of this
+    /// area.  The field does not have an appearance, so nothing is drawn on the page.  The name
+    /// must be unique in the document, otherwise writing the document fails.
+    pub fn add_signature_field(&self, name: impl Into<String>, position: Position, size: Size) {
+        if self.layer.dry_run {
+            return;
+        }
+        let position = self.layer.transform_position(self.position(position));
+        let rect = [
+            printpdf::Pt::from(position.x).0,
+            printpdf::Pt::from(position.y - size.height).0,
+            printpdf::Pt::from(position.x + size.width).0,
+            printpdf::Pt::from(position.y).0,
+        ];
+        self.layer
+            .page
+            .signature_fields
+            .borrow_mut()
+            .push((name.into(), rect));
+    }
+
+    /// Adds a link annotation to the given URI with the given position and size to this area.
+    ///
+    /// The position is relative to the upper left corner of this area.  The annotation does not
+    /// have a border, so nothing is drawn on the page.
+    pub fn add_link(&self, position: Position, size: Size, uri: impl Into<String>) {
+        if self.layer.dry_run {
+            return;
+        }
+        let position = self.layer.transform_position(self.position(position));
+        let rect = [
+            printpdf::Pt::from(position.x).0,
+            printpdf::Pt::from(position.y - size.height).0,
+            printpdf::Pt::from(position.x + size.width).0,
+            printpdf::Pt::from(position.y).0,
+        ];
+        self.layer.page.links.borrow_mut().push((uri.into(), rect));
+    }
+
+    /// Starts a marked content sequence for the given structure element.
+    ///
+    /// All content that is drawn on the layer of this area until the returned [`MarkedContent`][]
+    /// is dropped is associated with the given structure element.  Marked content sequences for
+    /// structure elements may not be nested, and they may not be started while a
+    /// [`TextSection`][] is active.  They may contain the `Span` sequences without a structure
+    /// element that carry the alternate texts of images (see [`add_image_with_alt_text`][]), for
+    /// example of emoji images in a text section (see [`FontCache::add_emoji_image`][]).
+    ///
+    /// [`add_image_with_alt_text`]: #method.add_image_with_alt_text
+    /// [`FontCache::add_emoji_image`]: ../fonts/struct.FontCache.html#method.add_emoji_image
+    ///
+    /// [`MarkedContent`]: struct.MarkedContent.html
+    /// [`TextSection`]: struct.TextSection.html
+    pub fn begin_marked_content(&self, element: StructureElement) -> MarkedContent<'p> {
+        MarkedContent::new(self.layer.clone(), element)
+    }
+
+    /// Fills the rectangle with the given position and size with the given color.
+    ///
+    /// The position of the upper left corner of the rectangle is relative to the upper left
+    /// corner of the area.
+    pub fn fill_rect(&self, position: Position, size: Size, color: Color) {
+        self.layer.set_fill_color(Some(color));
+        let points = [
+            position,
+            position + Position::new(size.width, 0),
+            position + Position::new(size.width, size.height),
+            position + Position::new(0, size.height),
+        ];
         self.layer
-            .add_line_shape(points.into_iter().map(|pos| self.position(pos)));
+            .add_filled_shape(points.iter().map(|pos| self.position(*pos)), false);
     }
 
     /// Tries to draw the given string at the given position and returns `true` if the area was
     /// large enough to draw the string.
     ///
     /// The font cache must contain the PDF font for the font set in the style.  The position is
-    /// relative to the upper left corner of the area.
+    /// relative to the upper left corner of the area.  Only the height of the area is checked, see
+    /// [`print_str_fitted`][] for a method that also splits strings that are too wide.
+    ///
+    /// [`print_str_fitted`]: #method.print_str_fitted
     pub fn print_str<S: AsRef<str>>(
         &self,
         font_cache: &fonts::FontCache,
@@ -613,6 +1924,76 @@ impl<'p> Area<'p> {
         }
     }
 
+    /// Draws as much of the given string at the given position as fits into this area and returns
+    /// information about the printed part.
+    ///
+    /// In contrast to [`print_str`][], this method does not only report whether the string fit
+    /// into the area, but also where it had to be split.  If the line is higher than the area,
+    /// nothing is printed.  Otherwise, the longest prefix of the string that fits into the
+    /// remaining width of the area is printed.  The remaining part of the string can then be
+    /// printed elsewhere, for example at the start of the next line.
+    ///
+    /// The font cache must contain the PDF font for the font set in the style.  The position is
+    /// relative to the upper left corner of the area.
+    ///
+    /// [`print_str`]: #method.print_str
+    pub fn print_str_fitted<'s>(
+        &self,
+        font_cache: &fonts::FontCache,
+        position: Position,
+        style: Style,
+        s: &'s str,
+    ) -> Result<FitInfo<'s>, Error> {
+        let mut section = if let Some(section) =
+            self.text_section(font_cache, position, style.metrics(font_cache))
+        {
+            section
+        } else {
+            return Ok(FitInfo::new(s, 0, Mm(0.0)));
+        };
+
+        let max_width = self.size.width - position.x;
+        let mut split = (0, Mm(0.0));
+        for idx in s
+            .char_indices()
+            .map(|(idx, _)| idx)
+            .skip(1)
+            .chain(iter::once(s.len()))
+        {
+            let width = style.str_width(font_cache, &s[..idx]);
+            if width > max_width {
+                break;
+            }
+            split = (idx, width);
+        }
+        let (idx, width) = split;
+        section.print_str(&s[..idx], style)?;
+        Ok(FitInfo::new(s, idx, width))
+    }
+
+    /// Returns the number of lines with the given metrics that fit into this area.
+    ///
+    /// The first line requires the glyph height, every following line the line height of the
+    /// metrics.  If the page has a baseline grid, the line height is rounded up to the grid and
+    /// the first line is moved down to the next grid line, like in a [`TextSection`][].  If the line
+    /// height is not positive, `usize::MAX` is returned if at least one line fits.
+    ///
+    /// [`TextSection`]: struct.TextSection.html
+    pub fn max_lines(&self, mut metrics: fonts::Metrics) -> usize {
+        let offset = baseline_offset(self, metrics);
+        if let Some(grid) = self.layer.page.baseline_grid {
+            metrics.line_height = snap_to_grid(metrics.line_height, grid).max(grid);
+        }
+        let available = self.size.height - offset - metrics.glyph_height;
+        if available < Mm(0.0) {
+            0
+        } else if metrics.line_height <= Mm(0.0) {
+            usize::MAX
+        } else {
+            1 + (available.0 / metrics.line_height.0).floor() as usize
+        }
+    }
+
     /// Creates a new text section at the given position if the text section fits in this area.
     ///
     /// The given style is only used to calculate the line height of the section.  The position is
@@ -626,7 +2007,58 @@ impl<'p> Area<'p> {
     ) -> Option<TextSection<'f, 'p>> {
         let mut area = self.clone();
         area.add_offset(position);
-        TextSection::new(font_cache, area, metrics)
+        TextSection::new(font_cache, area, metrics, self.origin)
+    }
+
+    /// Creates a new text section at the given position even if the text section does not fit in
+    /// this area.
+    ///
+    /// Everything that is printed with the returned text section is clipped at the borders of
+    /// this area.  Otherwise, this method behaves like [`text_section`][].
+    ///
+    /// [`text_section`]: #method.text_section
+    pub fn clipped_text_section<'f>(
+        &self,
+        font_cache: &'f fonts::FontCache,
+        position: Position,
+        metrics: fonts::Metrics,
+    ) -> TextSection<'f, 'p> {
+        self.layer.save_graphics_state();
+        self.layer
+            .set_clip_rect(self.position(Position::default()), self.size);
+        let mut area = self.clone();
+        area.add_offset(position);
+        TextSection::new_unchecked(font_cache, area, metrics, self.origin, true)
+    }
+
+    /// Calls the given function with a copy of this area and clips everything that it draws at
+    /// the borders of this area.
+    pub fn clipped<T>(&self, f: impl FnOnce(Area<'p>) -> T) -> T {
+        self.layer.save_graphics_state();
+        self.layer
+            .set_clip_rect(self.position(Position::default()), self.size);
+        let result = f(self.clone());
+        self.layer.restore_graphics_state();
+        result
+    }
+
+    /// Calls the given function with a copy of this area and rotates everything that it draws by
+    /// the given rotation clockwise around the upper left corner of this area.
+    ///
+    /// For example, if the rotation is 90 degrees, the content that the function draws from left
+    /// to right is drawn from top to bottom on the page, and the area that it draws to is on the
+    /// left of the upper left corner of this area.  Negative rotations are counterclockwise.
+    pub fn rotated<T>(&self, rotation: impl Into<Rotation>, f: impl FnOnce(Area<'p>) -> T) -> T {
+        let degrees = match rotation.into().degrees() {
+            Some(degrees) => degrees,
+            None => return f(self.clone()),
+        };
+        self.layer.save_graphics_state();
+        self.layer
+            .rotate_around(self.position(Position::default()), degrees);
+        let result = f(self.clone());
+        self.layer.restore_graphics_state();
+        result
     }
 
     /// Returns a position relative to the top left corner of this area.
@@ -635,90 +2067,1450 @@ impl<'p> Area<'p> {
     }
 }
 
-/// A text section that is drawn on an area of a PDF layer.
-pub struct TextSection<'f, 'p> {
-    font_cache: &'f fonts::FontCache,
-    area: Area<'p>,
-    is_first: bool,
-    metrics: fonts::Metrics,
-    font: Option<(printpdf::IndirectFontRef, u8)>,
+/// The type of a structure element in a tagged PDF document.
+///
+/// See [`Area::add_structure_element`][] for more information.
+///
+/// [`Area::add_structure_element`]: struct.Area.html#method.add_structure_element
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Tag {
+    /// A paragraph (`P`).
+    #[default]
+    Paragraph,
+    /// A heading with the given level from 1 to 6 (`H1` to `H6`).
+    Heading(u8),
+    /// A table (`Table`).
+    Table,
+    /// A row of a table (`TR`).
+    TableRow,
+    /// A cell of a table (`TD`).
+    TableCell,
+    /// A figure, for example an image (`Figure`).
+    Figure,
 }
 
-impl<'f, 'p> TextSection<'f, 'p> {
-    fn new(
-        font_cache: &'f fonts::FontCache,
-        area: Area<'p>,
-        metrics: fonts::Metrics,
-    ) -> Option<TextSection<'f, 'p>> {
-        if metrics.glyph_height > area.size.height {
-            return None;
+impl Tag {
+    /// Returns the name of the PDF structure type for this tag.
+    pub fn name(&self) -> String {
+        match self {
+            Tag::Paragraph => "P".to_owned(),
+            Tag::Heading(level) => format!("H{}", level.clamp(&1, &6)),
+            Tag::Table => "Table".to_owned(),
+            Tag::TableRow => "TR".to_owned(),
+            Tag::TableCell => "TD".to_owned(),
+            Tag::Figure => "Figure".to_owned(),
         }
+    }
+}
 
-        area.layer.begin_text_section();
-        area.layer.set_line_height(metrics.line_height);
+/// A reference to a structure element in a tagged PDF document.
+///
+/// Structure elements can be created using [`Area::add_structure_element`][].
+///
+/// [`Area::add_structure_element`]: struct.Area.html#method.add_structure_element
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct StructureElement(usize);
 
-        Some(TextSection {
-            font_cache,
-            area,
-            is_first: true,
-            metrics,
-            font: None,
-        })
+/// A marked content sequence that belongs to a structure element.
+///
+/// The marked content sequence is ended when this struct is dropped.  See
+/// [`Area::begin_marked_content`][] for more information.
+///
+/// [`Area::begin_marked_content`]: struct.Area.html#method.begin_marked_content
+pub struct MarkedContent<'p> {
+    layer: Layer<'p>,
+}
+
+impl<'p> MarkedContent<'p> {
+    fn new(layer: Layer<'p>, element: StructureElement) -> MarkedContent<'p> {
+        if layer.dry_run {
+            return MarkedContent { layer };
+        }
+        if let Some(structure) = &layer.page.structure {
+            let (tag, mcid) = structure
+                .borrow_mut()
+                .add_content(element, layer.page.index);
+            layer.begin_marked_content(tag, mcid);
+        }
+        MarkedContent { layer }
     }
+}
 
-    fn set_text_cursor(&self, x_offset: Mm) {
-        let cursor = self
-            .area
-            .position(Position::new(x_offset, self.metrics.ascent));
-        self.area.layer.set_text_cursor(cursor);
+impl<'p> Drop for MarkedContent<'p> {
+    fn drop(&mut self) {
+        if self.layer.page.structure.is_some() {
+            self.layer.end_marked_content();
+        }
     }
+}
 
-    fn set_font(&mut self, font: &printpdf::IndirectFontRef, font_size: u8) {
-        let font_is_set = self
-            .font
-            .as_ref()
-            .map(|(font, font_size)| (font, *font_size))
-            .map(|data| data == (font, font_size))
-            .unwrap_or_default();
-        if !font_is_set {
-            self.font = Some((font.clone(), font_size));
-            self.area.layer.set_font(font, font_size);
+#[derive(Debug)]
+enum StructureKid {
+    Element(usize),
+    Content { page: usize, mcid: usize },
+}
+
+#[derive(Debug)]
+struct StructureNode {
+    tag: Tag,
+    parent: Option<usize>,
+    alt_text: Option<String>,
+    kids: Vec<StructureKid>,
+}
+
+/// The structure tree of a tagged PDF document.
+#[derive(Debug, Default)]
+struct StructureTree {
+    elements: Vec<StructureNode>,
+    // the structure elements for the marked content sequences of each page, indexed by the MCID
+    pages: Vec<Vec<usize>>,
+}
+
+impl StructureTree {
+    fn add_element(&mut self, tag: Tag, parent: Option<StructureElement>) -> StructureElement {
+        let idx = self.elements.len();
+        let parent = parent.map(|parent| parent.0);
+        if let Some(parent) = parent {
+            self.elements[parent].kids.push(StructureKid::Element(idx));
         }
+        self.elements.push(StructureNode {
+            tag,
+            parent,
+            alt_text: None,
+            kids: Vec::new(),
+        });
+        StructureElement(idx)
     }
 
-    /// Tries to add a new line and returns `true` if the area was large enough to fit the new
-    /// line.
-    #[must_use]
-    pub fn add_newline(&mut self) -> bool {
-        if self.metrics.line_height > self.area.size.height {
-            false
-        } else {
-            self.area.layer.add_line_break();
-            self.area.add_offset((0, self.metrics.line_height));
-            true
+    fn add_content(&mut self, element: StructureElement, page: usize) -> (Tag, usize) {
+        if self.pages.len() <= page {
+            self.pages.resize_with(page + 1, Vec::new);
         }
+        let mcid = self.pages[page].len();
+        self.pages[page].push(element.0);
+        let node = &mut self.elements[element.0];
+        node.kids.push(StructureKid::Content { page, mcid });
+        (node.tag, mcid)
     }
 
-    /// Prints the given string with the given style.
-    ///
-    /// The font cache for this text section must contain the PDF font for the given style.
-    pub fn print_str(&mut self, s: impl AsRef<str>, style: Style) -> Result<(), Error> {
-        let font = style.font(self.font_cache);
+    /// Adds the structure tree to the given document and marks it as a tagged document.
+    fn write(&self, doc: &mut lopdf::Document) {
+        use lopdf::{Dictionary, Object};
+
+        let pages: Vec<_> = doc.get_pages().into_values().collect();
+        let root_id = doc.new_object_id();
+        let ids: Vec<_> = self.elements.iter().map(|_| doc.new_object_id()).collect();
+
+        for (node, id) in self.elements.iter().zip(&ids) {
+            let kids = node
+                .kids
+                .iter()
+                .map(|kid| match kid {
+                    StructureKid::Element(idx) => Object::Reference(ids[*idx]),
+                    StructureKid::Content { page, mcid } => {
+                        Object::Dictionary(Dictionary::from_iter(vec![
+                            ("Type", Object::Name(b"MCR".to_vec())),
+                            ("Pg", Object::Reference(pages[*page])),
+                            ("MCID", (*mcid as i64).into()),
+                        ]))
+                    }
+                })
+                .collect();
+            let parent = node.parent.map(|idx| ids[idx]).unwrap_or(root_id);
+            let mut dict = Dictionary::from_iter(vec![
+                ("Type", Object::Name(b"StructElem".to_vec())),
+                ("S", Object::Name(node.tag.name().into_bytes())),
+                ("P", Object::Reference(parent)),
+                ("K", Object::Array(kids)),
+            ]);
+            if let Some(alt_text) = &node.alt_text {
+                dict.set("Alt", text_string(alt_text));
+            }
+            doc.objects.insert(*id, Object::Dictionary(dict));
+        }
+
+        // The parent tree maps the marked content sequences of every page to their structure
+        // elements.
+        let mut nums = Vec::new();
+        for (page, elements) in self.pages.iter().enumerate() {
+            if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(pages[page]) {
+                dict.set("StructParents", page as i64);
+            }
+            nums.push((page as i64).into());
+            nums.push(Object::Array(
+                elements
+                    .iter()
+                    .map(|idx| Object::Reference(ids[*idx]))
+                    .collect(),
+            ));
+        }
+
+        let roots = self
+            .elements
+            .iter()
+            .zip(&ids)
+            .filter(|(node, _)| node.parent.is_none())
+            .map(|(_, id)| Object::Reference(*id))
+            .collect();
+        let root = Dictionary::from_iter(vec![
+            ("Type", Object::Name(b"StructTreeRoot".to_vec())),
+            ("K", Object::Array(roots)),
+            (
+                "ParentTree",
+                Object::Dictionary(Dictionary::from_iter(vec![("Nums", Object::Array(nums))])),
+            ),
+            ("ParentTreeNextKey", (self.pages.len() as i64).into()),
+        ]);
+        doc.objects.insert(root_id, Object::Dictionary(root));
+
+        if let Some(catalog) = catalog_mut(doc) {
+            catalog.set("StructTreeRoot", Object::Reference(root_id));
+            catalog.set(
+                "MarkInfo",
+                Dictionary::from_iter(vec![("Marked", Object::Boolean(true))]),
+            );
+        }
+    }
+}
+
+/// A page of an existing PDF document that can be drawn on an area, see
+/// [`Area::draw_imported_page`][].
+///
+/// The page is converted into a form XObject that contains its content and its resources.  The
+/// visible region of the page is its crop box, or its media box if it does not have a crop box.
+/// Annotations, for example links and form fields, and the page rotation are discarded.
+///
+/// [`Area::draw_imported_page`]: struct.Area.html#method.draw_imported_page
+#[derive(Clone, Debug)]
+pub struct ImportedPage {
+    // a document that only contains the form XObject and the objects it uses, referenced by the
+    // Form entry of the trailer
+    doc: rc::Rc<lopdf::Document>,
+    // the lower left corner of the bounding box in points
+    origin: (f32, f32),
+    size: Size,
+}
+
+impl ImportedPage {
+    /// Imports the page with the given index (starting at zero) from the given PDF document.
+    ///
+    /// If the data cannot be parsed or if the document does not have a page with the given
+    /// index, an error with the [`InvalidData`][] kind is returned.
+    ///
+    /// [`InvalidData`]: ../error/enum.ErrorKind.html#variant.InvalidData
+    pub fn from_bytes(data: &[u8], page_index: usize) -> Result<ImportedPage, Error> {
+        let mut doc = lopdf::Document::load_mem(data).map_err(|err| {
+            Error::new(
+                format!("Failed to parse the imported document: {}", err),
+                ErrorKind::InvalidData,
+            )
+        })?;
+        let page_id = doc
+            .get_pages()
+            .into_values()
+            .nth(page_index)
+            .ok_or_else(|| {
+                Error::new(
+                    format!("The imported document does not have a page {}", page_index),
+                    ErrorKind::InvalidData,
+                )
+            })?;
+        let bbox = inherited_page_attribute(&doc, page_id, b"CropBox")
+            .or_else(|| inherited_page_attribute(&doc, page_id, b"MediaBox"))
+            .and_then(|object| {
+                let values = resolve(&doc, &object).as_array().ok()?.clone();
+                values
+                    .iter()
+                    .map(|value| resolve(&doc, value).as_float().ok())
+                    .collect::<Option<Vec<_>>>()
+            })
+            .filter(|values| values.len() == 4)
+            .ok_or_else(|| {
+                Error::new(
+                    "The imported page does not have a valid media box",
+                    ErrorKind::InvalidData,
+                )
+            })?;
+        let (left, right) = (bbox[0].min(bbox[2]), bbox[0].max(bbox[2]));
+        let (bottom, top) = (bbox[1].min(bbox[3]), bbox[1].max(bbox[3]));
+        let content = doc.get_page_content(page_id).map_err(|err| {
+            Error::new(
+                format!("Failed to read the content of the imported page: {}", err),
+                ErrorKind::InvalidData,
+            )
+        })?;
+        let resources = inherited_page_attribute(&doc, page_id, b"Resources")
+            .unwrap_or_else(|| lopdf::Dictionary::new().into());
+
+        let form = lopdf::Dictionary::from_iter(vec![
+            ("Type", "XObject".into()),
+            ("Subtype", "Form".into()),
+            (
+                "BBox",
+                vec![left.into(), bottom.into(), right.into(), top.into()].into(),
+            ),
+            ("Resources", resources),
+        ]);
+        let form_id = doc.add_object(lopdf::Stream::new(form, content));
+        doc.trailer = lopdf::Dictionary::from_iter(vec![("Form", form_id.into())]);
+        doc.prune_objects();
+        Ok(ImportedPage {
+            doc: rc::Rc::new(doc),
+            origin: (left, bottom),
+            size: Size::new(
+                Mm::from(printpdf::Pt(right - left)),
+                Mm::from(printpdf::Pt(top - bottom)),
+            ),
+        })
+    }
+
+    /// Returns the size of the visible region of this page.
+    pub fn size(&self) -> Size {
+        self.size
+    }
+}
+
+/// Returns the value of the given attribute of the given page, looking it up in the parent nodes
+/// of the page tree if the page does not have it.
+fn inherited_page_attribute(
+    doc: &lopdf::Document,
+    page_id: lopdf::ObjectId,
+    key: &[u8],
+) -> Option<lopdf::Object> {
+    let mut node = doc.get_dictionary(page_id).ok();
+    // The depth limit protects against cycles in malformed documents.
+    for _ in 0..256 {
+        let dict = node?;
+        if let Ok(value) = dict.get(key) {
+            return Some(value.clone());
+        }
+        node = dict
+            .get(b"Parent")
+            .and_then(lopdf::Object::as_reference)
+            .and_then(|id| doc.get_dictionary(id))
+            .ok();
+    }
+    None
+}
+
+/// Returns the object that the given object refers to, or the object itself if it is not a
+/// reference.
+fn resolve<'a>(doc: &'a lopdf::Document, object: &'a lopdf::Object) -> &'a lopdf::Object {
+    match object {
+        lopdf::Object::Reference(id) => doc.get_object(*id).unwrap_or(object),
+        _ => object,
+    }
+}
+
+/// Adds the form XObjects of the imported pages to the page resources.
+fn set_page_form_xobjects(doc: &mut lopdf::Document, imported_pages: Vec<Vec<ImportedPage>>) {
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    for (page, imported_pages) in pages.into_iter().zip(imported_pages) {
+        if imported_pages.is_empty() {
+            continue;
+        }
+        let mut dict = lopdf::Dictionary::new();
+        for (idx, imported_page) in imported_pages.into_iter().enumerate() {
+            let mut form = (*imported_page.doc).clone();
+            form.renumber_objects_with(doc.max_id + 1);
+            if let Ok(form_id) = form
+                .trailer
+                .get(b"Form")
+                .and_then(lopdf::Object::as_reference)
+            {
+                doc.objects.extend(form.objects);
+                doc.max_id = doc.max_id.max(form.max_id);
+                dict.set(format!("Fm{}", idx), form_id);
+            }
+        }
+        add_page_resources(doc, page, "XObject", dict);
+    }
+}
+
+/// Returns the name of the image XObject for the emoji image of the given character.
+#[cfg_attr(not(feature = "images"), allow(dead_code))]
+fn emoji_image_name(c: char) -> String {
+    format!("Emoji{:X}", u32::from(c))
+}
+
+/// Adds the image XObjects of the emoji images to the page resources.
+///
+/// Every image is only added once, even if it is drawn on multiple pages.
+fn set_page_emoji_images(
+    doc: &mut lopdf::Document,
+    emoji_images: Vec<collections::BTreeMap<char, lopdf::Stream>>,
+) {
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    let mut ids = collections::BTreeMap::new();
+    for (page, images) in pages.into_iter().zip(emoji_images) {
+        if images.is_empty() {
+            continue;
+        }
+        let mut dict = lopdf::Dictionary::new();
+        for (c, image) in images {
+            let id = *ids.entry(c).or_insert_with(|| doc.add_object(image));
+            dict.set(emoji_image_name(c), id);
+        }
+        add_page_resources(doc, page, "XObject", dict);
+    }
+}
+
+/// Adds the given entries to the resources of the given category, for example `XObject`, of the
+/// given page, keeping the existing entries.
+fn add_page_resources(
+    doc: &mut lopdf::Document,
+    page: lopdf::ObjectId,
+    category: &str,
+    entries: lopdf::Dictionary,
+) {
+    let resources = doc
+        .get_dictionary(page)
+        .and_then(|page| page.get(b"Resources"))
+        .cloned();
+    let mut resources = match resources {
+        Ok(lopdf::Object::Reference(id)) => doc.get_dictionary(id).cloned().unwrap_or_default(),
+        Ok(lopdf::Object::Dictionary(resources)) => resources,
+        _ => lopdf::Dictionary::new(),
+    };
+    let mut category_dict = match resources.get(category.as_bytes()) {
+        Ok(lopdf::Object::Reference(id)) => doc.get_dictionary(*id).cloned().unwrap_or_default(),
+        Ok(lopdf::Object::Dictionary(dict)) => dict.clone(),
+        _ => lopdf::Dictionary::new(),
+    };
+    category_dict.extend(&entries);
+    resources.set(category, category_dict);
+    if let Ok(page) = doc.get_dictionary_mut(page) {
+        page.set("Resources", resources);
+    }
+}
+
+/// A tiling pattern that repeats an image, see [`Area::draw_image_tiled`][].
+///
+/// [`Area::draw_image_tiled`]: struct.Area.html#method.draw_image_tiled
+#[cfg_attr(not(feature = "images"), allow(dead_code))]
+struct TilingPattern {
+    image: lopdf::Stream,
+    // the lower left corner of the first tile in user space
+    origin: Position,
+    tile_size: Size,
+}
+
+/// Adds the tiling patterns of the pages to the page resources.
+fn set_page_patterns(doc: &mut lopdf::Document, patterns: Vec<Vec<TilingPattern>>) {
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    for (page, patterns) in pages.into_iter().zip(patterns) {
+        if patterns.is_empty() {
+            continue;
+        }
+        let mut dict = lopdf::Dictionary::new();
+        for (idx, pattern) in patterns.into_iter().enumerate() {
+            let image = doc.add_object(pattern.image);
+            let width = printpdf::Pt::from(pattern.tile_size.width).0;
+            let height = printpdf::Pt::from(pattern.tile_size.height).0;
+            let x = printpdf::Pt::from(pattern.origin.x).0;
+            let y = printpdf::Pt::from(pattern.origin.y).0;
+            let pattern_dict = lopdf::Dictionary::from_iter(vec![
+                ("Type", "Pattern".into()),
+                ("PatternType", 1.into()),
+                ("PaintType", 1.into()),
+                ("TilingType", 1.into()),
+                (
+                    "BBox",
+                    vec![0.into(), 0.into(), width.into(), height.into()].into(),
+                ),
+                ("XStep", width.into()),
+                ("YStep", height.into()),
+                (
+                    "Matrix",
+                    vec![1.into(), 0.into(), 0.into(), 1.into(), x.into(), y.into()].into(),
+                ),
+                (
+                    "Resources",
+                    lopdf::Dictionary::from_iter(vec![(
+                        "XObject",
+                        lopdf::Dictionary::from_iter(vec![("Im0", image.into())]).into(),
+                    )])
+                    .into(),
+                ),
+            ]);
+            let content = format!("q {} 0 0 {} 0 0 cm /Im0 Do Q", width, height);
+            let stream = lopdf::Stream::new(pattern_dict, content.into_bytes());
+            dict.set(format!("P{}", idx), doc.add_object(stream));
+        }
+
+        let resources = doc
+            .get_dictionary(page)
+            .and_then(|page| page.get(b"Resources"))
+            .cloned();
+        match resources {
+            Ok(lopdf::Object::Reference(id)) => {
+                if let Ok(resources) = doc.get_dictionary_mut(id) {
+                    resources.set("Pattern", dict);
+                }
+            }
+            Ok(lopdf::Object::Dictionary(mut resources)) => {
+                resources.set("Pattern", dict);
+                if let Ok(page) = doc.get_dictionary_mut(page) {
+                    page.set("Resources", resources);
+                }
+            }
+            _ => {
+                if let Ok(page) = doc.get_dictionary_mut(page) {
+                    page.set(
+                        "Resources",
+                        lopdf::Dictionary::from_iter(vec![("Pattern", dict.into())]),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Checks that the graphics state and text object operators of the given operations are balanced.
+fn check_content_operators(operations: &[lopdf::content::Operation]) -> Result<(), Error> {
+    let mut depth = 0usize;
+    let mut in_text = false;
+    for operation in operations {
+        let is_valid = match operation.operator.as_str() {
+            "q" if !in_text => {
+                depth += 1;
+                true
+            }
+            "Q" if !in_text && depth > 0 => {
+                depth -= 1;
+                true
+            }
+            "BT" if !in_text => {
+                in_text = true;
+                true
+            }
+            "ET" if in_text => {
+                in_text = false;
+                true
+            }
+            "q" | "Q" | "BT" | "ET" => false,
+            _ => true,
+        };
+        if !is_valid {
+            return Err(Error::new(
+                format!(
+                    "Unexpected {} operator in content stream",
+                    operation.operator
+                ),
+                ErrorKind::InvalidData,
+            ));
+        }
+    }
+    if depth > 0 || in_text {
+        return Err(Error::new(
+            "Unterminated graphics state or text object in content stream",
+            ErrorKind::InvalidData,
+        ));
+    }
+    Ok(())
+}
+
+/// Options for a layer of a page that are applied in the post-processing step.
+#[derive(Clone, Debug, Default)]
+struct LayerOptions {
+    name: Option<String>,
+    hidden: bool,
+}
+
+impl LayerOptions {
+    fn is_modified(&self) -> bool {
+        self.name.is_some() || self.hidden
+    }
+}
+
+/// Applies the layer options to the optional content groups of the given document.
+///
+/// `printpdf` adds one optional content group per layer to the catalog, in the order of the pages
+/// and layers, and turns all of them on by default.
+fn set_layers_options(doc: &mut lopdf::Document, layers_options: &[Vec<LayerOptions>]) {
+    let ocgs = doc
+        .catalog()
+        .and_then(|catalog| catalog.get(b"OCProperties"))
+        .and_then(lopdf::Object::as_dict)
+        .and_then(|properties| properties.get(b"OCGs"))
+        .and_then(lopdf::Object::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    let mut on = Vec::new();
+    let mut off = Vec::new();
+    for (ocg, options) in ocgs.into_iter().zip(layers_options.iter().flatten()) {
+        if let (Ok(id), Some(name)) = (ocg.as_reference(), &options.name) {
+            if let Ok(ocg) = doc.get_dictionary_mut(id) {
+                ocg.set("Name", text_string(name));
+            }
+        }
+        if options.hidden {
+            off.push(ocg);
+        } else {
+            on.push(ocg);
+        }
+    }
+    if off.is_empty() {
+        return;
+    }
+
+    if let Ok(config) = doc
+        .catalog_mut()
+        .and_then(|catalog| catalog.get_mut(b"OCProperties"))
+        .and_then(lopdf::Object::as_dict_mut)
+        .and_then(|properties| properties.get_mut(b"D"))
+        .and_then(lopdf::Object::as_dict_mut)
+    {
+        config.set("ON", on);
+        config.set("OFF", off);
+    }
+}
+
+/// Sets the `Rotate` entries of the pages of the given document.
+fn set_page_rotations(doc: &mut lopdf::Document, rotations: &[i64]) {
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    for (page, rotation) in pages.into_iter().zip(rotations) {
+        if *rotation != 0 {
+            if let Ok(page) = doc.get_dictionary_mut(page) {
+                page.set("Rotate", *rotation);
+            }
+        }
+    }
+}
+
+/// Sets the page boundaries (`CropBox`, `BleedBox` and `TrimBox`) of the pages of the given
+/// document.
+fn set_page_boxes(doc: &mut lopdf::Document, boxes: &[Vec<(&'static str, [f32; 4])>]) {
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    for (page, boxes) in pages.into_iter().zip(boxes) {
+        if let Ok(page) = doc.get_dictionary_mut(page) {
+            for (name, rect) in boxes {
+                let rect = rect.iter().map(|value| (*value).into()).collect();
+                page.set(*name, lopdf::Object::Array(rect));
+            }
+        }
+    }
+}
+
+/// Appends the pages of the other document to the page tree of the given document.
+///
+/// All objects of the other document except for its catalog and its page tree nodes are copied,
+/// so the objects that are not used by the appended pages should be pruned afterwards.  The
+/// attributes that the pages inherit from the dropped page tree nodes are copied to the pages.
+fn append_pages(doc: &mut lopdf::Document, mut other: lopdf::Document) {
+    let pages_id = doc
+        .catalog()
+        .and_then(|catalog| catalog.get(b"Pages"))
+        .and_then(lopdf::Object::as_reference);
+    let pages_id = if let Ok(pages_id) = pages_id {
+        pages_id
+    } else {
+        return;
+    };
+
+    other.renumber_objects_with(doc.max_id + 1);
+    let other_catalog = other
+        .trailer
+        .get(b"Root")
+        .and_then(lopdf::Object::as_reference)
+        .ok();
+    let other_pages: Vec<_> = other.get_pages().into_values().collect();
+    for page in &other_pages {
+        let inherited: Vec<_> = [&b"Resources"[..], b"MediaBox", b"CropBox", b"Rotate"]
+            .iter()
+            .filter_map(|key| {
+                inherited_page_attribute(&other, *page, key).map(|value| (key.to_vec(), value))
+            })
+            .collect();
+        if let Ok(page) = other.get_dictionary_mut(*page) {
+            for (key, value) in inherited {
+                page.set(key, value);
+            }
+        }
+    }
+    for (id, object) in other.objects {
+        let is_page_tree_node = object
+            .as_dict()
+            .and_then(|dict| dict.get(b"Type"))
+            .and_then(lopdf::Object::as_name)
+            .map(|name| name == b"Pages")
+            .unwrap_or_default();
+        if Some(id) != other_catalog && !is_page_tree_node {
+            doc.objects.insert(id, object);
+        }
+    }
+    doc.max_id = doc.max_id.max(other.max_id);
+
+    for page in &other_pages {
+        if let Ok(page) = doc.get_dictionary_mut(*page) {
+            page.set("Parent", lopdf::Object::Reference(pages_id));
+        }
+    }
+    if let Ok(pages) = doc.get_dictionary_mut(pages_id) {
+        let count = pages.get(b"Count").and_then(lopdf::Object::as_i64);
+        pages.set(
+            "Count",
+            count.unwrap_or_default() + other_pages.len() as i64,
+        );
+        if let Ok(kids) = pages.get_mut(b"Kids").and_then(lopdf::Object::as_array_mut) {
+            kids.extend(other_pages.into_iter().map(lopdf::Object::Reference));
+        }
+    }
+}
+
+/// Replaces the references to identical streams in the given document, for example fonts that
+/// are embedded in multiple appended documents, with references to a single stream.
+fn deduplicate_streams(doc: &mut lopdf::Document) {
+    let mut streams: collections::HashMap<(String, u64), lopdf::ObjectId> =
+        collections::HashMap::new();
+    let mut replacements = collections::HashMap::new();
+    for (id, object) in &doc.objects {
+        if let Ok(stream) = object.as_stream() {
+            let mut hasher = collections::hash_map::DefaultHasher::new();
+            stream.content.hash(&mut hasher);
+            let key = (format!("{:?}", stream.dict), hasher.finish());
+            match streams.get(&key) {
+                Some(first)
+                    if doc.objects[first]
+                        .as_stream()
+                        .ok()
+                        .map(|first| first.content == stream.content)
+                        == Some(true) =>
+                {
+                    replacements.insert(*id, *first);
+                }
+                Some(_) => {}
+                None => {
+                    streams.insert(key, *id);
+                }
+            }
+        }
+    }
+    if replacements.is_empty() {
+        return;
+    }
+
+    for object in doc.objects.values_mut() {
+        replace_references(object, &replacements);
+    }
+    for id in replacements.keys() {
+        doc.objects.remove(id);
+    }
+}
+
+/// Replaces the references in the given object according to the given map.
+fn replace_references(
+    object: &mut lopdf::Object,
+    replacements: &collections::HashMap<lopdf::ObjectId, lopdf::ObjectId>,
+) {
+    match object {
+        lopdf::Object::Reference(id) => {
+            if let Some(replacement) = replacements.get(id) {
+                *id = *replacement;
+            }
+        }
+        lopdf::Object::Array(array) => {
+            for object in array {
+                replace_references(object, replacements);
+            }
+        }
+        lopdf::Object::Dictionary(dict) => {
+            for (_, object) in dict.iter_mut() {
+                replace_references(object, replacements);
+            }
+        }
+        lopdf::Object::Stream(stream) => {
+            for (_, object) in stream.dict.iter_mut() {
+                replace_references(object, replacements);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Sets the `Metadata` stream of the catalog of the given document to the given XMP packet.
+fn set_xmp_metadata(doc: &mut lopdf::Document, xmp: &str) {
+    let dict = lopdf::Dictionary::from_iter(vec![
+        ("Type", lopdf::Object::Name(b"Metadata".to_vec())),
+        ("Subtype", lopdf::Object::Name(b"XML".to_vec())),
+    ]);
+    // Metadata streams should not be compressed so that they can be read by tools that do not
+    // parse the PDF file.
+    let stream = lopdf::Stream::new(dict, xmp.as_bytes().to_vec()).with_compression(false);
+    let metadata_id = doc.add_object(stream);
+    if let Some(catalog) = catalog_mut(doc) {
+        catalog.set("Metadata", lopdf::Object::Reference(metadata_id));
+    }
+}
+
+/// Returns the number of color components of the color space of the given ICC profile, or an
+/// error if the profile header is invalid or the color space is not supported.
+pub(crate) fn icc_components(icc_profile: &[u8]) -> Result<i64, Error> {
+    let color_space = if icc_profile.get(36..40) == Some(b"acsp") {
+        icc_profile.get(16..20)
+    } else {
+        None
+    };
+    match color_space {
+        Some(b"GRAY") => Ok(1),
+        Some(b"RGB ") | Some(b"Lab ") => Ok(3),
+        Some(b"CMYK") => Ok(4),
+        _ => Err(Error::new(
+            "Invalid ICC profile: The header is invalid or the color space is not supported",
+            ErrorKind::InvalidData,
+        )),
+    }
+}
+
+/// Writes the given named destinations to the `Dests` name tree of the given document and sets
+/// the `OpenAction` of the document to the destination with the given name.
+///
+/// The destinations are expected in the order of the pages of the document.
+fn set_named_destinations(
+    doc: &mut lopdf::Document,
+    destinations: Vec<Vec<(String, [f32; 2])>>,
+    open_destination: Option<&str>,
+) -> Result<(), Error> {
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    // The names in a name tree must be sorted.
+    let mut targets = collections::BTreeMap::new();
+    for (page, destinations) in pages.into_iter().zip(destinations) {
+        for (name, [left, top]) in destinations {
+            let target = vec![
+                lopdf::Object::Reference(page),
+                lopdf::Object::Name(b"XYZ".to_vec()),
+                left.into(),
+                top.into(),
+                lopdf::Object::Null,
+            ];
+            targets.insert(name, target);
+        }
+    }
+    let open_action = match open_destination {
+        Some(name) => Some(targets.get(name).cloned().ok_or_else(|| {
+            Error::new(
+                format!("The open destination {} does not exist", name),
+                ErrorKind::InvalidData,
+            )
+        })?),
+        None => None,
+    };
+    let names = targets
+        .into_iter()
+        .flat_map(|(name, target)| {
+            vec![
+                lopdf::Object::string_literal(name),
+                lopdf::Object::Array(target),
+            ]
+        })
+        .collect();
+    let dests = lopdf::Dictionary::from_iter(vec![("Names", lopdf::Object::Array(names))]);
+    let dests_id = doc.add_object(dests);
+
+    if let Some(catalog) = catalog_mut(doc) {
+        let mut names = catalog
+            .get(b"Names")
+            .and_then(lopdf::Object::as_dict)
+            .cloned()
+            .unwrap_or_default();
+        names.set("Dests", lopdf::Object::Reference(dests_id));
+        catalog.set("Names", lopdf::Object::Dictionary(names));
+        if let Some(open_action) = open_action {
+            catalog.set("OpenAction", lopdf::Object::Array(open_action));
+        }
+    }
+    Ok(())
+}
+
+/// Adds the given signature fields as widget annotations to the pages of the given document and
+/// to the `AcroForm` dictionary of its catalog.
+///
+/// The fields are expected in the order of the pages of the document.  The names of the fields
+/// must be unique.
+fn add_signature_fields(
+    doc: &mut lopdf::Document,
+    fields: Vec<Vec<(String, [f32; 4])>>,
+) -> Result<(), Error> {
+    let mut names = collections::HashSet::new();
+    for (name, _) in fields.iter().flatten() {
+        if !names.insert(name) {
+            return Err(Error::new(
+                format!("The signature field name {} is used more than once", name),
+                ErrorKind::InvalidData,
+            ));
+        }
+    }
+
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    let mut field_ids = Vec::new();
+    for (page, fields) in pages.into_iter().zip(fields) {
+        let mut annotation_ids = Vec::new();
+        for (name, rect) in fields {
+            let field = lopdf::Dictionary::from_iter(vec![
+                ("Type", lopdf::Object::Name(b"Annot".to_vec())),
+                ("Subtype", lopdf::Object::Name(b"Widget".to_vec())),
+                ("FT", lopdf::Object::Name(b"Sig".to_vec())),
+                ("T", lopdf::Object::string_literal(name)),
+                (
+                    "Rect",
+                    lopdf::Object::Array(rect.iter().map(|value| (*value).into()).collect()),
+                ),
+                // Print the annotation.
+                ("F", 4.into()),
+                ("P", lopdf::Object::Reference(page)),
+            ]);
+            annotation_ids.push(doc.add_object(field));
+        }
+        add_page_annotations(doc, page, &annotation_ids);
+        field_ids.extend(annotation_ids);
+    }
+
+    if let Some(catalog) = catalog_mut(doc) {
+        let fields = field_ids
+            .into_iter()
+            .map(lopdf::Object::Reference)
+            .collect();
+        let acro_form =
+            lopdf::Dictionary::from_iter(vec![("Fields", lopdf::Object::Array(fields))]);
+        catalog.set("AcroForm", lopdf::Object::Dictionary(acro_form));
+    }
+    Ok(())
+}
+
+/// Adds the given links as link annotations with URI actions to the pages of the given document.
+///
+/// The links are expected in the order of the pages of the document.
+fn add_links(doc: &mut lopdf::Document, links: Vec<Vec<(String, [f32; 4])>>) {
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    for (page, links) in pages.into_iter().zip(links) {
+        let mut annotation_ids = Vec::new();
+        for (uri, rect) in links {
+            let action = lopdf::Dictionary::from_iter(vec![
+                ("S", lopdf::Object::Name(b"URI".to_vec())),
+                ("URI", lopdf::Object::string_literal(uri)),
+            ]);
+            let link = lopdf::Dictionary::from_iter(vec![
+                ("Type", lopdf::Object::Name(b"Annot".to_vec())),
+                ("Subtype", lopdf::Object::Name(b"Link".to_vec())),
+                (
+                    "Rect",
+                    lopdf::Object::Array(rect.iter().map(|value| (*value).into()).collect()),
+                ),
+                (
+                    "Border",
+                    lopdf::Object::Array(vec![0.into(), 0.into(), 0.into()]),
+                ),
+                ("A", lopdf::Object::Dictionary(action)),
+            ]);
+            annotation_ids.push(doc.add_object(link));
+        }
+        add_page_annotations(doc, page, &annotation_ids);
+    }
+}
+
+/// Returns the document catalog of the given document, if it exists.
+fn catalog_mut(doc: &mut lopdf::Document) -> Option<&mut lopdf::Dictionary> {
+    doc.trailer
+        .get(b"Root")
+        .and_then(lopdf::Object::as_reference)
+        .and_then(|id| doc.get_object_mut(id))
+        .and_then(lopdf::Object::as_dict_mut)
+        .ok()
+}
+
+/// Appends the given annotations to the `Annots` array of the given page.
+fn add_page_annotations(
+    doc: &mut lopdf::Document,
+    page: lopdf::ObjectId,
+    annotation_ids: &[lopdf::ObjectId],
+) {
+    let annotations = doc
+        .get_dictionary(page)
+        .and_then(|page| page.get(b"Annots"))
+        .cloned();
+    let mut annotations = match annotations {
+        Ok(lopdf::Object::Reference(id)) => doc
+            .get_object(id)
+            .and_then(lopdf::Object::as_array)
+            .cloned()
+            .unwrap_or_default(),
+        Ok(lopdf::Object::Array(annotations)) => annotations,
+        _ => Vec::new(),
+    };
+    annotations.extend(annotation_ids.iter().copied().map(lopdf::Object::Reference));
+    if let Ok(page) = doc.get_dictionary_mut(page) {
+        page.set("Annots", lopdf::Object::Array(annotations));
+    }
+}
+
+/// Embeds the given ICC profile and sets it as the PDF/X output intent of the given document.
+fn set_output_intent(doc: &mut lopdf::Document, icc_profile: &[u8], name: &str) {
+    let mut dict = lopdf::Dictionary::new();
+    if let Ok(components) = icc_components(icc_profile) {
+        dict.set("N", components);
+    }
+    let stream = lopdf::Stream::new(dict, icc_profile.to_vec());
+    let profile_id = doc.add_object(stream);
+    let name = lopdf::Object::string_literal(name);
+    let output_intent = lopdf::Dictionary::from_iter(vec![
+        ("Type", lopdf::Object::Name(b"OutputIntent".to_vec())),
+        ("S", lopdf::Object::Name(b"GTS_PDFX".to_vec())),
+        ("OutputConditionIdentifier", name.clone()),
+        ("Info", name),
+        (
+            "DestinationOutputProfile",
+            lopdf::Object::Reference(profile_id),
+        ),
+    ]);
+    if let Some(catalog) = catalog_mut(doc) {
+        catalog.set(
+            "OutputIntents",
+            lopdf::Object::Array(vec![lopdf::Object::Dictionary(output_intent)]),
+        );
+    }
+}
+
+/// Sets the `Alt` entries of the image XObjects of the given document.
+///
+/// The alternate texts are stored in the property lists of the `Span` marked-content sequences
+/// that contain the images.  They are copied to the image XObjects that are referenced by the
+/// `Do` operators in these sequences.
+fn set_image_alt_texts(doc: &mut lopdf::Document, has_alt_texts: &[bool]) {
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    for (page, has_alt_texts) in pages.into_iter().zip(has_alt_texts) {
+        if !has_alt_texts {
+            continue;
+        }
+        let content = match doc
+            .get_page_content(page)
+            .ok()
+            .and_then(|data| lopdf::content::Content::decode(&data).ok())
+        {
+            Some(content) => content,
+            None => continue,
+        };
+        let (resources, resource_ids) = doc.get_page_resources(page);
+        let resources = resources.or_else(|| {
+            resource_ids
+                .first()
+                .and_then(|id| doc.get_dictionary(*id).ok())
+        });
+        let xobjects = resources
+            .and_then(|resources| resources.get(b"XObject").ok())
+            .and_then(|xobjects| match xobjects {
+                lopdf::Object::Reference(id) => doc.get_dictionary(*id).ok(),
+                lopdf::Object::Dictionary(dict) => Some(dict),
+                _ => None,
+            })
+            .cloned();
+        let xobjects = match xobjects {
+            Some(xobjects) => xobjects,
+            None => continue,
+        };
+
+        let mut alt_text = None;
+        for operation in content.operations {
+            match (operation.operator.as_str(), operation.operands.as_slice()) {
+                ("BDC", [lopdf::Object::Name(tag), lopdf::Object::Dictionary(properties)])
+                    if tag == b"Span" =>
+                {
+                    alt_text = properties.get(b"Alt").ok().cloned();
+                }
+                ("EMC", _) => alt_text = None,
+                ("Do", [lopdf::Object::Name(name)]) => {
+                    let xobject = xobjects.get(name).and_then(lopdf::Object::as_reference);
+                    if let (Ok(xobject), Some(alt_text)) = (xobject, alt_text.take()) {
+                        if let Ok(lopdf::Object::Stream(stream)) = doc.get_object_mut(xobject) {
+                            stream.dict.set("Alt", alt_text);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Encodes the given string as a PDF text string.
+fn text_string(s: &str) -> lopdf::Object {
+    if s.is_ascii() {
+        lopdf::Object::string_literal(s)
+    } else {
+        // Non-ASCII text strings are encoded as UTF-16BE with a byte order mark.
+        let bytes = [0xfe, 0xff]
+            .into_iter()
+            .chain(s.encode_utf16().flat_map(u16::to_be_bytes))
+            .collect();
+        lopdf::Object::String(bytes, lopdf::StringFormat::Hexadecimal)
+    }
+}
+
+/// Information about a string that has been printed with [`Area::print_str_fitted`][].
+///
+/// [`Area::print_str_fitted`]: struct.Area.html#method.print_str_fitted
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FitInfo<'s> {
+    /// The part of the string that has been printed.
+    pub printed: &'s str,
+    /// The part of the string that did not fit into the area and has not been printed.
+    pub rest: &'s str,
+    /// The number of characters that have been printed.
+    pub chars: usize,
+    /// The width of the printed part of the string.
+    pub width: Mm,
+}
+
+impl<'s> FitInfo<'s> {
+    fn new(s: &'s str, idx: usize, width: Mm) -> FitInfo<'s> {
+        let (printed, rest) = s.split_at(idx);
+        FitInfo {
+            printed,
+            rest,
+            chars: printed.chars().count(),
+            width,
+        }
+    }
+
+    /// Returns `true` if the complete string has been printed.
+    pub fn is_complete(&self) -> bool {
+        self.rest.is_empty()
+    }
+}
+
+/// A text section that is drawn on an area of a PDF layer.
+pub struct TextSection<'f, 'p> {
+    font_cache: &'f fonts::FontCache,
+    area: Area<'p>,
+    is_first: bool,
+    metrics: fonts::Metrics,
+    height: Mm,
+    font: Option<(printpdf::IndirectFontRef, u8)>,
+    // The text rise is part of the graphics state, so it is reset when the section is dropped.
+    text_rise: Mm,
+    last_char: Option<(fonts::Font, u8, char)>,
+    // The manual adjustment of the advance before the next character in 1/1000 em.
+    advance_adjustment: f32,
+    is_clipped: bool,
+    // The horizontal offset of the start of the lines and of the current position in the line,
+    // relative to the left edge of the area.  They are needed to position emoji images and
+    // highlights.
+    line_start: Mm,
+    line_offset: Mm,
+    // The additional height of the current line if it contains vertical runs that are higher than
+    // the line height.
+    line_extent: Mm,
+    // The origin of the area that this section was created with.
+    parent_origin: Position,
+}
+
+impl<'f, 'p> TextSection<'f, 'p> {
+    fn new(
+        font_cache: &'f fonts::FontCache,
+        area: Area<'p>,
+        metrics: fonts::Metrics,
+        parent_origin: Position,
+    ) -> Option<TextSection<'f, 'p>> {
+        let offset = baseline_offset(&area, metrics);
+        if offset + metrics.glyph_height > area.size.height {
+            return None;
+        }
+
+        Some(TextSection::new_unchecked(
+            font_cache,
+            area,
+            metrics,
+            parent_origin,
+            false,
+        ))
+    }
+
+    fn new_unchecked(
+        font_cache: &'f fonts::FontCache,
+        mut area: Area<'p>,
+        mut metrics: fonts::Metrics,
+        parent_origin: Position,
+        is_clipped: bool,
+    ) -> TextSection<'f, 'p> {
+        let offset = baseline_offset(&area, metrics);
+        area.add_offset((0, offset));
+        if let Some(grid) = area.layer.page.baseline_grid {
+            metrics.line_height = snap_to_grid(metrics.line_height, grid).max(grid);
+        }
+
+        area.layer.begin_text_section();
+        area.layer.set_line_height(metrics.line_height);
+
+        TextSection {
+            font_cache,
+            area,
+            is_first: true,
+            metrics,
+            height: offset + metrics.line_height,
+            font: None,
+            text_rise: Mm(0.0),
+            last_char: None,
+            advance_adjustment: 0.0,
+            is_clipped,
+            line_start: Mm(0.0),
+            line_offset: Mm(0.0),
+            line_extent: Mm(0.0),
+            parent_origin,
+        }
+    }
+
+    /// Returns the current position of the text cursor, i. e. the point on the baseline where the
+    /// next string will be printed.
+    ///
+    /// The position is relative to the upper left corner of the area that this text section was
+    /// created with.  It can be used to draw decorations that are aligned with the printed text.
+    /// Note that the start of the first line is moved to the left by the left side bearing of the
+    /// first printed character so that the glyph outlines start at the left border.
+    pub fn cursor_position(&self) -> Position {
+        let origin = self.area.origin - self.parent_origin;
+        origin + Position::new(self.line_start + self.line_offset, self.metrics.ascent)
+    }
+
+    /// Returns the vertical space used by the lines of this text section.
+    ///
+    /// This is the line height multiplied with the number of lines, plus the additional height of
+    /// vertical runs that are higher than their line.  If the page has a baseline grid, it also
+    /// includes the offset that moves the first baseline to the grid.
+    pub fn height(&self) -> Mm {
+        self.height
+    }
+
+    fn set_text_cursor(&self, x_offset: Mm) {
+        let cursor = self
+            .area
+            .position(Position::new(x_offset, self.metrics.ascent));
+        self.area.layer.set_text_cursor(cursor);
+    }
+
+    fn set_font(&mut self, font: &printpdf::IndirectFontRef, font_size: u8) {
+        let font_is_set = self
+            .font
+            .as_ref()
+            .map(|(font, font_size)| (font, *font_size))
+            .map(|data| data == (font, font_size))
+            .unwrap_or_default();
+        if !font_is_set {
+            self.font = Some((font.clone(), font_size));
+            self.area.layer.set_font(font, font_size);
+        }
+    }
+
+    fn set_text_rise(&mut self, rise: Mm) {
+        if self.text_rise != rise {
+            self.text_rise = rise;
+            self.area.layer.set_text_rise(rise);
+        }
+    }
+
+    /// Tries to add a new line and returns `true` if the area was large enough to fit the new
+    /// line.
+    #[must_use]
+    pub fn add_newline(&mut self) -> bool {
+        let offset = self.metrics.line_height + self.line_extent;
+        if offset > self.area.size.height {
+            false
+        } else {
+            if self.line_extent > Mm(0.0) {
+                // The line break operator only moves the cursor by the line height, so we start a
+                // new text object below the extended line.
+                self.area.layer.end_text_section();
+                self.area.layer.begin_text_section();
+                self.area.add_offset((0, offset));
+                self.set_text_cursor(self.line_start);
+            } else {
+                self.area.layer.add_line_break();
+                self.area.add_offset((0, offset));
+            }
+            self.height += self.metrics.line_height;
+            self.last_char = None;
+            self.advance_adjustment = 0.0;
+            self.line_offset = Mm(0.0);
+            self.line_extent = Mm(0.0);
+            true
+        }
+    }
+
+    /// Moves the text cursor to the right by the given width without printing anything, for
+    /// example to insert additional space between the words of justified text.
+    ///
+    /// If this method is called before the first string of this section is printed, it moves the
+    /// start of all lines of this section.
+    pub fn add_space(&mut self, width: Mm) {
+        if self.is_first {
+            self.line_start += width;
+            self.set_text_cursor(self.line_start);
+            self.is_first = false;
+        } else if let Some((_, font_size)) = self.font {
+            self.area.layer.move_text_cursor(width, font_size);
+            self.line_offset += width;
+        }
+    }
+
+    /// Adjusts the advance between the last printed character and the next printed character by
+    /// the given amount in thousandths of an em.
+    ///
+    /// The adjustment is added to the kerning of the character pair and is written to the
+    /// positioned codepoints of the next printed string, so it is applied even if kerning is
+    /// disabled for the style.  Positive values increase the distance between the characters,
+    /// negative values decrease it.  Multiple adjustments before the next character add up.  The
+    /// adjustment is discarded if a new line is started, and it is not applied to vertical text
+    /// (see [`Style::set_vertical`][]).
+    ///
+    /// [`Style::set_vertical`]: ../style/struct.Style.html#method.set_vertical
+    pub fn add_advance_adjustment(&mut self, adjustment: f32) {
+        self.advance_adjustment += adjustment;
+    }
+
+    /// Prints the given string with the given style.
+    ///
+    /// The font cache for this text section must contain the PDF font for the given style.
+    ///
+    /// Characters with an emoji image (see [`FontCache::add_emoji_image`][]) are replaced with
+    /// the image.  If the area of this text section does not have a positive width, an error
+    /// with the [`AreaTooSmall`][] kind is returned.
+    ///
+    /// [`FontCache::add_emoji_image`]: ../fonts/struct.FontCache.html#method.add_emoji_image
+    /// [`AreaTooSmall`]: ../error/enum.ErrorKind.html#variant.AreaTooSmall
+    pub fn print_str(&mut self, s: impl AsRef<str>, style: Style) -> Result<(), Error> {
+        let s = s.as_ref();
+        check_text_width(&self.area)?;
+        #[cfg(feature = "images")]
+        if self.font_cache.has_emoji_images() {
+            let mut start = 0;
+            for (idx, c) in s.char_indices() {
+                if let Some(image) = self.font_cache.emoji_image(c) {
+                    self.print_text(&s[start..idx], style)?;
+                    self.print_emoji(image, c, style);
+                    start = idx + c.len_utf8();
+                }
+            }
+            return self.print_text(&s[start..], style);
+        }
+        self.print_text(s, style)
+    }
+
+    /// Prints the given string with the given style and returns the size of the printed run.
+    ///
+    /// The width is the advance width of the run, including the kerning with the previous run on
+    /// the same line, and the height is the line height of this section.  Text sections never wrap
+    /// lines, so the run always occupies a single line.  Vertical runs (see
+    /// [`Style::set_vertical`][]) can be higher than the line if they contain multiple glyphs.
+    /// Otherwise, this method behaves like [`print_str`][].
+    ///
+    /// [`Style::set_vertical`]: ../style/struct.Style.html#method.set_vertical
+    /// [`print_str`]: #method.print_str
+    pub fn print_str_bounded(&mut self, s: impl AsRef<str>, style: Style) -> Result<Size, Error> {
         let s = s.as_ref();
+        let start = self.line_offset;
+        self.print_str(s, style)?;
+        let mut height = self.metrics.line_height;
+        if style.is_vertical() {
+            let glyph_height = style.font(self.font_cache).glyph_height(style.font_size());
+            let glyphs = s
+                .split('\n')
+                .map(|column| column.chars().count())
+                .max()
+                .unwrap_or_default();
+            height = height.max(glyph_height * glyphs as f32);
+        }
+        Ok(Size::new(self.line_offset - start, height))
+    }
 
-        // Adjust cursor to remove left bearing of the first character of the first string
+    /// Prints the given string with the given style and moves the text cursor back to the start of
+    /// the string.
+    ///
+    /// The next string is printed at the same position, so it overprints this string, for example
+    /// to combine a character with a custom diacritic.  The kerning of the next string is
+    /// calculated with the character before this string.  Otherwise, this method behaves like
+    /// [`print_str`][].
+    ///
+    /// [`print_str`]: #method.print_str
+    pub fn print_str_no_advance(&mut self, s: impl AsRef<str>, style: Style) -> Result<(), Error> {
+        let start = self.line_offset;
+        let last_char = self.last_char;
+        self.print_str(s, style)?;
+        let width = self.line_offset - start;
+        if width != Mm(0.0) {
+            if let Some((_, font_size)) = self.font {
+                self.area.layer.move_text_cursor(width * -1.0, font_size);
+            }
+            self.line_offset = start;
+        }
+        self.last_char = last_char;
+        Ok(())
+    }
+
+    fn print_text(&mut self, s: &str, style: Style) -> Result<(), Error> {
+        let font = style.font(self.font_cache);
+
+        // Empty strings don’t print anything, so they must not consume the first-string cursor
+        // adjustment below.
+        let first_c = if let Some(c) = s.chars().next() {
+            c
+        } else {
+            return Ok(());
+        };
+
+        if !self.area.is_dry_run() {
+            self.font_cache.report_missing_glyphs(font, s);
+        }
+
+        // Adjust cursor to remove left bearing of the first character of the first string.
+        // Whitespace has no outline, so we keep its full advance instead of shifting the cursor.
         if self.is_first {
-            let x_offset = if let Some(first_c) = s.chars().next() {
-                style.char_left_side_bearing(self.font_cache, first_c) * -1.0
-            } else {
+            let x_offset = if first_c.is_whitespace() {
                 Mm(0.0)
+            } else {
+                style.char_left_side_bearing(self.font_cache, first_c) * -1.0
             };
             self.set_text_cursor(x_offset);
+            self.line_start = x_offset;
+            self.is_first = false;
         }
-        self.is_first = false;
 
-        let positions = font
-            .kerning(self.font_cache, s.chars())
+        if style.is_vertical() {
+            return self.print_vertical_text(s, style);
+        }
+
+        // If the previous string on this line used the same font, we also apply the kerning for
+        // the pair that straddles the string boundary.
+        let mut kerning = match self.last_char {
+            _ if !style.is_kerning_enabled() => vec![0.0; s.chars().count()],
+            Some((last_font, last_font_size, last_c))
+                if last_font == font && last_font_size == style.font_size() =>
+            {
+                let mut kerning =
+                    font.kerning(self.font_cache, iter::once(last_c).chain(s.chars()));
+                kerning.remove(0);
+                kerning
+            }
+            _ => font.kerning(self.font_cache, s.chars()),
+        };
+        // The kerning is measured in em
+        kerning[0] += self.advance_adjustment / 1000.0;
+        self.advance_adjustment = 0.0;
+        self.last_char = s.chars().last().map(|c| (font, style.font_size(), c));
+        let font_size = style.font_size();
+        let kerning_width = kerning.iter().sum::<f32>() * f32::from(font_size);
+        let width = s
+            .chars()
+            .map(|c| font.char_width(self.font_cache, c, font_size))
+            .sum::<Mm>()
+            + Mm::from(printpdf::Pt(kerning_width));
+        if let Some(highlight) = style.highlight() {
+            self.print_highlight(width, self.metrics.line_height, highlight);
+        }
+        if style.is_overline() {
+            self.print_overline(width, font, style);
+        }
+        self.line_offset += width;
+
+        let positions = kerning
             .into_iter()
             // Kerning is measured in 1/1000 em
             .map(|pos| pos * -1000.0)
@@ -730,23 +3522,301 @@ impl<'f, 'p> TextSection<'f, 'p> {
             font.glyph_ids(self.font_cache, s.chars())
         };
 
-        let font = self
+        let font = self
+            .font_cache
+            .get_pdf_font(font)
+            .expect("Could not find PDF font in font cache");
+        self.area.layer.set_fill_color(style.color());
+        self.set_font(font, style.font_size());
+        self.set_text_rise(style.baseline_shift());
+
+        self.area
+            .layer
+            .write_positioned_codepoints(positions, codepoints);
+        Ok(())
+    }
+
+    fn print_vertical_text(&mut self, s: &str, style: Style) -> Result<(), Error> {
+        let font = style.font(self.font_cache);
+        let font_size = style.font_size();
+        let width = style.str_width(self.font_cache, s);
+        let advance = font.glyph_height(font_size);
+        let glyphs = s
+            .split('\n')
+            .map(|column| column.chars().count())
+            .max()
+            .unwrap_or_default();
+        let height = self.metrics.line_height.max(advance * glyphs as f32);
+        if let Some(highlight) = style.highlight() {
+            self.print_highlight(width, height, highlight);
+        }
+
+        let pdf_font = self
+            .font_cache
+            .get_pdf_font(font)
+            .expect("Could not find PDF font in font cache");
+        self.area.layer.set_fill_color(style.color());
+        self.set_font(pdf_font, font_size);
+        self.set_text_rise(style.baseline_shift());
+
+        // Every glyph needs its own cursor position, but text positioning operators are relative
+        // to the start of the current line, so we start a new text object for every glyph.  The
+        // columns are separated by line breaks and advance from right to left.
+        let mut x = self.line_start + self.line_offset + width;
+        for column in s.split('\n') {
+            x -= style.str_width(self.font_cache, column);
+            for (i, c) in column.chars().enumerate() {
+                let codepoints = if font.is_builtin() {
+                    encode_win1252(c.encode_utf8(&mut [0; 4]))?
+                } else {
+                    font.glyph_ids(self.font_cache, iter::once(c))
+                };
+                let y = self.metrics.ascent + advance * i as f32;
+                self.area.layer.end_text_section();
+                self.area.layer.begin_text_section();
+                self.area
+                    .layer
+                    .set_text_cursor(self.area.position(Position::new(x, y)));
+                self.area
+                    .layer
+                    .write_positioned_codepoints(iter::repeat(0), codepoints);
+            }
+        }
+
+        // Runs that are higher than the line increase the height of the line.
+        let line_extent = height - self.metrics.line_height;
+        if line_extent > self.line_extent {
+            self.height += line_extent - self.line_extent;
+            self.line_extent = line_extent;
+        }
+
+        // Restore the start of the line so that line breaks still work, and then move the cursor
+        // behind the column.
+        self.area.layer.end_text_section();
+        self.area.layer.begin_text_section();
+        self.set_text_cursor(self.line_start);
+        self.line_offset += width;
+        self.area
+            .layer
+            .move_text_cursor(self.line_offset, font_size);
+        self.last_char = None;
+        Ok(())
+    }
+
+    fn print_highlight(&mut self, width: Mm, height: Mm, color: Color) {
+        let position = Position::new(self.line_start + self.line_offset, 0);
+        self.print_decoration(|area| area.fill_rect(position, Size::new(width, height), color));
+    }
+
+    fn print_overline(&mut self, width: Mm, font: fonts::Font, style: Style) {
+        let font_size = style.font_size();
+        let thickness = Mm::from(printpdf::Pt(f32::from(font_size) / 20.0));
+        // The line is drawn with a gap of one line thickness above the cap height.
+        let y = self.metrics.ascent
+            - style.baseline_shift()
+            - font.cap_height(font_size)
+            - thickness * 1.5;
+        let x = self.line_start + self.line_offset;
+        let line_style = LineStyle::new()
+            .with_thickness(thickness)
+            .with_color(style.color().unwrap_or(Color::Rgb(0, 0, 0)));
+        self.print_decoration(|area| {
+            area.draw_line(
+                vec![Position::new(x, y), Position::new(x + width, y)],
+                line_style,
+            )
+        });
+    }
+
+    /// Interrupts the text object of this section to draw a decoration of the current run using
+    /// the given function.
+    fn print_decoration(&mut self, f: impl FnOnce(&Area<'p>)) {
+        // Paths cannot be drawn inside of a text object, so we have to interrupt it.
+        self.area.layer.end_text_section();
+        f(&self.area);
+        self.area.layer.begin_text_section();
+
+        // Restore the start of the line so that line breaks still work, and then move the cursor
+        // to the current position.
+        self.set_text_cursor(self.line_start);
+        if self.line_offset != Mm(0.0) {
+            if let Some((_, font_size)) = self.font {
+                self.area
+                    .layer
+                    .move_text_cursor(self.line_offset, font_size);
+            }
+        }
+    }
+
+    #[cfg(feature = "images")]
+    fn print_emoji(&mut self, image: &printpdf::image_crate::DynamicImage, c: char, style: Style) {
+        use printpdf::image_crate::GenericImageView as _;
+
+        let font = style.font(self.font_cache);
+        let font_size = style.font_size();
+        if self.is_first {
+            self.set_text_cursor(self.line_start);
+            self.is_first = false;
+        }
+
+        // XObjects cannot be used inside of a text object, so we have to interrupt it.
+        self.area.layer.end_text_section();
+        // The image spans the glyph height, i. e. its bottom edge is on the descent line.
+        let position = Position::new(
+            self.line_start + self.line_offset,
+            self.metrics.ascent - font.descent(font_size),
+        );
+        // The image is scaled to the glyph height, preserving its aspect ratio.
+        let (width, height) = image.dimensions();
+        let height_mm = font.glyph_height(font_size);
+        let width_mm = height_mm * (width as f32 / height.max(1) as f32);
+        self.area.layer.add_emoji_image(
+            c,
+            image,
+            self.area.position(position),
+            Size::new(width_mm, height_mm),
+        );
+        self.area.layer.begin_text_section();
+
+        // Restore the start of the line so that line breaks still work, and then move the cursor
+        // behind the image.
+        self.set_text_cursor(self.line_start);
+        self.line_offset += font.char_width(self.font_cache, c, font_size);
+        let pdf_font = self
             .font_cache
             .get_pdf_font(font)
             .expect("Could not find PDF font in font cache");
-        self.area.layer.set_fill_color(style.color());
-        self.set_font(font, style.font_size());
-
+        self.set_font(pdf_font, font_size);
         self.area
             .layer
-            .write_positioned_codepoints(positions, codepoints);
-        Ok(())
+            .move_text_cursor(self.line_offset, font_size);
+        self.last_char = None;
     }
 }
 
 impl<'f, 'p> Drop for TextSection<'f, 'p> {
     fn drop(&mut self) {
+        self.set_text_rise(Mm(0.0));
         self.area.layer.end_text_section();
+        if self.is_clipped {
+            self.area.layer.restore_graphics_state();
+        }
+    }
+}
+
+/// Returns the vertical offset that moves the first baseline of a text section in the given area
+/// down to the baseline grid of the page, or zero if the page does not have a baseline grid.
+///
+/// The offset is never negative so that the text does not overlap the content above the area.
+fn baseline_offset(area: &Area<'_>, metrics: fonts::Metrics) -> Mm {
+    if let Some(grid) = area.layer.page.baseline_grid {
+        let baseline = area.origin.y + metrics.ascent;
+        (snap_to_grid(baseline, grid) - baseline).max(Mm(0.0))
+    } else {
+        Mm(0.0)
+    }
+}
+
+/// Rounds the given value up to the next multiple of the grid spacing.
+///
+/// Values that are a multiple of the grid spacing except for rounding errors are not changed.
+fn snap_to_grid(value: Mm, grid: Mm) -> Mm {
+    grid * (value.0 / grid.0 - 1e-4).ceil()
+}
+
+/// Returns an error if the width of the given area is not positive so that no text can be printed
+/// in it, for example because the margins are wider than the page.
+pub(crate) fn check_text_width(area: &Area<'_>) -> Result<(), Error> {
+    let width = area.size().width;
+    if width.0 > 0.0 {
+        Ok(())
+    } else {
+        Err(Error::new(
+            format!(
+                "Cannot print text in an area with a width of {} mm",
+                width.0
+            ),
+            ErrorKind::AreaTooSmall,
+        ))
+    }
+}
+
+/// Returns an error if the given baseline grid spacing is not a positive number.
+pub(crate) fn check_grid_spacing(spacing: Mm) -> Result<(), Error> {
+    if spacing.0.is_finite() && spacing.0 > 0.0 {
+        Ok(())
+    } else {
+        Err(Error::new(
+            format!(
+                "The baseline grid spacing must be positive, received {}",
+                spacing.0
+            ),
+            ErrorKind::InvalidData,
+        ))
+    }
+}
+
+/// Checks whether the given font data contains an OpenType font with CFF outlines.
+fn is_cff_font(data: &[u8]) -> bool {
+    data.starts_with(b"OTTO")
+}
+
+/// Changes the font dictionaries of embedded OpenType fonts with CFF outlines.
+///
+/// `printpdf` always embeds fonts as TrueType fonts, i. e. as a `CIDFontType2` font with a
+/// `FontFile2` stream.  Fonts with CFF outlines have to be embedded as a `CIDFontType0` font with a
+/// `FontFile3` stream of the subtype `OpenType` instead.
+fn embed_cff_fonts(doc: &mut lopdf::Document) {
+    let mut descriptors = Vec::new();
+    for (id, object) in &doc.objects {
+        let font_file = object
+            .as_dict()
+            .and_then(|dict| dict.get(b"FontFile2"))
+            .and_then(lopdf::Object::as_reference);
+        if let Ok(font_file) = font_file {
+            let is_cff = doc
+                .get_object(font_file)
+                .and_then(lopdf::Object::as_stream)
+                .map(|stream| is_cff_font(&stream.content))
+                .unwrap_or_default();
+            if is_cff {
+                descriptors.push((*id, font_file));
+            }
+        }
+    }
+
+    for (descriptor, font_file) in &descriptors {
+        if let Ok(lopdf::Object::Stream(stream)) = doc.get_object_mut(*font_file) {
+            stream
+                .dict
+                .set("Subtype", lopdf::Object::Name(b"OpenType".to_vec()));
+        }
+        if let Ok(lopdf::Object::Dictionary(dict)) = doc.get_object_mut(*descriptor) {
+            dict.remove(b"FontFile2");
+            dict.set("FontFile3", lopdf::Object::Reference(*font_file));
+        }
+    }
+
+    // printpdf stores the CID font inline in the DescendantFonts array of the Type0 font.
+    for object in doc.objects.values_mut() {
+        let fonts = match object {
+            lopdf::Object::Dictionary(dict) => dict.get_mut(b"DescendantFonts"),
+            _ => continue,
+        };
+        if let Ok(lopdf::Object::Array(fonts)) = fonts {
+            for font in fonts {
+                if let lopdf::Object::Dictionary(font) = font {
+                    let descriptor = font
+                        .get(b"FontDescriptor")
+                        .and_then(lopdf::Object::as_reference);
+                    if let Ok(descriptor) = descriptor {
+                        if descriptors.iter().any(|(id, _)| *id == descriptor) {
+                            font.set("Subtype", lopdf::Object::Name(b"CIDFontType0".to_vec()));
+                        }
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -772,3 +3842,1236 @@ fn encode_win1252(s: &str) -> Result<Vec<u16>, Error> {
         Ok(bytes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+
+    use super::Renderer;
+    use crate::fonts::{FontCache, FontData, FontFamily, Metrics};
+    use crate::style::{Color, LineCap, LineJoin, LineStyle, Style};
+    use crate::tests::{as_f32, find_operations, font_cache, page_operations, write_renderer};
+    use crate::{Mm, Position, Size};
+
+    #[test]
+    fn test_print_str_leading_whitespace() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new();
+        {
+            let area = renderer.first_page().first_layer().area();
+            let mut section = area
+                .text_section(&font_cache, Position::default(), style.metrics(&font_cache))
+                .unwrap();
+            section.print_str("", style).unwrap();
+            section.print_str(" Hello", style).unwrap();
+            section.print_str("World", style).unwrap();
+        }
+
+        let doc = write_renderer(renderer);
+        // The cursor is set exactly once, at the left border of the area, and is not shifted by
+        // the left side bearing of the first visible character.
+        let cursors = find_operations(&doc, 0, "Td");
+        assert_eq!(1, cursors.len());
+        assert_eq!(Mm(0.0), Mm::from(printpdf::Pt(as_f32(&cursors[0][0]))));
+        // The empty string does not produce a text operation, and the leading space is printed
+        // as a glyph, so its advance is kept.
+        let texts = find_operations(&doc, 0, "TJ");
+        assert_eq!(2, texts.len());
+        let space = style.font(&font_cache).glyph_ids(&font_cache, [' '])[0];
+        let run = texts[0][0].as_array().unwrap()[0].as_str().unwrap();
+        assert_eq!(&space.to_be_bytes()[..], &run[..2]);
+    }
+
+    #[test]
+    fn test_cursor_position() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new();
+        let metrics = style.metrics(&font_cache);
+        let area = renderer.first_page().first_layer().area();
+        let mut section = area
+            .text_section(&font_cache, Position::new(10, 20), metrics)
+            .unwrap();
+        assert_eq!(
+            Position::new(10, 20) + Position::new(0, metrics.ascent),
+            section.cursor_position()
+        );
+
+        // The run starts with whitespace, so the start of the line is not moved.
+        section.print_str(" Hello", style).unwrap();
+        let width = style.str_width(&font_cache, " Hello");
+        let cursor = section.cursor_position();
+        assert!(approx_eq!(f32, 10.0 + width.0, cursor.x.0, epsilon = 0.001));
+        assert!(approx_eq!(
+            f32,
+            20.0 + metrics.ascent.0,
+            cursor.y.0,
+            epsilon = 0.001
+        ));
+
+        assert!(section.add_newline());
+        let cursor = section.cursor_position();
+        assert!(approx_eq!(f32, 10.0, cursor.x.0, epsilon = 0.001));
+        assert!(approx_eq!(
+            f32,
+            20.0 + metrics.ascent.0 + metrics.line_height.0,
+            cursor.y.0,
+            epsilon = 0.001
+        ));
+    }
+
+    #[test]
+    fn test_print_str_bounded() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new();
+        let mut metrics = style.metrics(&font_cache);
+        metrics.line_height = Mm(8.0);
+        let area = renderer.first_page().first_layer().area();
+        let mut section = area
+            .text_section(&font_cache, Position::default(), metrics)
+            .unwrap();
+
+        let size = section.print_str_bounded("Hello", style).unwrap();
+        assert_eq!(style.str_width(&font_cache, "Hello"), size.width);
+        assert_eq!(Mm(8.0), size.height);
+
+        // Kerning with the previous run is part of the advance of the run.
+        section.print_str("A", style).unwrap();
+        let size = section.print_str_bounded("V", style).unwrap();
+        let expected = style.str_width(&font_cache, "AV") - style.str_width(&font_cache, "A");
+        assert!(approx_eq!(f32, expected.0, size.width.0, epsilon = 0.001));
+    }
+
+    #[test]
+    fn test_print_str_no_advance() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new();
+        let metrics = style.metrics(&font_cache);
+        let area = renderer.first_page().first_layer().area();
+        let mut section = area
+            .text_section(&font_cache, Position::default(), metrics)
+            .unwrap();
+
+        section.print_str("a", style).unwrap();
+        let start = section.cursor_position();
+        section.print_str_no_advance("^", style).unwrap();
+        assert_eq!(start, section.cursor_position());
+        let size = section.print_str_bounded("e", style).unwrap();
+        assert!(approx_eq!(
+            f32,
+            (start.x + size.width).0,
+            section.cursor_position().x.0,
+            epsilon = 0.001
+        ));
+        drop(section);
+
+        // The cursor is moved back by the width of the overprinted run.
+        let doc = write_renderer(renderer);
+        let texts = find_operations(&doc, 0, "TJ");
+        assert_eq!(4, texts.len());
+        let offset = texts[2][0].as_array().unwrap();
+        assert_eq!(1, offset.len());
+        let width = style.str_width(&font_cache, "^");
+        let expected = printpdf::Pt::from(width).0 * 1000.0 / f32::from(style.font_size());
+        assert!(approx_eq!(
+            f32,
+            expected,
+            as_f32(&offset[0]),
+            epsilon = 0.01
+        ));
+    }
+
+    #[test]
+    fn test_output_intent() {
+        let err = Renderer::new(Size::new(100, 100), "test")
+            .unwrap()
+            .with_output_intent(vec![0; 128], "Test")
+            .err()
+            .unwrap();
+        assert!(matches!(err.kind(), crate::error::ErrorKind::InvalidData));
+
+        let mut icc_profile = vec![0; 128];
+        icc_profile[16..20].copy_from_slice(b"CMYK");
+        icc_profile[36..40].copy_from_slice(b"acsp");
+        let renderer = Renderer::new(Size::new(100, 100), "test")
+            .unwrap()
+            .with_output_intent(icc_profile.clone(), "Test CMYK")
+            .unwrap();
+
+        let doc = write_renderer(renderer);
+        let output_intents = doc
+            .catalog()
+            .unwrap()
+            .get(b"OutputIntents")
+            .and_then(lopdf::Object::as_array)
+            .unwrap();
+        assert_eq!(1, output_intents.len());
+        let output_intent = output_intents[0].as_dict().unwrap();
+        assert_eq!(
+            b"GTS_PDFX".as_ref(),
+            output_intent.get(b"S").unwrap().as_name().unwrap()
+        );
+        assert_eq!(
+            b"Test CMYK".as_ref(),
+            output_intent
+                .get(b"OutputConditionIdentifier")
+                .unwrap()
+                .as_str()
+                .unwrap()
+        );
+        let profile_id = output_intent
+            .get(b"DestinationOutputProfile")
+            .and_then(lopdf::Object::as_reference)
+            .unwrap();
+        let mut stream = doc
+            .get_object(profile_id)
+            .unwrap()
+            .as_stream()
+            .unwrap()
+            .clone();
+        assert_eq!(4, stream.dict.get(b"N").unwrap().as_i64().unwrap());
+        stream.decompress();
+        assert_eq!(icc_profile, stream.content);
+    }
+
+    #[test]
+    fn test_layer_names() {
+        let mut renderer = Renderer::new(Size::new(100, 100), "test")
+            .unwrap()
+            .with_layer_name("Drawing");
+        renderer.first_page_mut().add_layer("Dimensions");
+        renderer.add_page(Size::new(100, 100));
+        renderer.last_page_mut().add_layer("Notes");
+        renderer
+            .last_page_mut()
+            .set_layer_name(1, "Annotations")
+            .unwrap();
+        assert!(renderer.last_page_mut().set_layer_name(2, "Other").is_err());
+
+        let doc = write_renderer(renderer);
+        let names: Vec<_> = doc
+            .catalog()
+            .unwrap()
+            .get(b"OCProperties")
+            .and_then(lopdf::Object::as_dict)
+            .and_then(|properties| properties.get(b"OCGs"))
+            .and_then(lopdf::Object::as_array)
+            .unwrap()
+            .iter()
+            .map(|ocg| {
+                let ocg = doc.get_dictionary(ocg.as_reference().unwrap()).unwrap();
+                let name = ocg.get(b"Name").unwrap().as_str().unwrap();
+                String::from_utf8(name.to_vec()).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            vec!["Drawing", "Dimensions", "Drawing", "Annotations"],
+            names
+        );
+    }
+
+    #[test]
+    fn test_optional_layers() {
+        let mut renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let page = renderer.first_page_mut();
+        page.add_optional_layer("Notes", false);
+        page.last_layer()
+            .area()
+            .print_str(&font_cache, Position::new(10, 50), Style::new(), "Note")
+            .unwrap();
+        let doc = write_renderer(renderer);
+
+        let properties = doc
+            .catalog()
+            .unwrap()
+            .get(b"OCProperties")
+            .and_then(lopdf::Object::as_dict)
+            .unwrap();
+        let ocgs = properties.get(b"OCGs").unwrap().as_array().unwrap();
+        assert_eq!(2, ocgs.len());
+        let ocg = doc.get_dictionary(ocgs[1].as_reference().unwrap()).unwrap();
+        assert_eq!(b"OCG", ocg.get(b"Type").unwrap().as_name().unwrap());
+        assert_eq!(b"Notes", ocg.get(b"Name").unwrap().as_str().unwrap());
+        let config = properties.get(b"D").unwrap().as_dict().unwrap();
+        let ids = |objects: &[lopdf::Object]| -> Vec<_> {
+            objects
+                .iter()
+                .map(|object| object.as_reference().unwrap())
+                .collect()
+        };
+        let on = config.get(b"ON").unwrap().as_array().unwrap();
+        let off = config.get(b"OFF").unwrap().as_array().unwrap();
+        assert_eq!(ids(&ocgs[..1]), ids(on));
+        assert_eq!(ids(&ocgs[1..]), ids(off));
+
+        let operations = page_operations(&doc, 0);
+        let tj = operations
+            .iter()
+            .position(|op| op.operator == "Tj" || op.operator == "TJ")
+            .unwrap();
+        let bdc = operations[..tj]
+            .iter()
+            .rposition(|op| op.operator == "BDC")
+            .unwrap();
+        assert_eq!(b"OC", operations[bdc].operands[0].as_name().unwrap());
+        assert!(operations[tj..].iter().any(|op| op.operator == "EMC"));
+    }
+
+    #[test]
+    fn test_page_rotation() {
+        let mut renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        renderer.add_page(Size::new(100, 100));
+        renderer.first_page_mut().set_rotation(90).unwrap();
+        renderer.last_page_mut().set_rotation(-90).unwrap();
+        assert_eq!(270, renderer.last_page().rotation());
+        assert!(renderer.last_page_mut().set_rotation(45).is_err());
+        assert_eq!(270, renderer.last_page().rotation());
+
+        let doc = write_renderer(renderer);
+        let rotations: Vec<_> = doc
+            .page_iter()
+            .map(|id| {
+                doc.get_dictionary(id)
+                    .unwrap()
+                    .get(b"Rotate")
+                    .and_then(lopdf::Object::as_i64)
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(vec![90, 270], rotations);
+    }
+
+    #[test]
+    fn test_page_boxes() {
+        let mut renderer = Renderer::new(Size::new(100, 200), "test").unwrap();
+        let page = renderer.first_page_mut();
+        assert_eq!((Position::default(), Size::new(100, 200)), page.trim_box());
+        page.set_trim_box(Position::new(10, 20), Size::new(80, 150));
+        assert_eq!((Position::new(10, 20), Size::new(80, 150)), page.trim_box());
+
+        let doc = write_renderer(renderer);
+        let page = doc.get_dictionary(doc.page_iter().next().unwrap()).unwrap();
+        let get_box = |name: &[u8]| -> Vec<f32> {
+            page.get(name)
+                .and_then(lopdf::Object::as_array)
+                .unwrap()
+                .iter()
+                .map(|value| Mm::from(printpdf::Pt(as_f32(value))).0)
+                .collect()
+        };
+        let assert_box = |expected: [f32; 4], actual: Vec<f32>| {
+            assert_eq!(expected.len(), actual.len());
+            for (expected, actual) in expected.iter().zip(&actual) {
+                assert!(approx_eq!(f32, *expected, *actual, epsilon = 0.001));
+            }
+        };
+        // The boxes are measured from the lower left corner of the page.
+        assert_box([10.0, 30.0, 90.0, 180.0], get_box(b"TrimBox"));
+        // Boxes that have not been set are the media box.
+        assert_box([0.0, 0.0, 100.0, 200.0], get_box(b"CropBox"));
+    }
+
+    #[test]
+    fn test_snap_to_grid() {
+        let grid = Mm(2.0);
+        assert_eq!(Mm(2.0), super::snap_to_grid(Mm(1.1), grid));
+        assert_eq!(Mm(4.0), super::snap_to_grid(Mm(2.1), grid));
+        assert_eq!(Mm(4.0), super::snap_to_grid(Mm(3.9), grid));
+        assert_eq!(Mm(4.0), super::snap_to_grid(Mm(4.0), grid));
+        assert_eq!(Mm(8.0), super::snap_to_grid(Mm(4.9), Mm(4.0)));
+        assert!(super::check_grid_spacing(grid).is_ok());
+        assert!(super::check_grid_spacing(Mm(0.0)).is_err());
+        assert!(super::check_grid_spacing(Mm(-1.0)).is_err());
+        assert!(super::check_grid_spacing(Mm(f32::NAN)).is_err());
+    }
+
+    #[test]
+    fn test_max_lines() {
+        let renderer = Renderer::new(Size::new(100, 20), "test").unwrap();
+        let mut area = renderer.first_page().first_layer().area();
+        let metrics = Metrics::new(Mm(5.0), Mm(4.0), Mm(3.0));
+        // 4 + 3 * 5 = 19 mm
+        assert_eq!(4, area.max_lines(metrics));
+        area.set_height(Mm(19.0));
+        assert_eq!(4, area.max_lines(metrics));
+        area.set_height(Mm(18.0));
+        assert_eq!(3, area.max_lines(metrics));
+        area.set_height(Mm(4.0));
+        assert_eq!(1, area.max_lines(metrics));
+        area.set_height(Mm(3.0));
+        assert_eq!(0, area.max_lines(metrics));
+
+        // The line height is rounded up to the baseline grid of 6 mm, and the first baseline is
+        // moved from 3 mm to 6 mm: 3 + 4 + 2 * 6 = 19 mm
+        let renderer = Renderer::new(Size::new(100, 20), "test")
+            .unwrap()
+            .with_baseline_grid(Mm(6.0))
+            .unwrap();
+        let area = renderer.first_page().first_layer().area();
+        assert_eq!(3, area.max_lines(metrics));
+    }
+
+    #[test]
+    fn test_baseline_grid_offset() {
+        let renderer = Renderer::new(Size::new(100, 100), "test")
+            .unwrap()
+            .with_baseline_grid(Mm(6.0))
+            .unwrap();
+        let font_cache = font_cache(&renderer);
+        let metrics = Metrics::new(Mm(5.0), Mm(4.0), Mm(3.0));
+        // The area starts just after the grid line at 6 mm, so the first baseline at 9.1 mm is
+        // moved down to 12 mm instead of up to 6 mm, and the line height is rounded up to 6 mm.
+        let mut area = renderer.first_page().first_layer().area();
+        area.add_offset(Position::new(0, 6.1));
+        assert_eq!(15, area.max_lines(metrics));
+        {
+            let mut section = area
+                .text_section(&font_cache, Position::default(), metrics)
+                .unwrap();
+            section.print_str("x", Style::new()).unwrap();
+            assert!(approx_eq!(
+                f32,
+                2.9 + 6.0,
+                section.height().0,
+                epsilon = 0.001
+            ));
+        }
+
+        let doc = write_renderer(renderer);
+        let cursors = find_operations(&doc, 0, "Td");
+        assert_eq!(1, cursors.len());
+        let baseline = Mm(100.0) - printpdf::Pt(as_f32(&cursors[0][1])).into();
+        assert!(approx_eq!(f32, 12.0, baseline.0, epsilon = 0.01));
+    }
+
+    #[test]
+    fn test_print_str_fitted() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new();
+        let area = renderer.first_page().first_layer().area();
+
+        let width = style.str_width(&font_cache, "Hello W");
+        let mut narrow_area = area.clone();
+        narrow_area.set_width(width + Mm(0.1));
+        let info = narrow_area
+            .print_str_fitted(&font_cache, Position::default(), style, "Hello World")
+            .unwrap();
+        assert_eq!("Hello W", info.printed);
+        assert_eq!("orld", info.rest);
+        assert_eq!(7, info.chars);
+        assert_eq!(width, info.width);
+        assert!(!info.is_complete());
+
+        let info = area
+            .print_str_fitted(&font_cache, Position::default(), style, "Hello World")
+            .unwrap();
+        assert_eq!("", info.rest);
+        assert!(info.is_complete());
+
+        // Nothing is printed if the line is too high for the area.
+        let info = area
+            .print_str_fitted(&font_cache, Position::new(0, 99), style, "Hello World")
+            .unwrap();
+        assert_eq!("", info.printed);
+        assert_eq!("Hello World", info.rest);
+    }
+
+    #[test]
+    fn test_print_str_kerning_across_runs() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new();
+        {
+            let area = renderer.first_page().first_layer().area();
+            let metrics = style.metrics(&font_cache);
+            let mut section = area
+                .text_section(&font_cache, Position::default(), metrics)
+                .unwrap();
+            section.print_str("AV", style).unwrap();
+            let mut section = area
+                .text_section(&font_cache, Position::new(0, 20), metrics)
+                .unwrap();
+            section.print_str("A", style).unwrap();
+            section.print_str("V", style).unwrap();
+        }
+
+        let doc = write_renderer(renderer);
+        let texts: Vec<_> = find_operations(&doc, 0, "TJ")
+            .into_iter()
+            .map(|operands| operands[0].as_array().unwrap().clone())
+            .collect();
+        assert_eq!(3, texts.len());
+        // The kerning is written in whole thousandths of an em and moves the next glyph to the
+        // left.
+        let kerning = style.font(&font_cache).kerning(&font_cache, "AV".chars())[1];
+        let offset = (kerning * -1000.0) as i64;
+        assert!(offset > 0);
+        let glyph = |c: char| {
+            let id = style.font(&font_cache).glyph_ids(&font_cache, [c])[0];
+            id.to_be_bytes().to_vec()
+        };
+
+        // The single run contains the glyph A, the kerning adjustment and the glyph V.
+        assert_eq!(3, texts[0].len());
+        assert_eq!(glyph('A'), texts[0][0].as_str().unwrap());
+        assert_eq!(offset, texts[0][1].as_i64().unwrap());
+        assert_eq!(glyph('V'), texts[0][2].as_str().unwrap());
+
+        // The split runs contain the same glyphs and the same kerning adjustment.
+        assert_eq!(1, texts[1].len());
+        assert_eq!(glyph('A'), texts[1][0].as_str().unwrap());
+        assert_eq!(2, texts[2].len());
+        assert_eq!(offset, texts[2][0].as_i64().unwrap());
+        assert_eq!(glyph('V'), texts[2][1].as_str().unwrap());
+    }
+
+    #[test]
+    fn test_print_str_without_kerning() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new().with_kerning(false);
+        {
+            let area = renderer.first_page().first_layer().area();
+            let metrics = style.metrics(&font_cache);
+            let mut section = area
+                .text_section(&font_cache, Position::default(), metrics)
+                .unwrap();
+            section.print_str("AV", style).unwrap();
+        }
+
+        // The width is the sum of the advance widths of the glyphs.
+        let width = style.char_width(&font_cache, 'A') + style.char_width(&font_cache, 'V');
+        assert_eq!(width, style.str_width(&font_cache, "AV"));
+        assert!(width > Style::new().str_width(&font_cache, "AV"));
+
+        // There are no adjustments between the glyphs.
+        let doc = write_renderer(renderer);
+        let texts = find_operations(&doc, 0, "TJ");
+        assert_eq!(1, texts.len());
+        let operands = texts[0][0].as_array().unwrap();
+        assert!(operands.iter().all(|object| object.as_str().is_ok()));
+        let glyphs: usize = operands
+            .iter()
+            .map(|object| object.as_str().unwrap().len() / 2)
+            .sum();
+        assert_eq!(2, glyphs);
+    }
+
+    #[test]
+    fn test_advance_adjustment() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new().with_kerning(false);
+        let cursor = {
+            let area = renderer.first_page().first_layer().area();
+            let metrics = style.metrics(&font_cache);
+            let mut section = area
+                .text_section(&font_cache, Position::default(), metrics)
+                .unwrap();
+            section.print_str("ab", style).unwrap();
+            let start = section.cursor_position();
+            section.add_advance_adjustment(200.0);
+            section.add_advance_adjustment(50.0);
+            section.print_str("cd", style).unwrap();
+            section.print_str("ef", style).unwrap();
+            section.cursor_position().x - start.x
+        };
+
+        // The adjustment is added to the width of the line.
+        let width = style.str_width(&font_cache, "cdef");
+        let adjustment = Mm::from(printpdf::Pt(0.25 * f32::from(style.font_size())));
+        assert!(approx_eq!(
+            f32,
+            (width + adjustment).0,
+            cursor.0,
+            epsilon = 0.01
+        ));
+
+        // The adjustment is only written before the first glyph after the call.
+        let doc = write_renderer(renderer);
+        let texts = find_operations(&doc, 0, "TJ");
+        assert_eq!(3, texts.len());
+        let operands: Vec<_> = texts
+            .iter()
+            .map(|operands| operands[0].as_array().unwrap().clone())
+            .collect();
+        assert!(operands[0].iter().all(|object| object.as_str().is_ok()));
+        assert_eq!(-250, operands[1][0].as_i64().unwrap());
+        assert!(operands[1][1..]
+            .iter()
+            .all(|object| object.as_str().is_ok()));
+        assert!(operands[2].iter().all(|object| object.as_str().is_ok()));
+    }
+
+    #[test]
+    fn test_print_str_with_highlight() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new().with_kerning(false);
+        let highlight = style.with_highlight(Color::Rgb(255, 255, 0));
+        let metrics = style.metrics(&font_cache);
+        {
+            let area = renderer.first_page().first_layer().area();
+            let mut section = area
+                .text_section(&font_cache, Position::default(), metrics)
+                .unwrap();
+            section.print_str("Hi ", style).unwrap();
+            section.print_str("there", highlight).unwrap();
+            assert!(section.add_newline());
+            section.print_str("again", highlight).unwrap();
+        }
+
+        let doc = write_renderer(renderer);
+        let starts = find_operations(&doc, 0, "m");
+        let lines = find_operations(&doc, 0, "l");
+        assert_eq!(2, find_operations(&doc, 0, "f").len());
+        assert_eq!(2, starts.len());
+        assert_eq!(6, lines.len());
+
+        // Each rectangle spans exactly the advance width of its run and the line height.
+        let left_bearing = style.char_left_side_bearing(&font_cache, 'H');
+        let expected = [
+            (
+                style.str_width(&font_cache, "Hi ") - left_bearing,
+                "there",
+                0.0,
+            ),
+            (left_bearing * -1.0, "again", 1.0),
+        ];
+        for (i, (x, s, line)) in expected.iter().enumerate() {
+            let x0 = as_f32(&starts[i][0]);
+            let y0 = as_f32(&starts[i][1]);
+            let x1 = as_f32(&lines[3 * i][0]);
+            let y2 = as_f32(&lines[3 * i + 1][1]);
+            let width = style.str_width(&font_cache, s);
+            let top = Mm(100.0) - metrics.line_height * *line;
+            assert!((x0 - printpdf::Pt::from(*x).0).abs() < 0.01);
+            assert!((x1 - x0 - printpdf::Pt::from(width).0).abs() < 0.01);
+            assert!((y0 - printpdf::Pt::from(top).0).abs() < 0.01);
+            assert!((y0 - y2 - printpdf::Pt::from(metrics.line_height).0).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_print_str_with_overline() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new().with_kerning(false);
+        let metrics = style.metrics(&font_cache);
+        {
+            let area = renderer.first_page().first_layer().area();
+            let mut section = area
+                .text_section(&font_cache, Position::default(), metrics)
+                .unwrap();
+            section.print_str("x ", style).unwrap();
+            section.print_str("AB", style.with_overline()).unwrap();
+        }
+
+        let doc = write_renderer(renderer);
+        let starts = find_operations(&doc, 0, "m");
+        let ends = find_operations(&doc, 0, "l");
+        assert_eq!(1, starts.len());
+        assert_eq!(1, ends.len());
+
+        // The line spans the advance width of the run.
+        let left_bearing = style.char_left_side_bearing(&font_cache, 'x');
+        let x = style.str_width(&font_cache, "x ") - left_bearing;
+        let width = style.str_width(&font_cache, "AB");
+        assert!(approx_eq!(
+            f32,
+            printpdf::Pt::from(x).0,
+            as_f32(&starts[0][0]),
+            epsilon = 0.01
+        ));
+        assert!(approx_eq!(
+            f32,
+            printpdf::Pt::from(x + width).0,
+            as_f32(&ends[0][0]),
+            epsilon = 0.01
+        ));
+
+        // The line is drawn one line thickness above the cap height.
+        let font = style.font(&font_cache);
+        let thickness = Mm::from(printpdf::Pt(12.0 / 20.0));
+        let baseline = Mm(100.0) - metrics.ascent;
+        let y = baseline + font.cap_height(12) + thickness * 1.5;
+        assert!(approx_eq!(
+            f32,
+            printpdf::Pt::from(y).0,
+            as_f32(&starts[0][1]),
+            epsilon = 0.01
+        ));
+        assert_eq!(as_f32(&starts[0][1]), as_f32(&ends[0][1]));
+    }
+
+    #[test]
+    fn test_print_str_with_baseline_shift() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new();
+        let shifted = style.with_baseline_shift(2);
+        // The baseline shift does not change the line height.
+        assert_eq!(style.metrics(&font_cache), shifted.metrics(&font_cache));
+        {
+            let area = renderer.first_page().first_layer().area();
+            let mut section = area
+                .text_section(&font_cache, Position::default(), style.metrics(&font_cache))
+                .unwrap();
+            section.print_str("Footnote", style).unwrap();
+            section.print_str("1", shifted).unwrap();
+            section.print_str(" text", style).unwrap();
+            assert_eq!(style.line_height(&font_cache), section.height());
+        }
+
+        let doc = write_renderer(renderer);
+        let rises: Vec<_> = find_operations(&doc, 0, "Ts")
+            .iter()
+            .map(|operands| as_f32(&operands[0]))
+            .collect();
+        let rise: printpdf::Pt = Mm::from(2).into();
+        assert_eq!(2, rises.len());
+        assert!((rises[0] - rise.0).abs() < 0.01);
+        assert_eq!(0.0, rises[1]);
+    }
+
+    #[test]
+    fn test_print_str_vertical() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new().with_vertical(true);
+        let metrics = style.metrics(&font_cache);
+        {
+            let area = renderer.first_page().first_layer().area();
+            let mut section = area
+                .text_section(&font_cache, Position::default(), metrics)
+                .unwrap();
+            section.print_str("ABC", style).unwrap();
+        }
+
+        // The column is as wide as its widest glyph.
+        let widths = ['A', 'B', 'C'].map(|c| style.char_width(&font_cache, c));
+        let width = widths.into_iter().fold(Mm(0.0), Mm::max);
+        assert_eq!(width, style.str_width(&font_cache, "ABC"));
+
+        // Every glyph is printed separately, followed by the cursor movement behind the column,
+        // and the cursor moves down by the glyph height for every glyph while keeping its
+        // horizontal position.
+        let doc = write_renderer(renderer);
+        assert_eq!(4, find_operations(&doc, 0, "TJ").len());
+        let cursors: Vec<_> = find_operations(&doc, 0, "Td")
+            .iter()
+            .map(|operands| (as_f32(&operands[0]), as_f32(&operands[1])))
+            .collect();
+        assert_eq!(5, cursors.len());
+        let advance = printpdf::Pt::from(style.font(&font_cache).glyph_height(style.font_size()));
+        for i in 2..4 {
+            assert!(approx_eq!(f32, cursors[i].0, cursors[1].0, epsilon = 0.001));
+            assert!(approx_eq!(
+                f32,
+                cursors[i - 1].1 - cursors[i].1,
+                advance.0,
+                epsilon = 0.001
+            ));
+        }
+    }
+
+    #[test]
+    fn test_print_str_vertical_line_height() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new().with_vertical(true);
+        let metrics = style.metrics(&font_cache);
+        let advance = style.font(&font_cache).glyph_height(style.font_size());
+        assert!(advance * 3.0 > metrics.line_height);
+
+        let area = renderer.first_page().first_layer().area();
+        let mut section = area
+            .text_section(&font_cache, Position::default(), metrics)
+            .unwrap();
+        section.print_str("ABC", style).unwrap();
+        assert_eq!(advance * 3.0, section.height());
+
+        // The next line starts below the stacked glyphs instead of overlapping them.
+        assert!(section.add_newline());
+        section.print_str("D", Style::new()).unwrap();
+        assert_eq!(advance * 3.0 + metrics.line_height, section.height());
+        assert!(approx_eq!(
+            f32,
+            (advance * 3.0 + metrics.ascent).0,
+            section.cursor_position().y.0,
+            epsilon = 0.001
+        ));
+    }
+
+    #[test]
+    fn test_print_str_vertical_columns() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let font_cache = font_cache(&renderer);
+        let style = Style::new().with_vertical(true);
+        let metrics = style.metrics(&font_cache);
+        {
+            let area = renderer.first_page().first_layer().area();
+            let mut section = area
+                .text_section(&font_cache, Position::default(), metrics)
+                .unwrap();
+            section.print_str("AB\nC", style).unwrap();
+        }
+
+        // The run is as wide as both columns together.
+        let width = style.str_width(&font_cache, "AB") + style.str_width(&font_cache, "C");
+        assert_eq!(width, style.str_width(&font_cache, "AB\nC"));
+
+        // The first column is printed to the right of the second column, and the second column
+        // starts at the top again.
+        let doc = write_renderer(renderer);
+        let cursors: Vec<_> = find_operations(&doc, 0, "Td")
+            .iter()
+            .map(|operands| (as_f32(&operands[0]), as_f32(&operands[1])))
+            .collect();
+        let column_width = printpdf::Pt::from(style.str_width(&font_cache, "C"));
+        assert!(approx_eq!(
+            f32,
+            cursors[1].0 - cursors[3].0,
+            column_width.0,
+            epsilon = 0.001
+        ));
+        assert!(approx_eq!(f32, cursors[1].1, cursors[3].1, epsilon = 0.001));
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn test_draw_image_tiled() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let image = printpdf::image_crate::DynamicImage::new_rgb8(4, 4);
+        {
+            let mut area = renderer.first_page().first_layer().area();
+            area.add_margins(10);
+            area.draw_image_tiled(&image, Size::new(5, 5));
+        }
+
+        // The area is filled with the pattern.
+        let doc = write_renderer(renderer);
+        let fills = find_operations(&doc, 0, "scn");
+        assert_eq!(1, fills.len());
+        assert_eq!(b"P0", fills[0][0].as_name().unwrap());
+        let rect = find_operations(&doc, 0, "re");
+        let rect: Vec<_> = rect[0].iter().map(as_f32).collect();
+        for (actual, expected) in rect.iter().zip([10.0, 10.0, 80.0, 80.0]) {
+            assert!(approx_eq!(
+                f32,
+                *actual,
+                printpdf::Pt::from(Mm(expected)).0,
+                epsilon = 0.01
+            ));
+        }
+
+        // The pattern is a tiling pattern that draws the image once per tile.
+        let page = doc.page_iter().next().unwrap();
+        let (resources, resource_ids) = doc.get_page_resources(page);
+        let resources = resources
+            .or_else(|| {
+                resource_ids
+                    .first()
+                    .and_then(|id| doc.get_dictionary(*id).ok())
+            })
+            .unwrap();
+        let patterns = resources.get(b"Pattern").unwrap().as_dict().unwrap();
+        let pattern_id = patterns.get(b"P0").unwrap().as_reference().unwrap();
+        let pattern = doc.get_object(pattern_id).unwrap().as_stream().unwrap();
+        assert_eq!(
+            1,
+            pattern.dict.get(b"PatternType").unwrap().as_i64().unwrap()
+        );
+        let tile = printpdf::Pt::from(Mm(5.0)).0;
+        assert!(approx_eq!(
+            f32,
+            tile,
+            as_f32(pattern.dict.get(b"XStep").unwrap()),
+            epsilon = 0.01
+        ));
+        let image_id = pattern
+            .dict
+            .get(b"Resources")
+            .and_then(lopdf::Object::as_dict)
+            .and_then(|resources| resources.get(b"XObject"))
+            .and_then(lopdf::Object::as_dict)
+            .and_then(|xobjects| xobjects.get(b"Im0"))
+            .and_then(lopdf::Object::as_reference)
+            .unwrap();
+        let image = doc.get_object(image_id).unwrap().as_stream().unwrap();
+        assert_eq!(
+            b"Image",
+            image.dict.get(b"Subtype").unwrap().as_name().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_embed_cff_font() {
+        let font_data = FontData::load("./tests/files/cantarell/Cantarell-VF.otf", None)
+            .expect("Failed to load OTF font");
+        let family = FontFamily {
+            regular: font_data.clone(),
+            bold: font_data.clone(),
+            italic: font_data.clone(),
+            bold_italic: font_data,
+        };
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let mut font_cache = FontCache::new(family);
+        font_cache.load_pdf_fonts(&renderer).unwrap();
+        let style = Style::new();
+        assert!(style.str_width(&font_cache, "Hello").0 > 0.0);
+        {
+            let area = renderer.first_page().first_layer().area();
+            assert!(area
+                .print_str(&font_cache, Position::default(), style, "Hello")
+                .unwrap());
+        }
+
+        let doc = write_renderer(renderer);
+        assert_eq!(1, find_operations(&doc, 0, "TJ").len());
+        let descriptor = doc
+            .objects
+            .values()
+            .filter_map(|object| object.as_dict().ok())
+            .find(|dict| dict.has(b"FontFile3"))
+            .expect("Missing font descriptor for the CFF font");
+        assert!(!descriptor.has(b"FontFile2"));
+        let font_file = descriptor
+            .get(b"FontFile3")
+            .and_then(lopdf::Object::as_reference)
+            .and_then(|id| doc.get_object(id))
+            .and_then(lopdf::Object::as_stream)
+            .unwrap();
+        assert_eq!(
+            b"OpenType",
+            font_file.dict.get(b"Subtype").unwrap().as_name().unwrap()
+        );
+        let font = doc
+            .objects
+            .values()
+            .filter_map(|object| object.as_dict().ok())
+            .find_map(|dict| dict.get(b"DescendantFonts").ok())
+            .and_then(|fonts| fonts.as_array().ok())
+            .and_then(|fonts| fonts[0].as_dict().ok())
+            .unwrap();
+        assert_eq!(
+            b"CIDFontType0",
+            font.get(b"Subtype").unwrap().as_name().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_add_content_stream() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let page = renderer.first_page();
+        page.add_content_stream(b"q 1 0 0 RG 0 0 m 10 10 l S Q")
+            .unwrap();
+        for data in [&b"q 0 0 m"[..], b"Q", b"BT q ET", b"ET", b"BT (a) Tj"] {
+            assert!(page.add_content_stream(data).is_err());
+        }
+
+        // The operators are wrapped in a saved graphics state, and the invalid streams have not
+        // been added.
+        let doc = write_renderer(renderer);
+        let operators: Vec<_> = page_operations(&doc, 0)
+            .into_iter()
+            .map(|op| op.operator)
+            .collect();
+        let start = operators.iter().position(|op| op == "RG").unwrap();
+        assert_eq!(
+            vec!["q", "q", "RG", "m", "l", "S", "Q", "Q"],
+            operators[start - 2..start + 6]
+        );
+    }
+
+    #[test]
+    fn test_with_raw_layer() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        {
+            let mut area = renderer.first_page().first_layer().area();
+            area.add_margins(10);
+            let height = area.with_raw_layer(|layer| {
+                layer.set_fill_color(printpdf::Color::Rgb(printpdf::Rgb::new(
+                    1.0, 0.0, 0.0, None,
+                )));
+                layer.add_operation(lopdf::content::Operation::new(
+                    "re",
+                    vec![10.into(), 10.into(), 20.into(), 20.into()],
+                ));
+                layer.add_operation(lopdf::content::Operation::new("f", Vec::new()));
+                area.size().height
+            });
+            assert!(approx_eq!(Mm, Mm::from(80), height));
+        }
+
+        let doc = write_renderer(renderer);
+        let operators: Vec<_> = page_operations(&doc, 0)
+            .into_iter()
+            .map(|op| op.operator)
+            .filter(|op| ["q", "re", "f", "Q"].contains(&op.as_str()))
+            .collect();
+        // The layer itself is also wrapped in a saved graphics state.
+        assert_eq!(vec!["q", "q", "re", "f", "Q", "Q"], operators);
+    }
+
+    #[test]
+    fn test_line_cap_and_join() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        let area = renderer.first_page().first_layer().area();
+        let points = [Position::new(10, 10), Position::new(50, 10)];
+        area.draw_line(points, LineStyle::new());
+        area.draw_line(
+            points,
+            LineStyle::new()
+                .with_line_cap(LineCap::Round)
+                .with_line_join(LineJoin::Bevel),
+        );
+
+        let doc = write_renderer(renderer);
+        let operators: Vec<_> = page_operations(&doc, 0)
+            .into_iter()
+            .filter(|op| ["J", "j", "S"].contains(&op.operator.as_str()))
+            .map(|op| {
+                let operand = op.operands.first().map(as_f32);
+                (op.operator, operand)
+            })
+            .collect();
+        // The default line style does not emit any operators, and the styles are reset after
+        // the line has been drawn.
+        assert_eq!(
+            vec![
+                ("S".to_owned(), None),
+                ("J".to_owned(), Some(1.0)),
+                ("j".to_owned(), Some(2.0)),
+                ("S".to_owned(), None),
+                ("J".to_owned(), Some(0.0)),
+                ("j".to_owned(), Some(0.0)),
+            ],
+            operators
+        );
+    }
+
+    #[test]
+    fn test_draw_arrow() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        renderer.first_page().first_layer().area().draw_arrow(
+            Position::new(10, 50),
+            Position::new(50, 10),
+            LineStyle::new(),
+            6,
+        );
+
+        let doc = write_renderer(renderer);
+        let operations = page_operations(&doc, 0);
+        let fill = operations.iter().position(|op| op.operator == "f").unwrap();
+        let start = operations[..fill]
+            .iter()
+            .rposition(|op| op.operator == "m")
+            .unwrap();
+        let points: Vec<_> = operations[start..fill]
+            .iter()
+            .filter(|op| op.operator == "m" || op.operator == "l")
+            .map(|op| (as_f32(&op.operands[0]), as_f32(&op.operands[1])))
+            .collect();
+        assert_eq!(3, points.len());
+
+        // the tip is the end position of the line
+        let tip = points[0];
+        let x: printpdf::Pt = Mm::from(50).into();
+        let y: printpdf::Pt = Mm::from(90).into();
+        assert!((tip.0 - x.0).abs() < 0.01);
+        assert!((tip.1 - y.0).abs() < 0.01);
+
+        // the triangle points from the center of its base to the tip in the direction of the line
+        // (the y axis of the PDF coordinate system points upwards)
+        let base = (
+            (points[1].0 + points[2].0) / 2.0,
+            (points[1].1 + points[2].1) / 2.0,
+        );
+        let (dx, dy) = (tip.0 - base.0, tip.1 - base.1);
+        let length = dx.hypot(dy);
+        let expected = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((dx / length - expected).abs() < 0.01);
+        assert!((dy / length - expected).abs() < 0.01);
+        let head_size: printpdf::Pt = Mm::from(6).into();
+        assert!((length - head_size.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_fill_and_outline_color_cache() {
+        let renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        {
+            let area = renderer.first_page().first_layer().area();
+            let red = Color::Rgb(255, 0, 0);
+            let blue = Color::Rgb(0, 0, 255);
+            let points = || {
+                vec![
+                    Position::new(0, 0),
+                    Position::new(10, 0),
+                    Position::new(10, 10),
+                ]
+            };
+            let size = Size::new(10, 10);
+
+            area.fill_rect(Position::default(), size, red);
+            area.draw_line(points(), LineStyle::new().with_color(red));
+            area.draw_filled_shape(points(), red, Some(LineStyle::new().with_color(red)));
+            area.draw_line(points(), LineStyle::new().with_color(blue));
+            area.fill_rect(Position::default(), size, blue);
+        }
+
+        let doc = write_renderer(renderer);
+        let operators: Vec<_> = page_operations(&doc, 0)
+            .into_iter()
+            .map(|op| op.operator)
+            .filter(|op| ["rg", "RG", "f", "S", "b"].contains(&op.as_str()))
+            .collect();
+        assert_eq!(
+            vec!["rg", "f", "RG", "S", "b", "RG", "S", "rg", "f"],
+            operators
+        );
+    }
+
+    #[test]
+    fn test_append_pdf_inherited_attributes() {
+        // A document with a single page that inherits its resources, media box and rotation from
+        // the page tree node.
+        let mut other = lopdf::Document::with_version("1.5");
+        let pages_id = other.new_object_id();
+        let font_id = other.add_object(lopdf::Dictionary::from_iter(vec![
+            ("Type", "Font".into()),
+            ("Subtype", "Type1".into()),
+            ("BaseFont", "Helvetica".into()),
+        ]));
+        let content = b"BT /F1 12 Tf (Hi) Tj ET".to_vec();
+        let content_id = other.add_object(lopdf::Stream::new(lopdf::Dictionary::new(), content));
+        let page_id = other.add_object(lopdf::Dictionary::from_iter(vec![
+            ("Type", "Page".into()),
+            ("Parent", pages_id.into()),
+            ("Contents", content_id.into()),
+        ]));
+        let resources = lopdf::Dictionary::from_iter(vec![(
+            "Font",
+            lopdf::Dictionary::from_iter(vec![("F1", font_id.into())]).into(),
+        )]);
+        other.objects.insert(
+            pages_id,
+            lopdf::Dictionary::from_iter(vec![
+                ("Type", "Pages".into()),
+                ("Kids", vec![page_id.into()].into()),
+                ("Count", 1.into()),
+                ("Resources", resources.into()),
+                (
+                    "MediaBox",
+                    vec![0.into(), 0.into(), 200.into(), 100.into()].into(),
+                ),
+                ("Rotate", 90.into()),
+            ])
+            .into(),
+        );
+        let catalog_id = other.add_object(lopdf::Dictionary::from_iter(vec![
+            ("Type", "Catalog".into()),
+            ("Pages", pages_id.into()),
+        ]));
+        other.trailer.set("Root", catalog_id);
+        let mut data = Vec::new();
+        other.save_to(&mut data).unwrap();
+
+        let mut renderer = Renderer::new(Size::new(100, 100), "test").unwrap();
+        renderer.append_pdf(&data).unwrap();
+        let doc = write_renderer(renderer);
+
+        let page_id = doc.page_iter().nth(1).unwrap();
+        let page = doc.get_dictionary(page_id).unwrap();
+        let media_box: Vec<_> = page
+            .get(b"MediaBox")
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(as_f32)
+            .collect();
+        assert_eq!(vec![0.0, 0.0, 200.0, 100.0], media_box);
+        assert_eq!(90, page.get(b"Rotate").unwrap().as_i64().unwrap());
+        let resources = page.get(b"Resources").unwrap().as_dict().unwrap();
+        let fonts = resources.get(b"Font").unwrap().as_dict().unwrap();
+        let font_id = fonts.get(b"F1").unwrap().as_reference().unwrap();
+        let font = doc.get_dictionary(font_id).unwrap();
+        assert_eq!(
+            b"Helvetica",
+            font.get(b"BaseFont").unwrap().as_name().unwrap()
+        );
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn test_emoji_image() {
+        let emoji = '\u{1F600}';
+        let mut doc = crate::tests::document();
+        doc.add_emoji_image(emoji, printpdf::image_crate::DynamicImage::new_rgb8(4, 2));
+        doc.push(crate::elements::Text::new(format!("a{}b", emoji)));
+        let style = Style::new();
+        let font_cache = doc.font_cache();
+        let x = style.char_width(font_cache, 'a') - style.char_left_side_bearing(font_cache, 'a');
+        let width = style.char_width(font_cache, emoji);
+        let height = style.font(font_cache).glyph_height(style.font_size());
+        assert!(approx_eq!(Mm, width, height * 2.0, ulps = 2));
+        let doc = crate::tests::render_document(doc).unwrap();
+
+        assert_eq!(1, find_operations(&doc, 0, "Do").len());
+        let transform = &find_operations(&doc, 0, "cm")[0];
+        let x_pt = printpdf::Pt::from(x).0;
+        let width_pt = printpdf::Pt::from(width).0;
+        assert!(approx_eq!(
+            f32,
+            x_pt,
+            as_f32(&transform[4]),
+            epsilon = 0.001
+        ));
+        assert!(approx_eq!(
+            f32,
+            width_pt,
+            as_f32(&transform[0]),
+            epsilon = 0.001
+        ));
+        // Only the two letters are printed as text.
+        let texts = find_operations(&doc, 0, "TJ");
+        assert_eq!(3, texts.len());
+
+        // The image is only embedded once, even if the emoji is used multiple times and on
+        // multiple pages.
+        let mut doc = crate::tests::document();
+        doc.add_emoji_image(emoji, printpdf::image_crate::DynamicImage::new_rgb8(4, 2));
+        doc.push(crate::elements::Text::new(format!("a{}b{}", emoji, emoji)));
+        doc.push(crate::elements::PageBreak::new());
+        doc.push(crate::elements::Text::new(emoji.to_string()));
+        let doc = crate::tests::render_document(doc).unwrap();
+        let names = |page| -> Vec<Vec<u8>> {
+            find_operations(&doc, page, "Do")
+                .iter()
+                .map(|operands| operands[0].as_name().unwrap().to_vec())
+                .collect()
+        };
+        assert_eq!(vec![b"Emoji1F600".to_vec(); 2], names(0));
+        assert_eq!(vec![b"Emoji1F600".to_vec()], names(1));
+        let images = doc
+            .objects
+            .values()
+            .filter_map(|object| object.as_stream().ok())
+            .filter(|stream| {
+                stream
+                    .dict
+                    .get(b"Subtype")
+                    .and_then(lopdf::Object::as_name)
+                    .ok()
+                    == Some(b"Image")
+            })
+            .count();
+        assert_eq!(1, images);
+    }
+}
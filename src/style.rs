@@ -54,6 +54,70 @@ pub enum Color {
     Greyscale(u8),
 }
 
+impl Color {
+    /// Returns this color mixed with white.
+    ///
+    /// An amount of 0 returns this color, and an amount of 1 returns white.  The amount is clamped
+    /// to the range from 0 to 1.  The color space of the color is not changed.
+    pub fn lighten(self, amount: f32) -> Color {
+        let white = match self {
+            Color::Rgb(..) => Color::Rgb(255, 255, 255),
+            Color::Cmyk(..) => Color::Cmyk(0, 0, 0, 0),
+            Color::Greyscale(_) => Color::Greyscale(255),
+        };
+        self.blend(white, amount)
+    }
+
+    /// Returns this color mixed with black.
+    ///
+    /// An amount of 0 returns this color, and an amount of 1 returns black.  The amount is clamped
+    /// to the range from 0 to 1.  The color space of the color is not changed.
+    pub fn darken(self, amount: f32) -> Color {
+        let black = match self {
+            Color::Rgb(..) => Color::Rgb(0, 0, 0),
+            Color::Cmyk(..) => Color::Cmyk(0, 0, 0, 255),
+            Color::Greyscale(_) => Color::Greyscale(0),
+        };
+        self.blend(black, amount)
+    }
+
+    /// Returns the linear interpolation between this color and the given color.
+    ///
+    /// A factor of 0 returns this color, and a factor of 1 returns the other color.  The factor is
+    /// clamped to the range from 0 to 1.  If the colors use different color spaces, they are
+    /// converted to RGB before they are blended.
+    pub fn blend(self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+        match (self, other) {
+            (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => {
+                Color::Rgb(mix(r1, r2), mix(g1, g2), mix(b1, b2))
+            }
+            (Color::Cmyk(c1, m1, y1, k1), Color::Cmyk(c2, m2, y2, k2)) => {
+                Color::Cmyk(mix(c1, c2), mix(m1, m2), mix(y1, y2), mix(k1, k2))
+            }
+            (Color::Greyscale(v1), Color::Greyscale(v2)) => Color::Greyscale(mix(v1, v2)),
+            _ => self.to_rgb().blend(other.to_rgb(), t),
+        }
+    }
+
+    /// Converts this color to an RGB color.
+    fn to_rgb(self) -> Color {
+        match self {
+            Color::Rgb(..) => self,
+            Color::Cmyk(c, m, y, k) => {
+                let channel = |value: u8| {
+                    let value = 1.0 - f32::from(value) / 255.0;
+                    let key = 1.0 - f32::from(k) / 255.0;
+                    (255.0 * value * key).round() as u8
+                };
+                Color::Rgb(channel(c), channel(m), channel(y))
+            }
+            Color::Greyscale(val) => Color::Rgb(val, val, val),
+        }
+    }
+}
+
 impl From<Color> for printpdf::Color {
     fn from(color: Color) -> printpdf::Color {
         match color {
@@ -91,9 +155,16 @@ pub enum Effect {
 /// The annotation consists of:
 /// - a font family, see [`FontFamily`][] (defaults to the [`FontCache`][] default)
 /// - a font size in points (defaults to 12)
+/// - a minimum font size in points for text that is shrunk to fit (defaults to 1)
 /// - a line spacing factor, with 1 meaning single line spacing (defaults to 1)
+/// - an extra leading that is added to the line height (defaults to 0)
+/// - a baseline shift that raises or lowers the text (defaults to 0)
 /// - an outline color, see [`Color`][] (defaults to black)
+/// - a highlight color that is drawn behind the text, see [`Color`][] (defaults to none)
+/// - whether a line is drawn above the text (defaults to false)
 /// - a combination of text effects, see [`Effect`][] (defaults to none)
+/// - whether kerning is applied (defaults to true)
+/// - whether the glyphs are stacked vertically (defaults to false)
 ///
 /// All properties are optional.  If they are not set, they can be inferred from parent styles or
 /// from the defaults.
@@ -104,12 +175,19 @@ pub enum Effect {
 /// [`FontCache`]: ../fonts/struct.FontCache.html
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Style {
-    font_family: Option<fonts::FontFamily<fonts::Font>>,
+    font_family: Option<fonts::FontFamilyId>,
     font_size: Option<u8>,
+    min_font_size: Option<u8>,
     line_spacing: Option<f32>,
+    extra_leading: Option<Mm>,
+    baseline_shift: Option<Mm>,
     color: Option<Color>,
+    highlight: Option<Color>,
+    is_overline: bool,
     is_bold: bool,
     is_italic: bool,
+    kerning: Option<bool>,
+    vertical: Option<bool>,
 }
 
 impl Style {
@@ -127,15 +205,36 @@ impl Style {
         if let Some(font_size) = style.font_size {
             self.font_size = Some(font_size);
         }
+        if let Some(min_font_size) = style.min_font_size {
+            self.min_font_size = Some(min_font_size);
+        }
         if let Some(color) = style.color {
             self.color = Some(color);
         }
+        if let Some(extra_leading) = style.extra_leading {
+            self.extra_leading = Some(extra_leading);
+        }
+        if let Some(baseline_shift) = style.baseline_shift {
+            self.baseline_shift = Some(baseline_shift);
+        }
+        if let Some(highlight) = style.highlight {
+            self.highlight = Some(highlight);
+        }
+        if style.is_overline {
+            self.is_overline = true;
+        }
         if style.is_bold {
             self.is_bold = true;
         }
         if style.is_italic {
             self.is_italic = true;
         }
+        if let Some(kerning) = style.kerning {
+            self.kerning = Some(kerning);
+        }
+        if let Some(vertical) = style.vertical {
+            self.vertical = Some(vertical);
+        }
     }
 
     /// Combines this style and the given style and returns the result.
@@ -154,6 +253,16 @@ impl Style {
         self.color
     }
 
+    /// Returns the highlight color for this style, if set.
+    pub fn highlight(&self) -> Option<Color> {
+        self.highlight
+    }
+
+    /// Returns whether the overline is set.
+    pub fn is_overline(&self) -> bool {
+        self.is_overline
+    }
+
     /// Returns whether the bold text effect is set.
     pub fn is_bold(&self) -> bool {
         self.is_bold
@@ -164,16 +273,43 @@ impl Style {
         self.is_italic
     }
 
+    /// Returns whether kerning is applied to text with this style, or true if it is not set.
+    pub fn is_kerning_enabled(&self) -> bool {
+        self.kerning.unwrap_or(true)
+    }
+
+    /// Returns whether the glyphs of text with this style are stacked vertically, or false if it
+    /// is not set.
+    pub fn is_vertical(&self) -> bool {
+        self.vertical.unwrap_or_default()
+    }
+
     /// Returns the font size for this style in points, or 12 if no font size is set.
     pub fn font_size(&self) -> u8 {
         self.font_size.unwrap_or(12)
     }
 
+    /// Returns the minimum font size for text with this style that is shrunk to fit its area, or
+    /// 1 if no minimum font size is set.
+    pub fn min_font_size(&self) -> u8 {
+        self.min_font_size.unwrap_or(1).max(1)
+    }
+
     /// Returns the line spacing factor for this style, or 1 if no line spacing factor is set.
     pub fn line_spacing(&self) -> f32 {
         self.line_spacing.unwrap_or(1.0)
     }
 
+    /// Returns the extra leading for this style, or zero if no extra leading is set.
+    pub fn extra_leading(&self) -> Mm {
+        self.extra_leading.unwrap_or_default()
+    }
+
+    /// Returns the baseline shift for this style, or zero if no baseline shift is set.
+    pub fn baseline_shift(&self) -> Mm {
+        self.baseline_shift.unwrap_or_default()
+    }
+
     /// Sets the bold effect for this style.
     pub fn set_bold(&mut self) {
         self.is_bold = true;
@@ -196,13 +332,70 @@ impl Style {
         self
     }
 
+    /// Sets whether kerning is applied to text with this style.
+    ///
+    /// If kerning is disabled, the glyphs are printed with their advance width and without
+    /// adjustments between pairs of glyphs, for example to align tabular figures or to avoid
+    /// the bad kerning data of a font.  The width calculations take this setting into account.
+    pub fn set_kerning(&mut self, kerning: bool) {
+        self.kerning = Some(kerning);
+    }
+
+    /// Sets whether kerning is applied to text with this style and returns it.
+    pub fn with_kerning(mut self, kerning: bool) -> Style {
+        self.set_kerning(kerning);
+        self
+    }
+
+    /// Sets whether the glyphs of text with this style are stacked vertically.
+    ///
+    /// Vertical text is used for East Asian typesetting.  Instead of advancing the cursor to the
+    /// right, every glyph is printed below the previous glyph, so a vertical run occupies a
+    /// column that is as wide as its widest glyph.  A run can contain multiple columns that are
+    /// separated by line breaks (`\n`).  The columns advance from right to left, so the first
+    /// column is the rightmost one.  If a run is higher than the line, the line height is increased
+    /// accordingly.  Kerning is not applied to vertical text.
+    pub fn set_vertical(&mut self, vertical: bool) {
+        self.vertical = Some(vertical);
+    }
+
+    /// Sets whether the glyphs of text with this style are stacked vertically and returns it.
+    pub fn with_vertical(mut self, vertical: bool) -> Style {
+        self.set_vertical(vertical);
+        self
+    }
+
     /// Sets the font family for this style.
-    pub fn set_font_family(&mut self, font_family: fonts::FontFamily<fonts::Font>) {
+    pub fn set_font_family(&mut self, font_family: fonts::FontFamilyId) {
         self.font_family = Some(font_family);
     }
 
     /// Sets the font family for this style and returns it.
-    pub fn with_font_family(mut self, font_family: fonts::FontFamily<fonts::Font>) -> Style {
+    ///
+    /// The font family must have been added to the font cache of the document with
+    /// [`Document::add_font_family`][].  Different runs of a paragraph can use different font
+    /// families, for example a monospace font family for inline code.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use rckive_genpdf::{elements, fonts, style};
+    /// let default_font = fonts::from_files("./fonts", "LiberationSans", None)
+    ///     .expect("Failed to load the default font family");
+    /// let monospace_font = fonts::from_files("./fonts", "LiberationMono", None)
+    ///     .expect("Failed to load the monospace font family");
+    /// let mut doc = rckive_genpdf::Document::new(default_font);
+    /// let code = doc.add_font_family(monospace_font);
+    ///
+    /// let mut p = elements::Paragraph::default();
+    /// p.push("Call ");
+    /// p.push_styled("render_to_file", style::Style::new().with_font_family(code));
+    /// p.push(" to write the document.");
+    /// doc.push(p);
+    /// ```
+    ///
+    /// [`Document::add_font_family`]: ../struct.Document.html#method.add_font_family
+    pub fn with_font_family(mut self, font_family: fonts::FontFamilyId) -> Style {
         self.set_font_family(font_family);
         self
     }
@@ -218,6 +411,35 @@ impl Style {
         self
     }
 
+    /// Sets the extra leading for this style.
+    ///
+    /// The extra leading is added to the line height after it has been multiplied with the line
+    /// spacing factor.
+    pub fn set_extra_leading(&mut self, extra_leading: impl Into<Mm>) {
+        self.extra_leading = Some(extra_leading.into());
+    }
+
+    /// Sets the extra leading for this style and returns it.
+    pub fn with_extra_leading(mut self, extra_leading: impl Into<Mm>) -> Style {
+        self.set_extra_leading(extra_leading);
+        self
+    }
+
+    /// Sets the baseline shift for this style.
+    ///
+    /// Text with a positive baseline shift is raised above the baseline, text with a negative
+    /// shift is lowered, for example for footnote markers.  The font size and the line height are
+    /// not changed, so the shifted text may extend beyond the line.
+    pub fn set_baseline_shift(&mut self, baseline_shift: impl Into<Mm>) {
+        self.baseline_shift = Some(baseline_shift.into());
+    }
+
+    /// Sets the baseline shift for this style and returns it.
+    pub fn with_baseline_shift(mut self, baseline_shift: impl Into<Mm>) -> Style {
+        self.set_baseline_shift(baseline_shift);
+        self
+    }
+
     /// Sets the font size in points for this style.
     pub fn set_font_size(&mut self, font_size: u8) {
         self.font_size = Some(font_size);
@@ -229,6 +451,25 @@ impl Style {
         self
     }
 
+    /// Sets the minimum font size in points for text with this style that is shrunk to fit its
+    /// area.
+    ///
+    /// The font size is never reduced below this value when shrinking text, see
+    /// [`LineOverflow::Shrink`][].  If the text does not fit even with the minimum font size, it
+    /// is clipped at the border of the area instead.
+    ///
+    /// [`LineOverflow::Shrink`]: ../enum.LineOverflow.html#variant.Shrink
+    pub fn set_min_font_size(&mut self, min_font_size: u8) {
+        self.min_font_size = Some(min_font_size);
+    }
+
+    /// Sets the minimum font size in points for text with this style that is shrunk to fit its
+    /// area and returns it.
+    pub fn with_min_font_size(mut self, min_font_size: u8) -> Style {
+        self.set_min_font_size(min_font_size);
+        self
+    }
+
     /// Sets the outline color for this style.
     pub fn set_color(&mut self, color: Color) {
         self.color = Some(color);
@@ -240,6 +481,37 @@ impl Style {
         self
     }
 
+    /// Sets the highlight color for this style.
+    ///
+    /// The highlight is a filled rectangle behind the text that spans the advance width of the
+    /// text and the line height, like the marks of a highlighter pen.
+    pub fn set_highlight(&mut self, color: Color) {
+        self.highlight = Some(color);
+    }
+
+    /// Sets the highlight color for this style and returns it.
+    pub fn with_highlight(mut self, color: Color) -> Self {
+        self.set_highlight(color);
+        self
+    }
+
+    /// Sets the overline for this style.
+    ///
+    /// The overline is a line above the cap height of the font that spans the advance width of the
+    /// text.  It is drawn with the text color and a thickness of 1/20 em.  Vertical text (see
+    /// [`set_vertical`][]) is not overlined.
+    ///
+    /// [`set_vertical`]: #method.set_vertical
+    pub fn set_overline(&mut self) {
+        self.is_overline = true;
+    }
+
+    /// Sets the overline for this style and returns it.
+    pub fn with_overline(mut self) -> Self {
+        self.set_overline();
+        self
+    }
+
     /// Calculates the width of the given character with this style using the data in the given
     /// font cache.
     ///
@@ -265,12 +537,67 @@ impl Style {
     /// Calculates the width of the given string with this style using the data in the given font
     /// cache.
     ///
-    /// If the font family is set, it must have been created by the given [`FontCache`][].
+    /// For vertical text, this is the sum of the widths of the widest characters of the columns,
+    /// see [`set_vertical`][].  If the font family is set, it must have been created by the given
+    /// [`FontCache`][].
+    ///
+    /// [`set_vertical`]: #method.set_vertical
     ///
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn str_width(&self, font_cache: &fonts::FontCache, s: &str) -> Mm {
         let font = self.font(font_cache);
-        font.str_width(font_cache, s, self.font_size())
+        if self.is_vertical() {
+            s.split('\n')
+                .map(|column| {
+                    column
+                        .chars()
+                        .map(|c| font.char_width(font_cache, c, self.font_size()))
+                        .fold(Mm(0.0), Mm::max)
+                })
+                .sum()
+        } else if self.is_kerning_enabled() {
+            font.str_width(font_cache, s, self.font_size())
+        } else {
+            s.chars()
+                .map(|c| font.char_width(font_cache, c, self.font_size()))
+                .sum()
+        }
+    }
+
+    /// Returns the kerning that a text section inserts before the given string with this style if
+    /// it follows the given character with the given style on the same line.
+    ///
+    /// [`TextSection::print_str`][] kerns the character pair across the string boundary if both
+    /// strings use the same font and font size and kerning is enabled for this style, so this
+    /// kerning has to be added to the widths of the strings (see [`str_width`][]) to calculate the
+    /// width of a line.  If the font family is set, it must have been created by the given
+    /// [`FontCache`][].
+    ///
+    /// [`TextSection::print_str`]: ../render/struct.TextSection.html#method.print_str
+    /// [`str_width`]: #method.str_width
+    /// [`FontCache`]: ../fonts/struct.FontCache.html
+    pub fn kerning_after(
+        &self,
+        font_cache: &fonts::FontCache,
+        previous: (Style, char),
+        s: &str,
+    ) -> Mm {
+        let (previous_style, last_c) = previous;
+        let first_c = match s.chars().next() {
+            Some(c) => c,
+            None => return Mm(0.0),
+        };
+        let font = self.font(font_cache);
+        if !self.is_kerning_enabled()
+            || self.is_vertical()
+            || previous_style.is_vertical()
+            || previous_style.font(font_cache) != font
+            || previous_style.font_size() != self.font_size()
+        {
+            return Mm(0.0);
+        }
+        let kerning = font.kerning(font_cache, [last_c, first_c])[1];
+        Mm::from(printpdf::Pt(kerning * f32::from(self.font_size())))
     }
 
     /// Returns the font family for this style or the default font family using the given font
@@ -281,6 +608,7 @@ impl Style {
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn font_family(&self, font_cache: &fonts::FontCache) -> fonts::FontFamily<fonts::Font> {
         self.font_family
+            .map(|font_family| font_family.font_family())
             .unwrap_or_else(|| font_cache.default_font_family())
     }
 
@@ -301,6 +629,53 @@ impl Style {
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn line_height(&self, font_cache: &fonts::FontCache) -> Mm {
         self.font(font_cache).get_line_height(self.font_size()) * self.line_spacing()
+            + self.extra_leading()
+    }
+
+    /// Returns the ascent of the font for this style using the data in the given font cache.
+    ///
+    /// If the font family is set, it must have been created by the given [`FontCache`][].
+    ///
+    /// [`FontCache`]: ../fonts/struct.FontCache.html
+    pub fn ascent(&self, font_cache: &fonts::FontCache) -> Mm {
+        self.font(font_cache).ascent(self.font_size())
+    }
+
+    /// Returns the descent of the font for this style using the data in the given font cache.
+    ///
+    /// The descent is measured from the baseline, so it is negative for most fonts.  If the font
+    /// family is set, it must have been created by the given [`FontCache`][].
+    ///
+    /// [`FontCache`]: ../fonts/struct.FontCache.html
+    pub fn descent(&self, font_cache: &fonts::FontCache) -> Mm {
+        self.font(font_cache).descent(self.font_size())
+    }
+
+    /// Returns the line gap of the font for this style using the data in the given font cache.
+    ///
+    /// If the font family is set, it must have been created by the given [`FontCache`][].
+    ///
+    /// [`FontCache`]: ../fonts/struct.FontCache.html
+    pub fn line_gap(&self, font_cache: &fonts::FontCache) -> Mm {
+        self.font(font_cache).line_gap(self.font_size())
+    }
+
+    /// Returns the cap height of the font for this style using the data in the given font cache.
+    ///
+    /// If the font family is set, it must have been created by the given [`FontCache`][].
+    ///
+    /// [`FontCache`]: ../fonts/struct.FontCache.html
+    pub fn cap_height(&self, font_cache: &fonts::FontCache) -> Mm {
+        self.font(font_cache).cap_height(self.font_size())
+    }
+
+    /// Returns the x-height of the font for this style using the data in the given font cache.
+    ///
+    /// If the font family is set, it must have been created by the given [`FontCache`][].
+    ///
+    /// [`FontCache`]: ../fonts/struct.FontCache.html
+    pub fn x_height(&self, font_cache: &fonts::FontCache) -> Mm {
+        self.font(font_cache).x_height(self.font_size())
     }
 
     /// Calculate the metrics of the font for this style using the data in the given font cache.
@@ -310,7 +685,7 @@ impl Style {
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn metrics(&self, font_cache: &fonts::FontCache) -> fonts::Metrics {
         let mut metrics = self.font(font_cache).metrics(self.font_size());
-        metrics.line_height *= self.line_spacing();
+        metrics.line_height = metrics.line_height * self.line_spacing() + self.extra_leading();
         metrics
     }
 }
@@ -333,6 +708,12 @@ impl From<Effect> for Style {
 
 impl From<fonts::FontFamily<fonts::Font>> for Style {
     fn from(font_family: fonts::FontFamily<fonts::Font>) -> Style {
+        Style::new().with_font_family(font_family.into())
+    }
+}
+
+impl From<fonts::FontFamilyId> for Style {
+    fn from(font_family: fonts::FontFamilyId) -> Style {
         Style::new().with_font_family(font_family)
     }
 }
@@ -548,20 +929,76 @@ impl<'s> From<StyledString> for StyledCow<'s> {
     }
 }
 
+/// The shape of the ends of a line, see [`LineStyle::set_line_cap`][].
+///
+/// [`LineStyle::set_line_cap`]: struct.LineStyle.html#method.set_line_cap
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineCap {
+    /// The line is squared off at its end points.
+    #[default]
+    Butt,
+    /// The line ends with a semicircle around its end points.
+    Round,
+    /// The line is extended beyond its end points by half of its thickness and squared off.
+    Square,
+}
+
+impl From<LineCap> for printpdf::LineCapStyle {
+    fn from(line_cap: LineCap) -> printpdf::LineCapStyle {
+        match line_cap {
+            LineCap::Butt => printpdf::LineCapStyle::Butt,
+            LineCap::Round => printpdf::LineCapStyle::Round,
+            LineCap::Square => printpdf::LineCapStyle::ProjectingSquare,
+        }
+    }
+}
+
+/// The shape of the corners where two segments of a line meet, see
+/// [`LineStyle::set_line_join`][].
+///
+/// [`LineStyle::set_line_join`]: struct.LineStyle.html#method.set_line_join
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    /// The outer edges of the segments are extended until they meet at a sharp corner.
+    #[default]
+    Miter,
+    /// The corner is rounded off with a circle around the point where the segments meet.
+    Round,
+    /// The corner is cut off with a straight edge.
+    Bevel,
+}
+
+impl From<LineJoin> for printpdf::LineJoinStyle {
+    fn from(line_join: LineJoin) -> printpdf::LineJoinStyle {
+        match line_join {
+            LineJoin::Miter => printpdf::LineJoinStyle::Miter,
+            LineJoin::Round => printpdf::LineJoinStyle::Round,
+            // printpdf calls the bevel join `Limit`.
+            LineJoin::Bevel => printpdf::LineJoinStyle::Limit,
+        }
+    }
+}
+
 /// A style for a line, used in styling borders and shapes.
 ///
 /// The style consists of:
 /// - the line thickness in millimeters (defaults to 0.1)
 /// - the color of the line, see [`Color`][] (defaults to black)
+/// - the shape of the line ends, see [`LineCap`][] (defaults to butt)
+/// - the shape of the line corners, see [`LineJoin`][] (defaults to miter)
 ///
 /// Note that a line thickness of 0.0 does not make the line disappear, but rather makes it appear
 /// 1px wide across all devices and resolutions.
 ///
 /// [`Color`]: enum.Color.html
+/// [`LineCap`]: enum.LineCap.html
+/// [`LineJoin`]: enum.LineJoin.html
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct LineStyle {
     thickness: Mm,
     color: Color,
+    line_cap: LineCap,
+    line_join: LineJoin,
 }
 
 impl Default for LineStyle {
@@ -569,6 +1006,8 @@ impl Default for LineStyle {
         LineStyle {
             thickness: Mm::from(0.1),
             color: Color::Rgb(0, 0, 0),
+            line_cap: LineCap::default(),
+            line_join: LineJoin::default(),
         }
     }
 }
@@ -625,4 +1064,81 @@ impl LineStyle {
     pub fn color(&self) -> Color {
         self.color
     }
+
+    /// Sets the shape of the line ends.
+    pub fn set_line_cap(&mut self, line_cap: LineCap) {
+        self.line_cap = line_cap;
+    }
+
+    /// Sets the shape of the line ends and returns the line style.
+    pub fn with_line_cap(mut self, line_cap: LineCap) -> Self {
+        self.set_line_cap(line_cap);
+        self
+    }
+
+    /// Returns the shape of the line ends.
+    pub fn line_cap(&self) -> LineCap {
+        self.line_cap
+    }
+
+    /// Sets the shape of the line corners.
+    pub fn set_line_join(&mut self, line_join: LineJoin) {
+        self.line_join = line_join;
+    }
+
+    /// Sets the shape of the line corners and returns the line style.
+    pub fn with_line_join(mut self, line_join: LineJoin) -> Self {
+        self.set_line_join(line_join);
+        self
+    }
+
+    /// Returns the shape of the line corners.
+    pub fn line_join(&self) -> LineJoin {
+        self.line_join
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Color;
+
+    #[test]
+    fn test_color_lighten_darken() {
+        let color = Color::Rgb(200, 100, 0);
+        assert_eq!(color, color.lighten(0.0));
+        assert_eq!(Color::Rgb(255, 255, 255), color.lighten(1.0));
+        assert_eq!(Color::Rgb(255, 255, 255), color.lighten(2.0));
+        assert_eq!(Color::Rgb(228, 178, 128), color.lighten(0.5));
+        assert_eq!(color, color.darken(0.0));
+        assert_eq!(Color::Rgb(0, 0, 0), color.darken(1.0));
+        assert_eq!(Color::Rgb(100, 50, 0), color.darken(0.5));
+
+        assert_eq!(Color::Greyscale(255), Color::Greyscale(10).lighten(1.0));
+        assert_eq!(
+            Color::Cmyk(0, 0, 0, 0),
+            Color::Cmyk(10, 20, 30, 40).lighten(1.0)
+        );
+        assert_eq!(
+            Color::Cmyk(0, 0, 0, 255),
+            Color::Cmyk(10, 20, 30, 40).darken(1.0)
+        );
+    }
+
+    #[test]
+    fn test_color_blend() {
+        let a = Color::Rgb(0, 100, 200);
+        let b = Color::Rgb(100, 200, 0);
+        assert_eq!(a, a.blend(b, 0.0));
+        assert_eq!(b, a.blend(b, 1.0));
+        assert_eq!(Color::Rgb(50, 150, 100), a.blend(b, 0.5));
+        assert_eq!(
+            Color::Greyscale(50),
+            Color::Greyscale(0).blend(Color::Greyscale(100), 0.5)
+        );
+        // Colors with different color spaces are blended in RGB.
+        assert_eq!(
+            Color::Rgb(128, 128, 128),
+            Color::Greyscale(0).blend(Color::Cmyk(0, 0, 0, 0), 0.5)
+        );
+    }
 }
@@ -1,21 +1,34 @@
 //! Utilities for text wrapping.
 
+use std::collections;
 use std::mem;
 
+use crate::fonts;
 use crate::style;
 use crate::Context;
-use crate::Mm;
+use crate::{Mm, WordBreak};
 
 /// Combines a sequence of styled words into lines with a maximum width.
 ///
 /// If a word does not fit into a line, the wrapper tries to split it using the `split` function.
+/// If the word is longer than a line and cannot be split, it is broken at an arbitrary character
+/// depending on the [`WordBreak`][] setting.
+///
+/// [`WordBreak`]: ../enum.WordBreak.html
 pub struct Wrapper<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> {
     iter: I,
     context: &'c Context,
     width: Mm,
+    first_line_width: Option<Mm>,
     x: Mm,
     buf: Vec<style::StyledCow<'s>>,
+    rest: Option<style::StyledStr<'s>>,
+    lookahead: collections::VecDeque<style::StyledStr<'s>>,
+    word_len: usize,
+    word_break: WordBreak,
     has_overflowed: bool,
+    // the style and the last character of the last non-empty string of the current line
+    last: Option<(style::Style, char)>,
 }
 
 impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Wrapper<'c, 's, I> {
@@ -25,17 +38,97 @@ impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Wrapper<'c, 's, I> {
             iter,
             context,
             width,
+            first_line_width: None,
             x: Mm(0.0),
             buf: Vec::new(),
+            rest: None,
+            lookahead: collections::VecDeque::new(),
+            word_len: 0,
+            word_break: WordBreak::default(),
             has_overflowed: false,
+            last: None,
         }
     }
 
+    /// Sets the fallback for words that are longer than a line and returns the wrapper.
+    pub fn with_word_break(mut self, word_break: WordBreak) -> Self {
+        self.word_break = word_break;
+        self
+    }
+
+    /// Sets a different maximum width for the first line and returns the wrapper.
+    pub fn with_first_line_width(mut self, width: Mm) -> Self {
+        self.first_line_width = Some(width);
+        self
+    }
+
+    /// Returns the maximum width of the current line.
+    fn line_width(&self) -> Mm {
+        self.first_line_width.unwrap_or(self.width)
+    }
+
     /// Returns true if this wrapper has overflowed, i. e. if it encountered a word that it could
     /// not split so that it would fit into a line.
     pub fn has_overflowed(&self) -> bool {
         self.has_overflowed
     }
+
+    /// Returns the next string, either from the rest of a split word or from the input sequence.
+    fn next_str(&mut self) -> Option<style::StyledStr<'s>> {
+        self.rest
+            .take()
+            .or_else(|| self.lookahead.pop_front())
+            .or_else(|| self.iter.next())
+    }
+
+    /// Appends the given string to the current line.
+    fn push(&mut self, s: impl Into<style::StyledCow<'s>>) {
+        let s = s.into();
+        if let Some(c) = s.s.chars().last() {
+            self.last = Some((s.style, c));
+        }
+        if ends_word(&s.s) {
+            self.word_len = 0;
+        } else {
+            self.word_len += count_letters(&s.s);
+        }
+        self.buf.push(s);
+    }
+
+    /// Returns the number of letters of the word that the given string belongs to.
+    ///
+    /// A word can consist of multiple strings with different styles, so this includes the strings
+    /// of the word that have already been added to a line and the strings that follow the given
+    /// string.
+    fn word_len(&mut self, s: style::StyledStr<'s>) -> usize {
+        let mut len = self.word_len + count_letters(s.s);
+        if ends_word(s.s) {
+            return len;
+        }
+        for i in 0.. {
+            if i == self.lookahead.len() {
+                if let Some(next) = self.iter.next() {
+                    self.lookahead.push_back(next);
+                } else {
+                    break;
+                }
+            }
+            let next = self.lookahead[i];
+            len += count_letters(next.s);
+            if ends_word(next.s) {
+                break;
+            }
+        }
+        len
+    }
+
+    /// Returns the current line and starts a new line.
+    fn next_line(&mut self) -> Vec<style::StyledCow<'s>> {
+        self.x = Mm(0.0);
+        self.first_line_width = None;
+        self.last = None;
+        mem::take(&mut self.buf)
+    }
 }
 
 impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Iterator for Wrapper<'c, 's, I> {
@@ -45,70 +138,148 @@ impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Iterator for Wrapper<'c,
 
     fn next(&mut self) -> Option<(Vec<style::StyledCow<'s>>, usize)> {
         // Append words to self.buf until the maximum line length is reached
-        for s in self.iter.by_ref() {
-            let mut width = s.width(&self.context.font_cache);
-
-            if self.x + width > self.width {
-                // The word does not fit into the current line (at least not completely)
-
-                let mut delta = 0;
-                // Try to split the word so that the first part fits into the current line
-                let s = if let Some((start, end)) = split(self.context, s, self.width - self.x) {
-                    // Calculate the number of bytes that we added to the string when splitting it
-                    // (for the hyphen, if required).
-                    delta = start.s.len() + end.s.len() - s.s.len();
-                    self.buf.push(start);
-                    width = end.width(&self.context.font_cache);
-                    end
-                } else {
-                    s.into()
-                };
-
-                if width > self.width {
-                    // The remainder of the word is longer than the current page – we will never be
-                    // able to render it completely.
-                    // TODO: handle gracefully, emit warning
-                    self.has_overflowed = true;
-                    return None;
-                }
+        while let Some(s) = self.next_str() {
+            // The kerning with the previous string on this line is applied before the string.
+            let kerning = self.last.map_or(Mm(0.0), |last| {
+                s.style.kerning_after(&self.context.font_cache, last, s.s)
+            });
+            let width = kerning + s.width(&self.context.font_cache);
 
-                // Return the current line and add the word that did not fit to the next line
-                let v = std::mem::take(&mut self.buf);
-                self.buf.push(s);
-                self.x = width;
-                return Some((v, delta));
-            } else {
+            if self.x + width <= self.line_width() {
                 // The word fits in the current line, so just append it
-                self.buf.push(s.into());
+                self.push(s);
                 self.x += width;
+                continue;
+            }
+
+            // The word does not fit into the current line (at least not completely).  Try to split
+            // the word so that the first part fits into the current line.  If the word does not
+            // even fit into an empty line, we may also break it at an arbitrary character.
+            let available_width = self.line_width() - self.x - kerning;
+            let word_len = self.word_len(s);
+            let parts = split(self.context, s, available_width, word_len).or_else(|| {
+                if self.buf.is_empty() {
+                    break_word(self.context, s, available_width, self.word_break)
+                } else {
+                    None
+                }
+            });
+            if let Some((start, end)) = parts {
+                // Calculate the number of bytes that we added to the string when splitting it
+                // (for the hyphen, if required).
+                let delta = start.s.len() + end.s.len() - s.s.len();
+                self.push(start);
+                self.rest = Some(end);
+                return Some((self.next_line(), delta));
             }
+
+            if self.buf.is_empty() {
+                // The word is longer than the current page – we will never be able to render it
+                // completely.
+                // TODO: handle gracefully, emit warning
+                self.has_overflowed = true;
+                return None;
+            }
+
+            // Return the current line and add the word that did not fit to the next line
+            self.rest = Some(s);
+            return Some((self.next_line(), 0));
         }
 
         if self.buf.is_empty() {
             None
         } else {
-            Some((mem::take(&mut self.buf), 0))
+            Some((self.next_line(), 0))
         }
     }
 }
 
+/// Returns the width of a line consisting of the given strings and styles, including the kerning
+/// across the string boundaries (see [`Style::kerning_after`][]).
+///
+/// [`Style::kerning_after`]: ../style/struct.Style.html#method.kerning_after
+pub fn line_width<'a>(
+    font_cache: &fonts::FontCache,
+    line: impl IntoIterator<Item = (&'a str, style::Style)>,
+) -> Mm {
+    let mut width = Mm(0.0);
+    let mut last = None;
+    for (s, style) in line {
+        if let Some(last) = last {
+            width += style.kerning_after(font_cache, last, s);
+        }
+        width += style.str_width(font_cache, s);
+        if let Some(c) = s.chars().last() {
+            last = Some((style, c));
+        }
+    }
+    width
+}
+
+/// Breaks the given string at the last character that fits into the given width, optionally
+/// appending a hyphen to the first part.
+fn break_word<'s>(
+    context: &Context,
+    s: style::StyledStr<'s>,
+    width: Mm,
+    word_break: WordBreak,
+) -> Option<(style::StyledCow<'s>, style::StyledStr<'s>)> {
+    let mark = match word_break {
+        WordBreak::Never => return None,
+        WordBreak::Anywhere => "",
+        WordBreak::AnywhereWithHyphen => "-",
+    };
+    let mark_width = s.style.str_width(&context.font_cache, mark);
+
+    let mut idx = 0;
+    for (i, _) in s.s.char_indices().skip(1) {
+        if s.style.str_width(&context.font_cache, &s.s[..i]) + mark_width > width {
+            break;
+        }
+        idx = i;
+    }
+    if idx > 0 {
+        Some((
+            style::StyledCow::new(s.s[..idx].to_owned() + mark, s.style),
+            style::StyledStr::new(&s.s[idx..], s.style),
+        ))
+    } else {
+        None
+    }
+}
+
+/// Returns the number of letters in the given string.
+fn count_letters(s: &str) -> usize {
+    s.chars().filter(|c| c.is_alphabetic()).count()
+}
+
+/// Returns true if the given string is the end of a word, i. e. if it ends with whitespace.
+fn ends_word(s: &str) -> bool {
+    s.ends_with(char::is_whitespace)
+}
+
 #[cfg(not(feature = "hyphenation"))]
 fn split<'s>(
     _context: &Context,
     _s: style::StyledStr<'s>,
     _len: Mm,
-) -> Option<(style::StyledCow<'s>, style::StyledCow<'s>)> {
+    _word_len: usize,
+) -> Option<(style::StyledCow<'s>, style::StyledStr<'s>)> {
     None
 }
 
 /// Tries to split the given string into two parts so that the first part is shorter than the given
 /// width.
+///
+/// The string is not split if the word that it belongs to has at most
+/// `context.hyphenation_min_word_length` letters.
 #[cfg(feature = "hyphenation")]
 fn split<'s>(
     context: &Context,
     s: style::StyledStr<'s>,
     width: Mm,
-) -> Option<(style::StyledCow<'s>, style::StyledCow<'s>)> {
+    word_len: usize,
+) -> Option<(style::StyledCow<'s>, style::StyledStr<'s>)> {
     use hyphenation::{Hyphenator, Iter};
 
     let hyphenator = if let Some(hyphenator) = &context.hyphenator {
@@ -116,6 +287,9 @@ fn split<'s>(
     } else {
         return None;
     };
+    if word_len <= context.hyphenation_min_word_length {
+        return None;
+    }
 
     let mark = "-";
     let mark_width = s.style.str_width(&context.font_cache, mark);
@@ -139,7 +313,7 @@ fn split<'s>(
         let end = &s.s[idx..];
         Some((
             style::StyledCow::new(start, s.style),
-            style::StyledCow::new(end, s.style),
+            style::StyledStr::new(end, s.style),
         ))
     } else {
         None
@@ -183,3 +357,81 @@ impl<I: Iterator<Item = style::StyledString>> Iterator for Words<I> {
         }
     }
 }
+
+#[cfg(all(test, feature = "hyphenation"))]
+mod tests {
+    use super::Wrapper;
+    use crate::style;
+    use crate::tests::document;
+
+    #[test]
+    fn test_hyphenator_exception() {
+        use hyphenation::{Hyphenator as _, Load as _};
+
+        let mut hyphenator =
+            hyphenation::Standard::from_embedded(hyphenation::Language::EnglishUS).unwrap();
+        hyphenator.add_exception("genpdfcrate".to_owned(), vec![6]);
+        let mut doc = document();
+        doc.set_hyphenator(hyphenator);
+
+        let style = style::Style::new();
+        let width = style.str_width(&doc.context.font_cache, "genpdfcrat");
+        let words = std::iter::once(style::StyledStr::new("genpdfcrate", style));
+        let lines: Vec<Vec<String>> = Wrapper::new(words, &doc.context, width)
+            .map(|(line, _)| line.into_iter().map(|s| s.s.into_owned()).collect())
+            .collect();
+        assert_eq!(vec![vec!["genpdf-"], vec!["crate"]], lines);
+    }
+
+    #[test]
+    fn test_hyphenation_min_word_length() {
+        use hyphenation::{Hyphenator as _, Load as _};
+
+        let mut hyphenator =
+            hyphenation::Standard::from_embedded(hyphenation::Language::EnglishUS).unwrap();
+        // Allow breaks in the middle of four-letter words.
+        hyphenator.minima = (2, 2);
+        hyphenator.add_exception("abcd".to_owned(), vec![2]);
+        let mut doc = document();
+        doc.set_hyphenator(hyphenator);
+        doc.set_hyphenation_min_word_length(4);
+
+        let style = style::Style::new();
+        let wrap = |doc: &crate::Document, words: &[&str], width: &str| -> Vec<Vec<String>> {
+            let width = style.str_width(&doc.context.font_cache, width);
+            let words = words.iter().map(|word| style::StyledStr::new(word, style));
+            Wrapper::new(words, &doc.context, width)
+                .map(|(line, _)| line.into_iter().map(|s| s.s.into_owned()).collect())
+                .collect()
+        };
+        // The short word is moved to the next line instead of being hyphenated.
+        assert_eq!(
+            vec![vec!["x "], vec!["abcd"]],
+            wrap(&doc, &["x ", "abcd"], "x abc-")
+        );
+        // The long word is still hyphenated.
+        let lines = wrap(&doc, &["x ", "representative"], "x representa-");
+        assert_eq!(2, lines.len());
+        assert!(lines[0][1].ends_with('-'));
+        // The length of a word that consists of multiple strings is counted as a whole.
+        assert_eq!(
+            vec![vec!["x ", "ab-"], vec!["cd", "ef"]],
+            wrap(&doc, &["x ", "abcd", "ef"], "x abc-")
+        );
+        assert_eq!(
+            vec![vec!["x ", "ef", "ab-"], vec!["cd"]],
+            wrap(&doc, &["x ", "ef", "abcd"], "x efabc-")
+        );
+        // Punctuation is not counted.
+        assert_eq!(
+            vec![vec!["x "], vec!["abcd", ","]],
+            wrap(&doc, &["x ", "abcd", ","], "x abc-")
+        );
+
+        doc.set_hyphenation_min_word_length(0);
+        assert_eq!(
+            vec![vec!["x ", "ab-"], vec!["cd"]],
+            wrap(&doc, &["x ", "abcd"], "x abc-")
+        );
+    }
+}